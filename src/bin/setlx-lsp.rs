@@ -0,0 +1,6 @@
+use setlx_rs::lsp::Server;
+use std::io;
+
+fn main() -> io::Result<()> {
+    Server::new().run(&mut io::stdin().lock(), &mut io::stdout().lock())
+}
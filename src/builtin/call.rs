@@ -1,44 +1,98 @@
 #![allow(clippy::mutable_key_type)]
 
 use glass_pumpkin::prime;
+use nalgebra::{DMatrix, DVector};
+use num_bigint::BigInt;
 use num_prime::nt_funcs::is_prime as num_is_prime;
 use num_prime::{Primality, PrimalityTestConfig};
-use num_traits::{Pow, ToPrimitive};
+use num_rational::BigRational;
+use num_traits::{Pow, Signed, ToPrimitive, Zero};
 use pcre2::bytes::RegexBuilder;
 use rand::RngExt;
 use rustyline::DefaultEditor;
 use rustyline::error::ReadlineError;
+use std::any::Any;
 use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{self, OpenOptions};
-use std::io::{self, Read, Write};
+use std::io::{self, IsTerminal, Read, Write};
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
 use std::panic;
 use std::panic::AssertUnwindSafe;
-use std::process::{Command, exit};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio, exit};
 use std::rc::Rc;
+use std::str::FromStr;
 use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use yansi::Paint;
 
+use crate::builtin::csv::{csv_parse, csv_quote};
+use crate::builtin::file::FileHandle;
+use crate::builtin::json::{json_decode, json_encode};
+use crate::builtin::module::{module_hash, module_resolve};
+use crate::builtin::native::{NativeFn, NativeProc};
+use crate::builtin::plot::{PlotCanvas, PlotColor, PlotItem};
+use crate::builtin::rng::rng_new;
+use crate::builtin::stat::StatDist;
 use crate::builtin::*;
 use crate::cli::InputOpts;
-use crate::cst::{cst_expr_parse, cst_parse};
+use crate::cst::{cst_expr_parse_at, cst_parse_at};
 use crate::interp::{
     ast::{ast_to_cst_block, ast_to_cst_expr},
     debug::DebugData,
     except::*,
-    exec::exec_proc,
+    exec::{exec_proc, exec_report},
     get::InterpGet,
     heap::*,
+    io::io_get,
     memoize::InterpMemoize,
-    ops::val_cmp,
+    ops::{obj_to_f64, obj_to_rational, val_cmp},
     serialize::{SerializeOpts, serialize},
-    stack::InterpStack,
+    stack::{InterpStack, InterpStackEntry, InterpStackVar},
 };
+use crate::ir::cache::{IRCache, ir_cache_fresh, ir_cache_path};
 use crate::ir::def::*;
 use crate::ir::lower::CSTIRLower;
 use crate::ir::lower::expr::term_expr::{ast_tterm_tag_get, tterm_ast_tag_get};
+use crate::ir::symbol::IRSymbol;
+
+/* Natives sharing an implementation are dispatched on their BuiltinProc.
+ *
+ * native_variant!(native_floor, native_round_op, BuiltinProc::Floor);
+ */
+macro_rules! native_variant {
+    ($name:ident, $impl:ident, $proc:expr) => {
+        fn $name(
+            params: &[IRValue],
+            params_proc: &InterpVal,
+            vars: &mut [InterpVal],
+            stack: &mut InterpStack,
+            heap: &mut InterpImmediateHeap,
+            memo: &mut InterpMemoize,
+            cstore: &mut InterpClassStore,
+            breakpoints: &mut DebugData,
+            opts: &InputOpts,
+            rl: &mut DefaultEditor,
+        ) -> InterpVal {
+            $impl(
+                $proc,
+                params,
+                params_proc,
+                vars,
+                stack,
+                heap,
+                memo,
+                cstore,
+                breakpoints,
+                opts,
+                rl,
+            )
+        }
+    };
+}
 
 fn amount_val(input: &InterpVal, data: &DebugData) -> usize {
     match input {
@@ -93,1677 +147,4793 @@ fn list_card(ll: &InterpList, lr: &InterpList, heap: &mut InterpImmediateHeap) -
     InterpVal::Ref(heap.push_obj(obj))
 }
 
-pub fn builtin_call(
-    proc: BuiltinProc,
-    params: &[IRValue],
+/// Real eigenvalues in ascending order paired with their unit eigenvectors.
+fn la_eigen(m: &DMatrix<f64>, data: &DebugData) -> Vec<(f64, DVector<f64>)> {
+    if !m.is_square() {
+        exception_throw(
+            "builtin procedure",
+            "eigen decomposition requires a square matrix",
+            data,
+        );
+    }
+
+    let n = m.nrows();
+    let tol = 1e-10 * m.amax().max(1.0);
+    let mut out: Vec<(f64, DVector<f64>)> = if (m - m.transpose()).amax() <= tol {
+        let eigen = m.clone().symmetric_eigen();
+        eigen
+            .eigenvalues
+            .iter()
+            .zip(eigen.eigenvectors.column_iter())
+            .map(|(val, vec)| (*val, vec.into_owned()))
+            .collect()
+    } else {
+        m.complex_eigenvalues()
+            .iter()
+            .map(|c| {
+                if c.im.abs() > tol {
+                    exception_throw(
+                        "builtin procedure",
+                        "complex eigenvalues are not supported",
+                        data,
+                    );
+                }
+
+                // the eigenvector spans the null space of m - λI, which is the right
+                // singular vector of the smallest singular value
+                let svd = (m - DMatrix::<f64>::identity(n, n) * c.re).svd(false, true);
+                (c.re, svd.v_t.unwrap().row(n - 1).transpose())
+            })
+            .collect()
+    };
+
+    for (_, vec) in out.iter_mut() {
+        if vec
+            .iter()
+            .find(|i| i.abs() > 1e-12)
+            .is_some_and(|i| *i < 0.0)
+        {
+            *vec *= -1.0;
+        }
+    }
+
+    out.sort_by(|a, b| a.0.total_cmp(&b.0));
+    out
+}
+
+/// Dereferences the parameters of the calling stub so that optional
+/// parameters can be inspected with [`InterpGet`] via [`IRValue::Variable`].
+fn args_collect(
+    params: &IRValue,
+    vars: &[InterpVal],
     params_proc: &InterpVal,
-    vars: &mut [InterpVal],
+    data: &DebugData,
+) -> Vec<InterpVal> {
+    params
+        .to_list(vars, params_proc, data)
+        .0
+        .iter()
+        .map(|i| match i {
+            // SAFETY: IR-PTR
+            InterpVal::Ptr(p) => unsafe { (*p.ptr).clone() },
+            _ => i.clone(),
+        })
+        .collect()
+}
+
+fn arg_given(args: &[InterpVal], idx: usize) -> bool {
+    args.get(idx)
+        .is_some_and(|i| !matches!(i, InterpVal::Undefined))
+}
+
+fn plot_color(
+    args: &[InterpVal],
+    idx: usize,
+    canvas: &mut PlotCanvas,
+    data: &DebugData,
+) -> PlotColor {
+    if !arg_given(args, idx) {
+        return canvas.color_next();
+    }
+
+    let c = IRValue::Variable(idx).to_vector(args, &InterpVal::Undefined, data, "plot color");
+    if c.len() != 3 {
+        exception_throw("builtin procedure", "plot color must be [r, g, b]", data);
+    }
+    let channel = |v: f64| v.clamp(0.0, 255.0) as u8;
+    (channel(c[0]), channel(c[1]), channel(c[2]))
+}
+
+fn plot_points(args: &[InterpVal], idx: usize, data: &DebugData, op: &str) -> Vec<(f64, f64)> {
+    if let InterpVal::Ref(r) = &args[idx]
+        && let InterpObj::List(l) = unsafe { &*r.0 }
+        && l.0.is_empty()
+    {
+        return Vec::new();
+    }
+
+    let m = IRValue::Variable(idx).to_matrix(args, &InterpVal::Undefined, data, op);
+    if m.ncols() != 2 {
+        exception_throw(
+            "builtin procedure",
+            &format!("{op} expects [[x, y], ...]"),
+            data,
+        );
+    }
+    m.row_iter().map(|r| (r[0], r[1])).collect()
+}
+
+fn plot_range(args: &[InterpVal], idx: usize, data: &DebugData, op: &str) -> (f64, f64) {
+    let r = IRValue::Variable(idx).to_vector(args, &InterpVal::Undefined, data, op);
+    if r.len() != 2 || r[0].partial_cmp(&r[1]) != Some(Ordering::Less) {
+        exception_throw(
+            "builtin procedure",
+            &format!("{op} expects an interval [lower, upper]"),
+            data,
+        );
+    }
+    (r[0], r[1])
+}
+
+/// Evaluates the expression `f` in the variable `x` for every input.
+/// Results that aren't real numbers are returned as NaN.
+fn plot_fn_eval(
+    f: &str,
+    xs: &[f64],
     stack: &mut InterpStack,
-    heap: &mut InterpImmediateHeap,
     memo: &mut InterpMemoize,
     cstore: &mut InterpClassStore,
     breakpoints: &mut DebugData,
     opts: &InputOpts,
     rl: &mut DefaultEditor,
-) -> InterpVal {
-    match proc {
-        BuiltinProc::Amount => {
-            let val = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
-            let len = amount_val(&val.val, breakpoints);
+) -> Vec<f64> {
+    let base = breakpoints.src_add_eval(f, "plot");
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let new_opts = opts.exec_opts();
+        let expr = cst_expr_parse_at(f, &new_opts, base);
+        let eval_proc = IRCfg::from_expr(&expr, &new_opts);
 
-            InterpVal::Ref(heap.push_obj(InterpObj::Number(len.into())))
-        }
-        BuiltinProc::Contains => {
-            let set = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
-            let i = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
-
-            let out = match &set.val {
-                InterpVal::Ref(r) => match unsafe { &*r.0 } {
-                    // FIXME: don't use InterpObjVal
-                    InterpObj::Set(s) => InterpVal::Bool(s.0.contains(&i.val)),
-                    InterpObj::List(l) => InterpVal::Bool(l.contains(&i.val)),
-                    InterpObj::String(s) => {
-                        let needle = if let InterpVal::Ref(r) = i.val
-                            && let InterpObj::String(n) = unsafe { &*r.0 }
-                        {
-                            n.to_string()
-                        } else {
-                            serialize(
-                                &i.val,
-                                vars,
-                                stack,
-                                memo,
-                                cstore,
-                                breakpoints,
-                                opts,
-                                rl,
-                                SerializeOpts::default(),
-                            )
-                        };
-
-                        InterpVal::Bool(s.contains(&needle))
-                    }
-                    _ => exception_throw(
-                        "builtin procedure",
-                        "contains not defined for type",
-                        breakpoints,
-                    ),
-                },
-                _ => exception_throw(
-                    "builtin procedure",
-                    "contains not defined for type",
+        xs.iter()
+            .map(|x| {
+                stack.frame_push();
+                stack.push(InterpStackEntry::Variable(InterpStackVar {
+                    var: IRSymbol::new("x"),
+                    val: Box::new(InterpVal::Double(*x)),
+                }));
+                let y = exec_proc(
+                    eval_proc.clone(),
+                    &InterpVal::Undefined,
+                    stack,
+                    memo,
+                    cstore,
                     breakpoints,
-                ),
-            };
-
-            set.confirm();
-            i.confirm();
+                    &new_opts,
+                    rl,
+                );
+                stack.frame_pop();
 
-            out
-        }
-        BuiltinProc::Cartesian => {
-            let lhs = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
-            let rhs = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
-
-            let out = match (&lhs.val, &rhs.val) {
-                (InterpVal::Ref(rl), InterpVal::Ref(rr)) => {
-                    match (unsafe { &*rl.0 }, unsafe { &*rr.0 }) {
-                        (InterpObj::List(ll), InterpObj::List(lr)) => list_card(ll, lr, heap),
-                        (InterpObj::Set(sl), InterpObj::Set(sr)) => set_card(&sl.0, &sr.0, heap),
-                        _ => exception_throw(
-                            "builtin procedure",
-                            "cartesian undefined for type",
-                            breakpoints,
-                        ),
+                match y {
+                    InterpVal::Double(d) => d,
+                    InterpVal::Ref(r) => {
+                        let d = obj_to_f64(unsafe { &*r.0 }).unwrap_or(f64::NAN);
+                        unsafe { r.invalidate() };
+                        d
                     }
+                    _ => f64::NAN,
                 }
-                _ => exception_throw(
-                    "builtin procedure",
-                    "cartesian undefined for type",
-                    breakpoints,
-                ),
-            };
+            })
+            .collect::<Vec<_>>()
+    }));
 
-            lhs.confirm();
-            rhs.confirm();
+    match result {
+        Ok(o) => o,
+        Err(e) => {
+            if let Some(s) = e.downcast_ref::<&str>() {
+                exception_throw_raw(s);
+            } else if let Some(s) = e.downcast_ref::<String>() {
+                exception_throw_raw(s);
+            } else {
+                exception_throw_raw("plot function yielded error with non-string payload");
+            }
+        }
+    }
+}
 
-            out
+fn plot_segments(points: impl Iterator<Item = (f64, f64)>) -> Vec<Vec<(f64, f64)>> {
+    let mut out = vec![Vec::new()];
+    for (x, y) in points {
+        if x.is_finite() && y.is_finite() {
+            out.last_mut().unwrap().push((x, y));
+        } else if !out.last().unwrap().is_empty() {
+            out.push(Vec::new());
         }
-        BuiltinProc::Pow => {
-            let lhs = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
-            let rhs = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
+    }
+    out
+}
+
+fn native_amount(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let val = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
+    let len = amount_val(&val.val, breakpoints);
+
+    InterpVal::Ref(heap.push_obj(InterpObj::Number(len.into())))
+}
+
+fn native_contains(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let set = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
+    let i = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
 
-            fn powset(r: InterpObjRef, heap: &mut InterpImmediateHeap) -> InterpVal {
-                let set = if let InterpObj::Set(s) = unsafe { &*r.0 } {
-                    s
+    let out = match &set.val {
+        InterpVal::Ref(r) => match unsafe { &*r.0 } {
+            // FIXME: don't use InterpObjVal
+            InterpObj::Set(s) => InterpVal::Bool(s.0.contains(&i.val)),
+            InterpObj::List(l) => InterpVal::Bool(l.contains(&i.val)),
+            InterpObj::String(s) => {
+                let needle = if let InterpVal::Ref(r) = i.val
+                    && let InterpObj::String(n) = unsafe { &*r.0 }
+                {
+                    n.to_string()
                 } else {
-                    unreachable!()
+                    serialize(
+                        &i.val,
+                        vars,
+                        stack,
+                        memo,
+                        cstore,
+                        breakpoints,
+                        opts,
+                        rl,
+                        SerializeOpts::default(),
+                    )
                 };
-                let items = set.0.iter().cloned().collect::<Vec<_>>();
-                let n = items.len();
-                let mut result = BTreeSet::new();
-                for mask in 0..(1 << n) {
-                    let mut subset = BTreeSet::new();
-                    for (idx, i) in items.iter().enumerate() {
-                        if (mask & (1 << idx)) != 0 {
-                            subset.insert(i.unshare());
-                        }
-                    }
-                    result.insert(InterpVal::Ref(InterpObjRef::from_obj(InterpObj::Set(
-                        InterpSet(subset),
-                    ))));
-                }
 
-                InterpVal::Ref(heap.push(InterpObjRef::from_obj(InterpObj::Set(InterpSet(result)))))
+                InterpVal::Bool(s.contains(&needle))
             }
+            _ => exception_throw(
+                "builtin procedure",
+                "contains not defined for type",
+                breakpoints,
+            ),
+        },
+        _ => exception_throw(
+            "builtin procedure",
+            "contains not defined for type",
+            breakpoints,
+        ),
+    };
 
-            /* 2 ** set
-             * 2.0 ** set
-             * set ** 2
-             * set ** 2.0
-             * NUMBER ** NUMBER
-             * NUMBER ** DOUBLE
-             * DOUBLE ** NUMBER
-             * DOUBLE ** DOUBLE
-             */
-            let out = match (&lhs.val, &rhs.val) {
-                (InterpVal::Ref(rl), InterpVal::Ref(rr)) => {
-                    match (unsafe { &*rl.0 }, unsafe { &*rr.0 }) {
-                        (InterpObj::Number(nl), InterpObj::Set(_)) => {
-                            if *nl == 2.into() {
-                                powset(*rr, heap)
-                            } else {
-                                exception_throw(
-                                    "builtin procedure",
-                                    "pow undefined for type",
-                                    breakpoints,
-                                );
-                            }
-                        }
-                        (InterpObj::Set(sl), InterpObj::Number(nr)) => {
-                            if *nr == 2.into() {
-                                set_card(&sl.0, &sl.0, heap)
-                            } else {
-                                exception_throw(
-                                    "builtin procedure",
-                                    "pow undefined for type",
-                                    breakpoints,
-                                );
-                            }
-                        }
-                        (InterpObj::Number(nl), InterpObj::Number(nr)) => {
-                            InterpVal::Double(nl.to_f64().unwrap().pow(nr.to_i32().unwrap()))
-                        }
-                        _ => exception_throw(
-                            "builtin procedure",
-                            "pow undefined for type",
-                            breakpoints,
-                        ),
-                    }
-                }
-                (InterpVal::Ref(r), InterpVal::Double(dr)) => match unsafe { &*r.0 } {
-                    InterpObj::Set(s) => {
-                        if *dr == 2.0 {
-                            set_card(&s.0, &s.0, heap)
-                        } else {
-                            exception_throw(
-                                "builtin procedure",
-                                "pow undefined for type",
-                                breakpoints,
-                            );
-                        }
-                    }
-                    InterpObj::Number(n) => InterpVal::Double(n.to_f64().unwrap().powf(*dr)),
-                    _ => {
-                        exception_throw("builtin procedure", "pow undefined for type", breakpoints)
-                    }
-                },
-                (InterpVal::Double(dl), InterpVal::Ref(r)) => match unsafe { &*r.0 } {
-                    InterpObj::Set(_) => {
-                        if *dl == 2.0 {
-                            powset(*r, heap)
-                        } else {
-                            exception_throw(
-                                "builtin procedure",
-                                "pow undefined for type",
-                                breakpoints,
-                            );
-                        }
-                    }
-                    InterpObj::Number(n) => InterpVal::Double(dl.powf(n.to_f64().unwrap())),
-                    _ => {
-                        exception_throw("builtin procedure", "pow undefined for type", breakpoints)
-                    }
-                },
-                (InterpVal::Double(dl), InterpVal::Double(dr)) => InterpVal::Double(dl.powf(*dr)),
-                _ => exception_throw("builtin procedure", "pow undefined for type", breakpoints),
-            };
+    set.confirm();
+    i.confirm();
 
-            lhs.confirm();
-            rhs.confirm();
+    out
+}
 
-            out
-        }
-        BuiltinProc::TypeOf => InterpVal::Type(params[0].to_type(vars, params_proc, opts, heap)),
-        BuiltinProc::TermNew => {
-            let tag = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let len = params[1].to_usize(vars, params_proc, breakpoints, "term_new");
-            let is_tterm = params[2].to_immed_bool("term_new");
-
-            let obj = InterpTaggedList {
-                tag,
-                list: vec![InterpVal::Undefined; len],
-            };
-
-            if is_tterm {
-                InterpVal::Ref(heap.push_obj(InterpObj::TTerm(obj)))
-            } else {
-                InterpVal::Ref(heap.push_obj(InterpObj::Term(obj)))
-            }
-        }
-        BuiltinProc::TermKindEq => {
-            let lhs = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
-            let rhs = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
-
-            match (&lhs.val, &rhs.val) {
-                (InterpVal::Ref(rl), InterpVal::Ref(rr)) => {
-                    match (unsafe { &*rl.0 }, unsafe { &*rr.0 }) {
-                        (InterpObj::Ast(ll), InterpObj::Ast(lr))
-                        | (InterpObj::Term(ll), InterpObj::Term(lr))
-                        | (InterpObj::TTerm(ll), InterpObj::TTerm(lr)) => {
-                            InterpVal::Bool(ll.tag == lr.tag)
-                        }
-
-                        _ => InterpVal::Bool(false),
-                    }
-                }
-                _ => InterpVal::Bool(false),
-            }
-        }
-        BuiltinProc::Invalidate => {
-            if let Some(r) = params[0].to_ref(vars) {
-                unsafe {
-                    heap.free(r);
-                }
-            }
+fn native_cartesian(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let lhs = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
+    let rhs = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
 
-            InterpVal::Undefined
-        }
-        BuiltinProc::MarkPersist => {
-            if let Some(r) = params[0].to_ref(vars) {
-                InterpVal::Ref(r).persist(heap);
-            }
+    let out = match (&lhs.val, &rhs.val) {
+        (InterpVal::Ref(rl), InterpVal::Ref(rr)) => match (unsafe { &*rl.0 }, unsafe { &*rr.0 }) {
+            (InterpObj::List(ll), InterpObj::List(lr)) => list_card(ll, lr, heap),
+            (InterpObj::Set(sl), InterpObj::Set(sr)) => set_card(&sl.0, &sr.0, heap),
+            _ => exception_throw(
+                "builtin procedure",
+                "cartesian undefined for type",
+                breakpoints,
+            ),
+        },
+        _ => exception_throw(
+            "builtin procedure",
+            "cartesian undefined for type",
+            breakpoints,
+        ),
+    };
 
-            InterpVal::Undefined
-        }
-        BuiltinProc::MarkImmed => {
-            if let Some(r) = params[0].to_ref(vars) {
-                InterpVal::Ref(r).mark_immed(heap);
-            }
+    lhs.confirm();
+    rhs.confirm();
 
-            InterpVal::Undefined
-        }
-        BuiltinProc::Copy => params[0]
-            .to_val(vars, params_proc, breakpoints, opts, heap)
-            .confirm()
-            .val
-            .unshare_immed(heap),
-        BuiltinProc::StackFrameAdd => {
-            stack.frame_push();
-            InterpVal::Undefined
-        }
-        BuiltinProc::StackFramePop => {
-            stack.frame_pop();
-            InterpVal::Undefined
-        }
-        BuiltinProc::StackFrameSave => {
-            let obj = InterpObj::StackImage(stack.frame_save());
-            InterpVal::Ref(heap.push_obj(obj))
-        }
-        BuiltinProc::StackFrameRestore => {
-            if let IRValue::Variable(v) = &params[0]
-                && let InterpVal::Ref(r) = &vars[*v]
-                && let InterpObj::StackImage(i) = unsafe { &*r.0 }
-            {
-                stack.frame_restore(i)
-            } else {
-                panic!("internal: stack_frame_restore undefined for type");
-            }
-            InterpVal::Undefined
-        }
-        BuiltinProc::StackFrameCopy => {
-            let obj = InterpObj::StackImage(stack.frame_copy());
-            InterpVal::Ref(heap.push_obj(obj))
-        }
-        BuiltinProc::StackCopy => {
-            let obj = InterpObj::StackImage(stack.copy_reachable());
-            InterpVal::Ref(heap.push_obj(obj))
-        }
-        BuiltinProc::StackAdd => stack.add(params[0].to_immed_str("stack_add")),
-        BuiltinProc::StackAlias => {
-            let name = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let ptr = params[1].to_ptr(vars, "stack_alias");
-            let cross_frame = params[2].to_immed_bool("stack_alias");
+    out
+}
 
-            stack.alias(&name, &ptr, cross_frame);
-            InterpVal::Undefined
-        }
-        BuiltinProc::StackPop => {
-            let s = params[0].to_immed_str("stack_pop");
+fn native_pow(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let lhs = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
+    let rhs = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
 
-            stack.pop(s);
-            InterpVal::Undefined
-        }
-        BuiltinProc::StackGetOrNew => {
-            let s = if let IRValue::String(s) = &params[0] {
-                s
-            } else {
-                panic!("internal: stack get assert requires a string literal parameter");
-            };
-
-            if let Some(c) = cstore.0.get_mut(s) {
-                InterpVal::Ptr(InterpPtr {
-                    sgmt: InterpPtrSgmt::Class,
-                    ptr: &mut c.val as *mut InterpVal,
-                })
-            } else if let Some(i) = stack.get(s) {
-                i
-            } else {
-                if opts.warn_implicit_decl {
-                    eprintln!(
-                        "{}: implicit declaration of {}",
-                        "Warning".yellow().bold(),
-                        s.as_str().cyan()
-                    );
+    fn powset(r: InterpObjRef, heap: &mut InterpImmediateHeap) -> InterpVal {
+        let set = if let InterpObj::Set(s) = unsafe { &*r.0 } {
+            s
+        } else {
+            unreachable!()
+        };
+        let items = set.0.iter().cloned().collect::<Vec<_>>();
+        let n = items.len();
+        let mut result = BTreeSet::new();
+        for mask in 0..(1 << n) {
+            let mut subset = BTreeSet::new();
+            for (idx, i) in items.iter().enumerate() {
+                if (mask & (1 << idx)) != 0 {
+                    subset.insert(i.unshare());
                 }
-
-                stack.add(s)
             }
+            result.insert(InterpVal::Ref(InterpObjRef::from_obj(InterpObj::Set(
+                InterpSet(subset),
+            ))));
         }
-        BuiltinProc::StackInScope => InterpVal::Bool(
-            stack
-                .get(params[0].to_immed_str("stack_in_scope"))
-                .is_some(),
-        ),
-        BuiltinProc::ObjectNew => {
-            let name = if let IRValue::String(s) = &params[0] {
-                s
-            } else {
-                panic!("internal: object_new undefined for type");
-            };
 
-            let mut stack = BTreeMap::new();
+        InterpVal::Ref(heap.push(InterpObjRef::from_obj(InterpObj::Set(InterpSet(result)))))
+    }
 
-            if let Some(c) = cstore.0.get(name) {
-                stack.insert(
-                    String::from("getClass"),
-                    Box::new(InterpVal::Procedure(c.get_proc.clone())),
-                );
-                #[allow(clippy::collapsible_if)]
-                if let InterpVal::Ref(r) = c.val {
-                    if let InterpObj::Class(c) = unsafe { &*r.0 } {
-                        stack.extend(
-                            c.static_vars
-                                .iter()
-                                .map(|(s, v)| (s.to_string(), Box::new(v.unshare())))
-                                .collect::<Vec<(String, Box<InterpVal>)>>(),
-                        );
-                    }
+    /* 2 ** set
+     * 2.0 ** set
+     * set ** 2
+     * set ** 2.0
+     * NUMBER ** NUMBER
+     * RATIONAL ** NUMBER
+     * NUMBER ** RATIONAL
+     * RATIONAL ** RATIONAL
+     * NUMBER ** DOUBLE
+     * DOUBLE ** NUMBER
+     * DOUBLE ** DOUBLE
+     *
+     * integer exponents of numbers and rationals give exact results
+     */
+    let out = match (&lhs.val, &rhs.val) {
+        (InterpVal::Ref(rl), InterpVal::Ref(rr)) => match (unsafe { &*rl.0 }, unsafe { &*rr.0 }) {
+            (InterpObj::Number(nl), InterpObj::Set(_)) => {
+                if *nl == 2.into() {
+                    powset(*rr, heap)
+                } else {
+                    exception_throw("builtin procedure", "pow undefined for type", breakpoints);
                 }
-            } else {
-                panic!("internal: object can't be created without an associated class");
             }
-
-            InterpVal::Ref(heap.push_obj(InterpObj::Object(InterpClassObj(stack))))
-        }
-        BuiltinProc::ObjectGetOrNew => {
-            let obj = params[0]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .clone();
-            let var = params[1].to_immed_str("object_get_or_new");
-
-            if let InterpVal::Ref(r) = obj {
-                match unsafe { &mut *r.0 } {
-                    InterpObj::Object(o) => o.get(var).unwrap_or_else(|| o.add(var.to_string())),
-                    InterpObj::Class(c) => c.get(var).unwrap_or_else(|| c.add(var.to_string())),
-                    _ => {
-                        exception_throw("builtin procedure", "value is not an object", breakpoints)
-                    }
+            (InterpObj::Set(sl), InterpObj::Number(nr)) => {
+                if *nr == 2.into() {
+                    set_card(&sl.0, &sl.0, heap)
+                } else {
+                    exception_throw("builtin procedure", "pow undefined for type", breakpoints);
                 }
-            } else {
-                exception_throw("builtin procedure", "value is not an object", breakpoints);
             }
-        }
-        BuiltinProc::ObjectGet => {
-            let obj = params[0]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .clone();
-            let var = params[1].to_str(vars, params_proc, breakpoints, opts, heap);
-            if let InterpVal::Ref(r) = obj {
-                match unsafe { &mut *r.0 } {
-                    InterpObj::Object(o) => o.get(&var).unwrap_or(InterpVal::Undefined),
-                    InterpObj::Class(c) => c.get(&var).unwrap_or(InterpVal::Undefined),
-                    _ => {
-                        exception_throw("builtin procedure", "value is not an object", breakpoints)
-                    }
+            (l @ (InterpObj::Number(_) | InterpObj::Rational(_)), InterpObj::Number(nr)) => {
+                let base = obj_to_rational(l).unwrap();
+                let Some(exp) = nr.to_i32() else {
+                    exception_throw("builtin procedure", "pow: exponent too large", breakpoints);
+                };
+                if base.is_zero() && exp < 0 {
+                    exception_throw("builtin procedure", "division by zero", breakpoints);
                 }
-            } else {
-                exception_throw("builtin procedure", "value is not an object", breakpoints);
+
+                InterpVal::Ref(heap.push_obj(InterpObj::from_rational(base.pow(exp))))
             }
-        }
-        BuiltinProc::ObjectAdd => {
-            let obj = params[0]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .clone();
-            let var = params[1].to_str(vars, params_proc, breakpoints, opts, heap);
-            if let InterpVal::Ref(r) = obj {
-                match unsafe { &mut *r.0 } {
-                    InterpObj::Object(o) => o.add(var),
-                    InterpObj::Class(c) => c.add(var),
-                    _ => {
-                        exception_throw("builtin procedure", "value is not an object", breakpoints)
-                    }
+            (l @ (InterpObj::Number(_) | InterpObj::Rational(_)), r @ InterpObj::Rational(_)) => {
+                InterpVal::Double(obj_to_f64(l).unwrap().powf(obj_to_f64(r).unwrap()))
+            }
+            _ => exception_throw("builtin procedure", "pow undefined for type", breakpoints),
+        },
+        (InterpVal::Ref(r), InterpVal::Double(dr)) => match unsafe { &*r.0 } {
+            InterpObj::Set(s) => {
+                if *dr == 2.0 {
+                    set_card(&s.0, &s.0, heap)
+                } else {
+                    exception_throw("builtin procedure", "pow undefined for type", breakpoints);
                 }
-            } else {
-                exception_throw("builtin procedure", "value is not an object", breakpoints);
             }
-        }
-        BuiltinProc::ObjectAddImage => {
-            let obj = params[0]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .clone();
-            let mut stack = BTreeMap::new();
-
-            let s_obj = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
-            if let InterpVal::Ref(r) = &s_obj.val
-                && let InterpObj::StackImage(s) = unsafe { &*r.0 }
-            {
-                stack.extend(
-                    s.0.iter()
-                        .map(|(name, i)| (name.clone(), Box::new(i.clone())))
-                        .collect::<BTreeMap<String, Box<InterpVal>>>(),
-                )
-            } else {
-                panic!("internal: object_new undefined for type");
+            n @ (InterpObj::Number(_) | InterpObj::Rational(_)) => {
+                InterpVal::Double(obj_to_f64(n).unwrap().powf(*dr))
             }
-
-            s_obj.confirm();
-
-            if let InterpVal::Ref(r) = obj {
-                match unsafe { &mut *r.0 } {
-                    InterpObj::Object(o) => o.0.extend(stack),
-                    InterpObj::Class(c) => c.static_vars.extend(stack),
-                    _ => {
-                        exception_throw("builtin procedure", "value is not an object", breakpoints)
-                    }
+            _ => exception_throw("builtin procedure", "pow undefined for type", breakpoints),
+        },
+        (InterpVal::Double(dl), InterpVal::Ref(r)) => match unsafe { &*r.0 } {
+            InterpObj::Set(_) => {
+                if *dl == 2.0 {
+                    powset(*r, heap)
+                } else {
+                    exception_throw("builtin procedure", "pow undefined for type", breakpoints);
                 }
-            } else {
-                exception_throw("builtin procedure", "value is not an object", breakpoints);
             }
-
-            InterpVal::Undefined
-        }
-        BuiltinProc::ObjectIterNew => {
-            InterpVal::ObjIter(params[0].to_obj_iter(vars, params_proc, breakpoints))
-        }
-        BuiltinProc::ObjectIterNext => {
-            let key_ptr = params[1].to_ptr(vars, "obj_iter_next").ptr;
-            let val_ptr_ptr = params[2].to_ptr(vars, "obj_iter_next").ptr;
-            let iter = params[0].to_obj_iter_ref(vars, breakpoints);
-
-            if let Some((key, val)) = iter.next() {
-                // SAFETY: IR-PTR
-                unsafe {
-                    *key_ptr = InterpVal::Ref(heap.push_obj(InterpObj::String(key.to_string())));
-                    *val_ptr_ptr = InterpVal::Ptr(InterpPtr {
-                        sgmt: InterpPtrSgmt::Heap,
-                        ptr: &**val as *const InterpVal as *mut InterpVal,
-                    });
-                }
-                InterpVal::Bool(true)
-            } else {
-                InterpVal::Bool(false)
+            n @ (InterpObj::Number(_) | InterpObj::Rational(_)) => {
+                InterpVal::Double(dl.powf(obj_to_f64(n).unwrap()))
             }
-        }
-        BuiltinProc::ClassAdd => {
-            let name = if let IRValue::String(s) = &params[0] {
-                s
-            } else {
-                panic!("internal: class_add undefined for type");
-            };
-            let s_proc = params[1]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .proc_get()
-                .unwrap_or_else(|| panic!("internal: class_add undefined for type"));
-            let c_proc = params[2]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .proc_get()
-                .unwrap_or_else(|| panic!("internal: class_add undefined for type"));
-
-            let mut new_opts = InputOpts::none();
-            new_opts.debug_ir = opts.debug_ir;
-            let s_map = if let InterpVal::Ref(r) = exec_proc(
-                s_proc,
-                &InterpVal::Undefined,
-                stack,
-                memo,
-                cstore,
-                breakpoints,
-                &new_opts,
-                rl,
-            ) && let InterpObj::StackImage(s) = unsafe { &*r.0 }
-            {
-                s.0.iter()
-                    .map(|(name, i)| (name.clone(), Box::new(i.clone())))
-                    .collect::<BTreeMap<String, Box<InterpVal>>>()
-            } else {
-                panic!("internal: class_add static proc must return a stack image");
-            };
+            _ => exception_throw("builtin procedure", "pow undefined for type", breakpoints),
+        },
+        (InterpVal::Double(dl), InterpVal::Double(dr)) => InterpVal::Double(dl.powf(*dr)),
+        _ => exception_throw("builtin procedure", "pow undefined for type", breakpoints),
+    };
 
-            cstore.insert(name.to_string(), c_proc, s_map);
+    lhs.confirm();
+    rhs.confirm();
 
-            InterpVal::Undefined
-        }
-        BuiltinProc::SetNew => {
-            InterpVal::Ref(heap.push_obj(InterpObj::Set(InterpSet(BTreeSet::new()))))
-        }
-        BuiltinProc::SetInsert => {
-            let push_val = params[1]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .clone()
-                .persist(heap);
-            let push_val_ptr = &push_val as *const InterpVal as *mut InterpVal;
-            if let InterpVal::Ref(r) = params[0]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                && let InterpObj::Set(s) = unsafe { &mut *r.0 }
-            {
-                if !matches!(&push_val, InterpVal::Undefined) {
-                    s.0.insert(push_val);
-                    InterpVal::Ptr(InterpPtr {
-                        sgmt: InterpPtrSgmt::Heap,
-                        ptr: push_val_ptr,
-                    })
-                } else {
-                    InterpVal::Undefined
-                }
-            } else {
-                panic!("internal: list push undefined for type");
-            }
-        }
-        BuiltinProc::SetRange => {
-            let lhs = params[0].to_i64(vars, params_proc, breakpoints, "set_range");
-            let rhs = params[1].to_i64(vars, params_proc, breakpoints, "set_range");
+    out
+}
 
-            let obj = if lhs > rhs {
-                InterpObj::Set(InterpSet(BTreeSet::new()))
-            } else {
-                InterpObj::Set(InterpSet(
-                    (lhs..=rhs)
-                        .map(|i| {
-                            InterpVal::Ref(InterpObjRef::from_obj(InterpObj::Number(i.into())))
-                        })
-                        .collect(),
-                ))
-            };
-
-            InterpVal::Ref(heap.push_obj(obj))
-        }
-        BuiltinProc::SetBorrow => {
-            if params[1].to_bool(vars, params_proc, breakpoints, "set_borrow") {
-                if let Some(i) = params[0].to_set(vars, params_proc, breakpoints).first() {
-                    i.clone()
-                } else {
-                    exception_throw(
-                        "builtin procedure",
-                        "set_borrow is only implemented for sets",
-                        breakpoints,
-                    );
-                }
-            } else if let Some(i) = params[0].to_set(vars, params_proc, breakpoints).last() {
-                i.clone()
+fn native_type_of(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Type(params[0].to_type(vars, params_proc, opts, heap))
+}
+
+fn native_term_new(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let tag = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let len = params[1].to_usize(vars, params_proc, breakpoints, "term_new");
+    let is_tterm = params[2].to_immed_bool("term_new");
+
+    let obj = InterpTaggedList {
+        tag,
+        list: vec![InterpVal::Undefined; len],
+    };
+
+    if is_tterm {
+        InterpVal::Ref(heap.push_obj(InterpObj::TTerm(obj)))
+    } else {
+        InterpVal::Ref(heap.push_obj(InterpObj::Term(obj)))
+    }
+}
+
+fn native_term_kind_eq(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let lhs = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
+    let rhs = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
+
+    match (&lhs.val, &rhs.val) {
+        (InterpVal::Ref(rl), InterpVal::Ref(rr)) => match (unsafe { &*rl.0 }, unsafe { &*rr.0 }) {
+            (InterpObj::Ast(ll), InterpObj::Ast(lr))
+            | (InterpObj::Term(ll), InterpObj::Term(lr))
+            | (InterpObj::TTerm(ll), InterpObj::TTerm(lr)) => InterpVal::Bool(ll.tag == lr.tag),
+
+            _ => InterpVal::Bool(false),
+        },
+        _ => InterpVal::Bool(false),
+    }
+}
+
+fn native_invalidate(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    if let Some(r) = params[0].to_ref(vars) {
+        unsafe {
+            heap.free(r);
+        }
+    }
+
+    InterpVal::Undefined
+}
+
+fn native_mark_persist(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    if let Some(r) = params[0].to_ref(vars) {
+        InterpVal::Ref(r).persist(heap);
+    }
+
+    InterpVal::Undefined
+}
+
+fn native_mark_immed(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    if let Some(r) = params[0].to_ref(vars) {
+        InterpVal::Ref(r).mark_immed(heap);
+    }
+
+    InterpVal::Undefined
+}
+
+fn native_copy(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    params[0]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .unshare_immed(heap)
+}
+
+fn native_stack_frame_add(
+    _params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    stack.frame_push();
+    InterpVal::Undefined
+}
+
+fn native_stack_frame_pop(
+    _params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    stack.frame_pop();
+    InterpVal::Undefined
+}
+
+fn native_stack_frame_save(
+    _params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let obj = InterpObj::StackImage(stack.frame_save());
+    InterpVal::Ref(heap.push_obj(obj))
+}
+
+fn native_stack_frame_restore(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    if let IRValue::Variable(v) = &params[0]
+        && let InterpVal::Ref(r) = &vars[*v]
+        && let InterpObj::StackImage(i) = unsafe { &*r.0 }
+    {
+        stack.frame_restore(i)
+    } else {
+        panic!("internal: stack_frame_restore undefined for type");
+    }
+    InterpVal::Undefined
+}
+
+fn native_stack_frame_copy(
+    _params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let obj = InterpObj::StackImage(stack.frame_copy());
+    InterpVal::Ref(heap.push_obj(obj))
+}
+
+fn native_stack_copy(
+    _params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let obj = InterpObj::StackImage(stack.copy_reachable());
+    InterpVal::Ref(heap.push_obj(obj))
+}
+
+fn native_stack_add(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    stack.add(params[0].to_immed_sym("stack_add"))
+}
+
+fn native_stack_alias(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let name = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let ptr = params[1].to_ptr(vars, "stack_alias");
+    let cross_frame = params[2].to_immed_bool("stack_alias");
+
+    stack.alias(IRSymbol::new(&name), &ptr, cross_frame);
+    InterpVal::Undefined
+}
+
+fn native_stack_pop(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    stack.pop(params[0].to_immed_sym("stack_pop"));
+    InterpVal::Undefined
+}
+
+fn native_stack_get_or_new(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let s = params[0].to_immed_sym("stack_get_or_new");
+
+    if let Some(c) = cstore.0.get_mut(&*s.name()) {
+        InterpVal::Ptr(InterpPtr {
+            sgmt: InterpPtrSgmt::Class,
+            ptr: &mut c.val as *mut InterpVal,
+        })
+    } else if let Some(i) = stack.get(s) {
+        i
+    } else {
+        if opts.warn_implicit_decl {
+            eprintln!(
+                "{}: implicit declaration of {}",
+                "Warning".yellow().bold(),
+                s.to_string().cyan()
+            );
+        }
+
+        stack.add(s)
+    }
+}
+
+fn native_stack_in_scope(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Bool(
+        stack
+            .get(params[0].to_immed_sym("stack_in_scope"))
+            .is_some(),
+    )
+}
+
+fn native_object_new(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let name = if let IRValue::String(s) = &params[0] {
+        s
+    } else {
+        panic!("internal: object_new undefined for type");
+    };
+
+    let mut stack = BTreeMap::new();
+
+    if let Some(c) = cstore.0.get(name) {
+        stack.insert(
+            String::from("getClass"),
+            Box::new(InterpVal::Procedure(c.get_proc.clone())),
+        );
+        #[allow(clippy::collapsible_if)]
+        if let InterpVal::Ref(r) = c.val {
+            if let InterpObj::Class(c) = unsafe { &*r.0 } {
+                stack.extend(
+                    c.static_vars
+                        .iter()
+                        .map(|(s, v)| (s.to_string(), Box::new(v.unshare())))
+                        .collect::<Vec<(String, Box<InterpVal>)>>(),
+                );
+            }
+        }
+    } else {
+        panic!("internal: object can't be created without an associated class");
+    }
+
+    InterpVal::Ref(heap.push_obj(InterpObj::Object(InterpClassObj(stack))))
+}
+
+fn native_object_get_or_new(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let obj = params[0]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .clone();
+    let var = params[1].to_immed_str("object_get_or_new");
+
+    if let InterpVal::Ref(r) = obj {
+        match unsafe { &mut *r.0 } {
+            InterpObj::Object(o) => o.get(var).unwrap_or_else(|| o.add(var.to_string())),
+            InterpObj::Class(c) => c.get(var).unwrap_or_else(|| c.add(var.to_string())),
+            _ => exception_throw("builtin procedure", "value is not an object", breakpoints),
+        }
+    } else {
+        exception_throw("builtin procedure", "value is not an object", breakpoints);
+    }
+}
+
+fn native_object_get(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let obj = params[0]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .clone();
+    let var = params[1].to_str(vars, params_proc, breakpoints, opts, heap);
+    if let InterpVal::Ref(r) = obj {
+        match unsafe { &mut *r.0 } {
+            InterpObj::Object(o) => o.get(&var).unwrap_or(InterpVal::Undefined),
+            InterpObj::Class(c) => c.get(&var).unwrap_or(InterpVal::Undefined),
+            _ => exception_throw("builtin procedure", "value is not an object", breakpoints),
+        }
+    } else {
+        exception_throw("builtin procedure", "value is not an object", breakpoints);
+    }
+}
+
+fn native_object_add(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let obj = params[0]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .clone();
+    let var = params[1].to_str(vars, params_proc, breakpoints, opts, heap);
+    if let InterpVal::Ref(r) = obj {
+        match unsafe { &mut *r.0 } {
+            InterpObj::Object(o) => o.add(var),
+            InterpObj::Class(c) => c.add(var),
+            _ => exception_throw("builtin procedure", "value is not an object", breakpoints),
+        }
+    } else {
+        exception_throw("builtin procedure", "value is not an object", breakpoints);
+    }
+}
+
+fn native_object_add_image(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let obj = params[0]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .clone();
+    let mut stack = BTreeMap::new();
+
+    let s_obj = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
+    if let InterpVal::Ref(r) = &s_obj.val
+        && let InterpObj::StackImage(s) = unsafe { &*r.0 }
+    {
+        stack.extend(
+            s.0.iter()
+                .map(|(name, i)| (name.clone(), Box::new(i.clone())))
+                .collect::<BTreeMap<String, Box<InterpVal>>>(),
+        )
+    } else {
+        panic!("internal: object_new undefined for type");
+    }
+
+    s_obj.confirm();
+
+    if let InterpVal::Ref(r) = obj {
+        match unsafe { &mut *r.0 } {
+            InterpObj::Object(o) => o.0.extend(stack),
+            InterpObj::Class(c) => c.static_vars.extend(stack),
+            _ => exception_throw("builtin procedure", "value is not an object", breakpoints),
+        }
+    } else {
+        exception_throw("builtin procedure", "value is not an object", breakpoints);
+    }
+
+    InterpVal::Undefined
+}
+
+fn native_object_iter_new(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::ObjIter(params[0].to_obj_iter(vars, params_proc, breakpoints))
+}
+
+fn native_object_iter_next(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let key_ptr = params[1].to_ptr(vars, "obj_iter_next").ptr;
+    let val_ptr_ptr = params[2].to_ptr(vars, "obj_iter_next").ptr;
+    let iter = params[0].to_obj_iter_ref(vars, breakpoints);
+
+    if let Some((key, val)) = iter.next() {
+        // SAFETY: IR-PTR
+        unsafe {
+            *key_ptr = InterpVal::Ref(heap.push_obj(InterpObj::String(key.to_string())));
+            *val_ptr_ptr = InterpVal::Ptr(InterpPtr {
+                sgmt: InterpPtrSgmt::Heap,
+                ptr: &**val as *const InterpVal as *mut InterpVal,
+            });
+        }
+        InterpVal::Bool(true)
+    } else {
+        InterpVal::Bool(false)
+    }
+}
+
+fn native_class_add(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let name = if let IRValue::String(s) = &params[0] {
+        s
+    } else {
+        panic!("internal: class_add undefined for type");
+    };
+    let s_proc = params[1]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .proc_get()
+        .unwrap_or_else(|| panic!("internal: class_add undefined for type"));
+    let c_proc = params[2]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .proc_get()
+        .unwrap_or_else(|| panic!("internal: class_add undefined for type"));
+
+    let mut new_opts = InputOpts::none();
+    new_opts.debug_ir = opts.debug_ir;
+    let s_map = if let InterpVal::Ref(r) = exec_proc(
+        s_proc,
+        &InterpVal::Undefined,
+        stack,
+        memo,
+        cstore,
+        breakpoints,
+        &new_opts,
+        rl,
+    ) && let InterpObj::StackImage(s) = unsafe { &*r.0 }
+    {
+        s.0.iter()
+            .map(|(name, i)| (name.clone(), Box::new(i.clone())))
+            .collect::<BTreeMap<String, Box<InterpVal>>>()
+    } else {
+        panic!("internal: class_add static proc must return a stack image");
+    };
+
+    cstore.insert(name.to_string(), c_proc, s_map);
+
+    InterpVal::Undefined
+}
+
+fn native_set_new(
+    _params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Ref(heap.push_obj(InterpObj::Set(InterpSet(BTreeSet::new()))))
+}
+
+fn native_set_insert(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let push_val = params[1]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .clone()
+        .persist(heap);
+    if let InterpVal::Ref(r) = params[0]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        && let InterpObj::Set(s) = unsafe { &mut *r.0 }
+    {
+        if !matches!(&push_val, InterpVal::Undefined) {
+            // the pointer has to reference the element stored in the set
+            let key = push_val.clone();
+            s.0.insert(push_val);
+            InterpVal::Ptr(InterpPtr {
+                sgmt: InterpPtrSgmt::Heap,
+                ptr: s.0.get(&key).unwrap() as *const InterpVal as *mut InterpVal,
+            })
+        } else {
+            InterpVal::Undefined
+        }
+    } else {
+        panic!("internal: list push undefined for type");
+    }
+}
+
+fn native_set_range(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let lhs = params[0].to_i64(vars, params_proc, breakpoints, "set_range");
+    let rhs = params[1].to_i64(vars, params_proc, breakpoints, "set_range");
+
+    let obj = if lhs > rhs {
+        InterpObj::Set(InterpSet(BTreeSet::new()))
+    } else {
+        InterpObj::Set(InterpSet(
+            (lhs..=rhs)
+                .map(|i| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::Number(i.into()))))
+                .collect(),
+        ))
+    };
+
+    InterpVal::Ref(heap.push_obj(obj))
+}
+
+fn native_set_borrow(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    if params[1].to_bool(vars, params_proc, breakpoints, "set_borrow") {
+        if let Some(i) = params[0].to_set(vars, params_proc, breakpoints).first() {
+            i.clone()
+        } else {
+            exception_throw(
+                "builtin procedure",
+                "set_borrow is only implemented for sets",
+                breakpoints,
+            );
+        }
+    } else if let Some(i) = params[0].to_set(vars, params_proc, breakpoints).last() {
+        i.clone()
+    } else {
+        exception_throw(
+            "builtin procedure",
+            "set_borrow is only implemented for sets",
+            breakpoints,
+        );
+    }
+}
+
+fn native_set_take(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    if params[1].to_bool(vars, params_proc, breakpoints, "set_take") {
+        if let Some(i) = params[0].to_set(vars, params_proc, breakpoints).pop_first() {
+            i.clone().mark_immed(heap)
+        } else {
+            exception_throw(
+                "builtin procedure",
+                "set_take is only implemented for sets",
+                breakpoints,
+            );
+        }
+    } else if let Some(i) = params[0].to_set(vars, params_proc, breakpoints).pop_last() {
+        i.clone().mark_immed(heap)
+    } else {
+        exception_throw(
+            "builtin procedure",
+            "set_take is only implemented for sets",
+            breakpoints,
+        );
+    }
+}
+
+/// Returns the pairs of `set` whose first element equals `key`, lists of other
+/// lengths starting with `key` are skipped.
+///
+/// Lists compare lexicographically with shorter prefixes first, so these
+/// directly follow the one element list `[key]` and are found with a range
+/// query instead of a scan of the whole set.
+fn set_key_entries<'a>(
+    set: &'a BTreeSet<InterpVal>,
+    key: &InterpVal,
+) -> impl Iterator<Item = (&'a InterpVal, &'a InterpList)> {
+    let mut probe = InterpObj::List(InterpList(vec![key.clone()]));
+    let entries = set.range(InterpVal::Ref(InterpObjRef(&mut probe))..);
+    // the probe shares the object of `key`, dropping it must not free it
+    if let InterpObj::List(l) = &mut probe {
+        l.0.truncate(0);
+    }
+
+    entries
+        .map_while(move |i| {
+            if let InterpVal::Ref(r) = i
+                && let InterpObj::List(l) = unsafe { &*r.0 }
+                && l.0
+                    .first()
+                    .is_some_and(|j| val_cmp(j, key) == Ordering::Equal)
+            {
+                Some((i, l))
+            } else {
+                None
+            }
+        })
+        .filter(|(_, l)| l.0.len() == 2)
+}
+
+/// Returns the value of the only pair with first element `key`, `None` if
+/// there are no or multiple such pairs.
+fn set_key_unique(set: &BTreeSet<InterpVal>, key: &InterpVal) -> Option<InterpVal> {
+    let mut entries = set_key_entries(set, key).map(|(_, l)| &l.0[1]);
+    match (entries.next(), entries.next()) {
+        (Some(val), None) => Some(InterpVal::Ptr(InterpPtr {
+            sgmt: InterpPtrSgmt::Heap,
+            ptr: val as *const InterpVal as *mut InterpVal,
+        })),
+        _ => None,
+    }
+}
+
+fn native_set_get_tag(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let cmp_val = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
+
+    set_key_unique(params[0].to_set(vars, params_proc, breakpoints), &cmp_val.val)
+        .unwrap_or(InterpVal::Undefined)
+}
+
+fn native_set_get_tag_mut(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let cmp_val = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
+    let set = params[0].to_set(vars, params_proc, breakpoints);
+
+    if let Some(ptr) = set_key_unique(set, &cmp_val.val) {
+        return ptr;
+    }
+
+    let ambiguous = set_key_entries(set, &cmp_val.val)
+        .map(|(i, _)| i.clone())
+        .collect::<Vec<_>>();
+    for i in ambiguous {
+        if let Some(InterpVal::Ref(r)) = set.take(&i) {
+            unsafe { r.invalidate() };
+        }
+    }
+
+    InterpVal::Undefined
+}
+
+fn native_set_get_tag_all(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let cmp_val = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
+
+    let set = set_key_entries(params[0].to_set(vars, params_proc, breakpoints), &cmp_val.val)
+        .map(|(_, l)| l.0[1].unshare())
+        .collect::<BTreeSet<InterpVal>>();
+
+    InterpVal::Ref(heap.push_obj(InterpObj::Set(InterpSet(set))))
+}
+
+/// Collects the element at `idx` of every pair in a set, skipping `om`.
+fn set_pairs_project(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    heap: &mut InterpImmediateHeap,
+    breakpoints: &mut DebugData,
+    idx: usize,
+) -> InterpVal {
+    let set = params[0]
+        .to_set(vars, params_proc, breakpoints)
+        .iter()
+        .filter_map(|i| {
+            if let InterpVal::Ref(r) = i
+                && let InterpObj::List(l) = unsafe { &*r.0 }
+                && l.0.len() == 2
+                && !matches!(l.0[idx], InterpVal::Undefined)
+            {
+                Some(l.0[idx].unshare())
+            } else {
+                None
+            }
+        })
+        .collect::<BTreeSet<InterpVal>>();
+
+    InterpVal::Ref(heap.push_obj(InterpObj::Set(InterpSet(set))))
+}
+
+fn native_set_domain(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    set_pairs_project(params, params_proc, vars, heap, breakpoints, 0)
+}
+
+fn native_set_image(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    set_pairs_project(params, params_proc, vars, heap, breakpoints, 1)
+}
+
+fn native_list_new(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let n = if !params.is_empty() {
+        params[0].to_usize(vars, params_proc, breakpoints, "list_new")
+    } else {
+        0
+    };
+
+    InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(
+        (0..n).map(|_| InterpVal::Undefined).collect(),
+    ))))
+}
+
+fn native_list_push(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let val = params[1]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .clone()
+        .persist(heap);
+    params[0].to_list(vars, params_proc, breakpoints).push(val);
+    InterpVal::Undefined
+}
+
+fn native_pop(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let coll = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
+
+    if let InterpVal::Ref(r) = &coll.val {
+        match unsafe { &mut *r.0 } {
+            InterpObj::String(s) => InterpVal::Ref(heap.push_obj(InterpObj::String(
+                s.pop().map(|s| s.to_string()).unwrap_or(String::from("")),
+            ))),
+            InterpObj::List(l) => l.pop(heap).unwrap_or(InterpVal::Undefined),
+            InterpObj::Set(s) => s.0.pop_last().unwrap_or(InterpVal::Undefined),
+            _ => exception_throw("builtin procedure", "pop undefined for type", breakpoints),
+        }
+    } else {
+        exception_throw("builtin procedure", "pop undefined for type", breakpoints);
+    }
+}
+
+fn native_list_range(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let lhs = params[0].to_i64(vars, params_proc, breakpoints, "list_range");
+    let rhs = params[1].to_i64(vars, params_proc, breakpoints, "list_range");
+
+    let list: Vec<_> = if lhs > rhs {
+        Vec::new()
+    } else {
+        (lhs..=rhs)
+            .map(|i| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::Number(i.into()))))
+            .collect()
+    };
+    let obj = InterpObj::List(InterpList(list));
+
+    InterpVal::Ref(heap.push_obj(obj))
+}
+
+fn native_list_resize(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let n = params[1].to_usize(vars, params_proc, breakpoints, "list_resize");
+    let l = params[0].to_list(vars, params_proc, breakpoints);
+    // FIXME
+    l.0.truncate(n);
+
+    InterpVal::Undefined
+}
+
+fn native_slice(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let lhs = params[1].to_i64(vars, params_proc, breakpoints, "slice");
+    let rhs = params[2].to_i64(vars, params_proc, breakpoints, "slice");
+    InterpVal::Slice(params[0].to_slice(lhs, rhs, vars, params_proc, breakpoints, opts, heap))
+}
+
+fn native_iter_new(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Iter(params[0].to_iter(vars, params_proc, breakpoints))
+}
+
+fn native_iter_next(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let ptr = params[1].to_ptr(vars, "iter_next").ptr;
+    let i = params[0].to_iter_ref(vars, breakpoints);
+    let next = i.try_next().unwrap_or_else(|e| {
+        exception_throw(
+            "iteration",
+            &format!("reading the file failed: {e}"),
+            breakpoints,
+        )
+    });
+
+    if let Some(val) = next {
+        // SAFETY: IR-ptr
+        unsafe {
+            *ptr = val;
+        }
+        InterpVal::Bool(true)
+    } else {
+        InterpVal::Bool(false)
+    }
+}
+
+fn native_ast_node_new(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let obj = InterpObj::Ast(InterpTaggedList {
+        tag: params[0].to_immed_str("ast_node_new tag").to_string(),
+        list: params
+            .iter()
+            .skip(1)
+            .map(|i| {
+                i.to_val(vars, params_proc, breakpoints, opts, heap)
+                    .confirm()
+                    .val
+                    .unshare()
+            })
+            .collect::<Vec<InterpVal>>(),
+    });
+
+    InterpVal::Ref(heap.push_obj(obj))
+}
+
+fn native_ast_node_new_sized(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let obj = InterpObj::Ast(InterpTaggedList {
+        tag: params[0].to_str(vars, params_proc, breakpoints, opts, heap),
+        list: vec![
+            InterpVal::Undefined;
+            params[1].to_usize(vars, params_proc, breakpoints, "ast_node_new_sized")
+        ],
+    });
+
+    InterpVal::Ref(heap.push_obj(obj))
+}
+
+fn native_ast_tag_get(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    tterm_ast_tag_get(
+        &params[0].to_str(vars, params_proc, breakpoints, opts, heap),
+        params[1].to_i64(vars, params_proc, breakpoints, "ast_tag_get") as usize,
+    )
+    .map(|tag| InterpVal::Ref(heap.push_obj(InterpObj::String(tag))))
+    .unwrap_or(InterpVal::Undefined)
+}
+
+fn native_ast_t_term_tag_get(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    ast_tterm_tag_get(&params[0].to_str(vars, params_proc, breakpoints, opts, heap))
+        .map(|tag| InterpVal::Ref(heap.push_obj(InterpObj::String(tag))))
+        .unwrap_or(InterpVal::Undefined)
+}
+
+fn native_procedure_new(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let proc = params[0].to_proc(vars, breakpoints, "procedure_new");
+    let info = params[1].to_ast(vars, params_proc);
+    let stack = params[2].to_ref(vars).inspect(|s| {
+        InterpVal::Ref(*s).persist(heap);
+    });
+    let cross_frame = params[3].to_bool(vars, params_proc, breakpoints, "procedure_new");
+
+    InterpVal::Ref(heap.push_obj(InterpObj::Procedure(InterpProc {
+        proc,
+        stack,
+        info,
+        cross_frame,
+    })))
+}
+
+fn native_procedure_stack_get(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    params[0]
+        .to_ref(vars)
+        .map(|r| {
+            if let InterpObj::Procedure(p) = unsafe { &*r.0 }
+                && let Some(s) = &p.stack
+            {
+                InterpVal::Ref(*s)
+            } else {
+                InterpVal::Undefined
+            }
+        })
+        .unwrap_or(InterpVal::Undefined)
+}
+
+fn native_cache_lookup(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let proc = params[0].to_proc_ptr(vars, "cache_lookup");
+    let p = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
+    let out = params[2].to_ptr(vars, "cache_lookup");
+
+    let res = memo.get(&proc).and_then(|i| i.contains(&p.val));
+
+    if let Some(r) = &res {
+        unsafe {
+            *out.ptr = r.unshare();
+        }
+    }
+    InterpVal::Bool(res.is_some())
+}
+
+fn native_cache_add(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let proc = params[0].to_proc_ptr(vars, "cache_add");
+    let val = params[1]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .unshare();
+    let ret_val = params[2]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .unshare();
+    memo.entry(proc).or_default().insert(val, ret_val);
+
+    InterpVal::Undefined
+}
+
+fn native_cache_clear(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let proc = params[0].to_proc_ptr(vars, "cache_add");
+    if let Some(i) = memo.get_mut(&proc) {
+        i.clear()
+    };
+    InterpVal::Undefined
+}
+
+fn native_exit(
+    _params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    exec_report(breakpoints, opts);
+    exit(0);
+}
+
+fn native_exception_set(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let v = params[0]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .clone();
+    if let InterpVal::Ref(r) = v {
+        heap.refs.remove(&r);
+    }
+    exception_val_set(v);
+    InterpVal::Undefined
+}
+
+fn native_exception_reset(
+    _params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    exception_val_set(InterpVal::Undefined);
+    exception_kind_set(ExceptionKind::Lng);
+    InterpVal::Undefined
+}
+
+fn native_throw(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    if let IRValue::Number(n) = &params[0] {
+        match n.to_u8().unwrap() {
+            0 => exception_kind_set(ExceptionKind::Lng),
+            1 => exception_kind_set(ExceptionKind::Usr),
+            2 => exception_kind_set(ExceptionKind::Backtrack),
+            3 => {
+                eprintln!(
+                    "{}",
+                    serialize(
+                        &params[1]
+                            .to_val(vars, params_proc, breakpoints, opts, heap)
+                            .val,
+                        vars,
+                        stack,
+                        memo,
+                        cstore,
+                        breakpoints,
+                        opts,
+                        rl,
+                        SerializeOpts::default()
+                    )
+                );
+                exit(1);
+            }
+            _ => panic!("internal: throw encountered undefined exception kind"),
+        }
+    }
+
+    let v = params[1]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .clone()
+        .persist(heap);
+    exception_val_set(v.clone());
+    panic!(
+        "{}",
+        serialize(
+            &v,
+            vars,
+            stack,
+            memo,
+            cstore,
+            breakpoints,
+            opts,
+            rl,
+            exception_serialize_opts()
+        )
+    );
+}
+
+fn native_rethrow(
+    _params: &[IRValue],
+    _params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    panic!(
+        "{}",
+        serialize(
+            &exception_val_get(),
+            vars,
+            stack,
+            memo,
+            cstore,
+            breakpoints,
+            opts,
+            rl,
+            exception_serialize_opts()
+        )
+    );
+}
+
+/* Language exceptions are reported through their payload, which has to stay
+ * the unescaped report when they are rethrown.
+ */
+fn exception_serialize_opts() -> SerializeOpts {
+    match exception_kind_get() {
+        ExceptionKind::Lng => SerializeOpts::default(),
+        _ => SerializeOpts::ESCAPE_STR,
+    }
+}
+
+fn native_exception_throw(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let cat_msg = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let msg = params[1].to_str(vars, params_proc, breakpoints, opts, heap);
+    exception_throw(&cat_msg, &msg, breakpoints);
+}
+
+fn native_regex_compile(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let flags = params[1].to_i64(vars, params_proc, breakpoints, "regex_compile");
+    let pattern = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+
+    let mut builder = RegexBuilder::new();
+    if flags & 0x02 != 0 {
+        builder.multi_line(true);
+    }
+
+    InterpVal::Ref(heap.push_obj(InterpObj::Regex(InterpRegex {
+        is_anchored: flags & 0x01 != 0,
+        regex: match builder.build(&pattern) {
+            Ok(r) => r,
+            Err(e) => exception_throw(
+                "builtin procedure",
+                &format!(
+                    "PCRE2 compile error: code={}, offset={:?}, message={}",
+                    e.code(),
+                    e.offset(),
+                    e
+                ),
+                breakpoints,
+            ),
+        },
+    })))
+}
+
+fn native_regex_match(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let regex = params[1].to_regex(vars, params_proc, breakpoints);
+
+    let out = if regex.is_anchored {
+        match regex.regex.find(input.as_bytes()) {
+            Ok(Some(m)) => m.start() == 0 && m.end() == input.len(),
+            _ => false,
+        }
+    } else {
+        regex.regex.is_match(input.as_bytes()).unwrap_or(false)
+    };
+
+    InterpVal::Bool(out)
+}
+
+fn native_regex_match_groups(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let regex = params[1].to_regex(vars, params_proc, breakpoints);
+    let matched_addr = params[2].to_ptr(vars, "regex_match_groups");
+
+    let caps_opt = regex.regex.captures(input.as_bytes()).unwrap_or(None);
+
+    if let Some(caps) = caps_opt
+        && let Some(full) = caps.get(0)
+        && (!regex.is_anchored || (full.start() == 0 && full.end() == input.len()))
+    {
+        // SAFETY: IR-PTR
+        unsafe {
+            *matched_addr.ptr = InterpVal::Bool(true);
+        }
+
+        let obj = (0..caps.len())
+            .filter_map(|i| caps.get(i))
+            .map(|m| String::from_utf8_lossy(m.as_bytes()).to_string())
+            .map(|s| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(s))))
+            .collect::<Vec<_>>();
+
+        InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(obj))))
+    } else {
+        // SAFETY: IR-PTR
+        unsafe {
+            *matched_addr.ptr = InterpVal::Bool(false);
+        }
+        InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(Vec::new()))))
+    }
+}
+
+fn native_regex_match_len(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let regex = params[1].to_regex(vars, params_proc, breakpoints);
+    let len_addr = params[2].to_ptr(vars, "regex_match_groups");
+    let pos_addr = params[3].to_ptr(vars, "regex_match_groups");
+
+    let caps_opt = regex.regex.captures(input.as_bytes()).unwrap_or(None);
+
+    if let Some(caps) = caps_opt
+        && let Some(full) = caps.get(0)
+        && (!regex.is_anchored || (full.start() == 0 && full.end() == input.len()))
+    {
+        // SAFETY: IR-PTR
+        unsafe {
+            *len_addr.ptr = InterpVal::Ref(
+                heap.push_obj(InterpObj::Number((full.end() - full.start()).into())),
+            );
+            *pos_addr.ptr = InterpVal::Ref(heap.push_obj(InterpObj::Number(full.start().into())))
+        }
+        InterpVal::Bool(true)
+    } else {
+        InterpVal::Bool(false)
+    }
+}
+
+fn native_regex_match_groups_len(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let regex = params[1].to_regex(vars, params_proc, breakpoints);
+    let matched_addr = params[2].to_ptr(vars, "regex_match_groups");
+    let len_addr = params[3].to_ptr(vars, "regex_match_groups");
+    let pos_addr = params[4].to_ptr(vars, "regex_match_groups");
+
+    let caps_opt = regex.regex.captures(input.as_bytes()).unwrap_or(None);
+
+    if let Some(caps) = caps_opt
+        && let Some(full) = caps.get(0)
+        && (!regex.is_anchored || (full.start() == 0 && full.end() == input.len()))
+    {
+        // SAFETY: IR-PTR
+        unsafe {
+            *len_addr.ptr = InterpVal::Ref(
+                heap.push_obj(InterpObj::Number((full.end() - full.start()).into())),
+            );
+            *pos_addr.ptr = InterpVal::Ref(heap.push_obj(InterpObj::Number(full.start().into())));
+            *matched_addr.ptr = InterpVal::Bool(true);
+        }
+
+        let obj = (0..caps.len())
+            .filter_map(|i| caps.get(i))
+            .map(|m| String::from_utf8_lossy(m.as_bytes()).to_string())
+            .map(|s| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(s))))
+            .collect::<Vec<_>>();
+
+        InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(obj))))
+    } else {
+        // SAFETY: IR-PTR
+        unsafe {
+            *matched_addr.ptr = InterpVal::Bool(false);
+        }
+        InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(Vec::new()))))
+    }
+}
+
+fn native_serialize(
+    params: &[IRValue],
+    _params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    match &params[0] {
+        IRValue::Undefined => {
+            InterpVal::Ref(heap.push_obj(InterpObj::String("undefined".to_string())))
+        }
+        IRValue::Type(t) => InterpVal::Ref(heap.push_obj(InterpObj::String(t.to_string()))),
+        IRValue::Variable(v) => {
+            let vars_ptr = vars as *mut [InterpVal];
+            InterpVal::Ref(heap.push_obj(InterpObj::String(serialize(
+                &vars[*v],
+                // SAFETY: non-invalidating mutable borrow
+                unsafe { &mut *vars_ptr },
+                stack,
+                memo,
+                cstore,
+                breakpoints,
+                opts,
+                rl,
+                SerializeOpts::default(),
+            ))))
+        }
+        IRValue::String(s) => InterpVal::Ref(heap.push_obj(InterpObj::String(s.to_string()))),
+        IRValue::Number(n) => InterpVal::Ref(heap.push_obj(InterpObj::String(n.to_string()))),
+        IRValue::Double(d) => InterpVal::Ref(heap.push_obj(InterpObj::String(d.to_string()))),
+        IRValue::Bool(b) => InterpVal::Ref(heap.push_obj(InterpObj::String(b.to_string()))),
+        _ => panic!("internal: serialize undefined for internal type"),
+    }
+}
+
+fn native_print_stderr(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let s = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    if let Some(io) = io_get() {
+        io.print_err(&s);
+    } else {
+        eprint!("{s}");
+    }
+    InterpVal::Undefined
+}
+
+fn native_print_stdout(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let s = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    if let Some(io) = io_get() {
+        io.print(&s);
+    } else {
+        print!("{s}");
+        io::stdout().flush().unwrap();
+    }
+    InterpVal::Undefined
+}
+
+fn native_read_line_stdin(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let s = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+
+    if let Some(io) = io_get() {
+        return match io.read_line(&s) {
+            Some(line) => InterpVal::Ref(heap.push_obj(InterpObj::String(line))),
+            None => InterpVal::Undefined,
+        };
+    }
+
+    // readline exits on EOF, piped input ends with om instead so that filters
+    // can tell when they are done
+    if !io::stdin().is_terminal() {
+        let mut line = String::new();
+        return match io::stdin().read_line(&mut line) {
+            Ok(0) => InterpVal::Undefined,
+            Ok(_) => {
+                let len = line.trim_end_matches(['\n', '\r']).len();
+                line.truncate(len);
+                InterpVal::Ref(heap.push_obj(InterpObj::String(line)))
+            }
+            Err(e) => exception_throw("builtin procedure", &e.to_string(), breakpoints),
+        };
+    }
+
+    let mut input = match rl.readline(&s) {
+        Ok(line) => {
+            _ = rl.add_history_entry(line.as_str());
+            line
+        }
+        Err(ReadlineError::Interrupted) => {
+            exit(1);
+        }
+        Err(ReadlineError::Eof) => {
+            exit(0);
+        }
+        Err(err) => {
+            panic!("internal: readline error: {err}");
+        }
+    };
+    if input.ends_with('\n') {
+        input.pop();
+    }
+    InterpVal::Ref(heap.push_obj(InterpObj::String(input)))
+}
+
+fn native_eval(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let base = breakpoints.src_add_eval(&input, "eval");
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let new_opts = opts.exec_opts();
+        let expr = cst_expr_parse_at(&input, &new_opts, base);
+        let eval_proc = IRCfg::from_expr(&expr, &new_opts);
+        exec_proc(
+            eval_proc,
+            &InterpVal::Undefined,
+            stack,
+            memo,
+            cstore,
+            breakpoints,
+            &new_opts,
+            rl,
+        )
+    }));
+
+    match result {
+        Ok(o) => o,
+        Err(e) => {
+            if let Some(s) = e.downcast_ref::<&str>() {
+                exception_throw_raw(s);
+            } else if let Some(s) = e.downcast_ref::<String>() {
+                exception_throw_raw(s);
+            } else {
+                exception_throw_raw("eval yielded error with non-string payload");
+            }
+        }
+    }
+}
+
+fn native_eval_term(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let input = params[0]
+        .to_val(vars, params_proc, breakpoints, opts, heap)
+        .confirm()
+        .val
+        .clone();
+    let mut new_opts = opts.exec_opts();
+    new_opts.disable_annotations = true;
+    new_opts.bogus_annotations = false;
+    let result = if let Some(expr) = ast_to_cst_expr(&input) {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            let eval_proc = IRCfg::from_expr(&expr, &new_opts);
+            exec_proc(
+                eval_proc,
+                &InterpVal::Undefined,
+                stack,
+                memo,
+                cstore,
+                breakpoints,
+                &new_opts,
+                rl,
+            )
+        }))
+    } else if let Some(stmt) = ast_to_cst_block(&input) {
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            eprintln!("{:?}", stmt);
+            let stmt_proc = IRCfg::from_stmt(&stmt, &new_opts);
+            let out = exec_proc(
+                stmt_proc,
+                &InterpVal::Undefined,
+                stack,
+                memo,
+                cstore,
+                breakpoints,
+                &new_opts,
+                rl,
+            );
+
+            if let InterpVal::Ref(r) = out {
+                unsafe {
+                    r.invalidate();
+                }
+            }
+
+            InterpVal::Undefined
+        }))
+    } else {
+        exception_throw("parse error", "input isn't a valid term", breakpoints)
+    };
+    match result {
+        Ok(o) => o,
+        Err(e) => {
+            if let Some(s) = e.downcast_ref::<&str>() {
+                exception_throw_raw(s);
+            } else if let Some(s) = e.downcast_ref::<String>() {
+                exception_throw_raw(s);
+            } else {
+                exception_throw_raw("eval yielded error with non-string payload");
+            }
+        }
+    }
+}
+
+/// Applies the sandbox of `opts`, if any, to a file a builtin is about to
+/// read or write.
+fn sandbox_path(path: PathBuf, write: bool, opts: &InputOpts, breakpoints: &DebugData) -> PathBuf {
+    let Some(sandbox) = &opts.sandbox else {
+        return path;
+    };
+    let result = if write {
+        sandbox.write_path(&path)
+    } else {
+        sandbox.read_path(&path)
+    };
+    result.unwrap_or_else(|e| exception_throw("sandbox", &e, breakpoints))
+}
+
+/// Parses and executes `input` in the global scope, which was registered as
+/// `srcname` with the debugger at `base`. `proc` replaces parsing and
+/// lowering if the statements were already lowered.
+fn src_execute(
+    input: &str,
+    srcname: &str,
+    base: usize,
+    proc: Option<Rc<RefCell<IRProcedure>>>,
+    stack: &mut InterpStack,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> thread::Result<()> {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut new_opts = opts.exec_opts();
+        new_opts.srcname = srcname.to_string();
+        let stmt_proc = proc.unwrap_or_else(|| {
+            let stmt = cst_parse_at(input, &new_opts, base);
+            IRCfg::from_stmt(&stmt, &new_opts)
+        });
+        let out = exec_proc(
+            stmt_proc,
+            &InterpVal::Undefined,
+            stack,
+            memo,
+            cstore,
+            breakpoints,
+            &new_opts,
+            rl,
+        );
+
+        if let InterpVal::Ref(r) = out {
+            unsafe {
+                r.invalidate();
+            }
+        }
+    }))
+}
+
+fn src_execute_rethrow(e: Box<dyn Any + Send>, tag: &str) -> ! {
+    if let Some(s) = e.downcast_ref::<&str>() {
+        exception_throw_raw(s);
+    } else if let Some(s) = e.downcast_ref::<String>() {
+        exception_throw_raw(s);
+    } else {
+        exception_throw_raw(&format!("{tag} yielded error with non-string payload"));
+    }
+}
+
+fn native_execute(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let base = breakpoints.src_add_eval(&input, "execute");
+    match src_execute(
+        &input,
+        "execute",
+        base,
+        None,
+        stack,
+        memo,
+        cstore,
+        breakpoints,
+        opts,
+        rl,
+    ) {
+        Ok(_) => InterpVal::Bool(true),
+        Err(e) => src_execute_rethrow(e, "execute"),
+    }
+}
+
+fn native_load(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let name = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let search_path = params[1].to_str(vars, params_proc, breakpoints, opts, heap);
+
+    let Some(path) = module_resolve(&name, &search_path) else {
+        exception_throw(
+            "builtin procedure",
+            &format!("{name} not found"),
+            breakpoints,
+        );
+    };
+    let srcname = path.to_string_lossy().into_owned();
+    let path = sandbox_path(path, false, opts, breakpoints);
+    let key = fs::canonicalize(&path).unwrap_or(path);
+
+    if let Some(cycle) = breakpoints.modules.cycle(&key, &srcname) {
+        exception_throw(
+            "builtin procedure",
+            &format!("load cycle: {cycle}"),
+            breakpoints,
+        );
+    }
+
+    /* a compiled file newer than the source is executed in its place,
+     * compiled files that can't be read fall back to the source
+     */
+    let cache = ir_cache_fresh(&key)
+        .then(|| IRCache::read(&ir_cache_path(&key)).ok())
+        .flatten();
+    let input = match &cache {
+        Some(cache) => cache.src.clone(),
+        None => match fs::read_to_string(&key) {
+            Ok(s) => s,
+            Err(e) => exception_throw("builtin procedure", &e.to_string(), breakpoints),
+        },
+    };
+    let hash = module_hash(&input);
+    if breakpoints.modules.is_loaded(&key, hash) {
+        return InterpVal::Bool(true);
+    }
+
+    let base = breakpoints.src_add(&input, &srcname);
+    let proc = cache.and_then(|i| i.module(base).ok());
+    breakpoints.modules.enter(key, srcname.clone());
+    let result = src_execute(
+        &input,
+        &srcname,
+        base,
+        proc,
+        stack,
+        memo,
+        cstore,
+        breakpoints,
+        opts,
+        rl,
+    );
+    breakpoints.modules.leave(result.is_ok().then_some(hash));
+
+    match result {
+        Ok(_) => InterpVal::Bool(true),
+        Err(e) => src_execute_rethrow(e, "load"),
+    }
+}
+
+fn native_parse_ast(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let base = breakpoints.src_add_eval(&input, "execute");
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let new_opts = opts.exec_opts();
+        let expr = cst_expr_parse_at(&input, &new_opts, base);
+        let eval_proc = IRCfg::from_ast_expr(&expr, &new_opts);
+        exec_proc(
+            eval_proc,
+            &InterpVal::Undefined,
+            stack,
+            memo,
+            cstore,
+            breakpoints,
+            &new_opts,
+            rl,
+        )
+    }));
+
+    match result {
+        Ok(o) => o,
+        Err(e) => {
+            if let Some(s) = e.downcast_ref::<&str>() {
+                exception_throw_raw(s);
+            } else if let Some(s) = e.downcast_ref::<String>() {
+                exception_throw_raw(s);
+            } else {
+                exception_throw_raw("eval yielded error with non-string payload");
+            }
+        }
+    }
+}
+
+fn native_parse_ast_block(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let base = breakpoints.src_add_eval(&input, "execute");
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let new_opts = opts.exec_opts();
+        let expr = cst_parse_at(&input, &new_opts, base);
+        let eval_proc = IRCfg::from_ast_block(&expr, &new_opts);
+        exec_proc(
+            eval_proc,
+            &InterpVal::Undefined,
+            stack,
+            memo,
+            cstore,
+            breakpoints,
+            &new_opts,
+            rl,
+        )
+    }));
+
+    match result {
+        Ok(o) => o,
+        Err(e) => {
+            if let Some(s) = e.downcast_ref::<&str>() {
+                exception_throw_raw(s);
+            } else if let Some(s) = e.downcast_ref::<String>() {
+                exception_throw_raw(s);
             } else {
+                exception_throw_raw("eval yielded error with non-string payload");
+            }
+        }
+    }
+}
+
+fn native_open_at(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let base_path = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let rel_path = params[1].to_str(vars, params_proc, breakpoints, opts, heap);
+    let mode = params[2].to_i64(vars, params_proc, breakpoints, "open_at");
+
+    let mut o_opts = OpenOptions::new();
+    if mode & 0x01 != 0 {
+        o_opts.read(true);
+    }
+
+    if mode & 0x02 != 0 {
+        o_opts.write(true);
+    }
+
+    if mode & 0x04 != 0 {
+        o_opts.append(true);
+    }
+
+    if mode & 0x08 != 0 {
+        o_opts.create(true);
+    }
+
+    let path = sandbox_path(
+        PathBuf::from(base_path).join(rel_path),
+        mode & 0x0e != 0,
+        opts,
+        breakpoints,
+    );
+    match o_opts.open(path) {
+        Ok(file) => InterpVal::Ref(heap.push_obj(InterpObj::File(Rc::new(RefCell::new(
+            FileHandle::new(file),
+        ))))),
+        Err(e) => exception_throw("builtin procedure", &e.to_string(), breakpoints),
+    }
+}
+
+fn native_read_all(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let file = params[0].to_file(vars, params_proc, breakpoints);
+
+    let mut contents = Vec::new();
+    file.borrow_mut()
+        .read_to_end(&mut contents)
+        .unwrap_or_else(|e| exception_throw("builtin procedure", &e.to_string(), breakpoints));
+    InterpVal::Ref(heap.push_obj(InterpObj::String(
+        String::from_utf8_lossy(&contents).to_string(),
+    )))
+}
+
+fn native_read_all_list(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let file = params[0].to_file(vars, params_proc, breakpoints);
+
+    let mut contents = Vec::new();
+    file.borrow_mut()
+        .read_to_end(&mut contents)
+        .unwrap_or_else(|e| exception_throw("builtin procedure", &e.to_string(), breakpoints));
+    InterpVal::Ref(
+        heap.push_obj(InterpObj::List(InterpList(
+            String::from_utf8_lossy(&contents)
+                .trim_end_matches('\n')
+                .split("\n")
+                .map(|i| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(i.to_string()))))
+                .collect(),
+        ))),
+    )
+}
+
+fn native_write(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let file = params[0].to_file(vars, params_proc, breakpoints);
+    let s = params[1].to_str(vars, params_proc, breakpoints, opts, heap);
+
+    let mut file = file.borrow_mut();
+    file.write_str(&s)
+        .and_then(|_| file.flush())
+        .unwrap_or_else(|e| exception_throw("builtin procedure", &e.to_string(), breakpoints));
+
+    InterpVal::Undefined
+}
+
+fn native_file_open(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let path = IRValue::Variable(0).to_str(&args, params_proc, breakpoints, opts, heap);
+    let mode = if arg_given(&args, 1) {
+        IRValue::Variable(1).to_str(&args, params_proc, breakpoints, opts, heap)
+    } else {
+        String::from("r")
+    };
+
+    let mut o_opts = OpenOptions::new();
+    match mode.as_str() {
+        "r" => o_opts.read(true),
+        "r+" => o_opts.read(true).write(true),
+        "w" => o_opts.write(true).create(true).truncate(true),
+        "w+" => o_opts.read(true).write(true).create(true).truncate(true),
+        "a" => o_opts.append(true).create(true),
+        "a+" => o_opts.read(true).append(true).create(true),
+        _ => exception_throw(
+            "builtin procedure",
+            &format!("openFile: unknown mode \"{mode}\""),
+            breakpoints,
+        ),
+    };
+
+    let full_path = sandbox_path(PathBuf::from(&path), mode != "r", opts, breakpoints);
+    match o_opts.open(full_path) {
+        Ok(file) => InterpVal::Ref(heap.push_obj(InterpObj::File(Rc::new(RefCell::new(
+            FileHandle::new(file),
+        ))))),
+        Err(e) => exception_throw(
+            "builtin procedure",
+            &format!("openFile: {path}: {e}"),
+            breakpoints,
+        ),
+    }
+}
+
+fn native_file_read_line(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let file = IRValue::Variable(0).to_file(&args, params_proc, breakpoints);
+
+    let line = file.borrow_mut().read_line().unwrap_or_else(|e| {
+        exception_throw("builtin procedure", &format!("readLine: {e}"), breakpoints)
+    });
+    match line {
+        Some(line) => InterpVal::Ref(heap.push_obj(InterpObj::String(line))),
+        None => InterpVal::Undefined,
+    }
+}
+
+fn native_file_read_chars(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let file = IRValue::Variable(0).to_file(&args, params_proc, breakpoints);
+    let n = IRValue::Variable(1).to_usize(&args, params_proc, breakpoints, "readChars");
+
+    let chars = file.borrow_mut().read_chars(n).unwrap_or_else(|e| {
+        exception_throw("builtin procedure", &format!("readChars: {e}"), breakpoints)
+    });
+    match chars {
+        Some(chars) => InterpVal::Ref(heap.push_obj(InterpObj::String(chars))),
+        None => InterpVal::Undefined,
+    }
+}
+
+fn native_file_write_string(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let file = IRValue::Variable(0).to_file(&args, params_proc, breakpoints);
+    let s = IRValue::Variable(1).to_str(&args, params_proc, breakpoints, opts, heap);
+
+    file.borrow_mut().write_str(&s).unwrap_or_else(|e| {
+        exception_throw(
+            "builtin procedure",
+            &format!("writeString: {e}"),
+            breakpoints,
+        )
+    });
+    InterpVal::Undefined
+}
+
+fn native_file_flush(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let file = IRValue::Variable(0).to_file(&args, params_proc, breakpoints);
+
+    file.borrow_mut().flush().unwrap_or_else(|e| {
+        exception_throw("builtin procedure", &format!("flush: {e}"), breakpoints)
+    });
+    InterpVal::Undefined
+}
+
+fn native_file_close(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let file = IRValue::Variable(0).to_file(&args, params_proc, breakpoints);
+
+    file.borrow_mut().close().unwrap_or_else(|e| {
+        exception_throw("builtin procedure", &format!("close: {e}"), breakpoints)
+    });
+    InterpVal::Undefined
+}
+
+fn native_delete(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let file = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let file = sandbox_path(PathBuf::from(file), true, opts, breakpoints);
+    fs::remove_file(file).unwrap_or_else(|e| {
+        exception_throw("failed to delete file: {:?}", &e.to_string(), breakpoints);
+    });
+    InterpVal::Undefined
+}
+
+fn native_ln(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Double(params[0].to_f64(vars, params_proc, breakpoints, "ln").ln())
+}
+
+fn native_exp(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Double(
+        params[0]
+            .to_f64(vars, params_proc, breakpoints, "exp")
+            .exp(),
+    )
+}
+
+fn native_sqrt(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Double(
+        params[0]
+            .to_f64(vars, params_proc, breakpoints, "sqrt")
+            .sqrt(),
+    )
+}
+
+fn native_round_op(
+    proc: BuiltinProc,
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    if let Some(r) = params[0].to_ref(vars)
+        && let InterpObj::Rational(q) = unsafe { &*r.0 }
+    {
+        let n = match proc {
+            BuiltinProc::Round => q.round(),
+            BuiltinProc::Floor => q.floor(),
+            _ => q.ceil(),
+        };
+        return InterpVal::Ref(heap.push_obj(InterpObj::Number(n.to_integer())));
+    }
+
+    let d = params[0].to_f64(vars, params_proc, breakpoints, &proc.to_string());
+    InterpVal::Double(match proc {
+        BuiltinProc::Round => d.round(),
+        BuiltinProc::Floor => d.floor(),
+        _ => d.ceil(),
+    })
+}
+
+native_variant!(native_round, native_round_op, BuiltinProc::Round);
+
+native_variant!(native_floor, native_round_op, BuiltinProc::Floor);
+
+native_variant!(native_ceil, native_round_op, BuiltinProc::Ceil);
+
+fn native_rational(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    if let Some(r) = params[0].to_ref(vars)
+        && let InterpObj::String(s) = unsafe { &*r.0 }
+    {
+        let q = s.trim().parse::<BigRational>().unwrap_or_else(|_| {
+            exception_throw(
+                "builtin procedure",
+                &format!("\"{s}\" is not a rational number"),
+                breakpoints,
+            )
+        });
+        return InterpVal::Ref(heap.push_obj(InterpObj::from_rational(q)));
+    }
+
+    let q = params[0].to_rational(vars, params_proc, breakpoints, "rational");
+    InterpVal::Ref(heap.push_obj(InterpObj::from_rational(q)))
+}
+
+fn native_n_decimal_places(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let q = params[0].to_rational(vars, params_proc, breakpoints, "nDecimalPlaces");
+    let places = params[1].to_usize(vars, params_proc, breakpoints, "nDecimalPlaces");
+
+    let scale = BigInt::from(10).pow(places);
+    let digits = (q.abs() * &scale).trunc().to_integer();
+    let int_part = &digits / &scale;
+    let frac_part = (&digits % &scale).to_string();
+
+    let mut out = String::new();
+    if q.is_negative() && !digits.is_zero() {
+        out.push('-');
+    }
+    out.push_str(&int_part.to_string());
+    if places > 0 {
+        out.push('.');
+        out.push_str(&"0".repeat(places - frac_part.len()));
+        out.push_str(&frac_part);
+    }
+
+    InterpVal::Ref(heap.push_obj(InterpObj::String(out)))
+}
+
+fn native_sin(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Double(
+        params[0]
+            .to_f64(vars, params_proc, breakpoints, "sin")
+            .sin(),
+    )
+}
+
+fn native_cos(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Double(
+        params[0]
+            .to_f64(vars, params_proc, breakpoints, "cos")
+            .cos(),
+    )
+}
+
+fn native_tan(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Double(
+        params[0]
+            .to_f64(vars, params_proc, breakpoints, "tan")
+            .tan(),
+    )
+}
+
+fn native_sin_h(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Double(
+        params[0]
+            .to_f64(vars, params_proc, breakpoints, "sinh")
+            .sinh(),
+    )
+}
+
+fn native_cos_h(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Double(
+        params[0]
+            .to_f64(vars, params_proc, breakpoints, "cosh")
+            .cosh(),
+    )
+}
+
+fn native_tan_h(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Double(
+        params[0]
+            .to_f64(vars, params_proc, breakpoints, "tanh")
+            .tanh(),
+    )
+}
+
+fn native_ulp(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let x = params[0].to_f64(vars, params_proc, breakpoints, "ulp");
+    if x.is_nan() {
+        return InterpVal::Double(f64::NAN);
+    }
+
+    if x.is_infinite() {
+        return InterpVal::Double(f64::INFINITY);
+    }
+
+    let bits = x.to_bits();
+    let next_bits = if x > 0.0 { bits + 1 } else { bits - 1 };
+
+    let next = f64::from_bits(next_bits);
+
+    InterpVal::Double((next - x).abs())
+}
+
+fn native_rnd_float(
+    _params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Double(breakpoints.rng.random_range(0.0..=1.0))
+}
+
+fn native_rnd_seed(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+
+    match args.first() {
+        None | Some(InterpVal::Undefined) => breakpoints.rng = rng_new(opts.seed),
+        Some(_) => {
+            let seed = IRValue::Variable(0).to_i64(&args, params_proc, breakpoints, "resetRandom");
+            breakpoints.rng = rng_new(Some(seed as u64));
+        }
+    }
+
+    InterpVal::Undefined
+}
+
+fn native_to_char(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Char(params[0].to_usize(vars, params_proc, breakpoints, "to_char") as u8 as char)
+}
+
+fn native_sleep(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    thread::sleep(Duration::from_millis(
+        params[0].to_usize(vars, params_proc, breakpoints, "sleep") as u64,
+    ));
+    InterpVal::Undefined
+}
+
+fn native_unix_epoch(
+    _params: &[IRValue],
+    _params_proc: &InterpVal,
+    _vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    _breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Ref(
+        heap.push_obj(InterpObj::Number(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis()
+                .into(),
+        )),
+    )
+}
+
+fn native_cmp(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    match val_cmp(
+        &params[0]
+            .to_val(vars, params_proc, breakpoints, opts, heap)
+            .val,
+        &params[1]
+            .to_val(vars, params_proc, breakpoints, opts, heap)
+            .val,
+    ) {
+        Ordering::Less => InterpVal::Ref(heap.push_obj(InterpObj::Number((-1).into()))),
+        Ordering::Equal => InterpVal::Ref(heap.push_obj(InterpObj::Number(0.into()))),
+        Ordering::Greater => InterpVal::Ref(heap.push_obj(InterpObj::Number(1.into()))),
+    }
+}
+
+fn native_parse_int(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    params[0]
+        .to_str(vars, params_proc, breakpoints, opts, heap)
+        .parse::<i64>()
+        .map(|i| InterpVal::Ref(heap.push_obj(InterpObj::Number(i.into()))))
+        .unwrap_or(InterpVal::Undefined)
+}
+
+fn native_parse_float(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    params[0]
+        .to_str(vars, params_proc, breakpoints, opts, heap)
+        .parse::<f64>()
+        .map(InterpVal::Double)
+        .unwrap_or(InterpVal::Undefined)
+}
+
+fn native_cmd(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    pub fn system_capture(cmd: &str) -> io::Result<(String, String)> {
+        #[cfg(target_family = "unix")]
+        let output = Command::new("sh").arg("-c").arg(cmd).output()?;
+
+        #[cfg(target_family = "windows")]
+        let output = Command::new("cmd").arg("/C").arg(cmd).output()?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        Ok((stdout, stderr))
+    }
+
+    if opts.sandbox.is_some() {
+        exception_throw("sandbox", "running commands is not allowed", breakpoints);
+    }
+
+    let (stdout, stderr) =
+        system_capture(&params[0].to_str(vars, params_proc, breakpoints, opts, heap))
+            .unwrap_or_else(|e| {
                 exception_throw(
                     "builtin procedure",
-                    "set_borrow is only implemented for sets",
+                    &format!("failed to open shell: {e}"),
                     breakpoints,
-                );
+                )
+            });
+
+    let mut stdout_obj = InterpList::default();
+    stdout.lines().for_each(|i| {
+        stdout_obj.push(InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(
+            i.to_string(),
+        ))))
+    });
+
+    let mut stderr_obj = InterpList::default();
+    stderr.lines().for_each(|i| {
+        stderr_obj.push(InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(
+            i.to_string(),
+        ))))
+    });
+
+    let mut list_obj = InterpList::default();
+    list_obj.push(InterpVal::Ref(InterpObjRef::from_obj(InterpObj::List(
+        stdout_obj,
+    ))));
+    list_obj.push(InterpVal::Ref(InterpObjRef::from_obj(InterpObj::List(
+        stderr_obj,
+    ))));
+
+    InterpVal::Ref(heap.push_obj(InterpObj::List(list_obj)))
+}
+
+/// Entries of a SetlX map, which has to consist of pairs only.
+fn map_pairs<'a>(
+    set: &'a BTreeSet<InterpVal>,
+    data: &DebugData,
+    fnc: &str,
+) -> Vec<&'a [InterpVal]> {
+    set.iter()
+        .map(|i| match i {
+            InterpVal::Ref(r) => match unsafe { &*r.0 } {
+                InterpObj::List(l) if l.0.len() == 2 => &l.0[..],
+                _ => exception_throw("builtin procedure", &format!("{fnc}: not a map"), data),
+            },
+            _ => exception_throw("builtin procedure", &format!("{fnc}: not a map"), data),
+        })
+        .collect()
+}
+
+/// Kills `child` and the processes of its group.
+fn process_kill(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn native_run_process(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    if opts.sandbox.is_some() {
+        exception_throw("sandbox", "running commands is not allowed", breakpoints);
+    }
+
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    if args.is_empty() || args.len() > 2 {
+        exception_throw(
+            "builtin procedure",
+            "runProcess: expected an argument list and a map of options",
+            breakpoints,
+        );
+    }
+
+    let argv = &IRValue::Variable(0)
+        .to_list(&args, params_proc, breakpoints)
+        .0;
+    let argv: Vec<String> = (0..argv.len())
+        .map(|i| IRValue::Variable(i).to_str(argv, params_proc, breakpoints, opts, heap))
+        .collect();
+    let Some((prog, prog_args)) = argv.split_first() else {
+        exception_throw(
+            "builtin procedure",
+            "runProcess: empty argument list",
+            breakpoints,
+        );
+    };
+
+    let mut cmd = Command::new(prog);
+    cmd.args(prog_args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // a timeout ends the processes started by the child as well
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let mut input = String::new();
+    let mut timeout = None;
+    if arg_given(&args, 1) {
+        let options = IRValue::Variable(1).to_set(&args, params_proc, breakpoints);
+        for pair in map_pairs(options, breakpoints, "runProcess") {
+            let key = IRValue::Variable(0).to_str(pair, params_proc, breakpoints, opts, heap);
+            let val = IRValue::Variable(1);
+            match key.as_str() {
+                "stdin" => input = val.to_str(pair, params_proc, breakpoints, opts, heap),
+                "cwd" => {
+                    cmd.current_dir(val.to_str(pair, params_proc, breakpoints, opts, heap));
+                }
+                "timeout" => {
+                    let ms = val.to_i64(pair, params_proc, breakpoints, "runProcess");
+                    timeout = Some(Duration::from_millis(ms.max(0) as u64));
+                }
+                "env" => {
+                    let env = val.to_set(pair, params_proc, breakpoints);
+                    for i in map_pairs(env, breakpoints, "runProcess") {
+                        cmd.env(
+                            IRValue::Variable(0).to_str(i, params_proc, breakpoints, opts, heap),
+                            IRValue::Variable(1).to_str(i, params_proc, breakpoints, opts, heap),
+                        );
+                    }
+                }
+                _ => exception_throw(
+                    "builtin procedure",
+                    &format!("runProcess: unknown option {key}"),
+                    breakpoints,
+                ),
             }
         }
-        BuiltinProc::SetTake => {
-            if params[1].to_bool(vars, params_proc, breakpoints, "set_take") {
-                if let Some(i) = params[0].to_set(vars, params_proc, breakpoints).pop_first() {
-                    i.clone().mark_immed(heap)
-                } else {
-                    exception_throw(
-                        "builtin procedure",
-                        "set_take is only implemented for sets",
-                        breakpoints,
-                    );
-                }
-            } else if let Some(i) = params[0].to_set(vars, params_proc, breakpoints).pop_last() {
-                i.clone().mark_immed(heap)
-            } else {
+    }
+
+    let mut child = cmd.spawn().unwrap_or_else(|e| {
+        exception_throw(
+            "builtin procedure",
+            &format!("runProcess: {prog}: {e}"),
+            breakpoints,
+        )
+    });
+
+    /* the pipes are served by threads, so that a child blocking on a full
+     * pipe can't stall the timeout
+     */
+    let mut stdin = child.stdin.take().unwrap();
+    thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let pipe_read = |mut pipe: Box<dyn Read + Send>| {
+        thread::spawn(move || {
+            let mut out = Vec::new();
+            let _ = pipe.read_to_end(&mut out);
+            String::from_utf8_lossy(&out).into_owned()
+        })
+    };
+    let stdout = pipe_read(Box::new(child.stdout.take().unwrap()));
+    let stderr = pipe_read(Box::new(child.stderr.take().unwrap()));
+
+    /* the output is complete once processes started by the child closed
+     * their ends of the pipes as well
+     */
+    let start = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) if stdout.is_finished() && stderr.is_finished() => break status,
+            Ok(_) if timeout.is_some_and(|t| start.elapsed() > t) => {
+                process_kill(&mut child);
                 exception_throw(
                     "builtin procedure",
-                    "set_take is only implemented for sets",
+                    &format!("runProcess: {prog} timed out"),
                     breakpoints,
                 );
             }
+            Ok(_) => thread::sleep(Duration::from_millis(5)),
+            Err(e) => exception_throw(
+                "builtin procedure",
+                &format!("runProcess: {prog}: {e}"),
+                breakpoints,
+            ),
         }
-        BuiltinProc::SetGetTag => {
-            let cmp_val = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
-
-            params[0]
-                .to_set(vars, params_proc, breakpoints)
-                .iter()
-                .filter_map(|i| {
-                    if let InterpVal::Ref(r) = i
-                        && let InterpObj::List(l) = unsafe { &*r.0 }
-                        && let Some(val) = l.0.get(1)
-                    {
-                        Some((val as *const InterpVal, l))
-                    } else {
-                        None
-                    }
-                })
-                .find(|(_, l)| {
-                    l.0.first()
-                        .map(|j| val_cmp(j, &cmp_val.val) == Ordering::Equal)
-                        .unwrap_or(false)
-                })
-                .map(|(i, _)| {
-                    InterpVal::Ptr(InterpPtr {
-                        sgmt: InterpPtrSgmt::Heap,
-                        ptr: i as *mut InterpVal,
-                    })
-                })
-                .unwrap_or(InterpVal::Undefined)
-        }
-        BuiltinProc::SetGetTagAll => {
-            let cmp_val = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
-
-            let set = params[0]
-                .to_set(vars, params_proc, breakpoints)
-                .iter()
-                .filter_map(|i| {
-                    if let InterpVal::Ref(r) = i
-                        && let InterpObj::List(l) = unsafe { &*r.0 }
-                        && let Some(key) = l.0.first()
-                        && let Some(val) = l.0.get(1)
-                        && val_cmp(key, &cmp_val.val) == Ordering::Equal
-                    {
-                        Some(val.unshare())
-                    } else {
-                        None
-                    }
-                })
-                .collect::<BTreeSet<InterpVal>>();
+    };
 
-            InterpVal::Ref(heap.push_obj(InterpObj::Set(InterpSet(set))))
-        }
-        BuiltinProc::ListNew => {
-            let n = if !params.is_empty() {
-                params[0].to_usize(vars, params_proc, breakpoints, "list_new")
-            } else {
-                0
-            };
+    let code = match status.code() {
+        Some(c) => InterpVal::Ref(InterpObjRef::from_obj(InterpObj::Number(c.into()))),
+        None => InterpVal::Undefined,
+    };
+    let string_new = |s: String| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(s)));
 
-            InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(
-                (0..n).map(|_| InterpVal::Undefined).collect(),
-            ))))
-        }
-        BuiltinProc::ListPush => {
-            let val = params[1]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .clone()
-                .persist(heap);
-            params[0].to_list(vars, params_proc, breakpoints).push(val);
-            InterpVal::Undefined
-        }
-        BuiltinProc::Pop => {
-            let coll = params[0].to_val(vars, params_proc, breakpoints, opts, heap);
-
-            if let InterpVal::Ref(r) = &coll.val {
-                match unsafe { &mut *r.0 } {
-                    InterpObj::String(s) => InterpVal::Ref(heap.push_obj(InterpObj::String(
-                        s.pop().map(|s| s.to_string()).unwrap_or(String::from("")),
-                    ))),
-                    InterpObj::List(l) => l.pop(heap).unwrap_or(InterpVal::Undefined),
-                    InterpObj::Set(s) => s.0.pop_last().unwrap_or(InterpVal::Undefined),
-                    _ => {
-                        exception_throw("builtin procedure", "pop undefined for type", breakpoints)
-                    }
-                }
-            } else {
-                exception_throw("builtin procedure", "pop undefined for type", breakpoints);
-            }
-        }
-        BuiltinProc::ListRange => {
-            let lhs = params[0].to_i64(vars, params_proc, breakpoints, "list_range");
-            let rhs = params[1].to_i64(vars, params_proc, breakpoints, "list_range");
+    InterpVal::Ref(heap.push_obj(InterpObj::Term(InterpTaggedList {
+        tag: String::from("Process"),
+        list: vec![
+            code,
+            string_new(stdout.join().unwrap_or_default()),
+            string_new(stderr.join().unwrap_or_default()),
+        ],
+    })))
+}
+
+fn native_to_json(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let json =
+        json_encode(args.first().unwrap_or(&InterpVal::Undefined), cstore).unwrap_or_else(|e| {
+            exception_throw("builtin procedure", &format!("toJson: {e}"), breakpoints)
+        });
+
+    InterpVal::Ref(heap.push_obj(InterpObj::String(json.to_string())))
+}
+
+fn native_from_json(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let src = IRValue::Variable(0).to_str(&args, params_proc, breakpoints, opts, heap);
+    let json: serde_json::Value = serde_json::from_str(&src).unwrap_or_else(|e| {
+        exception_throw("builtin procedure", &format!("fromJson: {e}"), breakpoints)
+    });
 
-            let list: Vec<_> = if lhs > rhs {
-                Vec::new()
-            } else {
-                (lhs..=rhs)
-                    .map(|i| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::Number(i.into()))))
-                    .collect()
-            };
-            let obj = InterpObj::List(InterpList(list));
+    let out = json_decode(&json);
+    if let InterpVal::Ref(r) = out {
+        heap.push(r);
+    }
+    out
+}
 
-            InterpVal::Ref(heap.push_obj(obj))
-        }
-        BuiltinProc::ListResize => {
-            let n = params[1].to_usize(vars, params_proc, breakpoints, "list_resize");
-            let l = params[0].to_list(vars, params_proc, breakpoints);
-            // FIXME
-            l.0.truncate(n);
+/// Separator given by the `separator` option of `readCsv` and `writeCsv`
+fn csv_separator(val: &str, fnc: &str, data: &DebugData) -> char {
+    let mut chars = val.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c != '"' && c != '\n' && c != '\r' => c,
+        _ => exception_throw(
+            "builtin procedure",
+            &format!("{fnc}: invalid separator {val:?}"),
+            data,
+        ),
+    }
+}
 
-            InterpVal::Undefined
-        }
-        BuiltinProc::Slice => {
-            let lhs = params[1].to_i64(vars, params_proc, breakpoints, "slice");
-            let rhs = params[2].to_i64(vars, params_proc, breakpoints, "slice");
-            InterpVal::Slice(params[0].to_slice(
-                lhs,
-                rhs,
-                vars,
-                params_proc,
-                breakpoints,
-                opts,
-                heap,
-            ))
-        }
-        BuiltinProc::IterNew => InterpVal::Iter(params[0].to_iter(vars, params_proc, breakpoints)),
-        BuiltinProc::IterNext => {
-            let ptr = params[1].to_ptr(vars, "iter_next").ptr;
-            let i = params[0].to_iter_ref(vars, breakpoints);
+fn native_csv_read(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let path = IRValue::Variable(0).to_str(&args, params_proc, breakpoints, opts, heap);
 
-            if let Some(val) = i.next() {
-                // SAFETY: IR-ptr
-                unsafe {
-                    *ptr = val;
+    let mut sep = ',';
+    let mut header = false;
+    if arg_given(&args, 1) {
+        let options = IRValue::Variable(1).to_set(&args, params_proc, breakpoints);
+        for pair in map_pairs(options, breakpoints, "readCsv") {
+            let key = IRValue::Variable(0).to_str(pair, params_proc, breakpoints, opts, heap);
+            let val = IRValue::Variable(1);
+            match key.as_str() {
+                "separator" => {
+                    let val = val.to_str(pair, params_proc, breakpoints, opts, heap);
+                    sep = csv_separator(&val, "readCsv", breakpoints);
                 }
-                InterpVal::Bool(true)
-            } else {
-                InterpVal::Bool(false)
+                "header" => header = val.to_bool(pair, params_proc, breakpoints, "readCsv"),
+                _ => exception_throw(
+                    "builtin procedure",
+                    &format!("readCsv: unknown option {key}"),
+                    breakpoints,
+                ),
             }
         }
-        BuiltinProc::AstNodeNew => {
-            let obj = InterpObj::Ast(InterpTaggedList {
-                tag: params[0].to_immed_str("ast_node_new tag").to_string(),
-                list: params
-                    .iter()
-                    .skip(1)
-                    .map(|i| {
-                        i.to_val(vars, params_proc, breakpoints, opts, heap)
-                            .confirm()
-                            .val
-                            .unshare()
-                    })
-                    .collect::<Vec<InterpVal>>(),
-            });
-
-            InterpVal::Ref(heap.push_obj(obj))
-        }
-        BuiltinProc::AstNodeNewSized => {
-            let obj = InterpObj::Ast(InterpTaggedList {
-                tag: params[0].to_str(vars, params_proc, breakpoints, opts, heap),
-                list: vec![
-                    InterpVal::Undefined;
-                    params[1].to_usize(vars, params_proc, breakpoints, "ast_node_new_sized")
-                ],
-            });
+    }
 
-            InterpVal::Ref(heap.push_obj(obj))
-        }
-        BuiltinProc::AstTagGet => tterm_ast_tag_get(
-            &params[0].to_str(vars, params_proc, breakpoints, opts, heap),
-            params[1].to_i64(vars, params_proc, breakpoints, "ast_tag_get") as usize,
+    let full_path = sandbox_path(PathBuf::from(&path), false, opts, breakpoints);
+    let src = fs::read_to_string(full_path).unwrap_or_else(|e| {
+        exception_throw(
+            "builtin procedure",
+            &format!("readCsv: {path}: {e}"),
+            breakpoints,
         )
-        .map(|tag| InterpVal::Ref(heap.push_obj(InterpObj::String(tag))))
-        .unwrap_or(InterpVal::Undefined),
-        BuiltinProc::AstTTermTagGet => {
-            ast_tterm_tag_get(&params[0].to_str(vars, params_proc, breakpoints, opts, heap))
-                .map(|tag| InterpVal::Ref(heap.push_obj(InterpObj::String(tag))))
-                .unwrap_or(InterpVal::Undefined)
-        }
-        BuiltinProc::ProcedureNew => {
-            let proc = params[0].to_proc(vars, breakpoints, "procedure_new");
-            let info = params[1].to_ast(vars, params_proc);
-            let stack = params[2].to_ref(vars).inspect(|s| {
-                InterpVal::Ref(*s).persist(heap);
-            });
-            let cross_frame = params[3].to_bool(vars, params_proc, breakpoints, "procedure_new");
+    });
+    let mut records = csv_parse(&src, sep).unwrap_or_else(|e| {
+        exception_throw(
+            "builtin procedure",
+            &format!("readCsv: {path}: {e}"),
+            breakpoints,
+        )
+    });
 
-            InterpVal::Ref(heap.push_obj(InterpObj::Procedure(InterpProc {
-                proc,
-                stack,
-                info,
-                cross_frame,
-            })))
+    let string_new = |s: String| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(s)));
+    let rows = if header && !records.is_empty() {
+        let names = records.remove(0);
+        if let Some(idx) = records.iter().position(|i| i.len() > names.len()) {
+            exception_throw(
+                "builtin procedure",
+                &format!(
+                    "readCsv: {path}: record {} has more fields than the header",
+                    idx + 2
+                ),
+                breakpoints,
+            );
         }
-        BuiltinProc::ProcedureStackGet => params[0]
-            .to_ref(vars)
-            .map(|r| {
-                if let InterpObj::Procedure(p) = unsafe { &*r.0 }
-                    && let Some(s) = &p.stack
-                {
-                    InterpVal::Ref(*s)
-                } else {
-                    InterpVal::Undefined
-                }
+
+        records
+            .into_iter()
+            .map(|record| {
+                let pairs = names.iter().zip(record).map(|(name, field)| {
+                    InterpVal::Ref(InterpObjRef::from_obj(InterpObj::List(InterpList(vec![
+                        string_new(name.clone()),
+                        string_new(field),
+                    ]))))
+                });
+                InterpVal::Ref(InterpObjRef::from_obj(InterpObj::Set(InterpSet(
+                    pairs.collect(),
+                ))))
             })
-            .unwrap_or(InterpVal::Undefined),
-        BuiltinProc::CacheLookup => {
-            let proc = params[0].to_proc_ptr(vars, "cache_lookup");
-            let p = params[1].to_val(vars, params_proc, breakpoints, opts, heap);
-            let out = params[2].to_ptr(vars, "cache_lookup");
+            .collect()
+    } else {
+        records
+            .into_iter()
+            .map(|record| {
+                InterpVal::Ref(InterpObjRef::from_obj(InterpObj::List(InterpList(
+                    record.into_iter().map(string_new).collect(),
+                ))))
+            })
+            .collect()
+    };
 
-            let res = memo.get(&proc).and_then(|i| i.contains(&p.val));
+    InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(rows))))
+}
 
-            if let Some(r) = &res {
-                unsafe {
-                    *out.ptr = r.unshare();
+fn native_csv_write(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let path = IRValue::Variable(0).to_str(&args, params_proc, breakpoints, opts, heap);
+    let rows = &IRValue::Variable(1)
+        .to_list(&args, params_proc, breakpoints)
+        .0;
+
+    let mut sep = ',';
+    let mut header = None;
+    if arg_given(&args, 2) {
+        let options = IRValue::Variable(2).to_set(&args, params_proc, breakpoints);
+        for pair in map_pairs(options, breakpoints, "writeCsv") {
+            let key = IRValue::Variable(0).to_str(pair, params_proc, breakpoints, opts, heap);
+            let val = IRValue::Variable(1);
+            match key.as_str() {
+                "separator" => {
+                    let val = val.to_str(pair, params_proc, breakpoints, opts, heap);
+                    sep = csv_separator(&val, "writeCsv", breakpoints);
                 }
+                "header" => {
+                    let names = &val.to_list(pair, params_proc, breakpoints).0;
+                    header = Some(
+                        (0..names.len())
+                            .map(|i| {
+                                IRValue::Variable(i).to_str(
+                                    names,
+                                    params_proc,
+                                    breakpoints,
+                                    opts,
+                                    heap,
+                                )
+                            })
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                _ => exception_throw(
+                    "builtin procedure",
+                    &format!("writeCsv: unknown option {key}"),
+                    breakpoints,
+                ),
             }
-            InterpVal::Bool(res.is_some())
         }
-        BuiltinProc::CacheAdd => {
-            let proc = params[0].to_proc_ptr(vars, "cache_add");
-            let val = params[1]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .unshare();
-            let ret_val = params[2]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .unshare();
-            memo.entry(proc).or_default().insert(val, ret_val);
+    }
 
-            InterpVal::Undefined
-        }
-        BuiltinProc::CacheClear => {
-            let proc = params[0].to_proc_ptr(vars, "cache_add");
-            if let Some(i) = memo.get_mut(&proc) {
-                i.clear()
-            };
-            InterpVal::Undefined
-        }
-        BuiltinProc::Exit => {
-            exit(0);
-        }
-        BuiltinProc::ExceptionSet => {
-            let v = params[0]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .clone();
-            if let InterpVal::Ref(r) = v {
-                heap.refs.remove(&r);
+    let mut records: Vec<Vec<Option<&InterpVal>>> = Vec::new();
+    for (idx, row) in rows.iter().enumerate() {
+        if let Some(names) = &header {
+            let row =
+                IRValue::Variable(0).to_set(std::slice::from_ref(row), params_proc, breakpoints);
+            let mut cells = BTreeMap::new();
+            for pair in map_pairs(row, breakpoints, "writeCsv") {
+                let key = IRValue::Variable(0).to_str(pair, params_proc, breakpoints, opts, heap);
+                cells.insert(key, &pair[1]);
             }
-            exception_val_set(v);
-            InterpVal::Undefined
-        }
-        BuiltinProc::ExceptionReset => {
-            exception_val_set(InterpVal::Undefined);
-            exception_kind_set(ExceptionKind::Lng);
-            InterpVal::Undefined
+            records.push(names.iter().map(|i| cells.get(i).copied()).collect());
+        } else if let InterpVal::Ref(r) = row
+            && let InterpObj::List(l) = unsafe { &*r.0 }
+        {
+            records.push(l.0.iter().map(Some).collect());
+        } else {
+            exception_throw(
+                "builtin procedure",
+                &format!("writeCsv: row {} isn't a list", idx + 1),
+                breakpoints,
+            );
         }
-        BuiltinProc::Throw => {
-            if let IRValue::Number(n) = &params[0] {
-                match n.to_u8().unwrap() {
-                    0 => exception_kind_set(ExceptionKind::Lng),
-                    1 => exception_kind_set(ExceptionKind::Usr),
-                    2 => exception_kind_set(ExceptionKind::Backtrack),
-                    3 => {
-                        eprintln!(
-                            "{}",
-                            serialize(
-                                &params[1]
-                                    .to_val(vars, params_proc, breakpoints, opts, heap)
-                                    .val,
-                                vars,
-                                stack,
-                                memo,
-                                cstore,
-                                breakpoints,
-                                opts,
-                                rl,
-                                SerializeOpts::default()
-                            )
-                        );
-                        exit(1);
+    }
+
+    let mut out = String::new();
+    let mut line_push = |fields: &[String]| {
+        let fields = fields
+            .iter()
+            .map(|i| csv_quote(i, sep))
+            .collect::<Vec<_>>();
+        out.push_str(&fields.join(&sep.to_string()));
+        out.push('\n');
+    };
+
+    if let Some(names) = &header {
+        line_push(names);
+    }
+    for record in records {
+        let fields = record
+            .into_iter()
+            .map(|val| match val {
+                None | Some(InterpVal::Undefined) => String::new(),
+                Some(val) => {
+                    if let InterpVal::Ref(r) = val
+                        && let InterpObj::String(s) = unsafe { &*r.0 }
+                    {
+                        s.clone()
+                    } else {
+                        serialize(
+                            val,
+                            vars,
+                            stack,
+                            memo,
+                            cstore,
+                            breakpoints,
+                            opts,
+                            rl,
+                            SerializeOpts::default(),
+                        )
                     }
-                    _ => panic!("internal: throw encountered undefined exception kind"),
                 }
-            }
+            })
+            .collect::<Vec<_>>();
+        line_push(&fields);
+    }
 
-            let v = params[1]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .clone()
-                .persist(heap);
-            exception_val_set(v.clone());
-            panic!(
-                "{}",
-                serialize(
-                    &v,
-                    vars,
-                    stack,
-                    memo,
-                    cstore,
-                    breakpoints,
-                    opts,
-                    rl,
-                    SerializeOpts::ESCAPE_STR
-                )
-            );
-        }
-        BuiltinProc::Rethrow => {
-            panic!(
-                "{}",
-                serialize(
-                    &exception_val_get(),
-                    vars,
-                    stack,
-                    memo,
-                    cstore,
-                    breakpoints,
-                    opts,
-                    rl,
-                    SerializeOpts::ESCAPE_STR
-                )
-            );
-        }
-        BuiltinProc::ExceptionThrow => {
-            let cat_msg = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let msg = params[1].to_str(vars, params_proc, breakpoints, opts, heap);
-            exception_throw(&cat_msg, &msg, breakpoints);
-        }
-        BuiltinProc::RegexCompile => {
-            let flags = params[1].to_i64(vars, params_proc, breakpoints, "regex_compile");
-            let pattern = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    let full_path = sandbox_path(PathBuf::from(&path), true, opts, breakpoints);
+    fs::write(full_path, out).unwrap_or_else(|e| {
+        exception_throw(
+            "builtin procedure",
+            &format!("writeCsv: {path}: {e}"),
+            breakpoints,
+        )
+    });
+    InterpVal::Undefined
+}
 
-            let mut builder = RegexBuilder::new();
-            if flags & 0x02 != 0 {
-                builder.multi_line(true);
-            }
+fn native_is_prime(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let n = params[0]
+        .to_num(vars, params_proc, breakpoints, "is_prime")
+        .to_biguint();
+    if let Some(n) = n {
+        let res = match num_is_prime(&n, Some(PrimalityTestConfig::strict())) {
+            Primality::Yes => true,
+            Primality::No => false,
+            Primality::Probable(_) => prime::check(&n),
+        };
+        InterpVal::Bool(res)
+    } else {
+        InterpVal::Bool(false)
+    }
+}
 
-            InterpVal::Ref(heap.push_obj(InterpObj::Regex(InterpRegex {
-                is_anchored: flags & 0x01 != 0,
-                regex: match builder.build(&pattern) {
-                    Ok(r) => r,
-                    Err(e) => exception_throw(
-                        "builtin procedure",
-                        &format!(
-                            "PCRE2 compile error: code={}, offset={:?}, message={}",
-                            e.code(),
-                            e.offset(),
-                            e
-                        ),
-                        breakpoints,
-                    ),
-                },
-            })))
-        }
-        BuiltinProc::RegexMatch => {
-            let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let regex = params[1].to_regex(vars, params_proc, breakpoints);
-
-            let out = if regex.is_anchored {
-                match regex.regex.find(input.as_bytes()) {
-                    Ok(Some(m)) => m.start() == 0 && m.end() == input.len(),
-                    _ => false,
-                }
-            } else {
-                regex.regex.is_match(input.as_bytes()).unwrap_or(false)
-            };
+fn native_is_probable_prime(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let n = params[0]
+        .to_num(vars, params_proc, breakpoints, "is_prime")
+        .to_biguint();
+    if let Some(n) = n {
+        InterpVal::Bool(matches!(
+            num_is_prime(&n, None),
+            Primality::Yes | Primality::Probable(_)
+        ))
+    } else {
+        InterpVal::Bool(false)
+    }
+}
 
-            InterpVal::Bool(out)
-        }
-        BuiltinProc::RegexMatchGroups => {
-            let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let regex = params[1].to_regex(vars, params_proc, breakpoints);
-            let matched_addr = params[2].to_ptr(vars, "regex_match_groups");
+fn native_la_matrix(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    InterpVal::Ref(heap.push_obj(InterpObj::Matrix(params[0].to_matrix(
+        vars,
+        params_proc,
+        breakpoints,
+        "la_matrix",
+    ))))
+}
 
-            let caps_opt = regex.regex.captures(input.as_bytes()).unwrap_or(None);
+fn native_la_solve(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let m = params[0].to_matrix(vars, params_proc, breakpoints, "la_solve");
+    let b = params[1].to_vector(vars, params_proc, breakpoints, "la_solve");
+    if m.nrows() != b.len() {
+        exception_throw(
+            "builtin procedure",
+            "la_solve: dimensions mismatch",
+            breakpoints,
+        );
+    }
 
-            if let Some(caps) = caps_opt
-                && let Some(full) = caps.get(0)
-                && (!regex.is_anchored || (full.start() == 0 && full.end() == input.len()))
-            {
-                // SAFETY: IR-PTR
-                unsafe {
-                    *matched_addr.ptr = InterpVal::Bool(true);
-                }
+    let x = if m.is_square() {
+        m.lu().solve(&b)
+    } else {
+        m.svd(true, true).solve(&b, f64::EPSILON).ok()
+    }
+    .unwrap_or_else(|| {
+        exception_throw(
+            "builtin procedure",
+            "la_solve: matrix is singular",
+            breakpoints,
+        )
+    });
 
-                let obj = (0..caps.len())
-                    .filter_map(|i| caps.get(i))
-                    .map(|m| String::from_utf8_lossy(m.as_bytes()).to_string())
-                    .map(|s| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(s))))
-                    .collect::<Vec<_>>();
+    InterpVal::Ref(heap.push_obj(InterpObj::Vector(x)))
+}
 
-                InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(obj))))
-            } else {
-                // SAFETY: IR-PTR
-                unsafe {
-                    *matched_addr.ptr = InterpVal::Bool(false);
-                }
-                InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(Vec::new()))))
-            }
-        }
-        BuiltinProc::RegexMatchLen => {
-            let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let regex = params[1].to_regex(vars, params_proc, breakpoints);
-            let len_addr = params[2].to_ptr(vars, "regex_match_groups");
-            let pos_addr = params[3].to_ptr(vars, "regex_match_groups");
+fn native_la_det(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let m = params[0].to_matrix(vars, params_proc, breakpoints, "la_det");
+    if !m.is_square() {
+        exception_throw(
+            "builtin procedure",
+            "la_det requires a square matrix",
+            breakpoints,
+        );
+    }
 
-            let caps_opt = regex.regex.captures(input.as_bytes()).unwrap_or(None);
+    InterpVal::Double(m.determinant())
+}
 
-            if let Some(caps) = caps_opt
-                && let Some(full) = caps.get(0)
-                && (!regex.is_anchored || (full.start() == 0 && full.end() == input.len()))
-            {
-                // SAFETY: IR-PTR
-                unsafe {
-                    *len_addr.ptr = InterpVal::Ref(
-                        heap.push_obj(InterpObj::Number((full.end() - full.start()).into())),
-                    );
-                    *pos_addr.ptr =
-                        InterpVal::Ref(heap.push_obj(InterpObj::Number(full.start().into())))
-                }
-                InterpVal::Bool(true)
-            } else {
-                InterpVal::Bool(false)
-            }
-        }
-        BuiltinProc::RegexMatchGroupsLen => {
-            let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let regex = params[1].to_regex(vars, params_proc, breakpoints);
-            let matched_addr = params[2].to_ptr(vars, "regex_match_groups");
-            let len_addr = params[3].to_ptr(vars, "regex_match_groups");
-            let pos_addr = params[4].to_ptr(vars, "regex_match_groups");
-
-            let caps_opt = regex.regex.captures(input.as_bytes()).unwrap_or(None);
-
-            if let Some(caps) = caps_opt
-                && let Some(full) = caps.get(0)
-                && (!regex.is_anchored || (full.start() == 0 && full.end() == input.len()))
-            {
-                // SAFETY: IR-PTR
-                unsafe {
-                    *len_addr.ptr = InterpVal::Ref(
-                        heap.push_obj(InterpObj::Number((full.end() - full.start()).into())),
-                    );
-                    *pos_addr.ptr =
-                        InterpVal::Ref(heap.push_obj(InterpObj::Number(full.start().into())));
-                    *matched_addr.ptr = InterpVal::Bool(true);
-                }
+fn native_la_eigen_values(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let m = params[0].to_matrix(vars, params_proc, breakpoints, "la_eigenValues");
+    let list = la_eigen(&m, breakpoints)
+        .into_iter()
+        .map(|(val, _)| InterpVal::Double(val))
+        .collect();
 
-                let obj = (0..caps.len())
-                    .filter_map(|i| caps.get(i))
-                    .map(|m| String::from_utf8_lossy(m.as_bytes()).to_string())
-                    .map(|s| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(s))))
-                    .collect::<Vec<_>>();
+    InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(list))))
+}
 
-                InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(obj))))
-            } else {
-                // SAFETY: IR-PTR
-                unsafe {
-                    *matched_addr.ptr = InterpVal::Bool(false);
-                }
-                InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(Vec::new()))))
-            }
-        }
-        BuiltinProc::Serialize => match &params[0] {
-            IRValue::Undefined => {
-                InterpVal::Ref(heap.push_obj(InterpObj::String("undefined".to_string())))
-            }
-            IRValue::Type(t) => InterpVal::Ref(heap.push_obj(InterpObj::String(t.to_string()))),
-            IRValue::Variable(v) => {
-                let vars_ptr = vars as *mut [InterpVal];
-                InterpVal::Ref(heap.push_obj(InterpObj::String(serialize(
-                    &vars[*v],
-                    // SAFETY: non-invalidating mutable borrow
-                    unsafe { &mut *vars_ptr },
-                    stack,
-                    memo,
-                    cstore,
-                    breakpoints,
-                    opts,
-                    rl,
-                    SerializeOpts::default(),
-                ))))
-            }
-            IRValue::String(s) => InterpVal::Ref(heap.push_obj(InterpObj::String(s.to_string()))),
-            IRValue::Number(n) => InterpVal::Ref(heap.push_obj(InterpObj::String(n.to_string()))),
-            IRValue::Double(d) => InterpVal::Ref(heap.push_obj(InterpObj::String(d.to_string()))),
-            IRValue::Bool(b) => InterpVal::Ref(heap.push_obj(InterpObj::String(b.to_string()))),
-            _ => panic!("internal: serialize undefined for internal type"),
-        },
-        BuiltinProc::PrintStderr => {
-            eprint!(
-                "{}",
-                params[0].to_str(vars, params_proc, breakpoints, opts, heap)
-            );
-            InterpVal::Undefined
-        }
-        BuiltinProc::PrintStdout => {
-            print!(
-                "{}",
-                params[0].to_str(vars, params_proc, breakpoints, opts, heap)
-            );
-            io::stdout().flush().unwrap();
-            InterpVal::Undefined
-        }
-        BuiltinProc::ReadLineStdin => {
-            let s = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+fn native_la_eigen_vectors(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let m = params[0].to_matrix(vars, params_proc, breakpoints, "la_eigenVectors");
+    let list = la_eigen(&m, breakpoints)
+        .into_iter()
+        .map(|(_, vec)| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::Vector(vec))))
+        .collect();
+
+    InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(list))))
+}
+
+fn native_la_svd(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let svd = params[0]
+        .to_matrix(vars, params_proc, breakpoints, "la_svd")
+        .svd(true, true);
+    let s = DMatrix::from_diagonal(&svd.singular_values);
+    let list = [svd.u.unwrap(), s, svd.v_t.unwrap().transpose()]
+        .into_iter()
+        .map(|m| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::Matrix(m))))
+        .collect();
+
+    InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(list))))
+}
+
+fn native_la_pseudo_inverse(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let m = params[0].to_matrix(vars, params_proc, breakpoints, "la_pseudoInverse");
+    let dim = m.nrows().max(m.ncols()) as f64;
+    let svd = m.svd(true, true);
+    let eps = f64::EPSILON * dim * svd.singular_values.max();
+    let inv = svd.pseudo_inverse(eps).unwrap_or_else(|e| {
+        exception_throw(
+            "builtin procedure",
+            &format!("la_pseudoInverse: {e}"),
+            breakpoints,
+        )
+    });
+
+    InterpVal::Ref(heap.push_obj(InterpObj::Matrix(inv)))
+}
+
+fn native_la_cond(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let s = params[0]
+        .to_matrix(vars, params_proc, breakpoints, "la_cond")
+        .singular_values();
+
+    InterpVal::Double(s.max() / s.min())
+}
+
+fn native_stat(
+    proc: BuiltinProc,
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let name = params[0].to_immed_str("stat");
+    let dist = StatDist::from_str(name).unwrap();
+    let p = params[1..]
+        .iter()
+        .map(|i| i.to_f64(vars, params_proc, breakpoints, "stat"))
+        .collect::<Vec<_>>();
 
-            let mut input = match rl.readline(&s) {
-                Ok(line) => {
-                    _ = rl.add_history_entry(line.as_str());
-                    line
-                }
-                Err(ReadlineError::Interrupted) => {
-                    exit(1);
-                }
-                Err(ReadlineError::Eof) => {
-                    exit(0);
-                }
-                Err(err) => {
-                    panic!("internal: readline error: {err}");
-                }
-            };
-            if input.ends_with('\n') {
-                input.pop();
-            }
-            InterpVal::Ref(heap.push_obj(InterpObj::String(input)))
-        }
-        BuiltinProc::Eval => {
-            let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let (src, srcname) = breakpoints.get_src();
-            breakpoints.set_src(input.clone(), String::from("eval"));
-            let result = panic::catch_unwind(AssertUnwindSafe(|| {
-                let new_opts = opts.exec_opts();
-                let expr = cst_expr_parse(&input, &new_opts);
-                let eval_proc = IRCfg::from_expr(&expr, &new_opts);
-                exec_proc(
-                    eval_proc,
-                    &InterpVal::Undefined,
-                    stack,
-                    memo,
-                    cstore,
-                    breakpoints,
-                    &new_opts,
-                    rl,
-                )
-            }));
+    let out = match proc {
+        BuiltinProc::StatPdf if !p.is_empty() => dist.pdf(p[0], &p[1..]),
+        BuiltinProc::StatCdf if !p.is_empty() => dist.cdf(p[0], &p[1..]),
+        BuiltinProc::StatSample => dist.sample(&p, &mut breakpoints.rng),
+        _ => None,
+    };
 
-            breakpoints.set_src(src, srcname);
+    InterpVal::Double(out.unwrap_or_else(|| {
+        exception_throw(
+            "builtin procedure",
+            &format!("stat_{name}: invalid distribution parameters {p:?}"),
+            breakpoints,
+        )
+    }))
+}
 
-            match result {
-                Ok(o) => o,
-                Err(e) => {
-                    if let Some(s) = e.downcast_ref::<&str>() {
-                        exception_throw_raw(s);
-                    } else if let Some(s) = e.downcast_ref::<String>() {
-                        exception_throw_raw(s);
-                    } else {
-                        exception_throw_raw("eval yielded error with non-string payload");
-                    }
-                }
-            }
-        }
-        BuiltinProc::EvalTerm => {
-            let input = params[0]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .confirm()
-                .val
-                .clone();
-            let mut new_opts = opts.exec_opts();
-            new_opts.disable_annotations = true;
-            new_opts.bogus_annotations = false;
-            let result = if let Some(expr) = ast_to_cst_expr(&input) {
-                panic::catch_unwind(AssertUnwindSafe(|| {
-                    let eval_proc = IRCfg::from_expr(&expr, &new_opts);
-                    exec_proc(
-                        eval_proc,
-                        &InterpVal::Undefined,
-                        stack,
-                        memo,
-                        cstore,
-                        breakpoints,
-                        &new_opts,
-                        rl,
-                    )
-                }))
-            } else if let Some(stmt) = ast_to_cst_block(&input) {
-                panic::catch_unwind(AssertUnwindSafe(|| {
-                    eprintln!("{:?}", stmt);
-                    let stmt_proc = IRCfg::from_stmt(&stmt, &new_opts);
-                    let out = exec_proc(
-                        stmt_proc,
-                        &InterpVal::Undefined,
-                        stack,
-                        memo,
-                        cstore,
-                        breakpoints,
-                        &new_opts,
-                        rl,
-                    );
+native_variant!(native_stat_pdf, native_stat, BuiltinProc::StatPdf);
 
-                    if let InterpVal::Ref(r) = out {
-                        unsafe {
-                            r.invalidate();
-                        }
-                    }
+native_variant!(native_stat_cdf, native_stat, BuiltinProc::StatCdf);
 
-                    InterpVal::Undefined
-                }))
-            } else {
-                exception_throw("parse error", "input isn't a valid term", breakpoints)
-            };
-            match result {
-                Ok(o) => o,
-                Err(e) => {
-                    if let Some(s) = e.downcast_ref::<&str>() {
-                        exception_throw_raw(s);
-                    } else if let Some(s) = e.downcast_ref::<String>() {
-                        exception_throw_raw(s);
-                    } else {
-                        exception_throw_raw("eval yielded error with non-string payload");
-                    }
-                }
-            }
-        }
-        BuiltinProc::Execute => {
-            let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let (src, srcname) = breakpoints.get_src();
-            breakpoints.set_src(input.clone(), String::from("execute"));
-            let result = panic::catch_unwind(AssertUnwindSafe(|| {
-                let new_opts = opts.exec_opts();
-                let stmt = cst_parse(&input, &new_opts);
-                let stmt_proc = IRCfg::from_stmt(&stmt, &new_opts);
-                let out = exec_proc(
-                    stmt_proc,
-                    &InterpVal::Undefined,
-                    stack,
-                    memo,
-                    cstore,
-                    breakpoints,
-                    &new_opts,
-                    rl,
-                );
+native_variant!(native_stat_sample, native_stat, BuiltinProc::StatSample);
 
-                if let InterpVal::Ref(r) = out {
-                    unsafe {
-                        r.invalidate();
-                    }
-                }
+fn native_plot_create_canvas(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let title = if arg_given(&args, 0) {
+        IRValue::Variable(0).to_str(&args, params_proc, breakpoints, opts, heap)
+    } else {
+        String::new()
+    };
+
+    InterpVal::Ref(
+        heap.push_obj(InterpObj::Canvas(Rc::new(RefCell::new(PlotCanvas::new(
+            &title,
+        ))))),
+    )
+}
+
+fn native_plot_graph_add(
+    proc: BuiltinProc,
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let canvas = IRValue::Variable(0).to_canvas(&args, params_proc, breakpoints);
+    let f = IRValue::Variable(1).to_str(&args, params_proc, breakpoints, opts, heap);
 
-                InterpVal::Bool(true)
-            }));
+    let (segments, name_idx, color_idx) = if let BuiltinProc::PlotAddGraph = proc {
+        let (lo, hi) = canvas.borrow().sample_range();
+        let xs = (0..=500)
+            .map(|i| lo + (hi - lo) * i as f64 / 500.0)
+            .collect::<Vec<_>>();
+        let ys = plot_fn_eval(&f, &xs, stack, memo, cstore, breakpoints, opts, rl);
+        (plot_segments(xs.into_iter().zip(ys)), 2, 3)
+    } else {
+        let g = IRValue::Variable(2).to_str(&args, params_proc, breakpoints, opts, heap);
+        let (lo, hi) = plot_range(&args, 4, breakpoints, "plot_addParamGraph");
+        let ts = (0..=1000)
+            .map(|i| lo + (hi - lo) * i as f64 / 1000.0)
+            .collect::<Vec<_>>();
+        let xs = plot_fn_eval(&f, &ts, stack, memo, cstore, breakpoints, opts, rl);
+        let ys = plot_fn_eval(&g, &ts, stack, memo, cstore, breakpoints, opts, rl);
+        (plot_segments(xs.into_iter().zip(ys)), 3, 5)
+    };
 
-            breakpoints.set_src(src, srcname);
+    let name = IRValue::Variable(name_idx).to_str(&args, params_proc, breakpoints, opts, heap);
+    let mut c = canvas.borrow_mut();
+    let color = plot_color(&args, color_idx, &mut c, breakpoints);
+    let fill = arg_given(&args, color_idx + 1)
+        && IRValue::Variable(color_idx + 1).to_bool(&args, params_proc, breakpoints, "plot fill");
+    c.items.push(PlotItem::Graph {
+        name,
+        segments,
+        color,
+        fill,
+    });
+    InterpVal::Undefined
+}
 
-            match result {
-                Ok(o) => o,
-                Err(e) => {
-                    if let Some(s) = e.downcast_ref::<&str>() {
-                        exception_throw_raw(s);
-                    } else if let Some(s) = e.downcast_ref::<String>() {
-                        exception_throw_raw(s);
-                    } else {
-                        exception_throw_raw("execute yielded error with non-string payload");
-                    }
-                }
-            }
-        }
-        BuiltinProc::ParseAst => {
-            let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let (src, srcname) = breakpoints.get_src();
-            breakpoints.set_src(input.clone(), String::from("execute"));
-            let result = panic::catch_unwind(AssertUnwindSafe(|| {
-                let new_opts = opts.exec_opts();
-                let expr = cst_expr_parse(&input, &new_opts);
-                let eval_proc = IRCfg::from_ast_expr(&expr, &new_opts);
-                exec_proc(
-                    eval_proc,
-                    &InterpVal::Undefined,
-                    stack,
-                    memo,
-                    cstore,
-                    breakpoints,
-                    &new_opts,
-                    rl,
-                )
-            }));
+native_variant!(
+    native_plot_add_graph,
+    native_plot_graph_add,
+    BuiltinProc::PlotAddGraph
+);
 
-            breakpoints.set_src(src, srcname);
+native_variant!(
+    native_plot_add_param_graph,
+    native_plot_graph_add,
+    BuiltinProc::PlotAddParamGraph
+);
 
-            match result {
-                Ok(o) => o,
-                Err(e) => {
-                    if let Some(s) = e.downcast_ref::<&str>() {
-                        exception_throw_raw(s);
-                    } else if let Some(s) = e.downcast_ref::<String>() {
-                        exception_throw_raw(s);
-                    } else {
-                        exception_throw_raw("eval yielded error with non-string payload");
-                    }
-                }
-            }
-        }
-        BuiltinProc::ParseAstBlock => {
-            let input = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let (src, srcname) = breakpoints.get_src();
-            breakpoints.set_src(input.clone(), String::from("execute"));
-            let result = panic::catch_unwind(AssertUnwindSafe(|| {
-                let new_opts = opts.exec_opts();
-                let expr = cst_parse(&input, &new_opts);
-                let eval_proc = IRCfg::from_ast_block(&expr, &new_opts);
-                exec_proc(
-                    eval_proc,
-                    &InterpVal::Undefined,
+fn native_plot_add_list_graph(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let canvas = IRValue::Variable(0).to_canvas(&args, params_proc, breakpoints);
+    let points = plot_points(&args, 1, breakpoints, "plot_addListGraph");
+    let name = IRValue::Variable(2).to_str(&args, params_proc, breakpoints, opts, heap);
+
+    let mut c = canvas.borrow_mut();
+    let color = plot_color(&args, 3, &mut c, breakpoints);
+    let fill = arg_given(&args, 4)
+        && IRValue::Variable(4).to_bool(&args, params_proc, breakpoints, "plot fill");
+    c.items.push(PlotItem::Graph {
+        name,
+        segments: plot_segments(points.into_iter()),
+        color,
+        fill,
+    });
+    InterpVal::Undefined
+}
+
+fn native_plot_add_bar_chart(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let canvas = IRValue::Variable(0).to_canvas(&args, params_proc, breakpoints);
+    let values =
+        IRValue::Variable(1).to_vector(&args, params_proc, breakpoints, "plot_addBarChart");
+    let name = IRValue::Variable(2).to_str(&args, params_proc, breakpoints, opts, heap);
+
+    let mut c = canvas.borrow_mut();
+    let color = plot_color(&args, 3, &mut c, breakpoints);
+    let width = if arg_given(&args, 4) {
+        IRValue::Variable(4).to_f64(&args, params_proc, breakpoints, "plot_addBarChart")
+    } else {
+        0.8
+    };
+    c.items.push(PlotItem::Bars {
+        name,
+        values: values.iter().copied().collect(),
+        color,
+        width,
+    });
+    InterpVal::Undefined
+}
+
+fn native_plot_add_pie_chart(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let canvas = IRValue::Variable(0).to_canvas(&args, params_proc, breakpoints);
+    let values =
+        IRValue::Variable(1).to_vector(&args, params_proc, breakpoints, "plot_addPieChart");
+    let labels = if arg_given(&args, 2) {
+        IRValue::Variable(2)
+            .to_list(&args, params_proc, breakpoints)
+            .0
+            .iter()
+            .map(|i| {
+                serialize(
+                    i,
+                    vars,
                     stack,
                     memo,
                     cstore,
                     breakpoints,
-                    &new_opts,
+                    opts,
                     rl,
+                    SerializeOpts::empty(),
                 )
-            }));
-
-            breakpoints.set_src(src, srcname);
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
 
-            match result {
-                Ok(o) => o,
-                Err(e) => {
-                    if let Some(s) = e.downcast_ref::<&str>() {
-                        exception_throw_raw(s);
-                    } else if let Some(s) = e.downcast_ref::<String>() {
-                        exception_throw_raw(s);
-                    } else {
-                        exception_throw_raw("eval yielded error with non-string payload");
-                    }
-                }
-            }
-        }
-        BuiltinProc::OpenAt => {
-            let base_path = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            let rel_path = params[1].to_str(vars, params_proc, breakpoints, opts, heap);
-            let opts = params[2].to_i64(vars, params_proc, breakpoints, "open_at");
-
-            let mut o_opts = OpenOptions::new();
-            if opts & 0x01 != 0 {
-                o_opts.read(true);
-            }
+    canvas.borrow_mut().items.push(PlotItem::Pie {
+        values: values.iter().copied().collect(),
+        labels,
+    });
+    InterpVal::Undefined
+}
 
-            if opts & 0x02 != 0 {
-                o_opts.write(true);
-            }
+fn native_plot_add_bullets(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let canvas = IRValue::Variable(0).to_canvas(&args, params_proc, breakpoints);
+    let points = plot_points(&args, 1, breakpoints, "plot_addBullets");
 
-            if opts & 0x04 != 0 {
-                o_opts.append(true);
-            }
+    let mut c = canvas.borrow_mut();
+    let color = plot_color(&args, 2, &mut c, breakpoints);
+    let size = if arg_given(&args, 3) {
+        IRValue::Variable(3).to_f64(&args, params_proc, breakpoints, "plot_addBullets")
+    } else {
+        4.0
+    };
+    c.items.push(PlotItem::Bullets {
+        points,
+        color,
+        size,
+    });
+    InterpVal::Undefined
+}
 
-            if opts & 0x08 != 0 {
-                o_opts.create(true);
-            }
+fn native_plot_add_label(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let canvas = IRValue::Variable(0).to_canvas(&args, params_proc, breakpoints);
+    let pos = IRValue::Variable(1).to_vector(&args, params_proc, breakpoints, "plot_addLabel");
+    if pos.len() != 2 {
+        exception_throw(
+            "builtin procedure",
+            "plot_addLabel expects a position [x, y]",
+            breakpoints,
+        );
+    }
+    let text = IRValue::Variable(2).to_str(&args, params_proc, breakpoints, opts, heap);
+    let color = if arg_given(&args, 3) {
+        plot_color(&args, 3, &mut canvas.borrow_mut(), breakpoints)
+    } else {
+        (0, 0, 0)
+    };
 
-            match o_opts.open(std::path::PathBuf::from(base_path).join(rel_path)) {
-                Ok(file) => {
-                    InterpVal::Ref(heap.push_obj(InterpObj::File(Rc::new(RefCell::new(file)))))
-                }
-                Err(e) => exception_throw("builtin procedure", &e.to_string(), breakpoints),
-            }
-        }
-        BuiltinProc::ReadAll => {
-            let file = params[0].to_file(vars, params_proc, breakpoints);
-
-            let mut contents = Vec::new();
-            file.borrow_mut()
-                .read_to_end(&mut contents)
-                .unwrap_or_else(|e| {
-                    exception_throw("builtin procedure", &e.to_string(), breakpoints)
-                });
-            InterpVal::Ref(heap.push_obj(InterpObj::String(
-                String::from_utf8_lossy(&contents).to_string(),
-            )))
-        }
-        BuiltinProc::ReadAllList => {
-            let file = params[0].to_file(vars, params_proc, breakpoints);
-
-            let mut contents = Vec::new();
-            file.borrow_mut()
-                .read_to_end(&mut contents)
-                .unwrap_or_else(|e| {
-                    exception_throw("builtin procedure", &e.to_string(), breakpoints)
-                });
-            InterpVal::Ref(
-                heap.push_obj(InterpObj::List(InterpList(
-                    String::from_utf8_lossy(&contents)
-                        .trim_end_matches('\n')
-                        .split("\n")
-                        .map(|i| {
-                            InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(i.to_string())))
-                        })
-                        .collect(),
-                ))),
-            )
-        }
-        BuiltinProc::Write => {
-            let file = params[0].to_file(vars, params_proc, breakpoints);
-            let s = params[1].to_str(vars, params_proc, breakpoints, opts, heap);
-
-            file.borrow_mut()
-                .write_all(s.as_bytes())
-                .unwrap_or_else(|e| {
-                    exception_throw("builtin procedure", &e.to_string(), breakpoints)
-                });
+    canvas.borrow_mut().items.push(PlotItem::Label {
+        pos: (pos[0], pos[1]),
+        text,
+        color,
+    });
+    InterpVal::Undefined
+}
 
-            InterpVal::Undefined
-        }
-        BuiltinProc::Delete => {
-            let file = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            fs::remove_file(file).unwrap_or_else(|e| {
-                exception_throw("failed to delete file: {:?}", &e.to_string(), breakpoints);
-            });
-            InterpVal::Undefined
-        }
-        BuiltinProc::Ln => {
-            InterpVal::Double(params[0].to_f64(vars, params_proc, breakpoints, "ln").ln())
-        }
-        BuiltinProc::Exp => InterpVal::Double(
-            params[0]
-                .to_f64(vars, params_proc, breakpoints, "exp")
-                .exp(),
-        ),
-        BuiltinProc::Sqrt => InterpVal::Double(
-            params[0]
-                .to_f64(vars, params_proc, breakpoints, "sqrt")
-                .sqrt(),
-        ),
-        BuiltinProc::Round => InterpVal::Double(
-            params[0]
-                .to_f64(vars, params_proc, breakpoints, "round")
-                .round(),
-        ),
-        BuiltinProc::Floor => InterpVal::Double(
-            params[0]
-                .to_f64(vars, params_proc, breakpoints, "floor")
-                .floor(),
-        ),
-        BuiltinProc::Ceil => InterpVal::Double(
-            params[0]
-                .to_f64(vars, params_proc, breakpoints, "ceil")
-                .ceil(),
-        ),
-        BuiltinProc::Sin => InterpVal::Double(
-            params[0]
-                .to_f64(vars, params_proc, breakpoints, "sin")
-                .sin(),
-        ),
-        BuiltinProc::Cos => InterpVal::Double(
-            params[0]
-                .to_f64(vars, params_proc, breakpoints, "cos")
-                .cos(),
-        ),
-        BuiltinProc::Tan => InterpVal::Double(
-            params[0]
-                .to_f64(vars, params_proc, breakpoints, "tan")
-                .tan(),
-        ),
-        BuiltinProc::SinH => InterpVal::Double(
-            params[0]
-                .to_f64(vars, params_proc, breakpoints, "sinh")
-                .sinh(),
-        ),
-        BuiltinProc::CosH => InterpVal::Double(
-            params[0]
-                .to_f64(vars, params_proc, breakpoints, "cosh")
-                .cosh(),
-        ),
-        BuiltinProc::TanH => InterpVal::Double(
-            params[0]
-                .to_f64(vars, params_proc, breakpoints, "tanh")
-                .tanh(),
-        ),
-        BuiltinProc::Ulp => {
-            let x = params[0].to_f64(vars, params_proc, breakpoints, "ulp");
-            if x.is_nan() {
-                return InterpVal::Double(f64::NAN);
-            }
+fn native_plot_label_axis(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let canvas = IRValue::Variable(0).to_canvas(&args, params_proc, breakpoints);
+    let x_label = IRValue::Variable(1).to_str(&args, params_proc, breakpoints, opts, heap);
+    let y_label = IRValue::Variable(2).to_str(&args, params_proc, breakpoints, opts, heap);
 
-            if x.is_infinite() {
-                return InterpVal::Double(f64::INFINITY);
-            }
+    let mut c = canvas.borrow_mut();
+    c.x_label = x_label;
+    c.y_label = y_label;
+    InterpVal::Undefined
+}
 
-            let bits = x.to_bits();
-            let next_bits = if x > 0.0 { bits + 1 } else { bits - 1 };
+fn native_plot_define_title(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let canvas = IRValue::Variable(0).to_canvas(&args, params_proc, breakpoints);
+    canvas.borrow_mut().title =
+        IRValue::Variable(1).to_str(&args, params_proc, breakpoints, opts, heap);
+    InterpVal::Undefined
+}
 
-            let next = f64::from_bits(next_bits);
+fn native_plot_mod_scale(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let canvas = IRValue::Variable(0).to_canvas(&args, params_proc, breakpoints);
+    let x_range = plot_range(&args, 1, breakpoints, "plot_modScale");
+    let y_range = plot_range(&args, 2, breakpoints, "plot_modScale");
 
-            InterpVal::Double((next - x).abs())
-        }
-        BuiltinProc::RndFloat => InterpVal::Double(rand::rng().random_range(0.0..=1.0)),
-        BuiltinProc::ToChar => {
-            InterpVal::Char(
-                params[0].to_usize(vars, params_proc, breakpoints, "to_char") as u8 as char,
-            )
-        }
-        BuiltinProc::Sleep => {
-            thread::sleep(Duration::from_millis(params[0].to_usize(
-                vars,
-                params_proc,
-                breakpoints,
-                "sleep",
-            ) as u64));
-            InterpVal::Undefined
-        }
-        BuiltinProc::UnixEpoch => InterpVal::Ref(
-            heap.push_obj(InterpObj::Number(
-                SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis()
-                    .into(),
-            )),
-        ),
-        BuiltinProc::Cmp => match val_cmp(
-            &params[0]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .val,
-            &params[1]
-                .to_val(vars, params_proc, breakpoints, opts, heap)
-                .val,
-        ) {
-            Ordering::Less => InterpVal::Ref(heap.push_obj(InterpObj::Number((-1).into()))),
-            Ordering::Equal => InterpVal::Ref(heap.push_obj(InterpObj::Number(0.into()))),
-            Ordering::Greater => InterpVal::Ref(heap.push_obj(InterpObj::Number(1.into()))),
-        },
-        BuiltinProc::ParseInt => params[0]
-            .to_str(vars, params_proc, breakpoints, opts, heap)
-            .parse::<i64>()
-            .map(|i| InterpVal::Ref(heap.push_obj(InterpObj::Number(i.into()))))
-            .unwrap_or(InterpVal::Undefined),
-        BuiltinProc::ParseFloat => params[0]
-            .to_str(vars, params_proc, breakpoints, opts, heap)
-            .parse::<f64>()
-            .map(InterpVal::Double)
-            .unwrap_or(InterpVal::Undefined),
-        BuiltinProc::Cmd => {
-            pub fn system_capture(cmd: &str) -> (String, String) {
-                #[cfg(target_family = "unix")]
-                let output = Command::new("sh")
-                    .arg("-c")
-                    .arg(cmd)
-                    .output()
-                    .expect("failed to open shell");
-
-                #[cfg(target_family = "windows")]
-                let output = Command::new("cmd")
-                    .arg("/C")
-                    .arg(cmd)
-                    .output()
-                    .expect("failed to open shell");
-
-                let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-                let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-                (stdout, stderr)
-            }
+    let mut c = canvas.borrow_mut();
+    c.x_range = Some(x_range);
+    c.y_range = Some(y_range);
+    InterpVal::Undefined
+}
 
-            let (stdout, stderr) =
-                system_capture(&params[0].to_str(vars, params_proc, breakpoints, opts, heap));
+fn native_plot_export_canvas(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let args = args_collect(&params[0], vars, params_proc, breakpoints);
+    let canvas = IRValue::Variable(0).to_canvas(&args, params_proc, breakpoints);
+    let path = IRValue::Variable(1).to_str(&args, params_proc, breakpoints, opts, heap);
+    let path = sandbox_path(PathBuf::from(path), true, opts, breakpoints);
 
-            let mut stdout_obj = InterpList::default();
-            stdout.lines().for_each(|i| {
-                stdout_obj.push(InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(
-                    i.to_string(),
-                ))))
-            });
+    let path = canvas.borrow().export(&path.to_string_lossy()).unwrap_or_else(|e| {
+        exception_throw(
+            "builtin procedure",
+            &format!("plot_exportCanvas: {e}"),
+            breakpoints,
+        )
+    });
+    InterpVal::Ref(heap.push_obj(InterpObj::String(path)))
+}
 
-            let mut stderr_obj = InterpList::default();
-            stderr.lines().for_each(|i| {
-                stderr_obj.push(InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(
-                    i.to_string(),
-                ))))
-            });
+fn native_str_val(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let s = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
+    InterpVal::Ref(
+        heap.push_obj(InterpObj::Number(
+            (s.chars().next().unwrap_or_else(|| {
+                exception_throw(
+                    "builtin procedure",
+                    "empty string undefined for StrVal",
+                    breakpoints,
+                )
+            }) as u32)
+                .into(),
+        )),
+    )
+}
 
-            let mut list_obj = InterpList::default();
-            list_obj.push(InterpVal::Ref(InterpObjRef::from_obj(InterpObj::List(
-                stdout_obj,
-            ))));
-            list_obj.push(InterpVal::Ref(InterpObjRef::from_obj(InterpObj::List(
-                stderr_obj,
-            ))));
+pub fn builtin_native(proc: BuiltinProc) -> NativeProc {
+    let (arity, handler): (Option<usize>, NativeFn) = match proc {
+        BuiltinProc::Amount => (Some(1), native_amount),
+        BuiltinProc::Contains => (Some(2), native_contains),
+        BuiltinProc::Cartesian => (Some(2), native_cartesian),
+        BuiltinProc::Pow => (Some(2), native_pow),
+        BuiltinProc::TypeOf => (Some(1), native_type_of),
+        BuiltinProc::TermNew => (Some(3), native_term_new),
+        BuiltinProc::TermKindEq => (Some(2), native_term_kind_eq),
+        BuiltinProc::Invalidate => (Some(1), native_invalidate),
+        BuiltinProc::MarkPersist => (Some(1), native_mark_persist),
+        BuiltinProc::MarkImmed => (Some(1), native_mark_immed),
+        BuiltinProc::Copy => (Some(1), native_copy),
+        BuiltinProc::StackFrameAdd => (Some(0), native_stack_frame_add),
+        BuiltinProc::StackFramePop => (Some(0), native_stack_frame_pop),
+        BuiltinProc::StackFrameSave => (Some(0), native_stack_frame_save),
+        BuiltinProc::StackFrameRestore => (Some(1), native_stack_frame_restore),
+        BuiltinProc::StackFrameCopy => (Some(0), native_stack_frame_copy),
+        BuiltinProc::StackCopy => (Some(0), native_stack_copy),
+        BuiltinProc::StackAdd => (Some(1), native_stack_add),
+        BuiltinProc::StackAlias => (Some(3), native_stack_alias),
+        BuiltinProc::StackPop => (Some(1), native_stack_pop),
+        BuiltinProc::StackGetOrNew => (Some(1), native_stack_get_or_new),
+        BuiltinProc::StackInScope => (Some(1), native_stack_in_scope),
+        BuiltinProc::ObjectNew => (Some(1), native_object_new),
+        BuiltinProc::ObjectGetOrNew => (Some(2), native_object_get_or_new),
+        BuiltinProc::ObjectGet => (Some(2), native_object_get),
+        BuiltinProc::ObjectAdd => (Some(2), native_object_add),
+        BuiltinProc::ObjectAddImage => (Some(2), native_object_add_image),
+        BuiltinProc::ObjectIterNew => (Some(1), native_object_iter_new),
+        BuiltinProc::ObjectIterNext => (Some(3), native_object_iter_next),
+        BuiltinProc::ClassAdd => (Some(3), native_class_add),
+        BuiltinProc::SetNew => (Some(0), native_set_new),
+        BuiltinProc::SetInsert => (Some(2), native_set_insert),
+        BuiltinProc::SetRange => (Some(2), native_set_range),
+        BuiltinProc::SetBorrow => (Some(2), native_set_borrow),
+        BuiltinProc::SetTake => (Some(2), native_set_take),
+        BuiltinProc::SetGetTag => (Some(2), native_set_get_tag),
+        BuiltinProc::SetGetTagMut => (Some(2), native_set_get_tag_mut),
+        BuiltinProc::SetGetTagAll => (Some(2), native_set_get_tag_all),
+        BuiltinProc::SetDomain => (Some(1), native_set_domain),
+        BuiltinProc::SetImage => (Some(1), native_set_image),
+        BuiltinProc::ListNew => (None, native_list_new),
+        BuiltinProc::ListPush => (Some(2), native_list_push),
+        BuiltinProc::Pop => (Some(1), native_pop),
+        BuiltinProc::ListRange => (Some(2), native_list_range),
+        BuiltinProc::ListResize => (Some(2), native_list_resize),
+        BuiltinProc::Slice => (Some(3), native_slice),
+        BuiltinProc::IterNew => (Some(1), native_iter_new),
+        BuiltinProc::IterNext => (Some(2), native_iter_next),
+        BuiltinProc::AstNodeNew => (None, native_ast_node_new),
+        BuiltinProc::AstNodeNewSized => (Some(2), native_ast_node_new_sized),
+        BuiltinProc::AstTagGet => (Some(2), native_ast_tag_get),
+        BuiltinProc::AstTTermTagGet => (Some(1), native_ast_t_term_tag_get),
+        BuiltinProc::ProcedureNew => (Some(4), native_procedure_new),
+        BuiltinProc::ProcedureStackGet => (Some(1), native_procedure_stack_get),
+        BuiltinProc::CacheLookup => (Some(3), native_cache_lookup),
+        BuiltinProc::CacheAdd => (Some(3), native_cache_add),
+        BuiltinProc::CacheClear => (Some(1), native_cache_clear),
+        BuiltinProc::Exit => (Some(0), native_exit),
+        BuiltinProc::ExceptionSet => (Some(1), native_exception_set),
+        BuiltinProc::ExceptionReset => (Some(0), native_exception_reset),
+        BuiltinProc::Throw => (Some(2), native_throw),
+        BuiltinProc::Rethrow => (Some(0), native_rethrow),
+        BuiltinProc::ExceptionThrow => (Some(2), native_exception_throw),
+        BuiltinProc::RegexCompile => (Some(2), native_regex_compile),
+        BuiltinProc::RegexMatch => (Some(2), native_regex_match),
+        BuiltinProc::RegexMatchGroups => (Some(3), native_regex_match_groups),
+        BuiltinProc::RegexMatchLen => (Some(4), native_regex_match_len),
+        BuiltinProc::RegexMatchGroupsLen => (Some(5), native_regex_match_groups_len),
+        BuiltinProc::Serialize => (Some(1), native_serialize),
+        BuiltinProc::PrintStderr => (Some(1), native_print_stderr),
+        BuiltinProc::PrintStdout => (Some(1), native_print_stdout),
+        BuiltinProc::ReadLineStdin => (Some(1), native_read_line_stdin),
+        BuiltinProc::Eval => (Some(1), native_eval),
+        BuiltinProc::EvalTerm => (Some(1), native_eval_term),
+        BuiltinProc::Execute => (Some(1), native_execute),
+        BuiltinProc::Load => (Some(2), native_load),
+        BuiltinProc::ParseAst => (Some(1), native_parse_ast),
+        BuiltinProc::ParseAstBlock => (Some(1), native_parse_ast_block),
+        BuiltinProc::OpenAt => (Some(3), native_open_at),
+        BuiltinProc::ReadAll => (Some(1), native_read_all),
+        BuiltinProc::ReadAllList => (Some(1), native_read_all_list),
+        BuiltinProc::Write => (Some(2), native_write),
+        BuiltinProc::FileOpen => (Some(1), native_file_open),
+        BuiltinProc::FileReadLine => (Some(1), native_file_read_line),
+        BuiltinProc::FileReadChars => (Some(1), native_file_read_chars),
+        BuiltinProc::FileWriteString => (Some(1), native_file_write_string),
+        BuiltinProc::FileFlush => (Some(1), native_file_flush),
+        BuiltinProc::FileClose => (Some(1), native_file_close),
+        BuiltinProc::Delete => (Some(1), native_delete),
+        BuiltinProc::Ln => (Some(1), native_ln),
+        BuiltinProc::Exp => (Some(1), native_exp),
+        BuiltinProc::Sqrt => (Some(1), native_sqrt),
+        BuiltinProc::Round => (Some(1), native_round),
+        BuiltinProc::Floor => (Some(1), native_floor),
+        BuiltinProc::Ceil => (Some(1), native_ceil),
+        BuiltinProc::Rational => (Some(1), native_rational),
+        BuiltinProc::NDecimalPlaces => (Some(2), native_n_decimal_places),
+        BuiltinProc::Sin => (Some(1), native_sin),
+        BuiltinProc::Cos => (Some(1), native_cos),
+        BuiltinProc::Tan => (Some(1), native_tan),
+        BuiltinProc::SinH => (Some(1), native_sin_h),
+        BuiltinProc::CosH => (Some(1), native_cos_h),
+        BuiltinProc::TanH => (Some(1), native_tan_h),
+        BuiltinProc::Ulp => (Some(1), native_ulp),
+        BuiltinProc::RndFloat => (Some(0), native_rnd_float),
+        BuiltinProc::RndSeed => (Some(1), native_rnd_seed),
+        BuiltinProc::ToChar => (Some(1), native_to_char),
+        BuiltinProc::Sleep => (Some(1), native_sleep),
+        BuiltinProc::UnixEpoch => (Some(0), native_unix_epoch),
+        BuiltinProc::Cmp => (Some(2), native_cmp),
+        BuiltinProc::ParseInt => (Some(1), native_parse_int),
+        BuiltinProc::ParseFloat => (Some(1), native_parse_float),
+        BuiltinProc::Cmd => (Some(1), native_cmd),
+        BuiltinProc::RunProcess => (Some(1), native_run_process),
+        BuiltinProc::ToJson => (Some(1), native_to_json),
+        BuiltinProc::FromJson => (Some(1), native_from_json),
+        BuiltinProc::CsvRead => (Some(1), native_csv_read),
+        BuiltinProc::CsvWrite => (Some(1), native_csv_write),
+        BuiltinProc::IsPrime => (Some(1), native_is_prime),
+        BuiltinProc::IsProbablePrime => (Some(1), native_is_probable_prime),
+        BuiltinProc::LaMatrix => (Some(1), native_la_matrix),
+        BuiltinProc::LaSolve => (Some(2), native_la_solve),
+        BuiltinProc::LaDet => (Some(1), native_la_det),
+        BuiltinProc::LaEigenValues => (Some(1), native_la_eigen_values),
+        BuiltinProc::LaEigenVectors => (Some(1), native_la_eigen_vectors),
+        BuiltinProc::LaSvd => (Some(1), native_la_svd),
+        BuiltinProc::LaPseudoInverse => (Some(1), native_la_pseudo_inverse),
+        BuiltinProc::LaCond => (Some(1), native_la_cond),
+        BuiltinProc::StatPdf => (None, native_stat_pdf),
+        BuiltinProc::StatCdf => (None, native_stat_cdf),
+        BuiltinProc::StatSample => (None, native_stat_sample),
+        BuiltinProc::PlotCreateCanvas => (Some(1), native_plot_create_canvas),
+        BuiltinProc::PlotAddGraph => (Some(1), native_plot_add_graph),
+        BuiltinProc::PlotAddParamGraph => (Some(1), native_plot_add_param_graph),
+        BuiltinProc::PlotAddListGraph => (Some(1), native_plot_add_list_graph),
+        BuiltinProc::PlotAddBarChart => (Some(1), native_plot_add_bar_chart),
+        BuiltinProc::PlotAddPieChart => (Some(1), native_plot_add_pie_chart),
+        BuiltinProc::PlotAddBullets => (Some(1), native_plot_add_bullets),
+        BuiltinProc::PlotAddLabel => (Some(1), native_plot_add_label),
+        BuiltinProc::PlotLabelAxis => (Some(1), native_plot_label_axis),
+        BuiltinProc::PlotDefineTitle => (Some(1), native_plot_define_title),
+        BuiltinProc::PlotModScale => (Some(1), native_plot_mod_scale),
+        BuiltinProc::PlotExportCanvas => (Some(1), native_plot_export_canvas),
+        BuiltinProc::StrVal => (Some(1), native_str_val),
+    };
 
-            InterpVal::Ref(heap.push_obj(InterpObj::List(list_obj)))
-        }
-        BuiltinProc::IsPrime => {
-            let n = params[0]
-                .to_num(vars, params_proc, breakpoints, "is_prime")
-                .to_biguint();
-            if let Some(n) = n {
-                let res = match num_is_prime(&n, Some(PrimalityTestConfig::strict())) {
-                    Primality::Yes => true,
-                    Primality::No => false,
-                    Primality::Probable(_) => prime::check(&n),
-                };
-                InterpVal::Bool(res)
-            } else {
-                InterpVal::Bool(false)
-            }
-        }
-        BuiltinProc::IsProbablePrime => {
-            let n = params[0]
-                .to_num(vars, params_proc, breakpoints, "is_prime")
-                .to_biguint();
-            if let Some(n) = n {
-                InterpVal::Bool(matches!(
-                    num_is_prime(&n, None),
-                    Primality::Yes | Primality::Probable(_)
-                ))
-            } else {
-                InterpVal::Bool(false)
-            }
-        }
-        BuiltinProc::StrVal => {
-            let s = params[0].to_str(vars, params_proc, breakpoints, opts, heap);
-            InterpVal::Ref(
-                heap.push_obj(InterpObj::Number(
-                    (s.chars().next().unwrap_or_else(|| {
-                        exception_throw(
-                            "builtin procedure",
-                            "empty string undefined for StrVal",
-                            breakpoints,
-                        )
-                    }) as u32)
-                        .into(),
-                )),
-            )
-        }
-    }
+    NativeProc::builtin(proc, arity, handler)
 }
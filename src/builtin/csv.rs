@@ -0,0 +1,98 @@
+use std::borrow::Cow;
+
+/// Splits `src` into records of fields separated by `sep`. Fields may be
+/// quoted with `"` to contain separators, line breaks and doubled quotes.
+/// Blank lines are skipped. Errors name the line and column of the offending
+/// character.
+pub fn csv_parse(src: &str, sep: char) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    /* position of the current character and of the quote opening the
+     * current field
+     */
+    let (mut line, mut col) = (1, 0);
+    let mut quote_pos = (0, 0);
+
+    let mut chars = src.chars().peekable();
+    while let Some(c) = chars.next() {
+        col += 1;
+
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    col += 1;
+                    field.push('"');
+                }
+                '"' => {
+                    quoted = false;
+                    if let Some(&next) = chars.peek()
+                        && next != sep
+                        && next != '\n'
+                        && next != '\r'
+                    {
+                        return Err(format!(
+                            "unexpected {next:?} after closing quote at line {line} column {}",
+                            col + 1
+                        ));
+                    }
+                }
+                '\n' => {
+                    line += 1;
+                    col = 0;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => {
+                quoted = true;
+                quote_pos = (line, col);
+            }
+            '"' => {
+                return Err(format!(
+                    "unexpected quote in unquoted field at line {line} column {col}"
+                ));
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                // blank lines don't count as records
+                if !field.is_empty() || !record.is_empty() {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                line += 1;
+                col = 0;
+            }
+            _ if c == sep => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(format!(
+            "unterminated quoted field at line {} column {}",
+            quote_pos.0, quote_pos.1
+        ));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// `field` quoted as needed to be read back by [`csv_parse`].
+pub fn csv_quote(field: &str, sep: char) -> Cow<'_, str> {
+    if field.contains([sep, '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+
+/// Writes are collected until they reach this many bytes
+const WRITE_BUF_SIZE: usize = 8192;
+
+/// File opened by the interpreter, buffered in both directions so programs
+/// can read it line by line and write it piece by piece.
+#[derive(Debug)]
+pub struct FileHandle {
+    /// `None` once closed
+    file: Option<BufReader<File>>,
+    pending: Vec<u8>,
+}
+
+impl FileHandle {
+    pub fn new(file: File) -> Self {
+        FileHandle {
+            file: Some(BufReader::new(file)),
+            pending: Vec::new(),
+        }
+    }
+
+    fn inner(&mut self) -> io::Result<&mut BufReader<File>> {
+        self.file
+            .as_mut()
+            .ok_or_else(|| io::Error::other("file is closed"))
+    }
+
+    fn write_pending(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            self.inner()?.get_mut().write_all(&pending)?;
+        }
+        Ok(())
+    }
+
+    /// Next line without its line terminator, `None` at the end of the file.
+    pub fn read_line(&mut self) -> io::Result<Option<String>> {
+        self.write_pending()?;
+
+        let mut line = Vec::new();
+        if self.inner()?.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+
+        if line.ends_with(b"\n") {
+            line.pop();
+            if line.ends_with(b"\r") {
+                line.pop();
+            }
+        }
+        Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+    }
+
+    /// Up to `n` characters, `None` at the end of the file.
+    pub fn read_chars(&mut self, n: usize) -> io::Result<Option<String>> {
+        self.write_pending()?;
+
+        let file = self.inner()?;
+        let mut out = Vec::new();
+        for _ in 0..n {
+            let mut c = [0u8; 4];
+            if file.read(&mut c[..1])? == 0 {
+                break;
+            }
+
+            let width = match c[0] {
+                0xf0.. => 4,
+                0xe0.. => 3,
+                0xc0.. => 2,
+                _ => 1,
+            };
+            file.read_exact(&mut c[1..width])?;
+            out.extend_from_slice(&c[..width]);
+        }
+
+        if out.is_empty() && n > 0 {
+            Ok(None)
+        } else {
+            Ok(Some(String::from_utf8_lossy(&out).into_owned()))
+        }
+    }
+
+    pub fn read_to_end(&mut self, out: &mut Vec<u8>) -> io::Result<usize> {
+        self.write_pending()?;
+        self.inner()?.read_to_end(out)
+    }
+
+    pub fn write_str(&mut self, s: &str) -> io::Result<()> {
+        let file = self.inner()?;
+        /* Data read ahead has to be given back, so the write lands behind
+         * what the program has actually read
+         */
+        if !file.buffer().is_empty() {
+            let pos = file.stream_position()?;
+            file.seek(SeekFrom::Start(pos))?;
+        }
+
+        self.pending.extend_from_slice(s.as_bytes());
+        if self.pending.len() >= WRITE_BUF_SIZE {
+            self.write_pending()?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.write_pending()?;
+        self.inner()?.get_mut().flush()
+    }
+
+    /// Flushes and releases the file. Closing a closed file does nothing.
+    pub fn close(&mut self) -> io::Result<()> {
+        if self.file.is_some() {
+            self.flush()?;
+        }
+        self.file = None;
+        Ok(())
+    }
+}
+
+impl Drop for FileHandle {
+    fn drop(&mut self) {
+        let _ = self.write_pending();
+    }
+}
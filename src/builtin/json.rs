@@ -0,0 +1,220 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeSet;
+use std::rc::Rc;
+
+use crate::interp::heap::*;
+
+/// Converts `val` to JSON. Maps, i.e. sets of pairs with distinct string
+/// keys, become objects. Other sets, rationals, terms and class objects are
+/// encoded as objects tagged by an `@set`, `@rational`, `@term` or `@class`
+/// key.
+pub fn json_encode(val: &InterpVal, cstore: &InterpClassStore) -> Result<Value, String> {
+    match val {
+        InterpVal::Undefined => Ok(Value::Null),
+        InterpVal::Bool(b) => Ok(Value::Bool(*b)),
+        InterpVal::Double(d) => Number::from_f64(*d)
+            .map(Value::Number)
+            .ok_or_else(|| format!("{d} has no JSON representation")),
+        InterpVal::Char(c) => Ok(Value::String(c.to_string())),
+        InterpVal::Ref(r) => json_encode_obj(unsafe { &*r.0 }, cstore),
+        InterpVal::Procedure(_) => Err(String::from("procedures have no JSON representation")),
+        _ => Err(String::from("value has no JSON representation")),
+    }
+}
+
+/// Integers are written with all their digits, whatever their size.
+fn json_encode_int(n: &BigInt) -> Result<Value, String> {
+    serde_json::from_str::<Number>(&n.to_string())
+        .map(Value::Number)
+        .map_err(|e| e.to_string())
+}
+
+/// Exact value of an integer literal, `None` for fractions and exponents.
+fn json_decode_int(n: &Number) -> Option<BigInt> {
+    n.as_str().parse().ok()
+}
+
+fn json_tagged(tag: &str, val: Value) -> Value {
+    Value::Object(Map::from_iter([(tag.to_string(), val)]))
+}
+
+fn json_encode_list<'a>(
+    list: impl Iterator<Item = &'a InterpVal>,
+    cstore: &InterpClassStore,
+) -> Result<Value, String> {
+    list.map(|i| json_encode(i, cstore))
+        .collect::<Result<_, _>>()
+        .map(Value::Array)
+}
+
+/// Pairs of `set` by key, if it is a map with string keys
+fn json_map_entries(set: &BTreeSet<InterpVal>) -> Option<Vec<(&str, &InterpVal)>> {
+    let mut entries: Vec<(&str, &InterpVal)> = Vec::new();
+    for i in set {
+        let InterpVal::Ref(r) = i else {
+            return None;
+        };
+        let InterpObj::List(l) = (unsafe { &*r.0 }) else {
+            return None;
+        };
+        let [InterpVal::Ref(k), v] = &l.0[..] else {
+            return None;
+        };
+        let InterpObj::String(k) = (unsafe { &*k.0 }) else {
+            return None;
+        };
+
+        /* sets are ordered by their first element, so pairs sharing a key
+         * are adjacent
+         */
+        if entries.last().is_some_and(|(last, _)| last == k) {
+            return None;
+        }
+        entries.push((k, v));
+    }
+    Some(entries)
+}
+
+fn is_procedure(val: &InterpVal) -> bool {
+    match val {
+        InterpVal::Procedure(_) => true,
+        InterpVal::Ref(r) => matches!(unsafe { &*r.0 }, InterpObj::Procedure(_)),
+        _ => false,
+    }
+}
+
+fn json_encode_obj(obj: &InterpObj, cstore: &InterpClassStore) -> Result<Value, String> {
+    match obj {
+        InterpObj::String(s) => Ok(Value::String(s.clone())),
+        InterpObj::Number(n) => json_encode_int(n),
+        InterpObj::Rational(r) => Ok(json_tagged(
+            "@rational",
+            Value::Array(vec![
+                json_encode_int(r.numer())?,
+                json_encode_int(r.denom())?,
+            ]),
+        )),
+        InterpObj::List(l) => json_encode_list(l.0.iter(), cstore),
+        InterpObj::Set(s) => match json_map_entries(&s.0) {
+            Some(entries) => entries
+                .into_iter()
+                .map(|(k, v)| Ok((k.to_string(), json_encode(v, cstore)?)))
+                .collect::<Result<_, String>>()
+                .map(Value::Object),
+            None => Ok(json_tagged("@set", json_encode_list(s.0.iter(), cstore)?)),
+        },
+        InterpObj::Term(t) => Ok(Value::Object(Map::from_iter([
+            (String::from("@term"), Value::String(t.tag.clone())),
+            (
+                String::from("args"),
+                json_encode_list(t.list.iter(), cstore)?,
+            ),
+        ]))),
+        InterpObj::Object(o) => {
+            let class =
+                o.0.get("getClass")
+                    .and_then(|i| match &**i {
+                        InterpVal::Procedure(p) => cstore
+                            .0
+                            .iter()
+                            .find(|(_, c)| Rc::ptr_eq(&c.get_proc, p))
+                            .map(|(name, _)| name.clone()),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+
+            let mut out = Map::from_iter([(String::from("@class"), Value::String(class))]);
+            for (name, val) in &o.0 {
+                if !is_procedure(val) && !matches!(**val, InterpVal::Undefined) {
+                    out.insert(name.clone(), json_encode(val, cstore)?);
+                }
+            }
+            Ok(Value::Object(out))
+        }
+        InterpObj::Uninitialized => Ok(Value::Null),
+        InterpObj::Procedure(_) => Err(String::from("procedures have no JSON representation")),
+        InterpObj::Vector(_) | InterpObj::Matrix(_) => Err(String::from(
+            "vectors and matrices have no JSON representation",
+        )),
+        _ => Err(String::from("value has no JSON representation")),
+    }
+}
+
+fn json_obj_new(obj: InterpObj) -> InterpVal {
+    InterpVal::Ref(InterpObjRef::from_obj(obj))
+}
+
+fn json_set_insert(set: &mut BTreeSet<InterpVal>, val: InterpVal) {
+    if matches!(val, InterpVal::Undefined) || set.contains(&val) {
+        if let InterpVal::Ref(r) = val {
+            unsafe { r.invalidate() };
+        }
+    } else {
+        set.insert(val);
+    }
+}
+
+/// Converts JSON to a SetlX value, reversing [`json_encode`] except for
+/// class objects, which stay maps. Object members that are `null` are left
+/// out.
+pub fn json_decode(val: &Value) -> InterpVal {
+    match val {
+        Value::Null => InterpVal::Undefined,
+        Value::Bool(b) => InterpVal::Bool(*b),
+        Value::Number(n) => match json_decode_int(n) {
+            Some(i) => json_obj_new(InterpObj::Number(i)),
+            None => InterpVal::Double(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => json_obj_new(InterpObj::String(s.clone())),
+        Value::Array(a) => json_obj_new(InterpObj::List(InterpList(
+            a.iter().map(json_decode).collect(),
+        ))),
+        Value::Object(m) => json_decode_object(m),
+    }
+}
+
+fn json_decode_rational(r: &[Value]) -> Option<InterpObj> {
+    let [Value::Number(n), Value::Number(d)] = r else {
+        return None;
+    };
+    let (n, d) = (json_decode_int(n)?, json_decode_int(d)?);
+    (d != BigInt::ZERO).then(|| InterpObj::from_rational(BigRational::new(n, d)))
+}
+
+fn json_decode_object(m: &Map<String, Value>) -> InterpVal {
+    if m.len() == 2
+        && let Some(Value::String(tag)) = m.get("@term")
+        && let Some(Value::Array(args)) = m.get("args")
+    {
+        return json_obj_new(InterpObj::Term(InterpTaggedList {
+            tag: tag.clone(),
+            list: args.iter().map(json_decode).collect(),
+        }));
+    }
+    if m.len() == 1
+        && let Some(Value::Array(a)) = m.get("@set")
+    {
+        let mut set = BTreeSet::new();
+        a.iter()
+            .for_each(|i| json_set_insert(&mut set, json_decode(i)));
+        return json_obj_new(InterpObj::Set(InterpSet(set)));
+    }
+    if m.len() == 1
+        && let Some(Value::Array(r)) = m.get("@rational")
+        && let Some(r) = json_decode_rational(r)
+    {
+        return json_obj_new(r);
+    }
+
+    let mut set = BTreeSet::new();
+    m.iter().filter(|(_, v)| !v.is_null()).for_each(|(k, v)| {
+        let pair = InterpList(vec![
+            json_obj_new(InterpObj::String(k.clone())),
+            json_decode(v),
+        ]);
+        json_set_insert(&mut set, json_obj_new(InterpObj::List(pair)));
+    });
+    json_obj_new(InterpObj::Set(InterpSet(set)))
+}
@@ -1,14 +1,25 @@
 pub mod call;
+pub mod csv;
+pub mod file;
+pub mod json;
+pub mod module;
+pub mod native;
+pub mod plot;
+pub mod rng;
+pub mod sandbox;
+pub mod stat;
 pub mod stubs;
 
-use strum_macros::{Display, EnumString};
+use strum_macros::{Display, EnumCount, EnumIter, EnumString};
 
 use crate::cli::InputOpts;
 use crate::interp::except::{exception_kind_num_get, exception_val_get};
 use crate::interp::get::InterpImmedVal;
-use crate::interp::heap::{InterpImmediateHeap, InterpObj, InterpVal};
+use crate::interp::heap::{InterpImmediateHeap, InterpList, InterpObj, InterpObjRef, InterpVal};
 
-#[derive(Clone, Copy, Display, Debug, EnumString)]
+/// Builtins are dispatched through the native procedure table,
+/// see [`call::builtin_native`] for their handlers.
+#[derive(Clone, Copy, Display, Debug, EnumCount, EnumIter, EnumString)]
 #[strum(serialize_all = "snake_case")]
 pub enum BuiltinProc {
     Contains,
@@ -70,8 +81,19 @@ pub enum BuiltinProc {
      * ptr := set_get_tag(set, expr);
      */
     SetGetTag,
+    /*
+     * ptr<ptr|om> := set_get_tag_mut(set, expr);
+     *
+     * Like set_get_tag, but removes all pairs of an ambiguous key, so that the
+     * caller can insert a single new pair.
+     */
+    SetGetTagMut,
     // set := set_get_tag_all(set, expr);
     SetGetTagAll,
+    // set := set_domain(set);
+    SetDomain,
+    // set := set_image(set);
+    SetImage,
     Exit,
     /* @name: immediate string
      *
@@ -257,9 +279,23 @@ pub enum BuiltinProc {
     Ln,
     Exp,
     Sqrt,
+    /* @t_p1: double or rational, rationals are rounded to numbers
+     *
+     * t_ret := round(t_p1);
+     */
     Round,
     Floor,
     Ceil,
+    /* @t_p1: number, rational, double, or string
+     *
+     * t_ret := rational(t_p1);
+     */
+    Rational,
+    /* @t_ret: string of t_p1 truncated to t_n decimal places
+     *
+     * t_ret := n_decimal_places(t_p1, t_n);
+     */
+    NDecimalPlaces,
     Sin,
     Cos,
     Tan,
@@ -270,6 +306,13 @@ pub enum BuiltinProc {
     Eval,
     EvalTerm,
     Execute,
+    /*
+     * executes the file name in the first directory of search_path that
+     * contains it, unless it is already loaded and unchanged
+     *
+     * _ := load(name, search_path);
+     */
+    Load,
     ParseAst,
     ParseAstBlock,
     /*
@@ -293,6 +336,25 @@ pub enum BuiltinProc {
     ReadAllList,
     // _ := write(file, str);
     Write,
+    /* The file handle procedures read the parameters of the calling stub
+     * directly.
+     *
+     * @mode: "r", "r+", "w", "w+", "a" or "a+", defaulting to "r"
+     *
+     * file := file_open(params);
+     */
+    FileOpen,
+    /* @t_ret: next line without its terminator, om at the end of the file
+     *
+     * t_ret := file_read_line(params);
+     */
+    FileReadLine,
+    // t_ret := file_read_chars(params);
+    FileReadChars,
+    // _ := file_write_string(params);
+    FileWriteString,
+    FileFlush,
+    FileClose,
     /*
      * @file: str
      *
@@ -306,6 +368,12 @@ pub enum BuiltinProc {
      * t_ret := rnd_float();
      */
     RndFloat,
+    /* @params: list of at most one integer seed, without a seed the
+     *          generator is reset to --seed or seeded from the OS
+     *
+     * _ := rnd_seed(params);
+     */
+    RndSeed,
     /*
      * t_num := str_val(t_str);
      */
@@ -321,8 +389,97 @@ pub enum BuiltinProc {
      * t_out<list> := cmd(t_str);
      */
     Cmd,
+    /* @params: argument list, optionally followed by a map of the options
+     *          stdin, env, cwd and timeout
+     * @t_out: @Process(exit code or om, stdout, stderr)
+     *
+     * t_out := run_process(params);
+     */
+    RunProcess,
+    /* The data exchange procedures read the parameters of the calling stub
+     * directly, see json::json_encode for the encoding of values.
+     *
+     * t_ret<str> := to_json(params);
+     */
+    ToJson,
+    // t_ret := from_json(params);
+    FromJson,
+    /* @params: path, optionally followed by a map of the options separator
+     *          and header
+     * @t_ret: list of records, which are maps keyed by the header if given
+     *
+     * t_ret := csv_read(params);
+     */
+    CsvRead,
+    // _ := csv_write(params);
+    CsvWrite,
     IsPrime,
     IsProbablePrime,
+    /* @t_m: matrix, vector, or list of rows
+     *
+     * t_ret := la_matrix(t_m);
+     */
+    LaMatrix,
+    /* @t_b: vector or list
+     * @t_ret: vector x with t_m * x = t_b, least squares if t_m isn't square
+     *
+     * t_ret := la_solve(t_m, t_b);
+     */
+    LaSolve,
+    // t_ret<double> := la_det(t_m);
+    LaDet,
+    /* @t_ret: list of real eigenvalues in ascending order
+     *
+     * t_ret := la_eigen_values(t_m);
+     */
+    LaEigenValues,
+    /* @t_ret: list of unit eigenvectors ordered like la_eigen_values
+     *
+     * t_ret := la_eigen_vectors(t_m);
+     */
+    LaEigenVectors,
+    /* @t_ret: [u, s, v] with t_m = u * s * v'
+     *
+     * t_ret := la_svd(t_m);
+     */
+    LaSvd,
+    LaPseudoInverse,
+    // t_ret<double> := la_cond(t_m);
+    LaCond,
+    /* @dist: immediate string, see stat::StatDist
+     *
+     * t_ret<double> := stat_pdf("normal", t_x, t_mu, t_sigma);
+     */
+    StatPdf,
+    // t_ret<double> := stat_cdf("normal", t_x, t_mu, t_sigma);
+    StatCdf,
+    /* Draws from the same rng as rnd_float
+     *
+     * t_ret<double> := stat_sample("normal", t_mu, t_sigma);
+     */
+    StatSample,
+    /* The plot procedures read the parameters of the calling stub
+     * directly, as most of them take optional parameters.
+     *
+     * t_canvas := plot_create_canvas(params);
+     */
+    PlotCreateCanvas,
+    // _ := plot_add_graph(params);
+    PlotAddGraph,
+    PlotAddListGraph,
+    PlotAddParamGraph,
+    PlotAddBarChart,
+    PlotAddPieChart,
+    PlotAddBullets,
+    PlotAddLabel,
+    PlotLabelAxis,
+    PlotDefineTitle,
+    PlotModScale,
+    /* @t_ret: path of the written file
+     *
+     * t_ret := plot_export_canvas(params);
+     */
+    PlotExportCanvas,
 }
 
 #[derive(Clone, Copy, Debug, Display, EnumString)]
@@ -337,6 +494,8 @@ pub enum BuiltinVar {
     Params,
     LibraryPath,
     SourcePath,
+    // list of the command line arguments following the source file
+    Args,
 }
 
 impl BuiltinVar {
@@ -364,6 +523,17 @@ impl BuiltinVar {
                 ))),
                 heap,
             ),
+            BuiltinVar::Args => {
+                let args = opts
+                    .args
+                    .iter()
+                    .map(|i| InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(i.clone()))))
+                    .collect();
+                InterpImmedVal::from_val(
+                    InterpVal::Ref(heap.push_obj(InterpObj::List(InterpList(args)))),
+                    heap,
+                )
+            }
         }
     }
 
@@ -372,7 +542,7 @@ impl BuiltinVar {
             BuiltinVar::ExceptionVal => exception_val_get(),
             BuiltinVar::ExceptionKind => InterpVal::Double(exception_kind_num_get() as f64),
             BuiltinVar::Params => proc_params.clone(),
-            _ => panic!("lib_path, source_path is not allowed in this context"),
+            _ => panic!("lib_path, source_path, args is not allowed in this context"),
         }
     }
 }
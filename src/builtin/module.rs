@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Files executed by `load` and `loadLibrary`, keyed by canonical path
+#[derive(Default)]
+pub struct ModuleRegistry {
+    /// content hash of each module when it was last executed successfully
+    loaded: BTreeMap<PathBuf, u64>,
+    /// modules currently executing, outermost first, with the name they were
+    /// loaded as
+    loading: Vec<(PathBuf, String)>,
+}
+
+/// Finds `name` in the directories of `search_path`, which is separated like
+/// `PATH`. An empty search path resolves relative to the working directory.
+pub fn module_resolve(name: &str, search_path: &str) -> Option<PathBuf> {
+    env::split_paths(search_path)
+        .map(|i| i.join(name))
+        .find(|i| i.is_file())
+}
+
+pub fn module_hash(src: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    src.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl ModuleRegistry {
+    /// Chain of module names from the one that first loaded `path` back to
+    /// `name`, if loading `path` now would close a cycle.
+    pub fn cycle(&self, path: &Path, name: &str) -> Option<String> {
+        let idx = self.loading.iter().position(|(p, _)| p == path)?;
+        let mut names = self.loading[idx..]
+            .iter()
+            .map(|(_, n)| n.as_str())
+            .collect::<Vec<_>>();
+        names.push(name);
+        Some(names.join(" -> "))
+    }
+
+    pub fn is_loaded(&self, path: &Path, hash: u64) -> bool {
+        self.loaded.get(path) == Some(&hash)
+    }
+
+    pub fn enter(&mut self, path: PathBuf, name: String) {
+        self.loading.push((path, name));
+    }
+
+    /// Finishes the innermost module, which is only recorded as loaded if it
+    /// ran to completion.
+    pub fn leave(&mut self, hash: Option<u64>) {
+        if let Some((path, _)) = self.loading.pop()
+            && let Some(hash) = hash
+        {
+            self.loaded.insert(path, hash);
+        }
+    }
+}
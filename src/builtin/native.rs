@@ -0,0 +1,124 @@
+use rustyline::DefaultEditor;
+use std::sync::LazyLock;
+use strum::IntoEnumIterator;
+
+use crate::builtin::BuiltinProc;
+use crate::builtin::call::builtin_native;
+use crate::cli::InputOpts;
+use crate::interp::debug::DebugData;
+use crate::interp::except::exception_throw;
+use crate::interp::get::InterpGet;
+use crate::interp::heap::{InterpClassStore, InterpImmediateHeap, InterpVal};
+use crate::interp::memoize::InterpMemoize;
+use crate::interp::stack::InterpStack;
+use crate::ir::def::{IRType, IRValue};
+
+/// Handler of a native procedure.
+///
+/// `params` are the operands of the native call and are read with [`InterpGet`].
+/// Returned references must be allocated on the immediate heap.
+pub type NativeFn = fn(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal;
+
+#[derive(Debug)]
+pub struct NativeProc {
+    pub name: String,
+    /// `None` if the procedure takes a variable amount of parameters
+    pub arity: Option<usize>,
+    /// Accepted types of each parameter, unchecked if empty
+    pub params: Vec<IRType>,
+    pub ret: IRType,
+    pub handler: NativeFn,
+}
+
+impl NativeProc {
+    pub fn new(name: &str, params: Vec<IRType>, ret: IRType, handler: NativeFn) -> Self {
+        NativeProc {
+            name: name.to_string(),
+            arity: Some(params.len()),
+            params,
+            ret,
+            handler,
+        }
+    }
+
+    pub fn builtin(proc: BuiltinProc, arity: Option<usize>, handler: NativeFn) -> Self {
+        NativeProc {
+            arity,
+            ..NativeProc::new(&proc.to_string(), Vec::new(), IRType::all(), handler)
+        }
+    }
+}
+
+/* The builtin procedures are created once, in declaration order, so that
+ * IRValue::BuiltinProc(p) is found at p as usize. Procedures registered by
+ * embedders are kept in `InputOpts::natives` instead, so that they are
+ * dropped with the interpreter they were registered with.
+ */
+static BUILTINS: LazyLock<Vec<NativeProc>> =
+    LazyLock::new(|| BuiltinProc::iter().map(builtin_native).collect());
+
+pub fn native_builtin_get(proc: BuiltinProc) -> &'static NativeProc {
+    &BUILTINS[proc as usize]
+}
+
+pub fn native_call(
+    native: &NativeProc,
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    if let Some(n) = native.arity
+        && n != params.len()
+    {
+        exception_throw(
+            "builtin procedure",
+            &format!(
+                "{}: expected {n} parameters, got {}",
+                native.name,
+                params.len()
+            ),
+            breakpoints,
+        );
+    }
+
+    for (i, (p, t)) in params.iter().zip(&native.params).enumerate() {
+        if !t.intersects(p.to_type(vars, params_proc, opts, heap)) {
+            exception_throw(
+                "builtin procedure",
+                &format!("{}: parameter {} must be of type {t}", native.name, i + 1),
+                breakpoints,
+            );
+        }
+    }
+
+    (native.handler)(
+        params,
+        params_proc,
+        vars,
+        stack,
+        heap,
+        memo,
+        cstore,
+        breakpoints,
+        opts,
+        rl,
+    )
+}
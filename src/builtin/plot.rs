@@ -0,0 +1,536 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 600.0;
+const MARGIN_LEFT: f64 = 70.0;
+const MARGIN_RIGHT: f64 = 30.0;
+const MARGIN_TOP: f64 = 50.0;
+const MARGIN_BOTTOM: f64 = 60.0;
+
+const PALETTE: [PlotColor; 8] = [
+    (31, 119, 180),
+    (255, 127, 14),
+    (44, 160, 44),
+    (214, 39, 40),
+    (148, 103, 189),
+    (140, 86, 75),
+    (227, 119, 194),
+    (127, 127, 127),
+];
+
+pub type PlotColor = (u8, u8, u8);
+
+#[derive(Clone, Debug)]
+pub enum PlotItem {
+    // non finite samples split a graph into multiple segments
+    Graph {
+        name: String,
+        segments: Vec<Vec<(f64, f64)>>,
+        color: PlotColor,
+        fill: bool,
+    },
+    // bar i is centered at x = i + 1
+    Bars {
+        name: String,
+        values: Vec<f64>,
+        color: PlotColor,
+        width: f64,
+    },
+    Pie {
+        values: Vec<f64>,
+        labels: Vec<String>,
+    },
+    Bullets {
+        points: Vec<(f64, f64)>,
+        color: PlotColor,
+        size: f64,
+    },
+    Label {
+        pos: (f64, f64),
+        text: String,
+        color: PlotColor,
+    },
+}
+
+/// Headless replacement for the canvas window of the reference implementation.
+/// Graphs are accumulated and only rendered when the canvas is exported.
+#[derive(Clone, Debug, Default)]
+pub struct PlotCanvas {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub x_range: Option<(f64, f64)>,
+    pub y_range: Option<(f64, f64)>,
+    pub items: Vec<PlotItem>,
+    n_colors: usize,
+}
+
+struct PlotArea {
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+}
+
+impl PlotArea {
+    fn width() -> f64 {
+        WIDTH - MARGIN_LEFT - MARGIN_RIGHT
+    }
+
+    fn height() -> f64 {
+        HEIGHT - MARGIN_TOP - MARGIN_BOTTOM
+    }
+
+    fn x(&self, x: f64) -> f64 {
+        MARGIN_LEFT + (x - self.x_range.0) / (self.x_range.1 - self.x_range.0) * Self::width()
+    }
+
+    fn y(&self, y: f64) -> f64 {
+        HEIGHT
+            - MARGIN_BOTTOM
+            - (y - self.y_range.0) / (self.y_range.1 - self.y_range.0) * Self::height()
+    }
+}
+
+fn color_str(c: PlotColor) -> String {
+    format!("rgb({},{},{})", c.0, c.1, c.2)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn range_pad(lo: f64, hi: f64) -> (f64, f64) {
+    if !lo.is_finite() || !hi.is_finite() {
+        (-10.0, 10.0)
+    } else if hi - lo < f64::EPSILON * lo.abs().max(1.0) {
+        (lo - 1.0, hi + 1.0)
+    } else {
+        let pad = (hi - lo) * 0.05;
+        (lo - pad, hi + pad)
+    }
+}
+
+fn tick_step(lo: f64, hi: f64) -> f64 {
+    let raw = (hi - lo) / 8.0;
+    let mag = 10f64.powf(raw.log10().floor());
+    let norm = raw / mag;
+
+    mag * if norm < 1.5 {
+        1.0
+    } else if norm < 3.0 {
+        2.0
+    } else if norm < 7.0 {
+        5.0
+    } else {
+        10.0
+    }
+}
+
+fn ticks(lo: f64, hi: f64) -> Vec<String> {
+    let step = tick_step(lo, hi);
+    let decimals = if step >= 1.0 {
+        0
+    } else {
+        (-step.log10()).ceil() as usize
+    };
+
+    let mut out = Vec::new();
+    let mut i = (lo / step).ceil();
+    while i * step <= hi {
+        let v = i * step;
+        let s = format!("{v:.decimals$}");
+        // avoid printing -0
+        out.push(if s.trim_start_matches(['-', '0', '.']).is_empty() {
+            String::from("0")
+        } else {
+            s
+        });
+        i += 1.0;
+    }
+    out
+}
+
+impl PlotCanvas {
+    pub fn new(title: &str) -> Self {
+        PlotCanvas {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn color_next(&mut self) -> PlotColor {
+        let c = PALETTE[self.n_colors % PALETTE.len()];
+        self.n_colors += 1;
+        c
+    }
+
+    /// Interval that functions added via `plot_addGraph` are sampled in.
+    pub fn sample_range(&self) -> (f64, f64) {
+        self.x_range.unwrap_or((-10.0, 10.0))
+    }
+
+    fn area(&self) -> PlotArea {
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+
+        for i in &self.items {
+            match i {
+                PlotItem::Graph { segments, fill, .. } => {
+                    for (x, y) in segments.iter().flatten() {
+                        xs.push(*x);
+                        ys.push(*y);
+                    }
+                    if *fill {
+                        ys.push(0.0);
+                    }
+                }
+                PlotItem::Bars { values, width, .. } => {
+                    xs.push(1.0 - width / 2.0);
+                    xs.push(values.len() as f64 + width / 2.0);
+                    ys.push(0.0);
+                    ys.extend(values);
+                }
+                PlotItem::Bullets { points, .. } => {
+                    for (x, y) in points {
+                        xs.push(*x);
+                        ys.push(*y);
+                    }
+                }
+                PlotItem::Label { pos, .. } => {
+                    xs.push(pos.0);
+                    ys.push(pos.1);
+                }
+                PlotItem::Pie { .. } => (),
+            }
+        }
+
+        let bounds = |v: &[f64]| {
+            range_pad(
+                v.iter().copied().fold(f64::INFINITY, f64::min),
+                v.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            )
+        };
+
+        PlotArea {
+            x_range: self.x_range.unwrap_or_else(|| bounds(&xs)),
+            y_range: self.y_range.unwrap_or_else(|| bounds(&ys)),
+        }
+    }
+
+    fn svg_axes(&self, out: &mut String, area: &PlotArea) {
+        let (left, right) = (MARGIN_LEFT, WIDTH - MARGIN_RIGHT);
+        let (top, bottom) = (MARGIN_TOP, HEIGHT - MARGIN_BOTTOM);
+
+        for t in ticks(area.x_range.0, area.x_range.1) {
+            let x = area.x(t.parse().unwrap());
+            _ = writeln!(
+                out,
+                r##"<line x1="{x:.2}" y1="{top:.2}" x2="{x:.2}" y2="{bottom:.2}" stroke="#e0e0e0"/>"##
+            );
+            _ = writeln!(
+                out,
+                r#"<text x="{x:.2}" y="{:.2}" text-anchor="middle" font-size="12">{t}</text>"#,
+                bottom + 18.0
+            );
+        }
+        for t in ticks(area.y_range.0, area.y_range.1) {
+            let y = area.y(t.parse().unwrap());
+            _ = writeln!(
+                out,
+                r##"<line x1="{left:.2}" y1="{y:.2}" x2="{right:.2}" y2="{y:.2}" stroke="#e0e0e0"/>"##
+            );
+            _ = writeln!(
+                out,
+                r#"<text x="{:.2}" y="{:.2}" text-anchor="end" font-size="12">{t}</text>"#,
+                left - 6.0,
+                y + 4.0
+            );
+        }
+
+        if area.x_range.0 < 0.0 && 0.0 < area.x_range.1 {
+            let x = area.x(0.0);
+            _ = writeln!(
+                out,
+                r#"<line x1="{x:.2}" y1="{top:.2}" x2="{x:.2}" y2="{bottom:.2}" stroke="black"/>"#
+            );
+        }
+        if area.y_range.0 < 0.0 && 0.0 < area.y_range.1 {
+            let y = area.y(0.0);
+            _ = writeln!(
+                out,
+                r#"<line x1="{left:.2}" y1="{y:.2}" x2="{right:.2}" y2="{y:.2}" stroke="black"/>"#
+            );
+        }
+
+        _ = writeln!(
+            out,
+            r#"<rect x="{left:.2}" y="{top:.2}" width="{:.2}" height="{:.2}" fill="none" stroke="black"/>"#,
+            PlotArea::width(),
+            PlotArea::height()
+        );
+
+        if !self.x_label.is_empty() {
+            _ = writeln!(
+                out,
+                r#"<text x="{:.2}" y="{:.2}" text-anchor="middle" font-size="14">{}</text>"#,
+                left + PlotArea::width() / 2.0,
+                HEIGHT - 15.0,
+                escape(&self.x_label)
+            );
+        }
+        if !self.y_label.is_empty() {
+            _ = writeln!(
+                out,
+                r#"<text x="20" y="{:.2}" text-anchor="middle" font-size="14" transform="rotate(-90 20 {:.2})">{}</text>"#,
+                top + PlotArea::height() / 2.0,
+                top + PlotArea::height() / 2.0,
+                escape(&self.y_label)
+            );
+        }
+    }
+
+    fn svg_pie(out: &mut String, values: &[f64], labels: &[String]) {
+        let cx = MARGIN_LEFT + PlotArea::width() / 2.0;
+        let cy = MARGIN_TOP + PlotArea::height() / 2.0;
+        let r = PlotArea::width().min(PlotArea::height()) * 0.4;
+        let total: f64 = values.iter().filter(|v| **v > 0.0).sum();
+
+        let mut angle = -std::f64::consts::FRAC_PI_2;
+        for (idx, v) in values.iter().enumerate() {
+            if *v <= 0.0 || total <= 0.0 {
+                continue;
+            }
+
+            let color = color_str(PALETTE[idx % PALETTE.len()]);
+            let sweep = v / total * std::f64::consts::TAU;
+            if sweep >= std::f64::consts::TAU - 1e-9 {
+                _ = writeln!(
+                    out,
+                    r#"<circle cx="{cx:.2}" cy="{cy:.2}" r="{r:.2}" fill="{color}" stroke="white"/>"#
+                );
+            } else {
+                let (x0, y0) = (cx + r * angle.cos(), cy + r * angle.sin());
+                let (x1, y1) = (
+                    cx + r * (angle + sweep).cos(),
+                    cy + r * (angle + sweep).sin(),
+                );
+                let large = if sweep > std::f64::consts::PI { 1 } else { 0 };
+                _ = writeln!(
+                    out,
+                    r#"<path d="M {cx:.2} {cy:.2} L {x0:.2} {y0:.2} A {r:.2} {r:.2} 0 {large} 1 {x1:.2} {y1:.2} Z" fill="{color}" stroke="white"/>"#
+                );
+            }
+
+            if let Some(label) = labels.get(idx) {
+                let mid = angle + sweep / 2.0;
+                _ = writeln!(
+                    out,
+                    r#"<text x="{:.2}" y="{:.2}" text-anchor="middle" font-size="12">{}</text>"#,
+                    cx + r * 1.15 * mid.cos(),
+                    cy + r * 1.15 * mid.sin() + 4.0,
+                    escape(label)
+                );
+            }
+            angle += sweep;
+        }
+    }
+
+    fn svg_legend(&self, out: &mut String) {
+        let entries = self
+            .items
+            .iter()
+            .filter_map(|i| match i {
+                PlotItem::Graph { name, color, .. } | PlotItem::Bars { name, color, .. }
+                    if !name.is_empty() =>
+                {
+                    Some((name, *color))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let x = WIDTH - MARGIN_RIGHT - 10.0;
+        for (idx, (name, color)) in entries.iter().enumerate() {
+            let y = MARGIN_TOP + 20.0 + idx as f64 * 18.0;
+            _ = writeln!(
+                out,
+                r#"<rect x="{:.2}" y="{:.2}" width="12" height="12" fill="{}"/>"#,
+                x - 12.0,
+                y - 10.0,
+                color_str(*color)
+            );
+            _ = writeln!(
+                out,
+                r#"<text x="{:.2}" y="{y:.2}" text-anchor="end" font-size="12">{}</text>"#,
+                x - 18.0,
+                escape(name)
+            );
+        }
+    }
+
+    pub fn to_svg(&self) -> String {
+        let area = self.area();
+        let only_pies =
+            !self.items.is_empty() && self.items.iter().all(|i| matches!(i, PlotItem::Pie { .. }));
+
+        let mut out = String::new();
+        _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif">"#
+        );
+        _ = writeln!(
+            out,
+            r#"<rect width="{WIDTH}" height="{HEIGHT}" fill="white"/>"#
+        );
+        _ = writeln!(
+            out,
+            r#"<clipPath id="plot-area"><rect x="{MARGIN_LEFT}" y="{MARGIN_TOP}" width="{}" height="{}"/></clipPath>"#,
+            PlotArea::width(),
+            PlotArea::height()
+        );
+
+        if !self.title.is_empty() {
+            _ = writeln!(
+                out,
+                r#"<text x="{:.2}" y="30" text-anchor="middle" font-size="18">{}</text>"#,
+                WIDTH / 2.0,
+                escape(&self.title)
+            );
+        }
+
+        if !only_pies {
+            self.svg_axes(&mut out, &area);
+        }
+
+        out.push_str("<g clip-path=\"url(#plot-area)\">\n");
+        for i in &self.items {
+            match i {
+                PlotItem::Graph {
+                    segments,
+                    color,
+                    fill,
+                    ..
+                } => {
+                    for s in segments.iter().filter(|s| !s.is_empty()) {
+                        let points = s
+                            .iter()
+                            .map(|(x, y)| format!("{:.2},{:.2}", area.x(*x), area.y(*y)))
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        if *fill {
+                            let base = area.y(0.0);
+                            _ = writeln!(
+                                out,
+                                r#"<polygon points="{:.2},{base:.2} {points} {:.2},{base:.2}" fill="{}" fill-opacity="0.3" stroke="none"/>"#,
+                                area.x(s[0].0),
+                                area.x(s[s.len() - 1].0),
+                                color_str(*color)
+                            );
+                        }
+                        _ = writeln!(
+                            out,
+                            r#"<polyline points="{points}" fill="none" stroke="{}" stroke-width="2"/>"#,
+                            color_str(*color)
+                        );
+                    }
+                }
+                PlotItem::Bars {
+                    values,
+                    color,
+                    width,
+                    ..
+                } => {
+                    for (idx, v) in values.iter().enumerate() {
+                        let x0 = area.x(idx as f64 + 1.0 - width / 2.0);
+                        let x1 = area.x(idx as f64 + 1.0 + width / 2.0);
+                        let (y0, y1) = (area.y(0.0), area.y(*v));
+                        _ = writeln!(
+                            out,
+                            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}"/>"#,
+                            x0.min(x1),
+                            y0.min(y1),
+                            (x1 - x0).abs(),
+                            (y1 - y0).abs(),
+                            color_str(*color)
+                        );
+                    }
+                }
+                PlotItem::Pie { values, labels } => Self::svg_pie(&mut out, values, labels),
+                PlotItem::Bullets {
+                    points,
+                    color,
+                    size,
+                } => {
+                    for (x, y) in points {
+                        _ = writeln!(
+                            out,
+                            r#"<circle cx="{:.2}" cy="{:.2}" r="{size:.2}" fill="{}"/>"#,
+                            area.x(*x),
+                            area.y(*y),
+                            color_str(*color)
+                        );
+                    }
+                }
+                PlotItem::Label { pos, text, color } => {
+                    _ = writeln!(
+                        out,
+                        r#"<text x="{:.2}" y="{:.2}" font-size="12" fill="{}">{}</text>"#,
+                        area.x(pos.0),
+                        area.y(pos.1),
+                        color_str(*color),
+                        escape(text)
+                    );
+                }
+            }
+        }
+        out.push_str("</g>\n");
+
+        self.svg_legend(&mut out);
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// The format is chosen by the file extension, paths without an extension
+    /// are exported as svg.
+    pub fn export(&self, path: &str) -> Result<String, String> {
+        let mut path = path.to_string();
+        match Path::new(&path).extension().and_then(|e| e.to_str()) {
+            Some("svg") => (),
+            Some("png") => {
+                fs::write(&path, svg_to_png(&self.to_svg())?).map_err(|e| e.to_string())?;
+                return Ok(path);
+            }
+            Some(e) => return Err(format!("unsupported file format: {e}")),
+            None => path.push_str(".svg"),
+        }
+
+        fs::write(&path, self.to_svg()).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+}
+
+#[cfg(feature = "png")]
+fn svg_to_png(svg: &str) -> Result<Vec<u8>, String> {
+    use resvg::{tiny_skia, usvg};
+
+    let mut opt = usvg::Options::default();
+    opt.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_str(svg, &opt).map_err(|e| e.to_string())?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| String::from("invalid canvas size"))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "png"))]
+fn svg_to_png(_svg: &str) -> Result<Vec<u8>, String> {
+    Err(String::from(
+        "png export requires building with the png feature",
+    ))
+}
@@ -0,0 +1,15 @@
+use rand::rngs::StdRng;
+use rand::{SeedableRng, make_rng};
+
+/* Every builtin drawing random numbers uses the generator of the run in
+ * `DebugData::rng`, so that a seed set by --seed or resetRandom(seed)
+ * determines the whole run.
+ */
+
+/// Seeds a generator, from the OS if `seed` is `None`.
+pub fn rng_new(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(s) => StdRng::seed_from_u64(s),
+        None => make_rng(),
+    }
+}
@@ -0,0 +1,57 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Host access left to builtins when running untrusted code.
+///
+/// Commands can't be run at all, files can only be read below `read_dirs`
+/// and writes are redirected into `scratch_dir`.
+#[derive(Clone, Debug, Default)]
+pub struct Sandbox {
+    read_dirs: Vec<PathBuf>,
+    scratch_dir: Option<PathBuf>,
+}
+
+impl Sandbox {
+    /// Directories that don't exist are left out, so without an existing
+    /// `scratch_dir` every write is denied.
+    pub fn new(read_dirs: &[PathBuf], scratch_dir: Option<&Path>) -> Self {
+        Sandbox {
+            read_dirs: read_dirs
+                .iter()
+                .filter_map(|i| fs::canonicalize(i).ok())
+                .collect(),
+            scratch_dir: scratch_dir.and_then(|i| fs::canonicalize(i).ok()),
+        }
+    }
+
+    /// Location of the relative path `path` in the scratch directory.
+    fn scratch(&self, path: &Path) -> Option<PathBuf> {
+        let dir = self.scratch_dir.as_ref()?;
+        path.components()
+            .all(|i| matches!(i, Component::Normal(_) | Component::CurDir))
+            .then(|| dir.join(path))
+    }
+
+    /// Resolves `path` for reading, preferring a file written to the scratch
+    /// directory before.
+    pub fn read_path(&self, path: &Path) -> Result<PathBuf, String> {
+        if let Some(p) = self.scratch(path)
+            && p.exists()
+        {
+            return Ok(p);
+        }
+
+        let full = fs::canonicalize(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        if self.read_dirs.iter().any(|i| full.starts_with(i)) {
+            Ok(full)
+        } else {
+            Err(format!("reading {} is not allowed", path.display()))
+        }
+    }
+
+    /// Resolves `path` for writing or deleting inside the scratch directory.
+    pub fn write_path(&self, path: &Path) -> Result<PathBuf, String> {
+        self.scratch(path)
+            .ok_or_else(|| format!("writing {} is not allowed", path.display()))
+    }
+}
@@ -0,0 +1,148 @@
+use rand::Rng;
+use rand_distr::{Distribution, StandardNormal};
+use statrs::distribution::{self as sd, Continuous, ContinuousCDF};
+use statrs::function::erf::erfc;
+use std::f64::consts::PI;
+use strum_macros::{Display, EnumIter, EnumString};
+
+/* Argument conventions follow the reference implementation:
+ *
+ * stat_normal(x, mu, sigma)
+ * stat_chiSquared(x, k)
+ * stat_student(x, nu)
+ * stat_gamma(x, k, theta)        shape, scale
+ * stat_beta(x, alpha, beta)
+ * stat_weibull(x, k, lambda)     shape, scale
+ * stat_pareto(x, x_m, alpha)     scale, shape
+ * stat_cauchy(x, x_0, gamma)     location, scale
+ * stat_levy(x, mu, c)            location, scale
+ * stat_logNormal(x, mu, sigma)
+ * stat_fisher(x, d1, d2)
+ * stat_exponential(x, lambda)    rate
+ *
+ * The CDF variants take the same arguments, the sample variants omit x.
+ */
+#[derive(Clone, Copy, Debug, Display, EnumIter, EnumString)]
+#[strum(serialize_all = "camelCase")]
+pub enum StatDist {
+    Normal,
+    ChiSquared,
+    Student,
+    Gamma,
+    Beta,
+    Weibull,
+    Pareto,
+    Cauchy,
+    Levy,
+    LogNormal,
+    Fisher,
+    Exponential,
+}
+
+trait StatDensity {
+    fn density(&self, x: f64) -> f64;
+    fn cumulative(&self, x: f64) -> f64;
+}
+
+impl<T: Continuous<f64, f64> + ContinuousCDF<f64, f64>> StatDensity for T {
+    fn density(&self, x: f64) -> f64 {
+        self.pdf(x)
+    }
+
+    fn cumulative(&self, x: f64) -> f64 {
+        self.cdf(x)
+    }
+}
+
+// statrs doesn't provide the lévy distribution
+struct Levy {
+    mu: f64,
+    c: f64,
+}
+
+impl Levy {
+    fn new(mu: f64, c: f64) -> Option<Self> {
+        (mu.is_finite() && c.is_finite() && c > 0.0).then_some(Levy { mu, c })
+    }
+}
+
+impl StatDensity for Levy {
+    fn density(&self, x: f64) -> f64 {
+        if x <= self.mu {
+            return 0.0;
+        }
+
+        let d = x - self.mu;
+        (self.c / (2.0 * PI)).sqrt() * (-self.c / (2.0 * d)).exp() / d.powf(1.5)
+    }
+
+    fn cumulative(&self, x: f64) -> f64 {
+        if x <= self.mu {
+            return 0.0;
+        }
+
+        erfc((self.c / (2.0 * (x - self.mu))).sqrt())
+    }
+}
+
+impl StatDist {
+    pub fn n_params(&self) -> usize {
+        match self {
+            StatDist::ChiSquared | StatDist::Student | StatDist::Exponential => 1,
+            _ => 2,
+        }
+    }
+
+    fn dist(&self, p: &[f64]) -> Option<Box<dyn StatDensity>> {
+        if p.len() != self.n_params() {
+            return None;
+        }
+
+        Some(match self {
+            StatDist::Normal => Box::new(sd::Normal::new(p[0], p[1]).ok()?),
+            StatDist::ChiSquared => Box::new(sd::ChiSquared::new(p[0]).ok()?),
+            StatDist::Student => Box::new(sd::StudentsT::new(0.0, 1.0, p[0]).ok()?),
+            StatDist::Gamma => Box::new(sd::Gamma::new(p[0], 1.0 / p[1]).ok()?),
+            StatDist::Beta => Box::new(sd::Beta::new(p[0], p[1]).ok()?),
+            StatDist::Weibull => Box::new(sd::Weibull::new(p[0], p[1]).ok()?),
+            StatDist::Pareto => Box::new(sd::Pareto::new(p[0], p[1]).ok()?),
+            StatDist::Cauchy => Box::new(sd::Cauchy::new(p[0], p[1]).ok()?),
+            StatDist::Levy => Box::new(Levy::new(p[0], p[1])?),
+            StatDist::LogNormal => Box::new(sd::LogNormal::new(p[0], p[1]).ok()?),
+            StatDist::Fisher => Box::new(sd::FisherSnedecor::new(p[0], p[1]).ok()?),
+            StatDist::Exponential => Box::new(sd::Exp::new(p[0]).ok()?),
+        })
+    }
+
+    /// Returns `None` if the parameters don't describe a valid distribution.
+    pub fn pdf(&self, x: f64, p: &[f64]) -> Option<f64> {
+        Some(self.dist(p)?.density(x))
+    }
+
+    pub fn cdf(&self, x: f64, p: &[f64]) -> Option<f64> {
+        Some(self.dist(p)?.cumulative(x))
+    }
+
+    pub fn sample<R: Rng + ?Sized>(&self, p: &[f64], rng: &mut R) -> Option<f64> {
+        // validates the parameters with the same rules as pdf and cdf
+        self.dist(p)?;
+
+        Some(match self {
+            StatDist::Normal => rand_distr::Normal::new(p[0], p[1]).ok()?.sample(rng),
+            StatDist::ChiSquared => rand_distr::ChiSquared::new(p[0]).ok()?.sample(rng),
+            StatDist::Student => rand_distr::StudentT::new(p[0]).ok()?.sample(rng),
+            StatDist::Gamma => rand_distr::Gamma::new(p[0], p[1]).ok()?.sample(rng),
+            StatDist::Beta => rand_distr::Beta::new(p[0], p[1]).ok()?.sample(rng),
+            StatDist::Weibull => rand_distr::Weibull::new(p[1], p[0]).ok()?.sample(rng),
+            StatDist::Pareto => rand_distr::Pareto::new(p[0], p[1]).ok()?.sample(rng),
+            StatDist::Cauchy => rand_distr::Cauchy::new(p[0], p[1]).ok()?.sample(rng),
+            StatDist::Levy => {
+                let z: f64 = StandardNormal.sample(rng);
+                p[0] + p[1] / (z * z)
+            }
+            StatDist::LogNormal => rand_distr::LogNormal::new(p[0], p[1]).ok()?.sample(rng),
+            StatDist::Fisher => rand_distr::FisherF::new(p[0], p[1]).ok()?.sample(rng),
+            StatDist::Exponential => rand_distr::Exp::new(p[0]).ok()?.sample(rng),
+        })
+    }
+}
@@ -1,8 +1,11 @@
 use petgraph::stable_graph::NodeIndex;
 use std::cell::RefCell;
 use std::rc::Rc;
+use strum::IntoEnumIterator;
 
 use crate::IRTypes;
+use crate::builtin::native::NativeProc;
+use crate::builtin::stat::StatDist;
 use crate::builtin::*;
 use crate::interp::heap::InterpVal;
 use crate::interp::stack::{InterpStackEntry, InterpStackVar};
@@ -10,6 +13,7 @@ use crate::ir::def::*;
 use crate::ir::lower::IRSharedProc;
 use crate::ir::lower::expr::op_expr::{ObjOverloadRhs, block_obj_overload_push};
 use crate::ir::lower::util::{block_get, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 fn print_params_push(block_idx: &mut NodeIndex, t_target: IRVar, proc: &mut IRProcedure) {
     /* t_i := 0;
@@ -82,6 +86,7 @@ fn print_params_push(block_idx: &mut NodeIndex, t_target: IRVar, proc: &mut IRPr
             cond: IRValue::Variable(t_check),
             success: loop_idx,
             failure: follow_idx,
+            source: false,
         }),
     ]);
 
@@ -233,7 +238,7 @@ fn print_stub_new(is_stderr: bool) -> Rc<RefCell<IRProcedure>> {
      *  t_out := // print_params_push
      *  t_out_new := t_out + "\n";
      *  _ := invalidate(t_out);
-     *  _ := print_stdout(t_out_new);
+     *  _ := print_stdout(t_out_new); // print_stderr if is_stderr
      *  _ := invalidate(t_out_new);
      *  return om;
      */
@@ -274,7 +279,11 @@ fn print_stub_new(is_stderr: bool) -> Rc<RefCell<IRProcedure>> {
         IRStmt::Assign(IRAssign {
             target: IRTarget::Ignore,
             types: IRType::UNDEFINED,
-            source: IRValue::BuiltinProc(BuiltinProc::PrintStdout),
+            source: IRValue::BuiltinProc(if is_stderr {
+                BuiltinProc::PrintStderr
+            } else {
+                BuiltinProc::PrintStdout
+            }),
             op: IROp::NativeCall(vec![IRValue::Variable(t_out_new)]),
         }),
         IRStmt::Assign(IRAssign {
@@ -425,6 +434,7 @@ fn assert_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1),
             success: end_idx,
             failure: throw_prep_idx,
+            source: false,
         }),
     ]);
 
@@ -462,6 +472,7 @@ fn assert_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p2_type_str),
             success: str_idx,
             failure: literal_idx,
+            source: false,
         }),
     ]);
 
@@ -615,6 +626,7 @@ fn first_stub_new(tag: &str) -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_set),
             success: arb_idx,
             failure: arr_idx,
+            source: false,
         }),
     ]);
 
@@ -668,6 +680,7 @@ fn first_stub_new(tag: &str) -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_amount_zero),
             success: out_om_idx,
             failure: arr_acc_idx,
+            source: false,
         }),
     ]);
 
@@ -799,6 +812,7 @@ fn last_stub_new(tag: &str) -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_set),
             success: arb_idx,
             failure: arr_idx,
+            source: false,
         }),
     ]);
 
@@ -846,6 +860,7 @@ fn last_stub_new(tag: &str) -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_amount_zero),
             success: out_om_idx,
             failure: arr_acc_idx,
+            source: false,
         }),
     ]);
 
@@ -987,6 +1002,7 @@ fn fromb_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_type_set),
             success: set_idx,
             failure: list_idx,
+            source: false,
         }),
     ]);
 
@@ -1125,6 +1141,7 @@ fn get_stub_new(tag: &str) -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p_arg),
             success: arg_idx,
             failure: narg_idx,
+            source: false,
         }),
     ]);
 
@@ -1146,7 +1163,7 @@ fn get_stub_new(tag: &str) -> Rc<RefCell<IRProcedure>> {
         }),
         IRStmt::Assign(IRAssign {
             target: IRTarget::Variable(t_ret),
-            types: IRType::STRING,
+            types: IRType::STRING | IRType::UNDEFINED,
             source: IRValue::BuiltinProc(BuiltinProc::ReadLineStdin),
             op: IROp::NativeCall(vec![IRValue::Variable(t_p1)]),
         }),
@@ -1164,7 +1181,7 @@ fn get_stub_new(tag: &str) -> Rc<RefCell<IRProcedure>> {
         }),
         IRStmt::Assign(IRAssign {
             target: IRTarget::Variable(t_ret),
-            types: IRType::STRING,
+            types: IRType::STRING | IRType::UNDEFINED,
             source: IRValue::BuiltinProc(BuiltinProc::ReadLineStdin),
             op: IROp::NativeCall(vec![IRValue::Variable(t_p1)]),
         }),
@@ -1208,6 +1225,13 @@ fn read_stub_new() -> Rc<RefCell<IRProcedure>> {
      *  goto <ret_idx>
      *
      * <ret_idx>:
+     *  t_ret_om := t_ret == om;
+     *  if t_ret_om
+     *   goto <ret_fin_idx>
+     *  else
+     *   goto <int_idx>
+     *
+     * <int_idx>:
      *  t_ret_new := parse_int(t_ret);
      *  t_ret_new_om := t_ret_new == om;
      *  if t_ret_new_om
@@ -1238,6 +1262,7 @@ fn read_stub_new() -> Rc<RefCell<IRProcedure>> {
     let t_p1_addr = tmp_var_new(&mut proc.borrow_mut());
     let t_p1 = tmp_var_new(&mut proc.borrow_mut());
     let t_ret = tmp_var_new(&mut proc.borrow_mut());
+    let t_ret_om = tmp_var_new(&mut proc.borrow_mut());
     let t_ret_new = tmp_var_new(&mut proc.borrow_mut());
     let t_ret_new_om = tmp_var_new(&mut proc.borrow_mut());
 
@@ -1245,6 +1270,7 @@ fn read_stub_new() -> Rc<RefCell<IRProcedure>> {
     let arg_idx = proc.borrow_mut().blocks.add_node(Vec::new());
     let narg_idx = proc.borrow_mut().blocks.add_node(Vec::new());
     let ret_idx = proc.borrow_mut().blocks.add_node(Vec::new());
+    let int_idx = proc.borrow_mut().blocks.add_node(Vec::new());
     let double_idx = proc.borrow_mut().blocks.add_node(Vec::new());
     let ret_assign_idx = proc.borrow_mut().blocks.add_node(Vec::new());
     let ret_fin_idx = proc.borrow_mut().blocks.add_node(Vec::new());
@@ -1266,6 +1292,7 @@ fn read_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p_arg),
             success: arg_idx,
             failure: narg_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut().blocks.add_edge(init_idx, arg_idx, ());
@@ -1286,7 +1313,7 @@ fn read_stub_new() -> Rc<RefCell<IRProcedure>> {
         }),
         IRStmt::Assign(IRAssign {
             target: IRTarget::Variable(t_ret),
-            types: IRType::STRING,
+            types: IRType::STRING | IRType::UNDEFINED,
             source: IRValue::BuiltinProc(BuiltinProc::ReadLineStdin),
             op: IROp::NativeCall(vec![IRValue::Variable(t_p1)]),
         }),
@@ -1303,7 +1330,7 @@ fn read_stub_new() -> Rc<RefCell<IRProcedure>> {
         }),
         IRStmt::Assign(IRAssign {
             target: IRTarget::Variable(t_ret),
-            types: IRType::STRING,
+            types: IRType::STRING | IRType::UNDEFINED,
             source: IRValue::BuiltinProc(BuiltinProc::ReadLineStdin),
             op: IROp::NativeCall(vec![IRValue::Variable(t_p1)]),
         }),
@@ -1318,6 +1345,23 @@ fn read_stub_new() -> Rc<RefCell<IRProcedure>> {
     proc.borrow_mut().blocks.add_edge(narg_idx, ret_idx, ());
 
     block_get(&mut proc.borrow_mut(), ret_idx).extend(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret_om),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_ret),
+            op: IROp::Equal(IRValue::Undefined),
+        }),
+        IRStmt::Branch(IRBranch {
+            cond: IRValue::Variable(t_ret_om),
+            success: ret_fin_idx,
+            failure: int_idx,
+            source: false,
+        }),
+    ]);
+    proc.borrow_mut().blocks.add_edge(ret_idx, ret_fin_idx, ());
+    proc.borrow_mut().blocks.add_edge(ret_idx, int_idx, ());
+
+    block_get(&mut proc.borrow_mut(), int_idx).extend(vec![
         IRStmt::Assign(IRAssign {
             target: IRTarget::Variable(t_ret_new),
             types: IRType::NUMBER | IRType::UNDEFINED,
@@ -1334,12 +1378,13 @@ fn read_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_ret_new_om),
             success: double_idx,
             failure: ret_assign_idx,
+            source: false,
         }),
     ]);
-    proc.borrow_mut().blocks.add_edge(ret_idx, double_idx, ());
+    proc.borrow_mut().blocks.add_edge(int_idx, double_idx, ());
     proc.borrow_mut()
         .blocks
-        .add_edge(ret_idx, ret_assign_idx, ());
+        .add_edge(int_idx, ret_assign_idx, ());
 
     block_get(&mut proc.borrow_mut(), double_idx).extend(vec![
         IRStmt::Assign(IRAssign {
@@ -1358,6 +1403,7 @@ fn read_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_ret_new_om),
             success: ret_fin_idx,
             failure: ret_assign_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -1546,6 +1592,7 @@ fn eval_term_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_ast),
             success: eval_idx,
             failure: clone_idx,
+            source: false,
         }),
     ]);
 
@@ -1704,6 +1751,7 @@ fn pow_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_check),
             success: ret_idx,
             failure: throw_idx,
+            source: false,
         }),
     ]);
 
@@ -1858,6 +1906,7 @@ fn join_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_check),
             success: add_idx,
             failure: ret_idx,
+            source: false,
         }),
     ]);
 
@@ -1868,6 +1917,7 @@ fn join_stub_new() -> Rc<RefCell<IRProcedure>> {
         cond: IRValue::Variable(t_init_iter),
         success: add_init_idx,
         failure: add_clue_idx,
+        source: false,
     }));
 
     proc.borrow_mut().blocks.add_edge(add_idx, add_init_idx, ());
@@ -2058,6 +2108,7 @@ fn args_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_check),
             success: prep_idx,
             failure: throw_idx,
+            source: false,
         }),
     ]);
 
@@ -2112,6 +2163,7 @@ fn args_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_check),
             success: loop_idx,
             failure: ret_idx,
+            source: false,
         }),
     ]);
 
@@ -2153,6 +2205,7 @@ fn args_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_n_om),
             success: t_n_nil_idx,
             failure: t_n_copy_idx,
+            source: false,
         }),
     ]);
 
@@ -2333,6 +2386,7 @@ fn fct_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_ast),
             success: ast_idx,
             failure: term_dispatch_idx,
+            source: false,
         }),
     ]);
 
@@ -2382,6 +2436,7 @@ fn fct_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_tterm),
             success: ret_tt_init_idx,
             failure: ret_t_init_idx,
+            source: false,
         }),
     ]);
 
@@ -2437,6 +2492,7 @@ fn fct_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_tl),
             success: term_idx,
             failure: throw_idx,
+            source: false,
         }),
     ]);
 
@@ -2725,6 +2781,7 @@ fn make_term_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_cond),
             success: args_check_idx,
             failure: args_cnt_assign_idx,
+            source: false,
         }),
     ]);
 
@@ -2758,6 +2815,7 @@ fn make_term_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_entry_at),
             success: args_incr_idx,
             failure: args_cnt_assign_idx,
+            source: false,
         }),
     ]);
 
@@ -2817,6 +2875,7 @@ fn make_term_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_at_count_three),
             success: ast_check_idx,
             failure: term_check_idx,
+            source: false,
         }),
     ]);
 
@@ -2857,6 +2916,7 @@ fn make_term_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_tag_om),
             success: tterm_new_idx,
             failure: ast_new_idx,
+            source: false,
         }),
     ]);
 
@@ -2909,6 +2969,7 @@ fn make_term_stub_new() -> Rc<RefCell<IRProcedure>> {
         cond: IRValue::Variable(t_at_count_zero),
         success: term_zero_idx,
         failure: term_new_idx,
+        source: false,
     }));
 
     proc.borrow_mut()
@@ -2995,6 +3056,7 @@ fn make_term_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_check),
             success: param_assign_idx,
             failure: follow_idx,
+            source: false,
         }),
     ]);
 
@@ -3190,6 +3252,7 @@ fn matches_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_params_len_check),
             success: groups_check_idx,
             failure: matched_idx,
+            source: false,
         }),
     ]);
 
@@ -3215,6 +3278,7 @@ fn matches_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_group_check),
             success: groups_idx,
             failure: matched_idx,
+            source: false,
         }),
     ]);
 
@@ -3449,6 +3513,7 @@ fn replace_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_matched),
             success: append_idx,
             failure: end_idx,
+            source: false,
         }),
     ]);
 
@@ -3742,6 +3807,7 @@ fn replace_first_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_matched),
             success: append_idx,
             failure: end_idx,
+            source: false,
         }),
     ]);
 
@@ -4017,6 +4083,7 @@ fn split_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_matched),
             success: append_idx,
             failure: end_idx,
+            source: false,
         }),
     ]);
 
@@ -4275,6 +4342,7 @@ pub fn max_stub_new(tag: &str, comp_max: bool) -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_cond),
             success: loop_idx,
             failure: ret_idx,
+            source: false,
         }),
     ]);
 
@@ -4292,6 +4360,7 @@ pub fn max_stub_new(tag: &str, comp_max: bool) -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_out_om),
             success: assign_idx,
             failure: check_idx,
+            source: false,
         }),
     ]);
 
@@ -4336,6 +4405,7 @@ pub fn max_stub_new(tag: &str, comp_max: bool) -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_out_less),
             success: assign_idx,
             failure: cond_idx,
+            source: false,
         }),
     ]);
 
@@ -4352,12 +4422,8 @@ fn load_library_stub_new() -> Rc<RefCell<IRProcedure>> {
     /*  t_p1_addr := params[0];
      *  t_p1 := *t_p1_addr;
      *  t_name := t_p1 + ".stlx";
-     *  t_file := open_at(LIBRARY_PATH, t_name, 0x01);
+     *  _ := load(t_name, LIBRARY_PATH);
      *  _ := invalidate(t_name);
-     *  t_str := read_all(t_file);
-     *  _ := invalidate(t_file);
-     *  _ := execute(t_str);
-     *  _ := invalidate(t_str);
      *  return true;
      */
     let proc = Rc::new(RefCell::new(IRProcedure::from_tag("loadLibrary")));
@@ -4365,8 +4431,6 @@ fn load_library_stub_new() -> Rc<RefCell<IRProcedure>> {
     let t_p1_addr = tmp_var_new(&mut proc.borrow_mut());
     let t_p1 = tmp_var_new(&mut proc.borrow_mut());
     let t_name = tmp_var_new(&mut proc.borrow_mut());
-    let t_file = tmp_var_new(&mut proc.borrow_mut());
-    let t_str = tmp_var_new(&mut proc.borrow_mut());
 
     let init_idx = proc.borrow_mut().blocks.add_node(Vec::new());
 
@@ -4390,13 +4454,12 @@ fn load_library_stub_new() -> Rc<RefCell<IRProcedure>> {
             op: IROp::Plus(IRValue::String(".stlx".to_string())),
         }),
         IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_file),
-            types: IRType::FILE,
-            source: IRValue::BuiltinProc(BuiltinProc::OpenAt),
+            target: IRTarget::Ignore,
+            types: IRType::UNDEFINED,
+            source: IRValue::BuiltinProc(BuiltinProc::Load),
             op: IROp::NativeCall(vec![
-                IRValue::BuiltinVar(BuiltinVar::LibraryPath),
                 IRValue::Variable(t_name),
-                IRValue::Number(1.into()),
+                IRValue::BuiltinVar(BuiltinVar::LibraryPath),
             ]),
         }),
         IRStmt::Assign(IRAssign {
@@ -4405,30 +4468,6 @@ fn load_library_stub_new() -> Rc<RefCell<IRProcedure>> {
             source: IRValue::BuiltinProc(BuiltinProc::Invalidate),
             op: IROp::NativeCall(vec![IRValue::Variable(t_name)]),
         }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_str),
-            types: IRType::STRING,
-            source: IRValue::BuiltinProc(BuiltinProc::ReadAll),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_file)]),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Ignore,
-            types: IRType::UNDEFINED,
-            source: IRValue::BuiltinProc(BuiltinProc::Invalidate),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_file)]),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Ignore,
-            types: IRType::UNDEFINED,
-            source: IRValue::BuiltinProc(BuiltinProc::Execute),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_str)]),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Ignore,
-            types: IRType::UNDEFINED,
-            source: IRValue::BuiltinProc(BuiltinProc::Invalidate),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_str)]),
-        }),
         IRStmt::Return(IRValue::Bool(true)),
     ]);
 
@@ -4441,19 +4480,13 @@ fn load_library_stub_new() -> Rc<RefCell<IRProcedure>> {
 fn load_stub_new() -> Rc<RefCell<IRProcedure>> {
     /*  t_p1_addr := params[0];
      *  t_p1 := *t_p1_addr;
-     *  t_file := open_at(., t_p1, 0x01);
-     *  t_str := read_all(t_file);
-     *  _ := invalidate(t_file);
-     *  _ := execute(t_str);
-     *  _ := invalidate(t_str);
+     *  _ := load(t_p1, "");
      *  return true;
      */
     let proc = Rc::new(RefCell::new(IRProcedure::from_tag("load")));
 
     let t_p1_addr = tmp_var_new(&mut proc.borrow_mut());
     let t_p1 = tmp_var_new(&mut proc.borrow_mut());
-    let t_file = tmp_var_new(&mut proc.borrow_mut());
-    let t_str = tmp_var_new(&mut proc.borrow_mut());
 
     let init_idx = proc.borrow_mut().blocks.add_node(Vec::new());
 
@@ -4471,39 +4504,14 @@ fn load_stub_new() -> Rc<RefCell<IRProcedure>> {
             op: IROp::PtrDeref,
         }),
         IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_file),
-            types: IRType::FILE,
-            source: IRValue::BuiltinProc(BuiltinProc::OpenAt),
+            target: IRTarget::Ignore,
+            types: IRType::UNDEFINED,
+            source: IRValue::BuiltinProc(BuiltinProc::Load),
             op: IROp::NativeCall(vec![
-                IRValue::String(".".to_string()),
                 IRValue::Variable(t_p1),
-                IRValue::Number(1.into()),
+                IRValue::String(String::new()),
             ]),
         }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_str),
-            types: IRType::STRING,
-            source: IRValue::BuiltinProc(BuiltinProc::ReadAll),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_file)]),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Ignore,
-            types: IRType::UNDEFINED,
-            source: IRValue::BuiltinProc(BuiltinProc::Invalidate),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_file)]),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Ignore,
-            types: IRType::UNDEFINED,
-            source: IRValue::BuiltinProc(BuiltinProc::Execute),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_str)]),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Ignore,
-            types: IRType::UNDEFINED,
-            source: IRValue::BuiltinProc(BuiltinProc::Invalidate),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_str)]),
-        }),
         IRStmt::Return(IRValue::Bool(true)),
     ]);
 
@@ -4750,6 +4758,7 @@ fn is_variable_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_ast),
             success: ast_idx,
             failure: ret_idx,
+            source: false,
         }),
     ]);
 
@@ -4834,7 +4843,9 @@ fn is_number_stub_new() -> Rc<RefCell<IRProcedure>> {
      * t_p1_type := type_of(t_p1);
      * t_n := t_p1_type == TYPE_NUMBER;
      * t_d := t_p1_type == TYPE_DOUBLE;
-     * t_ret := t_n || t_d;
+     * t_r := t_p1_type == TYPE_RATIONAL;
+     * t_nd := t_n || t_d;
+     * t_ret := t_nd || t_r;
      * return t_ret;
      */
     let proc = Rc::new(RefCell::new(IRProcedure::from_tag("isNumber")));
@@ -4844,6 +4855,8 @@ fn is_number_stub_new() -> Rc<RefCell<IRProcedure>> {
     let t_p1_type = tmp_var_new(&mut proc.borrow_mut());
     let t_n = tmp_var_new(&mut proc.borrow_mut());
     let t_d = tmp_var_new(&mut proc.borrow_mut());
+    let t_r = tmp_var_new(&mut proc.borrow_mut());
+    let t_nd = tmp_var_new(&mut proc.borrow_mut());
     let t_ret = tmp_var_new(&mut proc.borrow_mut());
 
     let init_idx = proc.borrow_mut().blocks.add_node(vec![
@@ -4878,11 +4891,87 @@ fn is_number_stub_new() -> Rc<RefCell<IRProcedure>> {
             op: IROp::Equal(IRValue::Type(IRType::DOUBLE)),
         }),
         IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_ret),
+            target: IRTarget::Variable(t_r),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_type),
+            op: IROp::Equal(IRValue::Type(IRType::RATIONAL)),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_nd),
             types: IRType::BOOL,
             source: IRValue::Variable(t_d),
             op: IROp::Or(IRValue::Variable(t_n)),
         }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_nd),
+            op: IROp::Or(IRValue::Variable(t_r)),
+        }),
+        IRStmt::Return(IRValue::Variable(t_ret)),
+    ]);
+
+    proc.borrow_mut().start_block = init_idx;
+    proc.borrow_mut().end_block = init_idx;
+
+    proc
+}
+
+fn is_rational_stub_new() -> Rc<RefCell<IRProcedure>> {
+    /* t_p1_addr := params[0];
+     * t_p1 := *t_p1_addr;
+     * t_p1_type := type_of(t_p1);
+     * t_n := t_p1_type == TYPE_NUMBER;
+     * t_r := t_p1_type == TYPE_RATIONAL;
+     * t_ret := t_n || t_r;
+     * return t_ret;
+     */
+    let proc = Rc::new(RefCell::new(IRProcedure::from_tag("isRational")));
+
+    let t_p1_addr = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1 = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_type = tmp_var_new(&mut proc.borrow_mut());
+    let t_n = tmp_var_new(&mut proc.borrow_mut());
+    let t_r = tmp_var_new(&mut proc.borrow_mut());
+    let t_ret = tmp_var_new(&mut proc.borrow_mut());
+
+    let init_idx = proc.borrow_mut().blocks.add_node(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_addr),
+            types: IRType::PTR,
+            source: IRValue::BuiltinVar(BuiltinVar::Params),
+            op: IROp::AccessArray(IRValue::Number(0.into())),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1),
+            types: IRTypes!("any"),
+            source: IRValue::Variable(t_p1_addr),
+            op: IROp::PtrDeref,
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_type),
+            types: IRType::TYPE,
+            source: IRValue::BuiltinProc(BuiltinProc::TypeOf),
+            op: IROp::NativeCall(vec![IRValue::Variable(t_p1)]),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_n),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_type),
+            op: IROp::Equal(IRValue::Type(IRType::NUMBER)),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_r),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_type),
+            op: IROp::Equal(IRValue::Type(IRType::RATIONAL)),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_n),
+            op: IROp::Or(IRValue::Variable(t_r)),
+        }),
         IRStmt::Return(IRValue::Variable(t_ret)),
     ]);
 
@@ -4899,7 +4988,9 @@ fn log10_stub_new() -> Rc<RefCell<IRProcedure>> {
      *  t_p1_type := type_of(t_p1);
      *  t_p1_int := t_p1_type == TYPE_NUMBER;
      *  t_p1_float := t_p1_type == TYPE_DOUBLE;
-     *  t_p1_num := t_p1_int || t_p1_float;
+     *  t_p1_rat := t_p1_type == TYPE_RATIONAL;
+     *  t_p1_real := t_p1_int || t_p1_float;
+     *  t_p1_num := t_p1_real || t_p1_rat;
      *  if t_p1_num
      *   goto <assign_idx>
      *  else
@@ -4927,6 +5018,8 @@ fn log10_stub_new() -> Rc<RefCell<IRProcedure>> {
     let t_p1_type = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_int = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_float = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_rat = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_real = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_num = tmp_var_new(&mut proc.borrow_mut());
     let t_ln = tmp_var_new(&mut proc.borrow_mut());
     let t_ln_t = tmp_var_new(&mut proc.borrow_mut());
@@ -4969,15 +5062,28 @@ fn log10_stub_new() -> Rc<RefCell<IRProcedure>> {
             op: IROp::Equal(IRValue::Type(IRType::DOUBLE)),
         }),
         IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_p1_num),
+            target: IRTarget::Variable(t_p1_rat),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_type),
+            op: IROp::Equal(IRValue::Type(IRType::RATIONAL)),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_real),
             types: IRType::BOOL,
             source: IRValue::Variable(t_p1_int),
             op: IROp::Or(IRValue::Variable(t_p1_float)),
         }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_num),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_real),
+            op: IROp::Or(IRValue::Variable(t_p1_rat)),
+        }),
         IRStmt::Branch(IRBranch {
             cond: IRValue::Variable(t_p1_num),
             success: assign_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -5048,7 +5154,9 @@ fn log1p_stub_new() -> Rc<RefCell<IRProcedure>> {
      *  t_p1_type := type_of(t_p1);
      *  t_p1_int := t_p1_type == TYPE_NUMBER;
      *  t_p1_float := t_p1_type == TYPE_DOUBLE;
-     *  t_p1_num := t_p1_int || t_p1_float;
+     *  t_p1_rat := t_p1_type == TYPE_RATIONAL;
+     *  t_p1_real := t_p1_int || t_p1_float;
+     *  t_p1_num := t_p1_real || t_p1_rat;
      *  if t_p1_num
      *   goto <assign_idx>
      *  else
@@ -5074,6 +5182,8 @@ fn log1p_stub_new() -> Rc<RefCell<IRProcedure>> {
     let t_p1_type = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_int = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_float = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_rat = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_real = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_num = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_n = tmp_var_new(&mut proc.borrow_mut());
     let t_ret = tmp_var_new(&mut proc.borrow_mut());
@@ -5115,15 +5225,28 @@ fn log1p_stub_new() -> Rc<RefCell<IRProcedure>> {
             op: IROp::Equal(IRValue::Type(IRType::DOUBLE)),
         }),
         IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_p1_num),
+            target: IRTarget::Variable(t_p1_rat),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_type),
+            op: IROp::Equal(IRValue::Type(IRType::RATIONAL)),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_real),
             types: IRType::BOOL,
             source: IRValue::Variable(t_p1_int),
             op: IROp::Or(IRValue::Variable(t_p1_float)),
         }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_num),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_real),
+            op: IROp::Or(IRValue::Variable(t_p1_rat)),
+        }),
         IRStmt::Branch(IRBranch {
             cond: IRValue::Variable(t_p1_num),
             success: assign_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -5185,7 +5308,9 @@ fn ln_exp_stub_new(is_exp: bool) -> Rc<RefCell<IRProcedure>> {
      *  t_p1_type := type_of(t_p1);
      *  t_p1_int := t_p1_type == TYPE_NUMBER;
      *  t_p1_float := t_p1_type == TYPE_DOUBLE;
-     *  t_p1_num := t_p1_int || t_p1_float;
+     *  t_p1_rat := t_p1_type == TYPE_RATIONAL;
+     *  t_p1_real := t_p1_int || t_p1_float;
+     *  t_p1_num := t_p1_real || t_p1_rat;
      *  if t_p1_num
      *   goto <assign_idx>
      *  else
@@ -5213,6 +5338,8 @@ fn ln_exp_stub_new(is_exp: bool) -> Rc<RefCell<IRProcedure>> {
     let t_p1_type = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_int = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_float = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_rat = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_real = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_num = tmp_var_new(&mut proc.borrow_mut());
     let t_ret = tmp_var_new(&mut proc.borrow_mut());
 
@@ -5271,15 +5398,28 @@ fn ln_exp_stub_new(is_exp: bool) -> Rc<RefCell<IRProcedure>> {
             op: IROp::Equal(IRValue::Type(IRType::DOUBLE)),
         }),
         IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_p1_num),
+            target: IRTarget::Variable(t_p1_rat),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_type),
+            op: IROp::Equal(IRValue::Type(IRType::RATIONAL)),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_real),
             types: IRType::BOOL,
             source: IRValue::Variable(t_p1_int),
             op: IROp::Or(IRValue::Variable(t_p1_float)),
         }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_num),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_real),
+            op: IROp::Or(IRValue::Variable(t_p1_rat)),
+        }),
         IRStmt::Branch(IRBranch {
             cond: IRValue::Variable(t_p1_num),
             success: assign_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -5330,7 +5470,9 @@ fn sqrt_stub_new() -> Rc<RefCell<IRProcedure>> {
      *  t_p1_type := type_of(t_p1);
      *  t_p1_int := t_p1_type == TYPE_NUMBER;
      *  t_p1_float := t_p1_type == TYPE_DOUBLE;
-     *  t_p1_num := t_p1_int || t_p1_float;
+     *  t_p1_rat := t_p1_type == TYPE_RATIONAL;
+     *  t_p1_real := t_p1_int || t_p1_float;
+     *  t_p1_num := t_p1_real || t_p1_rat;
      *  if t_p1_num
      *   goto <assign_idx>
      *  else
@@ -5354,6 +5496,8 @@ fn sqrt_stub_new() -> Rc<RefCell<IRProcedure>> {
     let t_p1_type = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_int = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_float = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_rat = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_real = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_num = tmp_var_new(&mut proc.borrow_mut());
     let t_ret = tmp_var_new(&mut proc.borrow_mut());
 
@@ -5394,15 +5538,28 @@ fn sqrt_stub_new() -> Rc<RefCell<IRProcedure>> {
             op: IROp::Equal(IRValue::Type(IRType::DOUBLE)),
         }),
         IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_p1_num),
+            target: IRTarget::Variable(t_p1_rat),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_type),
+            op: IROp::Equal(IRValue::Type(IRType::RATIONAL)),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_real),
             types: IRType::BOOL,
             source: IRValue::Variable(t_p1_int),
             op: IROp::Or(IRValue::Variable(t_p1_float)),
         }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_num),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_real),
+            op: IROp::Or(IRValue::Variable(t_p1_rat)),
+        }),
         IRStmt::Branch(IRBranch {
             cond: IRValue::Variable(t_p1_num),
             success: assign_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -5449,48 +5606,15 @@ pub fn domain_range_stub_new(is_range: bool) -> Rc<RefCell<IRProcedure>> {
      *  t_p1_type := type_of(t_p1);
      *  t_p1_set := t_p1_type == TYPE_SET;
      *  if t_p1_set
-     *   goto <iter_idx>
-     *  else
-     *   goto <fail_idx>
-     *
-     * <iter_idx>:
-     *  t_set := set_new();
-     *  t_iter := iter_new(t_p1);
-     *  goto <iter_cond_idx>
-     *
-     * <iter_cond_idx>:
-     *  t_i := om;
-     *  t_i_addr := &t_i;
-     *  t_cond := iter_next(t_iter, t_i_addr);
-     *  if t_cond
-     *   goto <check_list_idx>
-     *  else
      *   goto <ret_idx>
-     *
-     * <check_list_idx>:
-     *  t_i_type := type_of(t_i);
-     *  t_i_list := t_i_type == TYPE_LIST;
-     *  if t_i_list
-     *   goto <check_len_idx>
      *  else
-     *   goto <iter_cond_idx>
-     *
-     * <check_len_idx>:
-     *  t_i_len := amount(t_i);
-     *  t_i_len_eq := t_i_len == 2;
-     *  _ := invalidate(t_i_len);
-     *  if t_i_len_eq
-     *   goto <set_insert_idx>
-     *  else
-     *   goto <iter_cond_idx>
-     *
-     * <set_insert_idx>:
-     *  t_j := t_i[0];
-     *  t_insert := copy(t_i);
-     *  _ := set_insert(t_set, t_insert);
-     *  goto <iter_cond_idx>
+     *   goto <fail_idx>
      *
      * <ret_idx>:
+     *  // is_range
+     *  t_set := set_image(t_p1);
+     *  // !is_range
+     *  t_set := set_domain(t_p1);
      *  return t_set;
      *
      * <fail_idx>:
@@ -5509,27 +5633,8 @@ pub fn domain_range_stub_new(is_range: bool) -> Rc<RefCell<IRProcedure>> {
     let t_p1_set = tmp_var_new(&mut proc.borrow_mut());
 
     let t_set = tmp_var_new(&mut proc.borrow_mut());
-    let t_iter = tmp_var_new(&mut proc.borrow_mut());
-
-    let t_i = tmp_var_new(&mut proc.borrow_mut());
-    let t_j = tmp_var_new(&mut proc.borrow_mut());
-    let t_i_addr = tmp_var_new(&mut proc.borrow_mut());
-    let t_cond = tmp_var_new(&mut proc.borrow_mut());
-
-    let t_i_type = tmp_var_new(&mut proc.borrow_mut());
-    let t_i_list = tmp_var_new(&mut proc.borrow_mut());
-
-    let t_i_len = tmp_var_new(&mut proc.borrow_mut());
-    let t_i_len_eq = tmp_var_new(&mut proc.borrow_mut());
-
-    let t_insert = tmp_var_new(&mut proc.borrow_mut());
 
     let init_idx = proc.borrow_mut().blocks.add_node(Vec::new());
-    let iter_idx = proc.borrow_mut().blocks.add_node(Vec::new());
-    let iter_cond_idx = proc.borrow_mut().blocks.add_node(Vec::new());
-    let check_list_idx = proc.borrow_mut().blocks.add_node(Vec::new());
-    let check_len_idx = proc.borrow_mut().blocks.add_node(Vec::new());
-    let set_insert_idx = proc.borrow_mut().blocks.add_node(Vec::new());
     let ret_idx = proc.borrow_mut().blocks.add_node(Vec::new());
     let fail_idx = proc.borrow_mut().blocks.add_node(Vec::new());
 
@@ -5560,164 +5665,38 @@ pub fn domain_range_stub_new(is_range: bool) -> Rc<RefCell<IRProcedure>> {
         }),
         IRStmt::Branch(IRBranch {
             cond: IRValue::Variable(t_p1_set),
-            success: iter_idx,
+            success: ret_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
-    proc.borrow_mut().blocks.add_edge(init_idx, iter_idx, ());
+    proc.borrow_mut().blocks.add_edge(init_idx, ret_idx, ());
     proc.borrow_mut().blocks.add_edge(init_idx, fail_idx, ());
 
-    block_get(&mut proc.borrow_mut(), iter_idx).extend(vec![
+    block_get(&mut proc.borrow_mut(), ret_idx).extend(vec![
         IRStmt::Assign(IRAssign {
             target: IRTarget::Variable(t_set),
             types: IRType::SET,
-            source: IRValue::BuiltinProc(BuiltinProc::SetNew),
-            op: IROp::NativeCall(vec![]),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_iter),
-            types: IRType::ITERATOR,
-            source: IRValue::BuiltinProc(BuiltinProc::IterNew),
+            source: IRValue::BuiltinProc(if is_range {
+                BuiltinProc::SetImage
+            } else {
+                BuiltinProc::SetDomain
+            }),
             op: IROp::NativeCall(vec![IRValue::Variable(t_p1)]),
         }),
-        IRStmt::Goto(iter_cond_idx),
+        IRStmt::Return(IRValue::Variable(t_set)),
     ]);
 
-    proc.borrow_mut()
-        .blocks
-        .add_edge(iter_idx, iter_cond_idx, ());
-
-    block_get(&mut proc.borrow_mut(), iter_cond_idx).extend(vec![
+    block_get(&mut proc.borrow_mut(), fail_idx).extend(vec![
         IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_i),
+            target: IRTarget::Ignore,
             types: IRType::UNDEFINED,
-            source: IRValue::Undefined,
-            op: IROp::Assign,
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_i_addr),
-            types: IRType::PTR,
-            source: IRValue::Variable(t_i),
-            op: IROp::PtrAddress,
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_cond),
-            types: IRType::BOOL,
-            source: IRValue::BuiltinProc(BuiltinProc::IterNext),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_iter), IRValue::Variable(t_i_addr)]),
-        }),
-        IRStmt::Branch(IRBranch {
-            cond: IRValue::Variable(t_cond),
-            success: check_list_idx,
-            failure: ret_idx,
-        }),
-    ]);
-
-    proc.borrow_mut()
-        .blocks
-        .add_edge(iter_cond_idx, check_list_idx, ());
-    proc.borrow_mut()
-        .blocks
-        .add_edge(iter_cond_idx, ret_idx, ());
-
-    block_get(&mut proc.borrow_mut(), check_list_idx).extend(vec![
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_i_type),
-            types: IRType::TYPE,
-            source: IRValue::BuiltinProc(BuiltinProc::TypeOf),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_i)]),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_i_list),
-            types: IRType::BOOL,
-            source: IRValue::Variable(t_i_type),
-            op: IROp::Equal(IRValue::Type(IRType::LIST)),
-        }),
-        IRStmt::Branch(IRBranch {
-            cond: IRValue::Variable(t_i_list),
-            success: check_len_idx,
-            failure: iter_cond_idx,
-        }),
-    ]);
-
-    proc.borrow_mut()
-        .blocks
-        .add_edge(check_list_idx, check_len_idx, ());
-    proc.borrow_mut()
-        .blocks
-        .add_edge(check_list_idx, iter_cond_idx, ());
-
-    block_get(&mut proc.borrow_mut(), check_len_idx).extend(vec![
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_i_len),
-            types: IRType::NUMBER,
-            source: IRValue::BuiltinProc(BuiltinProc::Amount),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_i)]),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_i_len_eq),
-            types: IRType::BOOL,
-            source: IRValue::Variable(t_i_len),
-            op: IROp::Equal(IRValue::Number(2.into())),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Ignore,
-            types: IRType::UNDEFINED,
-            source: IRValue::BuiltinProc(BuiltinProc::Invalidate),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_i_len)]),
-        }),
-        IRStmt::Branch(IRBranch {
-            cond: IRValue::Variable(t_i_len_eq),
-            success: set_insert_idx,
-            failure: iter_cond_idx,
-        }),
-    ]);
-
-    proc.borrow_mut()
-        .blocks
-        .add_edge(check_len_idx, set_insert_idx, ());
-    proc.borrow_mut()
-        .blocks
-        .add_edge(check_len_idx, iter_cond_idx, ());
-
-    block_get(&mut proc.borrow_mut(), set_insert_idx).extend(vec![
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_j),
-            types: IRTypes!("any"),
-            source: IRValue::Variable(t_i),
-            op: IROp::AccessArray(IRValue::Number(if is_range { 1.into() } else { 0.into() })),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_insert),
-            types: IRTypes!("any"),
-            source: IRValue::BuiltinProc(BuiltinProc::Copy),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_j)]),
-        }),
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Ignore,
-            types: IRType::UNDEFINED,
-            source: IRValue::BuiltinProc(BuiltinProc::SetInsert),
-            op: IROp::NativeCall(vec![IRValue::Variable(t_set), IRValue::Variable(t_insert)]),
-        }),
-        IRStmt::Goto(iter_cond_idx),
-    ]);
-
-    proc.borrow_mut()
-        .blocks
-        .add_edge(set_insert_idx, iter_cond_idx, ());
-
-    block_get(&mut proc.borrow_mut(), ret_idx).push(IRStmt::Return(IRValue::Variable(t_set)));
-
-    block_get(&mut proc.borrow_mut(), fail_idx).extend(vec![
-        IRStmt::Assign(IRAssign {
-            target: IRTarget::Ignore,
-            types: IRType::UNDEFINED,
-            source: IRValue::BuiltinProc(BuiltinProc::ExceptionThrow),
-            op: IROp::NativeCall(vec![
-                IRValue::String("predefined procedure".to_string()),
-                IRValue::String("operand is not a set".to_string()),
-            ]),
+            source: IRValue::BuiltinProc(BuiltinProc::ExceptionThrow),
+            op: IROp::NativeCall(vec![
+                IRValue::String("predefined procedure".to_string()),
+                IRValue::String("operand is not a set".to_string()),
+            ]),
         }),
         IRStmt::Unreachable,
     ]);
@@ -5795,6 +5774,7 @@ pub fn sleep_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_int),
             success: check_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -5812,6 +5792,7 @@ pub fn sleep_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_np),
             success: fail_idx,
             failure: sleep_idx,
+            source: false,
         }),
     ]);
 
@@ -5984,6 +5965,7 @@ pub fn reverse_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_list),
             success: list_new_idx,
             failure: str_check_idx,
+            source: false,
         }),
     ]);
 
@@ -6030,6 +6012,7 @@ pub fn reverse_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_cond),
             success: list_loop_idx,
             failure: list_inv_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -6104,6 +6087,7 @@ pub fn reverse_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_string),
             success: str_new_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -6149,6 +6133,7 @@ pub fn reverse_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_cond),
             success: str_loop_idx,
             failure: str_inv_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -6376,6 +6361,7 @@ pub fn math_const_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_str),
             success: check_pi_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -6395,6 +6381,7 @@ pub fn math_const_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_eq),
             success: pi_idx,
             failure: check_e_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut().blocks.add_edge(check_pi_idx, pi_idx, ());
@@ -6424,6 +6411,7 @@ pub fn math_const_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_eq),
             success: e_idx,
             failure: check_inf_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut().blocks.add_edge(check_e_idx, e_idx, ());
@@ -6465,6 +6453,7 @@ pub fn math_const_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_eq),
             success: inf_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -6546,7 +6535,9 @@ fn float_op_stub_new(op: FloatOp) -> Rc<RefCell<IRProcedure>> {
      *  t_p1_type := type_of(t_p1);
      *  t_p1_int := t_p1_type == TYPE_NUMBER;
      *  t_p1_d := t_p1_type == TYPE_DOUBLE;
-     *  t_p1_num := t_p1_int || t_p1_d;
+     *  t_p1_rat := t_p1_type == TYPE_RATIONAL;
+     *  t_p1_real := t_p1_int || t_p1_d;
+     *  t_p1_num := t_p1_real || t_p1_rat;
      *  if t_p1_num
      *   goto <num_check_idx>
      *  else
@@ -6580,6 +6571,8 @@ fn float_op_stub_new(op: FloatOp) -> Rc<RefCell<IRProcedure>> {
     let t_p1_type = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_int = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_d = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_rat = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_real = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_num = tmp_var_new(&mut proc.borrow_mut());
     let t_ret = tmp_var_new(&mut proc.borrow_mut());
 
@@ -6622,15 +6615,28 @@ fn float_op_stub_new(op: FloatOp) -> Rc<RefCell<IRProcedure>> {
             op: IROp::Equal(IRValue::Type(IRType::DOUBLE)),
         }),
         IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_p1_num),
+            target: IRTarget::Variable(t_p1_rat),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_type),
+            op: IROp::Equal(IRValue::Type(IRType::RATIONAL)),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_real),
             types: IRType::BOOL,
             source: IRValue::Variable(t_p1_int),
             op: IROp::Or(IRValue::Variable(t_p1_d)),
         }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_num),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_real),
+            op: IROp::Or(IRValue::Variable(t_p1_rat)),
+        }),
         IRStmt::Branch(IRBranch {
             cond: IRValue::Variable(t_p1_num),
             success: num_check_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -6642,6 +6648,7 @@ fn float_op_stub_new(op: FloatOp) -> Rc<RefCell<IRProcedure>> {
         cond: IRValue::Variable(t_p1_int),
         success: int_idx,
         failure: float_idx,
+        source: false,
     }));
     proc.borrow_mut()
         .blocks
@@ -6664,7 +6671,7 @@ fn float_op_stub_new(op: FloatOp) -> Rc<RefCell<IRProcedure>> {
     block_get(&mut proc.borrow_mut(), float_idx).extend(vec![
         IRStmt::Assign(IRAssign {
             target: IRTarget::Variable(t_ret),
-            types: IRType::DOUBLE,
+            types: IRType::DOUBLE | IRType::NUMBER,
             source: IRValue::BuiltinProc(match op {
                 FloatOp::Round => BuiltinProc::Round,
                 FloatOp::Floor => BuiltinProc::Floor,
@@ -6697,6 +6704,108 @@ fn float_op_stub_new(op: FloatOp) -> Rc<RefCell<IRProcedure>> {
     proc
 }
 
+fn rational_stub_new() -> Rc<RefCell<IRProcedure>> {
+    /* t_p1_addr := params[0];
+     * t_p1 := *t_p1_addr;
+     * t_ret := rational(t_p1);
+     * return t_ret;
+     */
+    let proc = Rc::new(RefCell::new(IRProcedure::from_tag("rational")));
+    let mut proc_ref = proc.borrow_mut();
+
+    let t_p1_addr = tmp_var_new(&mut proc_ref);
+    let t_p1 = tmp_var_new(&mut proc_ref);
+    let t_ret = tmp_var_new(&mut proc_ref);
+
+    let block_idx = proc_ref.blocks.add_node(Vec::new());
+    proc_ref.start_block = block_idx;
+    proc_ref.end_block = block_idx;
+
+    block_get(&mut proc_ref, block_idx).extend(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_addr),
+            types: IRType::PTR,
+            source: IRValue::BuiltinVar(BuiltinVar::Params),
+            op: IROp::AccessArray(IRValue::Number(0.into())),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1),
+            types: IRTypes!("any"),
+            source: IRValue::Variable(t_p1_addr),
+            op: IROp::PtrDeref,
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret),
+            types: IRType::NUMBER | IRType::RATIONAL,
+            source: IRValue::BuiltinProc(BuiltinProc::Rational),
+            op: IROp::NativeCall(vec![IRValue::Variable(t_p1)]),
+        }),
+        IRStmt::Return(IRValue::Variable(t_ret)),
+    ]);
+
+    drop(proc_ref);
+    proc
+}
+
+fn n_decimal_places_stub_new() -> Rc<RefCell<IRProcedure>> {
+    /* t_p1_addr := params[0];
+     * t_p1 := *t_p1_addr;
+     * t_p2_addr := params[1];
+     * t_p2 := *t_p2_addr;
+     * t_ret := n_decimal_places(t_p1, t_p2);
+     * return t_ret;
+     */
+    let proc = Rc::new(RefCell::new(IRProcedure::from_tag("nDecimalPlaces")));
+    let mut proc_ref = proc.borrow_mut();
+
+    let t_p1_addr = tmp_var_new(&mut proc_ref);
+    let t_p1 = tmp_var_new(&mut proc_ref);
+    let t_p2_addr = tmp_var_new(&mut proc_ref);
+    let t_p2 = tmp_var_new(&mut proc_ref);
+    let t_ret = tmp_var_new(&mut proc_ref);
+
+    let block_idx = proc_ref.blocks.add_node(Vec::new());
+    proc_ref.start_block = block_idx;
+    proc_ref.end_block = block_idx;
+
+    block_get(&mut proc_ref, block_idx).extend(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_addr),
+            types: IRType::PTR,
+            source: IRValue::BuiltinVar(BuiltinVar::Params),
+            op: IROp::AccessArray(IRValue::Number(0.into())),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1),
+            types: IRTypes!("any"),
+            source: IRValue::Variable(t_p1_addr),
+            op: IROp::PtrDeref,
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p2_addr),
+            types: IRType::PTR,
+            source: IRValue::BuiltinVar(BuiltinVar::Params),
+            op: IROp::AccessArray(IRValue::Number(1.into())),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p2),
+            types: IRTypes!("any"),
+            source: IRValue::Variable(t_p2_addr),
+            op: IROp::PtrDeref,
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret),
+            types: IRType::STRING,
+            source: IRValue::BuiltinProc(BuiltinProc::NDecimalPlaces),
+            op: IROp::NativeCall(vec![IRValue::Variable(t_p1), IRValue::Variable(t_p2)]),
+        }),
+        IRStmt::Return(IRValue::Variable(t_ret)),
+    ]);
+
+    drop(proc_ref);
+    proc
+}
+
 fn abs_stub_new() -> Rc<RefCell<IRProcedure>> {
     /* <init_idx>:
      *  t_p1_addr := params[0];
@@ -6704,7 +6813,9 @@ fn abs_stub_new() -> Rc<RefCell<IRProcedure>> {
      *  t_p1_type := type_of(t_p1);
      *  t_p1_int := t_p1_type == TYPE_NUMBER;
      *  t_p1_d := t_p1_type == TYPE_DOUBLE;
-     *  t_p1_num := t_p1_int || t_p1_d;
+     *  t_p1_rat := t_p1_type == TYPE_RATIONAL;
+     *  t_p1_real := t_p1_int || t_p1_d;
+     *  t_p1_num := t_p1_real || t_p1_rat;
      *  if t_p1_num
      *   goto <neg_check_idx>
      *  else
@@ -6762,6 +6873,8 @@ fn abs_stub_new() -> Rc<RefCell<IRProcedure>> {
     let t_p1_type = tmp_var_new(&mut proc_ref);
     let t_p1_int = tmp_var_new(&mut proc_ref);
     let t_p1_d = tmp_var_new(&mut proc_ref);
+    let t_p1_rat = tmp_var_new(&mut proc_ref);
+    let t_p1_real = tmp_var_new(&mut proc_ref);
     let t_p1_num = tmp_var_new(&mut proc_ref);
     let t_p1_neg = tmp_var_new(&mut proc_ref);
     let t_p1_len = tmp_var_new(&mut proc_ref);
@@ -6809,15 +6922,28 @@ fn abs_stub_new() -> Rc<RefCell<IRProcedure>> {
             op: IROp::Equal(IRValue::Type(IRType::DOUBLE)),
         }),
         IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_p1_num),
+            target: IRTarget::Variable(t_p1_rat),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_type),
+            op: IROp::Equal(IRValue::Type(IRType::RATIONAL)),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_real),
             types: IRType::BOOL,
             source: IRValue::Variable(t_p1_int),
             op: IROp::Or(IRValue::Variable(t_p1_d)),
         }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_num),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_real),
+            op: IROp::Or(IRValue::Variable(t_p1_rat)),
+        }),
         IRStmt::Branch(IRBranch {
             cond: IRValue::Variable(t_p1_num),
             success: neg_check_idx,
             failure: check_str_idx,
+            source: false,
         }),
     ]);
 
@@ -6835,6 +6961,7 @@ fn abs_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_neg),
             success: inv_idx,
             failure: int_idx,
+            source: false,
         }),
     ]);
 
@@ -6876,6 +7003,7 @@ fn abs_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_type),
             success: check_str_len_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -6907,6 +7035,7 @@ fn abs_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_num),
             success: abs_str_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -7011,6 +7140,7 @@ fn is_infinite_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_float),
             success: is_infinite_idx,
             failure: false_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -7119,6 +7249,7 @@ fn char_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_int),
             success: range_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut().blocks.add_edge(init_idx, range_idx, ());
@@ -7135,6 +7266,7 @@ fn char_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_cond),
             success: cast_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut().blocks.add_edge(range_idx, cast_idx, ());
@@ -7229,6 +7361,7 @@ fn run_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_str),
             success: float_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -7342,6 +7475,7 @@ fn random_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_num),
             success: rnd_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -7402,7 +7536,9 @@ fn num_op_stub_new(op: NumOp) -> Rc<RefCell<IRProcedure>> {
      *  t_p1_type := type_of(t_p1);
      *  t_p1_int := t_p1_type == TYPE_NUMBER;
      *  t_p1_d := t_p1_type == TYPE_DOUBLE;
-     *  t_p1_num := t_p1_int || t_p1_d;
+     *  t_p1_rat := t_p1_type == TYPE_RATIONAL;
+     *  t_p1_real := t_p1_int || t_p1_d;
+     *  t_p1_num := t_p1_real || t_p1_rat;
      *  if t_p1_num
      *   goto <num_check_idx>
      *  else
@@ -7423,6 +7559,8 @@ fn num_op_stub_new(op: NumOp) -> Rc<RefCell<IRProcedure>> {
     let t_p1_type = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_int = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_d = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_rat = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_real = tmp_var_new(&mut proc.borrow_mut());
     let t_p1_num = tmp_var_new(&mut proc.borrow_mut());
     let t_ret = tmp_var_new(&mut proc.borrow_mut());
 
@@ -7462,15 +7600,28 @@ fn num_op_stub_new(op: NumOp) -> Rc<RefCell<IRProcedure>> {
             op: IROp::Equal(IRValue::Type(IRType::DOUBLE)),
         }),
         IRStmt::Assign(IRAssign {
-            target: IRTarget::Variable(t_p1_num),
+            target: IRTarget::Variable(t_p1_rat),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_type),
+            op: IROp::Equal(IRValue::Type(IRType::RATIONAL)),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_real),
             types: IRType::BOOL,
             source: IRValue::Variable(t_p1_int),
             op: IROp::Or(IRValue::Variable(t_p1_d)),
         }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_num),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_real),
+            op: IROp::Or(IRValue::Variable(t_p1_rat)),
+        }),
         IRStmt::Branch(IRBranch {
             cond: IRValue::Variable(t_p1_num),
             success: float_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut().blocks.add_edge(init_idx, float_idx, ());
@@ -7845,6 +7996,7 @@ pub fn ask_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_check),
             success: list_check_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -7901,6 +8053,7 @@ pub fn ask_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_len_zero),
             success: fail_idx,
             failure: iter_opts_init_idx,
+            source: false,
         }),
     ]);
 
@@ -7936,6 +8089,7 @@ pub fn ask_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_i_cond),
             success: iter_opts_loop_idx,
             failure: check_one_idx,
+            source: false,
         }),
     ]);
 
@@ -8055,6 +8209,7 @@ pub fn ask_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_len_one),
             success: one_idx,
             failure: selection_idx,
+            source: false,
         }),
     ]);
 
@@ -8166,6 +8321,7 @@ pub fn ask_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_num_om),
             success: selection_idx,
             failure: check_num_idx,
+            source: false,
         }),
     ]);
 
@@ -8205,6 +8361,7 @@ pub fn ask_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_len_check),
             success: ser_idx,
             failure: inv_sel_idx,
+            source: false,
         }),
     ]);
 
@@ -8391,6 +8548,7 @@ pub fn signum_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_num),
             success: check_lz_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -8410,6 +8568,7 @@ pub fn signum_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_lz),
             success: ret_neg_idx,
             failure: check_z_idx,
+            source: false,
         }),
     ]);
 
@@ -8443,6 +8602,7 @@ pub fn signum_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_z),
             success: ret_z_idx,
             failure: ret_p_idx,
+            source: false,
         }),
     ]);
 
@@ -8503,19 +8663,39 @@ pub fn int_stub_new() -> Rc<RefCell<IRProcedure>> {
     /* <init_idx>:
      *  t_p1_addr := params[0];
      *  t_p1 := *t_p1_addr;
+     *  t_p1_type := type_of(t_p1);
+     *  t_p1_rat := t_p1_type == TYPE_RATIONAL;
+     *  if t_p1_rat
+     *   goto <rat_idx>
+     *  else
+     *   goto <parse_idx>
+     *
+     * <rat_idx>:
+     *  t_ret := t_p1 \ 1;
+     *  goto <ret_idx>
+     *
+     * <parse_idx>:
      *  t_s := serialize(t_p1);
      *  t_ret := parse_int(t_s);
      *  _ := invalidate(t_s);
+     *  goto <ret_idx>
+     *
+     * <ret_idx>:
      *  return t_ret;
      */
     let proc = Rc::new(RefCell::new(IRProcedure::from_tag("int")));
 
     let t_p1_addr = tmp_var_new(&mut proc.borrow_mut());
     let t_p1 = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_type = tmp_var_new(&mut proc.borrow_mut());
+    let t_p1_rat = tmp_var_new(&mut proc.borrow_mut());
     let t_s = tmp_var_new(&mut proc.borrow_mut());
     let t_ret = tmp_var_new(&mut proc.borrow_mut());
 
     let init_idx = proc.borrow_mut().blocks.add_node(Vec::new());
+    let rat_idx = proc.borrow_mut().blocks.add_node(Vec::new());
+    let parse_idx = proc.borrow_mut().blocks.add_node(Vec::new());
+    let ret_idx = proc.borrow_mut().blocks.add_node(Vec::new());
 
     block_get(&mut proc.borrow_mut(), init_idx).extend(vec![
         IRStmt::Assign(IRAssign {
@@ -8530,6 +8710,42 @@ pub fn int_stub_new() -> Rc<RefCell<IRProcedure>> {
             source: IRValue::Variable(t_p1_addr),
             op: IROp::PtrDeref,
         }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_type),
+            types: IRType::TYPE,
+            source: IRValue::BuiltinProc(BuiltinProc::TypeOf),
+            op: IROp::NativeCall(vec![IRValue::Variable(t_p1)]),
+        }),
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_p1_rat),
+            types: IRType::BOOL,
+            source: IRValue::Variable(t_p1_type),
+            op: IROp::Equal(IRValue::Type(IRType::RATIONAL)),
+        }),
+        IRStmt::Branch(IRBranch {
+            cond: IRValue::Variable(t_p1_rat),
+            success: rat_idx,
+            failure: parse_idx,
+            source: false,
+        }),
+    ]);
+
+    proc.borrow_mut().blocks.add_edge(init_idx, rat_idx, ());
+    proc.borrow_mut().blocks.add_edge(init_idx, parse_idx, ());
+
+    block_get(&mut proc.borrow_mut(), rat_idx).extend(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret),
+            types: IRType::NUMBER,
+            source: IRValue::Variable(t_p1),
+            op: IROp::IntDivide(IRValue::Number(1.into())),
+        }),
+        IRStmt::Goto(ret_idx),
+    ]);
+
+    proc.borrow_mut().blocks.add_edge(rat_idx, ret_idx, ());
+
+    block_get(&mut proc.borrow_mut(), parse_idx).extend(vec![
         IRStmt::Assign(IRAssign {
             target: IRTarget::Variable(t_s),
             types: IRType::STRING,
@@ -8548,11 +8764,16 @@ pub fn int_stub_new() -> Rc<RefCell<IRProcedure>> {
             source: IRValue::BuiltinProc(BuiltinProc::Invalidate),
             op: IROp::NativeCall(vec![IRValue::Variable(t_s)]),
         }),
-        IRStmt::Return(IRValue::Variable(t_ret)),
+        IRStmt::Goto(ret_idx),
     ]);
 
+    proc.borrow_mut().blocks.add_edge(parse_idx, ret_idx, ());
+
+    block_get(&mut proc.borrow_mut(), ret_idx)
+        .extend(vec![IRStmt::Return(IRValue::Variable(t_ret))]);
+
     proc.borrow_mut().start_block = init_idx;
-    proc.borrow_mut().end_block = init_idx;
+    proc.borrow_mut().end_block = ret_idx;
 
     proc
 }
@@ -8788,6 +9009,7 @@ fn logo_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_out_penny),
             success: print_penny_idx,
             failure: check_nickel_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -8827,6 +9049,7 @@ fn logo_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_out_nickel),
             success: nickel_idx,
             failure: check_dime_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -8866,6 +9089,7 @@ fn logo_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_out_dime),
             success: dime_idx,
             failure: check_quarter_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -8907,6 +9131,7 @@ fn logo_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_out_quarter),
             success: quarter_idx,
             failure: cnt_check_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -8996,6 +9221,7 @@ fn logo_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_cond),
             success: print_idx,
             failure: inv_str_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -9052,6 +9278,7 @@ fn logo_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_cnt_check),
             success: inc_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -9273,6 +9500,7 @@ fn rnd_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p_len_two),
             success: rnd_quot_idx,
             failure: rnd_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -9339,6 +9567,7 @@ fn rnd_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p2_check),
             success: rnd_quot_geq_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -9359,6 +9588,7 @@ fn rnd_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p2_check),
             success: rnd_quot_calc_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -9434,6 +9664,7 @@ fn rnd_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_num),
             success: rnd_calc_idx,
             failure: rnd_check_acc_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut().blocks.add_edge(rnd_idx, rnd_calc_idx, ());
@@ -9493,6 +9724,7 @@ fn rnd_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_p1_acc),
             success: rnd_acc_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
     proc.borrow_mut()
@@ -9792,6 +10024,7 @@ fn write_file_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_content_str),
             success: copy_idx,
             failure: init_iter_idx,
+            source: false,
         }),
     ]);
     proc_ref.blocks.add_edge(init_idx, copy_idx, ());
@@ -9842,6 +10075,7 @@ fn write_file_stub_new() -> Rc<RefCell<IRProcedure>> {
             cond: IRValue::Variable(t_concat),
             success: concat_idx,
             failure: write_idx,
+            source: false,
         }),
     ]);
     proc_ref.blocks.add_edge(iter_idx, concat_idx, ());
@@ -10040,387 +10274,976 @@ fn is_probable_prime_stub_new() -> Rc<RefCell<IRProcedure>> {
     proc
 }
 
-fn reset_random_stub_new() -> Rc<RefCell<IRProcedure>> {
+fn run_process_stub_new() -> Rc<RefCell<IRProcedure>> {
     /*
      * <init_idx>:
-     *  return om;
+     *  t_ret := run_process(params);
+     *  return t_ret;
      */
-    let proc = Rc::new(RefCell::new(IRProcedure::from_tag("resetRandom")));
+    let proc = Rc::new(RefCell::new(IRProcedure::from_tag("runProcess")));
+    let t_ret = tmp_var_new(&mut proc.borrow_mut());
 
     let init_idx = proc.borrow_mut().blocks.add_node(Vec::new());
 
     proc.borrow_mut().start_block = init_idx;
     proc.borrow_mut().end_block = init_idx;
 
-    block_get(&mut proc.borrow_mut(), init_idx).extend(vec![IRStmt::Return(IRValue::Undefined)]);
-
-    proc
+    block_get(&mut proc.borrow_mut(), init_idx).extend(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret),
+            types: IRType::TERM,
+            source: IRValue::BuiltinProc(BuiltinProc::RunProcess),
+            op: IROp::NativeCall(vec![IRValue::BuiltinVar(BuiltinVar::Params)]),
+        }),
+        IRStmt::Return(IRValue::Variable(t_ret)),
+    ]);
+
+    proc
+}
+
+fn reset_random_stub_new() -> Rc<RefCell<IRProcedure>> {
+    /*
+     * <init_idx>:
+     *  _ := rnd_seed(params);
+     *  return om;
+     */
+    let proc = Rc::new(RefCell::new(IRProcedure::from_tag("resetRandom")));
+
+    let init_idx = proc.borrow_mut().blocks.add_node(Vec::new());
+
+    proc.borrow_mut().start_block = init_idx;
+    proc.borrow_mut().end_block = init_idx;
+
+    block_get(&mut proc.borrow_mut(), init_idx).extend(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Ignore,
+            types: IRType::UNDEFINED,
+            source: IRValue::BuiltinProc(BuiltinProc::RndSeed),
+            op: IROp::NativeCall(vec![IRValue::BuiltinVar(BuiltinVar::Params)]),
+        }),
+        IRStmt::Return(IRValue::Undefined),
+    ]);
+
+    proc
+}
+
+fn la_stub_new(tag: &str, op: BuiltinProc, n_params: usize) -> Rc<RefCell<IRProcedure>> {
+    /* t_p1_addr := params[0];
+     * t_p1 := *t_p1_addr;
+     * ...
+     * t_ret := op(t_p1, ...);
+     * return t_ret;
+     */
+    let proc = Rc::new(RefCell::new(IRProcedure::from_tag(tag)));
+    let mut proc_ref = proc.borrow_mut();
+
+    let block_idx = proc_ref.blocks.add_node(Vec::new());
+    proc_ref.start_block = block_idx;
+    proc_ref.end_block = block_idx;
+
+    let mut t_params = Vec::new();
+    for i in 0..n_params {
+        let t_p_addr = tmp_var_new(&mut proc_ref);
+        let t_p = tmp_var_new(&mut proc_ref);
+
+        block_get(&mut proc_ref, block_idx).extend(vec![
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Variable(t_p_addr),
+                types: IRType::PTR,
+                source: IRValue::BuiltinVar(BuiltinVar::Params),
+                op: IROp::AccessArray(IRValue::Number(i.into())),
+            }),
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Variable(t_p),
+                types: IRTypes!("any"),
+                source: IRValue::Variable(t_p_addr),
+                op: IROp::PtrDeref,
+            }),
+        ]);
+        t_params.push(IRValue::Variable(t_p));
+    }
+
+    let t_ret = tmp_var_new(&mut proc_ref);
+    block_get(&mut proc_ref, block_idx).extend(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret),
+            types: IRType::DOUBLE | IRType::VECTOR | IRType::MATRIX | IRType::LIST,
+            source: IRValue::BuiltinProc(op),
+            op: IROp::NativeCall(t_params),
+        }),
+        IRStmt::Return(IRValue::Variable(t_ret)),
+    ]);
+
+    drop(proc_ref);
+    proc
 }
 
-pub fn stubs_init() -> Vec<InterpStackEntry> {
-    vec![
+fn plot_stub_new(tag: &str, op: BuiltinProc) -> Rc<RefCell<IRProcedure>> {
+    /* t_ret := op(params);
+     * return t_ret;
+     */
+    let proc = Rc::new(RefCell::new(IRProcedure::from_tag(tag)));
+    let mut proc_ref = proc.borrow_mut();
+
+    let block_idx = proc_ref.blocks.add_node(Vec::new());
+    proc_ref.start_block = block_idx;
+    proc_ref.end_block = block_idx;
+
+    let t_ret = tmp_var_new(&mut proc_ref);
+    block_get(&mut proc_ref, block_idx).extend(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret),
+            types: IRType::CANVAS | IRType::STRING | IRType::UNDEFINED,
+            source: IRValue::BuiltinProc(op),
+            op: IROp::NativeCall(vec![IRValue::BuiltinVar(BuiltinVar::Params)]),
+        }),
+        IRStmt::Return(IRValue::Variable(t_ret)),
+    ]);
+
+    drop(proc_ref);
+    proc
+}
+
+fn params_stub_new(tag: &str, op: BuiltinProc, types: IRType) -> Rc<RefCell<IRProcedure>> {
+    /* t_ret := op(params);
+     * return t_ret;
+     */
+    let proc = Rc::new(RefCell::new(IRProcedure::from_tag(tag)));
+    let mut proc_ref = proc.borrow_mut();
+
+    let block_idx = proc_ref.blocks.add_node(Vec::new());
+    proc_ref.start_block = block_idx;
+    proc_ref.end_block = block_idx;
+
+    let t_ret = tmp_var_new(&mut proc_ref);
+    block_get(&mut proc_ref, block_idx).extend(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret),
+            types,
+            source: IRValue::BuiltinProc(op),
+            op: IROp::NativeCall(vec![IRValue::BuiltinVar(BuiltinVar::Params)]),
+        }),
+        IRStmt::Return(IRValue::Variable(t_ret)),
+    ]);
+
+    drop(proc_ref);
+    proc
+}
+
+fn stat_stub_new(
+    tag: &str,
+    op: BuiltinProc,
+    dist: StatDist,
+    n_params: usize,
+) -> Rc<RefCell<IRProcedure>> {
+    /* t_p1_addr := params[0];
+     * t_p1 := *t_p1_addr;
+     * ...
+     * t_ret := op("dist", t_p1, ...);
+     * return t_ret;
+     */
+    let proc = Rc::new(RefCell::new(IRProcedure::from_tag(tag)));
+    let mut proc_ref = proc.borrow_mut();
+
+    let block_idx = proc_ref.blocks.add_node(Vec::new());
+    proc_ref.start_block = block_idx;
+    proc_ref.end_block = block_idx;
+
+    let mut t_params = vec![IRValue::String(dist.to_string())];
+    for i in 0..n_params {
+        let t_p_addr = tmp_var_new(&mut proc_ref);
+        let t_p = tmp_var_new(&mut proc_ref);
+
+        block_get(&mut proc_ref, block_idx).extend(vec![
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Variable(t_p_addr),
+                types: IRType::PTR,
+                source: IRValue::BuiltinVar(BuiltinVar::Params),
+                op: IROp::AccessArray(IRValue::Number(i.into())),
+            }),
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Variable(t_p),
+                types: IRTypes!("any"),
+                source: IRValue::Variable(t_p_addr),
+                op: IROp::PtrDeref,
+            }),
+        ]);
+        t_params.push(IRValue::Variable(t_p));
+    }
+
+    let t_ret = tmp_var_new(&mut proc_ref);
+    block_get(&mut proc_ref, block_idx).extend(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret),
+            types: IRType::DOUBLE,
+            source: IRValue::BuiltinProc(op),
+            op: IROp::NativeCall(t_params),
+        }),
+        IRStmt::Return(IRValue::Variable(t_ret)),
+    ]);
+
+    drop(proc_ref);
+    proc
+}
+
+fn native_stub_new(native: &Rc<NativeProc>) -> Rc<RefCell<IRProcedure>> {
+    /* <init_idx>:
+     *  t_n := amount(params);
+     *  t_n_ok := t_n == <arity>;
+     *  _ := invalidate(t_n);
+     *  if t_n_ok
+     *   goto <call_idx>
+     *  else
+     *   goto <fail_idx>
+     *
+     * <call_idx>:
+     *  t_p1_addr := params[0];
+     *  t_p1 := *t_p1_addr;
+     *  ...
+     *  t_ret := native(t_p1, ...);
+     *  return t_ret;
+     *
+     * <fail_idx>:
+     *  _ := exception_throw("builtin procedure", "<name>: expected <arity> parameters");
+     *  unreachable;
+     *
+     * natives without a fixed arity are passed the parameter list instead:
+     *
+     * <call_idx>:
+     *  t_ret := native(params);
+     *  return t_ret;
+     */
+    let proc = Rc::new(RefCell::new(IRProcedure::from_tag(&native.name)));
+    let mut proc_ref = proc.borrow_mut();
+
+    let call_idx = proc_ref.blocks.add_node(Vec::new());
+    proc_ref.start_block = call_idx;
+    proc_ref.end_block = call_idx;
+
+    let t_params = if let Some(n_params) = native.arity {
+        let init_idx = proc_ref.blocks.add_node(Vec::new());
+        let fail_idx = proc_ref.blocks.add_node(Vec::new());
+        proc_ref.start_block = init_idx;
+
+        let t_n = tmp_var_new(&mut proc_ref);
+        let t_n_ok = tmp_var_new(&mut proc_ref);
+        block_get(&mut proc_ref, init_idx).extend(vec![
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Variable(t_n),
+                types: IRType::NUMBER,
+                source: IRValue::BuiltinProc(BuiltinProc::Amount),
+                op: IROp::NativeCall(vec![IRValue::BuiltinVar(BuiltinVar::Params)]),
+            }),
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Variable(t_n_ok),
+                types: IRType::BOOL,
+                source: IRValue::Variable(t_n),
+                op: IROp::Equal(IRValue::Number(n_params.into())),
+            }),
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Ignore,
+                types: IRType::UNDEFINED,
+                source: IRValue::BuiltinProc(BuiltinProc::Invalidate),
+                op: IROp::NativeCall(vec![IRValue::Variable(t_n)]),
+            }),
+            IRStmt::Branch(IRBranch {
+                cond: IRValue::Variable(t_n_ok),
+                success: call_idx,
+                failure: fail_idx,
+                source: false,
+            }),
+        ]);
+        proc_ref.blocks.add_edge(init_idx, call_idx, ());
+        proc_ref.blocks.add_edge(init_idx, fail_idx, ());
+
+        block_get(&mut proc_ref, fail_idx).extend(vec![
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Ignore,
+                types: IRType::UNDEFINED,
+                source: IRValue::BuiltinProc(BuiltinProc::ExceptionThrow),
+                op: IROp::NativeCall(vec![
+                    IRValue::String("builtin procedure".to_string()),
+                    IRValue::String(format!("{}: expected {n_params} parameters", native.name)),
+                ]),
+            }),
+            IRStmt::Unreachable,
+        ]);
+
+        let mut t_params = Vec::new();
+        for i in 0..n_params {
+            let t_p_addr = tmp_var_new(&mut proc_ref);
+            let t_p = tmp_var_new(&mut proc_ref);
+
+            block_get(&mut proc_ref, call_idx).extend(vec![
+                IRStmt::Assign(IRAssign {
+                    target: IRTarget::Variable(t_p_addr),
+                    types: IRType::PTR,
+                    source: IRValue::BuiltinVar(BuiltinVar::Params),
+                    op: IROp::AccessArray(IRValue::Number(i.into())),
+                }),
+                IRStmt::Assign(IRAssign {
+                    target: IRTarget::Variable(t_p),
+                    types: IRTypes!("any"),
+                    source: IRValue::Variable(t_p_addr),
+                    op: IROp::PtrDeref,
+                }),
+            ]);
+            t_params.push(IRValue::Variable(t_p));
+        }
+        t_params
+    } else {
+        vec![IRValue::BuiltinVar(BuiltinVar::Params)]
+    };
+
+    let t_ret = tmp_var_new(&mut proc_ref);
+    block_get(&mut proc_ref, call_idx).extend(vec![
+        IRStmt::Assign(IRAssign {
+            target: IRTarget::Variable(t_ret),
+            types: native.ret,
+            source: IRValue::NativeProc(native.clone()),
+            op: IROp::NativeCall(t_params),
+        }),
+        IRStmt::Return(IRValue::Variable(t_ret)),
+    ]);
+
+    drop(proc_ref);
+    proc
+}
+
+pub fn stubs_init(natives: &[Rc<NativeProc>]) -> Vec<InterpStackEntry> {
+    let mut stubs = vec![
         InterpStackEntry::Variable(InterpStackVar {
-            var: "abort".to_string(),
+            var: IRSymbol::new("abort"),
             val: Box::new(InterpVal::Procedure(abort_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "abs".to_string(),
+            var: IRSymbol::new("abs"),
             val: Box::new(InterpVal::Procedure(abs_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "arb".to_string(),
+            var: IRSymbol::new("arb"),
             val: Box::new(InterpVal::Procedure(first_stub_new("arb"))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "args".to_string(),
+            var: IRSymbol::new("args"),
             val: Box::new(InterpVal::Procedure(args_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "ask".to_string(),
+            var: IRSymbol::new("ask"),
             val: Box::new(InterpVal::Procedure(ask_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "assert".to_string(),
+            var: IRSymbol::new("assert"),
             val: Box::new(InterpVal::Procedure(assert_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "ceil".to_string(),
+            var: IRSymbol::new("ceil"),
             val: Box::new(InterpVal::Procedure(float_op_stub_new(FloatOp::Ceil))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "char".to_string(),
+            var: IRSymbol::new("char"),
             val: Box::new(InterpVal::Procedure(char_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "clearCache".to_string(),
+            var: IRSymbol::new("clearCache"),
             val: Box::new(InterpVal::Procedure(clear_cache_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "compare".to_string(),
+            var: IRSymbol::new("close"),
+            val: Box::new(InterpVal::Procedure(params_stub_new(
+                "close",
+                BuiltinProc::FileClose,
+                IRType::UNDEFINED,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("compare"),
             val: Box::new(InterpVal::Procedure(compare_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "cos".to_string(),
+            var: IRSymbol::new("cos"),
             val: Box::new(InterpVal::Procedure(num_op_stub_new(NumOp::Cos))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "cosh".to_string(),
+            var: IRSymbol::new("cosh"),
             val: Box::new(InterpVal::Procedure(num_op_stub_new(NumOp::CosH))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "deleteFile".to_string(),
+            var: IRSymbol::new("deleteFile"),
             val: Box::new(InterpVal::Procedure(delete_file_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "domain".to_string(),
+            var: IRSymbol::new("domain"),
             val: Box::new(InterpVal::Procedure(domain_range_stub_new(false))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "double".to_string(),
+            var: IRSymbol::new("double"),
             val: Box::new(InterpVal::Procedure(double_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "eval".to_string(),
+            var: IRSymbol::new("eval"),
             val: Box::new(InterpVal::Procedure(eval_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "evalTerm".to_string(),
+            var: IRSymbol::new("evalTerm"),
             val: Box::new(InterpVal::Procedure(eval_term_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "execute".to_string(),
+            var: IRSymbol::new("execute"),
             val: Box::new(InterpVal::Procedure(execute_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "exp".to_string(),
+            var: IRSymbol::new("exp"),
             val: Box::new(InterpVal::Procedure(ln_exp_stub_new(true))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "fct".to_string(),
+            var: IRSymbol::new("fct"),
             val: Box::new(InterpVal::Procedure(fct_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "first".to_string(),
+            var: IRSymbol::new("first"),
             val: Box::new(InterpVal::Procedure(first_stub_new("first"))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "floor".to_string(),
+            var: IRSymbol::new("floor"),
             val: Box::new(InterpVal::Procedure(float_op_stub_new(FloatOp::Floor))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "from".to_string(),
+            var: IRSymbol::new("flush"),
+            val: Box::new(InterpVal::Procedure(params_stub_new(
+                "flush",
+                BuiltinProc::FileFlush,
+                IRType::UNDEFINED,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("from"),
             val: Box::new(InterpVal::Procedure(from_stub_new("from"))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "fromB".to_string(),
+            var: IRSymbol::new("fromB"),
             val: Box::new(InterpVal::Procedure(fromb_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "fromE".to_string(),
+            var: IRSymbol::new("fromE"),
             val: Box::new(InterpVal::Procedure(from_stub_new("fromE"))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "get".to_string(),
+            var: IRSymbol::new("fromJson"),
+            val: Box::new(InterpVal::Procedure(params_stub_new(
+                "fromJson",
+                BuiltinProc::FromJson,
+                IRTypes!("any"),
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("get"),
             val: Box::new(InterpVal::Procedure(get_stub_new("get"))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "getOsID".to_string(),
+            var: IRSymbol::new("getOsID"),
             val: Box::new(InterpVal::Procedure(get_os_id_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "int".to_string(),
+            var: IRSymbol::new("int"),
             val: Box::new(InterpVal::Procedure(int_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isBoolean".to_string(),
+            var: IRSymbol::new("isBoolean"),
             val: Box::new(InterpVal::Procedure(is_type_stub_new(
                 "isString",
                 IRType::BOOL,
             ))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isDouble".to_string(),
+            var: IRSymbol::new("isDouble"),
             val: Box::new(InterpVal::Procedure(is_type_stub_new(
                 "isDouble",
                 IRType::DOUBLE,
             ))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isInfinite".to_string(),
+            var: IRSymbol::new("isInfinite"),
             val: Box::new(InterpVal::Procedure(is_infinite_stub_new())),
         }),
         // TODO isMap isError
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isInteger".to_string(),
+            var: IRSymbol::new("isInteger"),
             val: Box::new(InterpVal::Procedure(is_type_stub_new(
                 "isInteger",
                 IRType::NUMBER,
             ))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isNumber".to_string(),
+            var: IRSymbol::new("isNumber"),
             val: Box::new(InterpVal::Procedure(is_number_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isList".to_string(),
+            var: IRSymbol::new("isList"),
             val: Box::new(InterpVal::Procedure(is_type_stub_new(
                 "isList",
                 IRType::LIST,
             ))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isObject".to_string(),
+            var: IRSymbol::new("isObject"),
             val: Box::new(InterpVal::Procedure(is_type_stub_new(
                 "isString",
                 IRType::OBJECT,
             ))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isPrime".to_string(),
+            var: IRSymbol::new("isPrime"),
             val: Box::new(InterpVal::Procedure(is_prime_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isProbablePrime".to_string(),
+            var: IRSymbol::new("isProbablePrime"),
             val: Box::new(InterpVal::Procedure(is_probable_prime_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isSet".to_string(),
+            var: IRSymbol::new("isRational"),
+            val: Box::new(InterpVal::Procedure(is_rational_stub_new())),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("isSet"),
             val: Box::new(InterpVal::Procedure(is_type_stub_new("isSet", IRType::SET))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isString".to_string(),
+            var: IRSymbol::new("isString"),
             val: Box::new(InterpVal::Procedure(is_type_stub_new(
                 "isString",
                 IRType::STRING,
             ))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isTerm".to_string(),
+            var: IRSymbol::new("isTerm"),
             val: Box::new(InterpVal::Procedure(is_term_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "isVariable".to_string(),
+            var: IRSymbol::new("isVariable"),
             val: Box::new(InterpVal::Procedure(is_variable_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "join".to_string(),
+            var: IRSymbol::new("join"),
             val: Box::new(InterpVal::Procedure(join_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "last".to_string(),
+            var: IRSymbol::new("la_cond"),
+            val: Box::new(InterpVal::Procedure(la_stub_new(
+                "la_cond",
+                BuiltinProc::LaCond,
+                1,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("la_det"),
+            val: Box::new(InterpVal::Procedure(la_stub_new(
+                "la_det",
+                BuiltinProc::LaDet,
+                1,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("la_eigenValues"),
+            val: Box::new(InterpVal::Procedure(la_stub_new(
+                "la_eigenValues",
+                BuiltinProc::LaEigenValues,
+                1,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("la_eigenVectors"),
+            val: Box::new(InterpVal::Procedure(la_stub_new(
+                "la_eigenVectors",
+                BuiltinProc::LaEigenVectors,
+                1,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("la_matrix"),
+            val: Box::new(InterpVal::Procedure(la_stub_new(
+                "la_matrix",
+                BuiltinProc::LaMatrix,
+                1,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("la_pseudoInverse"),
+            val: Box::new(InterpVal::Procedure(la_stub_new(
+                "la_pseudoInverse",
+                BuiltinProc::LaPseudoInverse,
+                1,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("la_solve"),
+            val: Box::new(InterpVal::Procedure(la_stub_new(
+                "la_solve",
+                BuiltinProc::LaSolve,
+                2,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("la_svd"),
+            val: Box::new(InterpVal::Procedure(la_stub_new(
+                "la_svd",
+                BuiltinProc::LaSvd,
+                1,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("last"),
             val: Box::new(InterpVal::Procedure(last_stub_new("last"))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "load".to_string(),
+            var: IRSymbol::new("load"),
             val: Box::new(InterpVal::Procedure(load_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "loadLibrary".to_string(),
+            var: IRSymbol::new("loadLibrary"),
             val: Box::new(InterpVal::Procedure(load_library_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "log".to_string(),
+            var: IRSymbol::new("log"),
             val: Box::new(InterpVal::Procedure(ln_exp_stub_new(false))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "log10".to_string(),
+            var: IRSymbol::new("log10"),
             val: Box::new(InterpVal::Procedure(log10_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "log1p".to_string(),
+            var: IRSymbol::new("log1p"),
             val: Box::new(InterpVal::Procedure(log1p_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "logo".to_string(),
+            var: IRSymbol::new("logo"),
             val: Box::new(InterpVal::Procedure(logo_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "makeTerm".to_string(),
+            var: IRSymbol::new("makeTerm"),
             val: Box::new(InterpVal::Procedure(make_term_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "matches".to_string(),
+            var: IRSymbol::new("matches"),
             val: Box::new(InterpVal::Procedure(matches_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "mathConst".to_string(),
+            var: IRSymbol::new("mathConst"),
             val: Box::new(InterpVal::Procedure(math_const_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "max".to_string(),
+            var: IRSymbol::new("max"),
             val: Box::new(InterpVal::Procedure(max_stub_new("max", true))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "min".to_string(),
+            var: IRSymbol::new("min"),
             val: Box::new(InterpVal::Procedure(max_stub_new("min", false))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "nPrint".to_string(),
+            var: IRSymbol::new("nDecimalPlaces"),
+            val: Box::new(InterpVal::Procedure(n_decimal_places_stub_new())),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("nPrint"),
             val: Box::new(InterpVal::Procedure(n_print_stub_new(false))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "nPrintErr".to_string(),
+            var: IRSymbol::new("nPrintErr"),
             val: Box::new(InterpVal::Procedure(n_print_stub_new(true))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "now".to_string(),
+            var: IRSymbol::new("now"),
             val: Box::new(InterpVal::Procedure(now_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "parse".to_string(),
+            var: IRSymbol::new("openFile"),
+            val: Box::new(InterpVal::Procedure(params_stub_new(
+                "openFile",
+                BuiltinProc::FileOpen,
+                IRType::FILE,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("parse"),
             val: Box::new(InterpVal::Procedure(parse_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "parseStatements".to_string(),
+            var: IRSymbol::new("parseStatements"),
             val: Box::new(InterpVal::Procedure(parse_statements_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "pow".to_string(),
+            var: IRSymbol::new("plot_addBarChart"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_addBarChart",
+                BuiltinProc::PlotAddBarChart,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("plot_addBullets"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_addBullets",
+                BuiltinProc::PlotAddBullets,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("plot_addGraph"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_addGraph",
+                BuiltinProc::PlotAddGraph,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("plot_addLabel"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_addLabel",
+                BuiltinProc::PlotAddLabel,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("plot_addListGraph"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_addListGraph",
+                BuiltinProc::PlotAddListGraph,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("plot_addParamGraph"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_addParamGraph",
+                BuiltinProc::PlotAddParamGraph,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("plot_addPieChart"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_addPieChart",
+                BuiltinProc::PlotAddPieChart,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("plot_createCanvas"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_createCanvas",
+                BuiltinProc::PlotCreateCanvas,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("plot_defineTitle"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_defineTitle",
+                BuiltinProc::PlotDefineTitle,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("plot_exportCanvas"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_exportCanvas",
+                BuiltinProc::PlotExportCanvas,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("plot_labelAxis"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_labelAxis",
+                BuiltinProc::PlotLabelAxis,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("plot_modScale"),
+            val: Box::new(InterpVal::Procedure(plot_stub_new(
+                "plot_modScale",
+                BuiltinProc::PlotModScale,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("pow"),
             val: Box::new(InterpVal::Procedure(pow_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "print".to_string(),
+            var: IRSymbol::new("print"),
             val: Box::new(InterpVal::Procedure(print_stub_new(false))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "printErr".to_string(),
+            var: IRSymbol::new("printErr"),
             val: Box::new(InterpVal::Procedure(print_stub_new(true))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "raise".to_string(),
+            var: IRSymbol::new("raise"),
             val: Box::new(InterpVal::Procedure(throw_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "random".to_string(),
+            var: IRSymbol::new("random"),
             val: Box::new(InterpVal::Procedure(random_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "range".to_string(),
+            var: IRSymbol::new("range"),
             val: Box::new(InterpVal::Procedure(domain_range_stub_new(true))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "read".to_string(),
+            var: IRSymbol::new("rational"),
+            val: Box::new(InterpVal::Procedure(rational_stub_new())),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("read"),
             val: Box::new(InterpVal::Procedure(read_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "readFile".to_string(),
+            var: IRSymbol::new("readChars"),
+            val: Box::new(InterpVal::Procedure(params_stub_new(
+                "readChars",
+                BuiltinProc::FileReadChars,
+                IRType::STRING | IRType::UNDEFINED,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("readCsv"),
+            val: Box::new(InterpVal::Procedure(params_stub_new(
+                "readCsv",
+                BuiltinProc::CsvRead,
+                IRType::LIST,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("readFile"),
             val: Box::new(InterpVal::Procedure(read_file_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "replace".to_string(),
+            var: IRSymbol::new("readLine"),
+            val: Box::new(InterpVal::Procedure(params_stub_new(
+                "readLine",
+                BuiltinProc::FileReadLine,
+                IRType::STRING | IRType::UNDEFINED,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("replace"),
             val: Box::new(InterpVal::Procedure(replace_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "replaceFirst".to_string(),
+            var: IRSymbol::new("replaceFirst"),
             val: Box::new(InterpVal::Procedure(replace_first_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "resetRandom".to_string(),
+            var: IRSymbol::new("resetRandom"),
             val: Box::new(InterpVal::Procedure(reset_random_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "reverse".to_string(),
+            var: IRSymbol::new("reverse"),
             val: Box::new(InterpVal::Procedure(reverse_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "rnd".to_string(),
+            var: IRSymbol::new("rnd"),
             val: Box::new(InterpVal::Procedure(rnd_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "round".to_string(),
+            var: IRSymbol::new("round"),
             val: Box::new(InterpVal::Procedure(float_op_stub_new(FloatOp::Round))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "run".to_string(),
+            var: IRSymbol::new("run"),
             val: Box::new(InterpVal::Procedure(run_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "signum".to_string(),
+            var: IRSymbol::new("runProcess"),
+            val: Box::new(InterpVal::Procedure(run_process_stub_new())),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("signum"),
             val: Box::new(InterpVal::Procedure(signum_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "sin".to_string(),
+            var: IRSymbol::new("sin"),
             val: Box::new(InterpVal::Procedure(num_op_stub_new(NumOp::Sin))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "sinh".to_string(),
+            var: IRSymbol::new("sinh"),
             val: Box::new(InterpVal::Procedure(num_op_stub_new(NumOp::SinH))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "sleep".to_string(),
+            var: IRSymbol::new("sleep"),
             val: Box::new(InterpVal::Procedure(sleep_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "split".to_string(),
+            var: IRSymbol::new("split"),
             val: Box::new(InterpVal::Procedure(split_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "sqrt".to_string(),
+            var: IRSymbol::new("sqrt"),
             val: Box::new(InterpVal::Procedure(sqrt_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "str".to_string(),
+            var: IRSymbol::new("str"),
             val: Box::new(InterpVal::Procedure(str_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "tan".to_string(),
+            var: IRSymbol::new("tan"),
             val: Box::new(InterpVal::Procedure(num_op_stub_new(NumOp::Tan))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "tanh".to_string(),
+            var: IRSymbol::new("tanh"),
             val: Box::new(InterpVal::Procedure(num_op_stub_new(NumOp::TanH))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "throw".to_string(),
+            var: IRSymbol::new("throw"),
             val: Box::new(InterpVal::Procedure(throw_stub_new())),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "ulp".to_string(),
+            var: IRSymbol::new("toJson"),
+            val: Box::new(InterpVal::Procedure(params_stub_new(
+                "toJson",
+                BuiltinProc::ToJson,
+                IRType::STRING,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("ulp"),
             val: Box::new(InterpVal::Procedure(num_op_stub_new(NumOp::Ulp))),
         }),
         InterpStackEntry::Variable(InterpStackVar {
-            var: "writeFile".to_string(),
+            var: IRSymbol::new("writeCsv"),
+            val: Box::new(InterpVal::Procedure(params_stub_new(
+                "writeCsv",
+                BuiltinProc::CsvWrite,
+                IRType::UNDEFINED,
+            ))),
+        }),
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("writeFile"),
             val: Box::new(InterpVal::Procedure(write_file_stub_new())),
         }),
-    ]
+        InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new("writeString"),
+            val: Box::new(InterpVal::Procedure(params_stub_new(
+                "writeString",
+                BuiltinProc::FileWriteString,
+                IRType::UNDEFINED,
+            ))),
+        }),
+    ];
+
+    for dist in StatDist::iter() {
+        let n = dist.n_params();
+        for (suffix, op, n_params) in [
+            ("", BuiltinProc::StatPdf, n + 1),
+            ("CDF", BuiltinProc::StatCdf, n + 1),
+            ("Sample", BuiltinProc::StatSample, n),
+        ] {
+            let tag = format!("stat_{dist}{suffix}");
+            stubs.push(InterpStackEntry::Variable(InterpStackVar {
+                val: Box::new(InterpVal::Procedure(stat_stub_new(
+                    &tag, op, dist, n_params,
+                ))),
+                var: IRSymbol::new(&tag),
+            }));
+        }
+    }
+
+    for native in natives {
+        stubs.push(InterpStackEntry::Variable(InterpStackVar {
+            var: IRSymbol::new(&native.name),
+            val: Box::new(InterpVal::Procedure(native_stub_new(native))),
+        }));
+    }
+
+    stubs
 }
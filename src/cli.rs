@@ -1,12 +1,21 @@
 use clap::{Arg, ArgAction, Command};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::builtin::native::NativeProc;
+use crate::builtin::sandbox::Sandbox;
+use crate::golden::{GoldenOpts, golden_blacklist_parse};
+use crate::interp::limits::ExecLimits;
 
 pub struct InputOpts {
     pub path: PathBuf,
     pub shell: bool,
     pub srcname: String,
     pub lib_path: String,
+    /// arguments following the source file, readable as `params`
+    pub args: Vec<String>,
     pub stem: String,
     pub diff_stdout: Option<PathBuf>,
     pub dump_cst_parse: bool,
@@ -14,8 +23,31 @@ pub struct InputOpts {
     pub dump_cst_pass_check: bool,
     pub dump_cst_pass_noop: bool,
     pub dump_ir_lower: bool,
+    pub dump_ir_opt: bool,
+    pub opt_level: u8,
+    pub seed: Option<u64>,
+    pub limits: ExecLimits,
+    /// restricts file and process access of builtins if set
+    pub sandbox: Option<Sandbox>,
+    /// native procedures bound after the builtins, shadowing builtins of the
+    /// same name
+    pub natives: Vec<Rc<NativeProc>>,
+    pub debug: bool,
     pub debug_ir: bool,
+    /// measure procedures and source lines and report them at exit
+    pub profile: bool,
+    /// file the folded call stacks of the profile are written to
+    pub profile_folded: Option<PathBuf>,
+    /// file the LCOV coverage of statements and branches is written to
+    pub coverage: Option<PathBuf>,
+    /// calls after which a procedure is compiled to native code, `None`
+    /// interprets every procedure
+    pub jit_threshold: Option<u32>,
     pub dry_run: bool,
+    /// write the lowered IR to a `.stlxc` file instead of running it
+    pub compile: bool,
+    /// run the golden-output cases of a directory instead of a program
+    pub test: Option<GoldenOpts>,
     pub warn_implicit_decl: bool,
     pub warn_unresolved_tterm: bool,
     pub warn_unreachable_code: bool,
@@ -46,6 +78,7 @@ impl InputOpts {
             path: env::current_dir().unwrap(),
             srcname: String::from(""),
             lib_path: library_path_get(),
+            args: Vec::new(),
             shell: false,
             stem: String::new(),
             diff_stdout: None,
@@ -54,8 +87,21 @@ impl InputOpts {
             dump_cst_pass_check: false,
             dump_cst_pass_noop: false,
             dump_ir_lower: false,
+            dump_ir_opt: false,
+            opt_level: 1,
+            seed: None,
+            limits: ExecLimits::default(),
+            sandbox: None,
+            natives: Vec::new(),
+            debug: false,
             debug_ir: false,
+            profile: false,
+            profile_folded: None,
+            coverage: None,
+            jit_threshold: None,
             dry_run: false,
+            compile: false,
+            test: None,
             warn_implicit_decl: true,
             warn_invalid_backslash: true,
             warn_unreachable_code: true,
@@ -72,17 +118,52 @@ impl InputOpts {
         out.disable_annotations = self.bogus_annotations;
         out.bogus_annotations = self.bogus_annotations;
         out.lib_path = self.lib_path.clone();
+        out.args = self.args.clone();
         out.srcname = String::from("execute");
+        out.debug = self.debug;
         out.debug_ir = self.debug_ir;
+        out.profile = self.profile;
+        out.coverage = self.coverage.clone();
+        out.jit_threshold = self.jit_threshold;
+        out.opt_level = self.opt_level;
+        out.seed = self.seed;
+        out.limits = self.limits;
+        out.sandbox = self.sandbox.clone();
+        out.natives = self.natives.clone();
 
         out
     }
 }
 
+fn dir_parse(input: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(input);
+    if path.is_dir() {
+        Ok(path)
+    } else {
+        Err(String::from("not a directory"))
+    }
+}
+
+fn duration_parse(input: &str) -> Result<Duration, String> {
+    input
+        .parse::<f64>()
+        .map_err(|e| e.to_string())
+        .and_then(|i| Duration::try_from_secs_f64(i).map_err(|e| e.to_string()))
+}
+
 pub fn cli() -> InputOpts {
     let cmd = Command::new(env!("CARGO_PKG_NAME"))
         .version(env!("CARGO_PKG_VERSION"))
         .disable_help_subcommand(true)
+        // `test` following a program is one of its params
+        .args_conflicts_with_subcommands(true)
+        .arg(
+            Arg::new("debug")
+                .long("debug")
+                .help("Step through the program by SetlX statement")
+                .conflicts_with_all(["diff-stdout", "debug-ir"])
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("debug-ir")
                 .long("debug-ir")
@@ -95,6 +176,13 @@ pub fn cli() -> InputOpts {
                 .help("Run all parsing and code-gen steps but don't interpret the IR")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("compile")
+                .long("compile")
+                .help("Write the lowered program to a .stlxc file instead of running it")
+                .requires("file")
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("diff-stdout")
                 .long("diff-stdout")
@@ -146,10 +234,115 @@ pub fn cli() -> InputOpts {
                 .long("dump-ir-lower")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("dump-ir-opt")
+                .long("dump-ir-opt")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("opt-level")
+                .short('O')
+                .value_name("level")
+                .help("IR optimization level")
+                .value_parser(["0", "1"])
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("n")
+                .help("Seed of the random number generator")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("max-steps")
+                .long("max-steps")
+                .value_name("n")
+                .help("Abort after executing n IR statements")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("max-heap")
+                .long("max-heap")
+                .value_name("n")
+                .help("Abort when more than n heap objects are alive")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .value_name("seconds")
+                .help("Abort after running for the given number of seconds")
+                .value_parser(duration_parse),
+        )
+        .arg(
+            Arg::new("max-depth")
+                .long("max-depth")
+                .value_name("n")
+                .help("Abort when more than n procedure calls are nested")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .help("Report the steps and time spent in procedures and source lines at exit")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("profile-folded")
+                .long("profile-folded")
+                .value_name("file")
+                .help("Write the profile as folded call stacks for flamegraph tools")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("coverage")
+                .long("coverage")
+                .value_name("file")
+                .help("Write the covered statements and branches as LCOV and summarize them")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("jit")
+                .long("jit")
+                .value_name("calls")
+                .help("Compile procedures to native code once they were called this often")
+                .value_parser(|i: &str| {
+                    if cfg!(feature = "jit") {
+                        i.parse::<u32>().map_err(|e| e.to_string())
+                    } else {
+                        Err(String::from("requires building with the jit feature"))
+                    }
+                }),
+        )
+        .arg(
+            Arg::new("sandbox")
+                .long("sandbox")
+                .help("Deny commands and confine file access to the program's directory")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("sandbox-read")
+                .long("sandbox-read")
+                .value_name("dir")
+                .help("Additional directory the sandboxed program may read from")
+                .value_parser(dir_parse)
+                .requires("sandbox")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("sandbox-write")
+                .long("sandbox-write")
+                .value_name("dir")
+                .help("Directory the writes of the sandboxed program are redirected to")
+                .value_parser(dir_parse)
+                .requires("sandbox"),
+        )
         .arg(
             Arg::new("library-path")
                 .long("library-path")
                 .value_name("path")
+                .help("Directories searched by loadLibrary, separated like PATH")
                 .required(false)
                 .num_args(1),
         )
@@ -188,10 +381,67 @@ pub fn cli() -> InputOpts {
                 .long("disable-annotations")
                 .action(ArgAction::SetTrue),
         )
-        .arg(Arg::new("file").index(1).required(false).help("input file"));
+        .arg(Arg::new("file").index(1).required(false).help("input file"))
+        .arg(
+            Arg::new("args")
+                .index(2)
+                .num_args(0..)
+                .trailing_var_arg(true)
+                .allow_hyphen_values(true)
+                .help("arguments passed to the program as params"),
+        )
+        .subcommand(
+            Command::new("test")
+                .about("Compare the output of each .stlx file to its .stlx.reference file")
+                .arg(
+                    Arg::new("dir")
+                        .required(true)
+                        .value_parser(dir_parse)
+                        .help("directory searched for test cases"),
+                )
+                .arg(
+                    Arg::new("timeout")
+                        .long("timeout")
+                        .value_name("seconds")
+                        .help("Kill test cases running for longer than this")
+                        .value_parser(duration_parse)
+                        .default_value("30"),
+                )
+                .arg(
+                    Arg::new("blacklist")
+                        .long("blacklist")
+                        .value_name("file")
+                        .help("JSON list, or suite file with a blacklist, of cases to skip")
+                        .value_parser(golden_blacklist_parse),
+                )
+                .arg(
+                    Arg::new("bless")
+                        .long("bless")
+                        .help("Overwrite the references of cases whose output differs, new cases need an empty reference")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("junit")
+                        .long("junit")
+                        .value_name("file")
+                        .help("Write the results as JUnit XML")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                ),
+        );
 
     let matches = cmd.get_matches();
 
+    let test = matches.subcommand_matches("test").map(|i| GoldenOpts {
+        dir: i.get_one::<PathBuf>("dir").unwrap().clone(),
+        timeout: *i.get_one::<Duration>("timeout").unwrap(),
+        blacklist: i
+            .get_one::<Vec<String>>("blacklist")
+            .cloned()
+            .unwrap_or_default(),
+        bless: i.get_flag("bless"),
+        junit: i.get_one::<PathBuf>("junit").cloned(),
+    });
+
     let path = matches
         .get_one::<String>("file")
         .map(|path| PathBuf::from(path.to_string().clone()));
@@ -202,6 +452,10 @@ pub fn cli() -> InputOpts {
         "shell".to_string()
     };
 
+    let args = matches
+        .get_many::<String>("args")
+        .map(|i| i.cloned().collect())
+        .unwrap_or_default();
     let diff_stdout = matches.get_one::<String>("diff-stdout").map(PathBuf::from);
     let lib_path = matches
         .get_one::<String>("library-path")
@@ -223,20 +477,66 @@ pub fn cli() -> InputOpts {
         env::current_dir().unwrap()
     });
 
+    let sandbox = matches.get_flag("sandbox").then(|| {
+        let src_dir = if shell {
+            path.clone()
+        } else {
+            path.parent()
+                .filter(|i| !i.as_os_str().is_empty())
+                .unwrap_or(Path::new("."))
+                .to_path_buf()
+        };
+        let read_dirs: Vec<PathBuf> = std::iter::once(src_dir)
+            .chain(env::split_paths(&lib_path))
+            .chain(
+                matches
+                    .get_many::<PathBuf>("sandbox-read")
+                    .into_iter()
+                    .flatten()
+                    .cloned(),
+            )
+            .collect();
+        let scratch_dir = matches.get_one::<PathBuf>("sandbox-write");
+        Sandbox::new(&read_dirs, scratch_dir.map(PathBuf::as_path))
+    });
+
     InputOpts {
         path,
         shell,
         srcname,
         stem,
         lib_path,
+        args,
         dry_run,
+        compile: matches.get_flag("compile"),
+        test,
         dump_cst_parse: matches.get_flag("dump-cst-parse") || dump_cst_all,
         dump_cst_pass_string: matches.get_flag("dump-cst-pass-string") || dump_cst_all,
         dump_cst_pass_check: matches.get_flag("dump-cst-pass-check") || dump_cst_all,
         dump_cst_pass_noop: matches.get_flag("dump-cst-pass-noop") || dump_cst_all,
         dump_ir_lower: matches.get_flag("dump-ir-lower") || dump_ir_all,
+        dump_ir_opt: matches.get_flag("dump-ir-opt") || dump_ir_all,
+        opt_level: matches
+            .get_one::<String>("opt-level")
+            .unwrap()
+            .parse()
+            .unwrap(),
+        seed: matches.get_one::<u64>("seed").copied(),
+        sandbox,
+        natives: Vec::new(),
+        limits: ExecLimits {
+            steps: matches.get_one::<u64>("max-steps").copied(),
+            heap: matches.get_one::<usize>("max-heap").copied(),
+            timeout: matches.get_one::<Duration>("timeout").copied(),
+            depth: matches.get_one::<usize>("max-depth").copied(),
+        },
         diff_stdout,
+        debug: matches.get_flag("debug"),
         debug_ir: matches.get_flag("debug-ir"),
+        profile: matches.get_flag("profile") || matches.contains_id("profile-folded"),
+        profile_folded: matches.get_one::<PathBuf>("profile-folded").cloned(),
+        coverage: matches.get_one::<PathBuf>("coverage").cloned(),
+        jit_threshold: matches.get_one::<u32>("jit").copied(),
         warn_implicit_decl: !matches.get_flag("no-warn") || matches.get_flag("warn-implicit-decl"),
         warn_invalid_backslash: !matches.get_flag("no-warn")
             || matches.get_flag("warn-invalid-backslash"),
@@ -7,7 +7,7 @@ mod dot;
 mod dump;
 use dump::cst_dump;
 mod passes;
-use passes::{cst_expr_passes, cst_passes};
+use passes::{cst_expr_offset, cst_expr_passes, cst_offset, cst_passes};
 
 pub fn cst_parse(input: &str, opts: &InputOpts) -> CSTBlock {
     let mut cst = match setlx_parse::BlockParser::new().parse(input) {
@@ -45,3 +45,16 @@ pub fn cst_expr_parse(input: &str, opts: &InputOpts) -> CSTExpression {
 
     cst
 }
+
+/// Parses a source registered at `base` with `DebugData::src_add`.
+pub fn cst_parse_at(input: &str, opts: &InputOpts, base: usize) -> CSTBlock {
+    let mut cst = cst_parse(input, opts);
+    cst_offset(&mut cst, base);
+    cst
+}
+
+pub fn cst_expr_parse_at(input: &str, opts: &InputOpts, base: usize) -> CSTExpression {
+    let mut cst = cst_expr_parse(input, opts);
+    cst_expr_offset(&mut cst, base);
+    cst
+}
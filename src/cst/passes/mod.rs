@@ -45,3 +45,11 @@ pub fn cst_expr_passes(mut cst: CSTExpression, opts: &InputOpts, src: &str) -> C
 
     cst
 }
+
+pub fn cst_offset(cst: &mut CSTBlock, offset: usize) {
+    pass_offset::pass_block(cst, offset);
+}
+
+pub fn cst_expr_offset(cst: &mut CSTExpression, offset: usize) {
+    pass_offset::pass_expr(cst, offset);
+}
@@ -200,6 +200,7 @@ pub fn pass_expr(e: &mut CSTExpression, offset: usize) {
         CSTExpressionKind::Matrix(m) => pass_matrix(m, offset),
         CSTExpressionKind::Vector(v) => pass_vector(v, offset),
         CSTExpressionKind::Quantifier(q) => pass_quant(q, offset),
+        CSTExpressionKind::Serialize(e) => pass_expr(&mut *e, offset),
         _ => (),
     }
 }
@@ -227,7 +228,7 @@ fn pass_stmt(s: &mut CSTStatement, offset: usize) {
     }
 }
 
-fn pass_block(cst: &mut CSTBlock, offset: usize) {
+pub fn pass_block(cst: &mut CSTBlock, offset: usize) {
     let mut idx = 0;
 
     for i in cst.iter_mut() {
@@ -2,6 +2,55 @@ use ariadne::{Color, Config, Label, Report, ReportKind, Source};
 use lalrpop_util::ParseError;
 use std::io::{self, IsTerminal};
 
+/// Diagnostic captured instead of rendered while a collector is installed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub is_error: bool,
+    pub cat_msg: String,
+    pub msg: String,
+    pub lhs: usize,
+    pub rhs: usize,
+}
+
+static mut COLLECTOR: Option<Vec<Diagnostic>> = None;
+
+/// Starts capturing diagnostics; `report` and `report_parse_error` no longer
+/// render anything until `collector_take` is called.
+#[allow(dead_code)]
+#[allow(static_mut_refs)]
+pub fn collector_install() {
+    //SAFETY: setlx-rs is single-threaded
+    unsafe {
+        COLLECTOR = Some(Vec::new());
+    }
+}
+
+/// Stops capturing diagnostics and returns everything reported since
+/// `collector_install`.
+#[allow(dead_code)]
+#[allow(static_mut_refs)]
+pub fn collector_take() -> Vec<Diagnostic> {
+    //SAFETY: setlx-rs is single-threaded
+    unsafe { COLLECTOR.take().unwrap_or_default() }
+}
+
+#[allow(static_mut_refs)]
+fn collector_push(is_error: bool, cat_msg: &str, msg: &str, lhs: usize, rhs: usize) -> bool {
+    //SAFETY: setlx-rs is single-threaded
+    if let Some(c) = unsafe { COLLECTOR.as_mut() } {
+        c.push(Diagnostic {
+            is_error,
+            cat_msg: cat_msg.to_string(),
+            msg: msg.to_string(),
+            lhs,
+            rhs,
+        });
+        true
+    } else {
+        false
+    }
+}
+
 pub fn report(
     kind: ReportKind,
     cat_msg: &str,
@@ -12,6 +61,10 @@ pub fn report(
     srcname: &str,
     output: &mut String,
 ) {
+    if collector_push(matches!(kind, ReportKind::Error), cat_msg, msg, lhs, rhs) {
+        return;
+    }
+
     let mut buf = Vec::new();
 
     Report::build(kind, (srcname, lhs..rhs))
@@ -43,80 +96,61 @@ pub fn report_parse_error<Tok, E>(
     Tok: std::fmt::Debug,
     E: std::fmt::Display,
 {
-    let mut buf = Vec::new();
-
-    match err {
-        ParseError::InvalidToken { location } => {
-            Report::build(ReportKind::Error, (srcname, location..location))
-                .with_config(Config::default().with_color(io::stdout().is_terminal()))
-                .with_message("Invalid token")
-                .with_label(
-                    Label::new((srcname, location..location + 1))
-                        .with_message("Unexpected token here")
-                        .with_color(Color::Red),
-                )
-                .finish()
-                .write((srcname, Source::from(src)), &mut buf)
-                .unwrap();
-        }
-
-        ParseError::UnrecognizedEof { location, expected } => {
-            Report::build(ReportKind::Error, (srcname, location..location))
-                .with_config(Config::default().with_color(io::stdout().is_terminal()))
-                .with_message("Unexpected end of input")
-                .with_label(
-                    Label::new((srcname, location..location))
-                        .with_message(format!("Expected {}", expected.join(", ")))
-                        .with_color(Color::Red),
-                )
-                .finish()
-                .write((srcname, Source::from(src)), &mut buf)
-                .unwrap();
-        }
-
+    let (cat_msg, msg, lhs, rhs) = match err {
+        ParseError::InvalidToken { location } => (
+            String::from("Invalid token"),
+            Some(String::from("Unexpected token here")),
+            location,
+            location + 1,
+        ),
+        ParseError::UnrecognizedEof { location, expected } => (
+            String::from("Unexpected end of input"),
+            Some(format!("Expected {}", expected.join(", "))),
+            location,
+            location,
+        ),
         ParseError::UnrecognizedToken {
             token: (start, tok, end),
             expected,
-        } => {
-            Report::build(ReportKind::Error, (srcname, start..end))
-                .with_config(Config::default().with_color(io::stdout().is_terminal()))
-                .with_message("Unexpected token")
-                .with_label(
-                    Label::new((srcname, start..end))
-                        .with_message(format!("Found {:?}, expected {}", tok, expected.join(", ")))
-                        .with_color(Color::Red),
-                )
-                .finish()
-                .write((srcname, Source::from(src)), &mut buf)
-                .unwrap();
-        }
-
+        } => (
+            String::from("Unexpected token"),
+            Some(format!("Found {:?}, expected {}", tok, expected.join(", "))),
+            start,
+            end,
+        ),
         ParseError::ExtraToken {
             token: (start, tok, end),
-        } => {
-            Report::build(ReportKind::Error, (srcname, start..end))
-                .with_config(Config::default().with_color(io::stdout().is_terminal()))
-                .with_message("Extra token")
-                .with_label(
-                    Label::new((srcname, start..end))
-                        .with_message(format!("Unexpected {:?}", tok))
-                        .with_color(Color::Red),
-                )
-                .finish()
-                .write((srcname, Source::from(src)), &mut buf)
-                .unwrap();
-        }
+        } => (
+            String::from("Extra token"),
+            Some(format!("Unexpected {:?}", tok)),
+            start,
+            end,
+        ),
+        ParseError::User { error } => (format!("Parser error: {}", error), None, 0, 0),
+    };
 
-        ParseError::User { error } => {
-            Report::build(ReportKind::Error, (srcname, 0..0))
-                .with_config(Config::default().with_color(io::stdout().is_terminal()))
-                .with_message(format!("Parser error: {}", error))
-                .finish()
-                .write((srcname, Source::from(src)), &mut buf)
-                .unwrap();
-        }
+    if collector_push(true, &cat_msg, msg.as_deref().unwrap_or(""), lhs, rhs) {
+        return;
+    }
+
+    let mut buf = Vec::new();
+    let mut report = Report::build(ReportKind::Error, (srcname, lhs..rhs))
+        .with_config(Config::default().with_color(io::stdout().is_terminal()))
+        .with_message(cat_msg);
+
+    if let Some(msg) = msg {
+        report = report.with_label(
+            Label::new((srcname, lhs..rhs))
+                .with_message(msg)
+                .with_color(Color::Red),
+        );
     }
 
+    report
+        .finish()
+        .write((srcname, Source::from(src)), &mut buf)
+        .unwrap();
+
     output.push_str(&String::from_utf8(buf).unwrap());
 }
 
@@ -0,0 +1,464 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use rustyline::{Config, DefaultEditor};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+use crate::builtin::rng::rng_new;
+use crate::cli::InputOpts;
+use crate::cst::{cst_expr_parse_at, cst_parse_at};
+use crate::interp::debug::DebugData;
+use crate::interp::except::{
+    ExceptionKind, exception_kind_get, exception_kind_set, exception_unwind_str, exception_val_get,
+};
+use crate::interp::exec::exec_proc;
+use crate::interp::global::GlobalGuard;
+use crate::interp::heap::{
+    InterpClassStore, InterpList, InterpObj, InterpObjRef, InterpSet, InterpSlice,
+    InterpTaggedList, InterpVal,
+};
+use crate::interp::io::{InterpIO, io_set};
+use crate::interp::limits::limits_reset;
+use crate::interp::memoize::InterpMemoize;
+use crate::interp::stack::InterpStack;
+use crate::ir::def::IRCfg;
+use crate::ir::lower::CSTIRLower;
+use crate::ir::symbol::IRSymbol;
+
+/// SetlX value detached from the interpreter heap.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Undefined,
+    Bool(bool),
+    Number(BigInt),
+    /// Never integral, those are passed as `Number`
+    Rational(BigRational),
+    Double(f64),
+    String(String),
+    List(Vec<Value>),
+    Set(Vec<Value>),
+    Term(String, Vec<Value>),
+}
+
+impl Value {
+    /// Returns `None` for values without a Rust counterpart, e.g. procedures.
+    pub fn from_interp(val: &InterpVal) -> Option<Self> {
+        match val {
+            InterpVal::Undefined => Some(Value::Undefined),
+            InterpVal::Bool(b) => Some(Value::Bool(*b)),
+            InterpVal::Double(d) => Some(Value::Double(*d)),
+            InterpVal::Char(c) => Some(Value::String(c.to_string())),
+            // SAFETY: IR-PTR
+            InterpVal::Ptr(p) => Value::from_interp(unsafe { &*p.ptr }),
+            InterpVal::Slice(InterpSlice::StringSlice(s)) => {
+                Some(Value::String(s.slice.clone().collect()))
+            }
+            InterpVal::Slice(InterpSlice::ListSlice(l)) => l
+                .iter()
+                .map(Value::from_interp)
+                .collect::<Option<_>>()
+                .map(Value::List),
+            InterpVal::Ref(r) => match unsafe { &*r.0 } {
+                InterpObj::Number(n) => Some(Value::Number(n.clone())),
+                InterpObj::Rational(r) => Some(Value::Rational(r.clone())),
+                InterpObj::String(s) => Some(Value::String(s.clone())),
+                InterpObj::List(l) => {
+                    l.0.iter()
+                        .map(Value::from_interp)
+                        .collect::<Option<_>>()
+                        .map(Value::List)
+                }
+                InterpObj::Set(s) => {
+                    s.0.iter()
+                        .map(Value::from_interp)
+                        .collect::<Option<_>>()
+                        .map(Value::Set)
+                }
+                InterpObj::Term(t) => t
+                    .list
+                    .iter()
+                    .map(Value::from_interp)
+                    .collect::<Option<_>>()
+                    .map(|list| Value::Term(t.tag.clone(), list)),
+                InterpObj::Uninitialized => Some(Value::Undefined),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Allocates the value on the interpreter heap, the caller owns the result.
+    pub fn to_interp(&self) -> InterpVal {
+        let obj = match self {
+            Value::Undefined => return InterpVal::Undefined,
+            Value::Bool(b) => return InterpVal::Bool(*b),
+            Value::Double(d) => return InterpVal::Double(*d),
+            Value::Number(n) => InterpObj::Number(n.clone()),
+            Value::Rational(r) => InterpObj::from_rational(r.clone()),
+            Value::String(s) => InterpObj::String(s.clone()),
+            Value::List(l) => InterpObj::List(InterpList(l.iter().map(Value::to_interp).collect())),
+            Value::Set(s) => InterpObj::Set(InterpSet(
+                s.iter().map(Value::to_interp).collect::<BTreeSet<_>>(),
+            )),
+            Value::Term(tag, list) => InterpObj::Term(InterpTaggedList {
+                tag: tag.clone(),
+                list: list.iter().map(Value::to_interp).collect(),
+            }),
+        };
+
+        InterpVal::Ref(InterpObjRef::from_obj(obj))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Number(BigInt::from(n))
+    }
+}
+
+impl From<BigInt> for Value {
+    fn from(n: BigInt) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl From<BigRational> for Value {
+    fn from(r: BigRational) -> Self {
+        if r.is_integer() {
+            Value::Number(r.to_integer())
+        } else {
+            Value::Rational(r)
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(d: f64) -> Self {
+        Value::Double(d)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(l: Vec<T>) -> Self {
+        Value::List(l.into_iter().map(Into::into).collect())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterpErrorKind {
+    Io,
+    Parse,
+    /// Uncaught exception or runtime error
+    Runtime,
+    /// Uncaught error raised by one of the `limits` of the options
+    Limit,
+}
+
+#[derive(Clone, Debug)]
+pub struct InterpError {
+    pub kind: InterpErrorKind,
+    /// Rendered report, as printed by the command line interpreter
+    pub message: String,
+    pub srcname: String,
+    /// Source range of the statement that failed
+    pub span: Option<Range<usize>>,
+    /// Value passed to `throw`, if the error is a SetlX exception
+    pub value: Option<Box<Value>>,
+}
+
+impl fmt::Display for InterpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for InterpError {}
+
+/// Waits for runs on other threads, a run of the current thread is still busy
+/// with the global state.
+fn global_acquire(srcname: &str) -> Result<GlobalGuard, InterpError> {
+    if GlobalGuard::held() {
+        return Err(InterpError {
+            kind: InterpErrorKind::Runtime,
+            message: String::from("another interpreter is running on this thread"),
+            srcname: srcname.to_string(),
+            span: None,
+            value: None,
+        });
+    }
+
+    Ok(GlobalGuard::acquire())
+}
+
+/// Interpreter whose global variables persist between calls.
+///
+/// Output of `print`/`printErr` and input of `get`/`read` is routed through
+/// the handler passed to [`Interpreter::set_io`] if there is one.
+///
+/// Each interpreter owns a random number generator seeded from `opts.seed` and
+/// the registry of the modules it has loaded.
+///
+/// While running, an interpreter swaps its handler into process-global state,
+/// next to the pending exception and the limits, which are process-global as
+/// well. Interpreters on different threads therefore wait for each other, and
+/// running another interpreter from an I/O handler fails with an
+/// [`InterpErrorKind::Runtime`] error.
+///
+/// Nested calls raise an error once they use
+/// [`LIMITS_STACK`](crate::interp::limits::LIMITS_STACK) bytes of stack, so
+/// programs recursing deeply need a thread with a larger stack, or a
+/// `limits.depth`.
+pub struct Interpreter {
+    opts: InputOpts,
+    stack: InterpStack,
+    memo: InterpMemoize,
+    cstore: InterpClassStore,
+    breakpoints: DebugData,
+    rl: DefaultEditor,
+    io: Option<Box<dyn InterpIO>>,
+    /// `!Send`, the global state is only synchronized by [`GlobalGuard`]
+    _global: PhantomData<*const ()>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new(InputOpts::none())
+    }
+}
+
+impl Drop for Interpreter {
+    fn drop(&mut self) {
+        // freeing the objects of the variables updates the heap count
+        let _global = GlobalGuard::acquire();
+        drop(std::mem::take(&mut self.stack));
+        drop(std::mem::take(&mut self.memo));
+        drop(std::mem::take(&mut self.cstore));
+    }
+}
+
+impl Interpreter {
+    pub fn new(opts: InputOpts) -> Self {
+        let _global = GlobalGuard::acquire();
+        let config = Config::builder().enable_signals(true).build();
+        let mut breakpoints = DebugData::from_src(String::new(), opts.srcname.clone());
+        breakpoints.rng = rng_new(opts.seed);
+
+        Interpreter {
+            breakpoints,
+            stack: InterpStack::new(&opts.natives),
+            memo: BTreeMap::new(),
+            cstore: InterpClassStore::default(),
+            rl: DefaultEditor::with_config(config).unwrap(),
+            io: None,
+            _global: PhantomData,
+            opts,
+        }
+    }
+
+    pub fn set_io(&mut self, io: Box<dyn InterpIO>) {
+        self.io = Some(io);
+    }
+
+    /// Executes a sequence of statements in the global scope.
+    pub fn run(&mut self, src: &str) -> Result<(), InterpError> {
+        let srcname = self.opts.srcname.clone();
+        self.run_named(src, &srcname)
+    }
+
+    pub fn run_file(&mut self, path: &Path) -> Result<(), InterpError> {
+        let srcname = path.to_string_lossy().into_owned();
+        let src = fs::read_to_string(path).map_err(|e| InterpError {
+            kind: InterpErrorKind::Io,
+            message: format!("{srcname}: {e}"),
+            srcname: srcname.clone(),
+            span: None,
+            value: None,
+        })?;
+
+        self.run_named(&src, &srcname)
+    }
+
+    fn run_named(&mut self, src: &str, srcname: &str) -> Result<(), InterpError> {
+        let _global = global_acquire(srcname)?;
+        let mut opts = self.opts.exec_opts();
+        opts.srcname = srcname.to_string();
+
+        let base = self.breakpoints.src_add(src, srcname);
+        let stmt = self.parse(srcname, || cst_parse_at(src, &opts, base))?;
+        let proc = IRCfg::from_stmt(&stmt, &opts);
+
+        self.exec(|interp| {
+            let out = exec_proc(
+                proc,
+                &InterpVal::Undefined,
+                &mut interp.stack,
+                &mut interp.memo,
+                &mut interp.cstore,
+                &mut interp.breakpoints,
+                &opts,
+                &mut interp.rl,
+            );
+
+            if let InterpVal::Ref(r) = out {
+                unsafe {
+                    r.invalidate();
+                }
+            }
+        })
+    }
+
+    /// Evaluates an expression in the global scope.
+    ///
+    /// Results without a Rust counterpart are returned as [`Value::Undefined`].
+    pub fn eval(&mut self, expr: &str) -> Result<Value, InterpError> {
+        let _global = global_acquire("eval")?;
+        let mut opts = self.opts.exec_opts();
+        opts.srcname = String::from("eval");
+
+        let base = self.breakpoints.src_add_eval(expr, "eval");
+        let expr_cst = self.parse("eval", || cst_expr_parse_at(expr, &opts, base))?;
+        let proc = IRCfg::from_expr(&expr_cst, &opts);
+
+        self.exec(|interp| {
+            let out = exec_proc(
+                proc,
+                &InterpVal::Undefined,
+                &mut interp.stack,
+                &mut interp.memo,
+                &mut interp.cstore,
+                &mut interp.breakpoints,
+                &opts,
+                &mut interp.rl,
+            );
+            let val = Value::from_interp(&out).unwrap_or(Value::Undefined);
+
+            if let InterpVal::Ref(r) = out {
+                unsafe {
+                    r.invalidate();
+                }
+            }
+
+            val
+        })
+    }
+
+    /// Reads a global variable, `None` if it is not defined or has no Rust
+    /// counterpart.
+    pub fn get(&mut self, name: &str) -> Option<Value> {
+        let _global = GlobalGuard::acquire();
+        self.stack
+            .get(IRSymbol::new(name))
+            .and_then(|v| Value::from_interp(&v))
+    }
+
+    /// Assigns a global variable, declaring it if necessary.
+    pub fn set(&mut self, name: &str, val: &Value) {
+        let _global = GlobalGuard::acquire();
+        let ptr = match self.stack.get(IRSymbol::new(name)) {
+            Some(InterpVal::Ptr(p)) => p,
+            _ => match self.stack.add(IRSymbol::new(name)) {
+                InterpVal::Ptr(p) => p,
+                _ => unreachable!(),
+            },
+        };
+
+        // SAFETY: IR-PTR
+        let old = std::mem::replace(unsafe { &mut *ptr.ptr }, val.to_interp());
+        if let InterpVal::Ref(r) = old {
+            unsafe {
+                r.invalidate();
+            }
+        }
+    }
+
+    fn parse<T>(&mut self, srcname: &str, f: impl FnOnce() -> T) -> Result<T, InterpError> {
+        let old_hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let result = panic::catch_unwind(AssertUnwindSafe(f));
+        panic::set_hook(old_hook);
+
+        result.map_err(|e| InterpError {
+            kind: InterpErrorKind::Parse,
+            message: if let Some(s) = e.downcast_ref::<&str>() {
+                s.to_string()
+            } else if let Some(s) = e.downcast_ref::<String>() {
+                s.to_string()
+            } else {
+                String::from("parse error with non-string payload")
+            },
+            srcname: srcname.to_string(),
+            span: None,
+            value: None,
+        })
+    }
+
+    fn exec<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> Result<T, InterpError> {
+        let old_io = io_set(self.io.take());
+        let old_hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+
+        exception_kind_set(ExceptionKind::Lng);
+        limits_reset(&self.opts.limits);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(self)));
+        panic::set_hook(old_hook);
+        let exceeded = limits_reset(&self.opts.limits);
+
+        let result = result.map_err(|e| {
+            let value = match exception_kind_get() {
+                ExceptionKind::Usr => Value::from_interp(&exception_val_get()).map(Box::new),
+                _ => None,
+            };
+
+            let message = exception_unwind_str(
+                e,
+                &mut Vec::new(),
+                &mut self.stack,
+                &mut self.memo,
+                &mut self.cstore,
+                &mut self.breakpoints,
+                &self.opts,
+                &mut self.rl,
+            );
+            let (src, span) = self.breakpoints.code_src();
+
+            InterpError {
+                kind: if exceeded && matches!(exception_kind_get(), ExceptionKind::Lng) {
+                    InterpErrorKind::Limit
+                } else {
+                    InterpErrorKind::Runtime
+                },
+                message,
+                srcname: src.name.clone(),
+                span: Some(span),
+                value,
+            }
+        });
+
+        self.io = io_set(old_io);
+        result
+    }
+}
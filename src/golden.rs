@@ -0,0 +1,389 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// lines of context around the changes of a diff
+const GOLDEN_DIFF_CONTEXT: usize = 3;
+
+pub struct GoldenOpts {
+    /// directory searched for `.stlx` files with a `.stlx.reference` file
+    pub dir: PathBuf,
+    /// time after which a case is killed
+    pub timeout: Duration,
+    /// case paths relative to `dir` that aren't run
+    pub blacklist: Vec<String>,
+    /// overwrite the references of cases whose output differs, files without
+    /// a reference aren't cases and are left alone
+    pub bless: bool,
+    /// file the results are written to as JUnit XML
+    pub junit: Option<PathBuf>,
+}
+
+/// Reads the `blacklist` of a suite JSON file, or a JSON list of case paths.
+pub fn golden_blacklist_parse(input: &str) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(input).map_err(|e| e.to_string())?;
+    let json: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    let list = match &json {
+        serde_json::Value::Object(suite) => suite.get("blacklist"),
+        list => Some(list),
+    };
+
+    list.and_then(|i| i.as_array())
+        .and_then(|i| i.iter().map(|i| i.as_str().map(String::from)).collect())
+        .ok_or_else(|| String::from("expected a list of paths or an object with a blacklist"))
+}
+
+enum GoldenOutcome {
+    Pass,
+    Blessed,
+    /// unified diff of the reference and the output
+    Fail(String),
+    Error(String),
+    Blacklisted,
+}
+
+struct GoldenCase {
+    name: String,
+    time: Duration,
+    outcome: GoldenOutcome,
+    stderr: String,
+}
+
+/* Cases are sorted so that the report and the XML don't depend on the order
+ * of the directory entries
+ */
+fn golden_cases_find(dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|i| i.map(|i| i.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            golden_cases_find(&path, out)?;
+        } else if path.extension().is_some_and(|i| i == "stlx")
+            && golden_reference_path(&path).is_file()
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn golden_reference_path(path: &Path) -> PathBuf {
+    let mut out = path.as_os_str().to_owned();
+    out.push(".reference");
+    PathBuf::from(out)
+}
+
+/* Each case runs in its own process with the default options, so a case
+ * that crashes, leaks global state or never ends can't affect the others.
+ * Cases run from their directory, so the files they load or write don't
+ * depend on where the runner was started
+ */
+fn golden_exec(path: &Path, golden: &GoldenOpts) -> Result<String, (String, String)> {
+    let exe = env::current_exe().map_err(|e| (e.to_string(), String::new()))?;
+    let path = std::path::absolute(path).map_err(|e| (e.to_string(), String::new()))?;
+    let mut child = Command::new(exe)
+        .arg(&path)
+        .current_dir(path.parent().unwrap_or(Path::new("/")))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| (e.to_string(), String::new()))?;
+
+    let pipe_read = |mut pipe: Box<dyn Read + Send>| {
+        thread::spawn(move || {
+            let mut out = Vec::new();
+            let _ = pipe.read_to_end(&mut out);
+            String::from_utf8_lossy(&out).into_owned()
+        })
+    };
+    let stdout = pipe_read(Box::new(child.stdout.take().unwrap()));
+    let stderr = pipe_read(Box::new(child.stderr.take().unwrap()));
+
+    let start = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) if start.elapsed() > golden.timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break Err(format!("timed out after {:?}", golden.timeout));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(5)),
+            Err(e) => break Err(e.to_string()),
+        }
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    match status {
+        Ok(status) if status.success() => Ok(stdout),
+        Ok(status) => match status.code() {
+            Some(code) => Err((format!("exited with code {code}"), stderr)),
+            None => Err((String::from("killed by a signal"), stderr)),
+        },
+        Err(e) => Err((e, stderr)),
+    }
+}
+
+fn golden_diff_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        len => format!("{},{len}", start + 1),
+    }
+}
+
+/// Unified diff from `reference` to `output`.
+fn golden_diff(name: &str, reference: &str, output: &str) -> String {
+    let old = reference.split_inclusive('\n').collect::<Vec<_>>();
+    let new = output.split_inclusive('\n').collect::<Vec<_>>();
+    let ops = diff::slice(&old, &new);
+
+    // lines of the reference and of the output preceding each operation
+    let mut pos = vec![(0, 0)];
+    for op in &ops {
+        let (l, r) = pos[pos.len() - 1];
+        pos.push(match op {
+            diff::Result::Left(_) => (l + 1, r),
+            diff::Result::Right(_) => (l, r + 1),
+            diff::Result::Both(..) => (l + 1, r + 1),
+        });
+    }
+    let changes = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, diff::Result::Both(..)))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+
+    let mut out = format!("--- {name}.reference\n+++ {name}\n");
+    let mut idx = 0;
+    while idx < changes.len() {
+        let start = changes[idx].saturating_sub(GOLDEN_DIFF_CONTEXT);
+        let mut end = changes[idx] + 1;
+        // hunks separated by little context are merged
+        while changes
+            .get(idx + 1)
+            .is_some_and(|i| *i <= end + 2 * GOLDEN_DIFF_CONTEXT)
+        {
+            idx += 1;
+            end = changes[idx] + 1;
+        }
+        let end = (end + GOLDEN_DIFF_CONTEXT).min(ops.len());
+        idx += 1;
+
+        let (l_start, r_start) = pos[start];
+        let (l_end, r_end) = pos[end];
+        let _ = writeln!(
+            out,
+            "@@ -{} +{} @@",
+            golden_diff_range(l_start, l_end - l_start),
+            golden_diff_range(r_start, r_end - r_start)
+        );
+        for op in &ops[start..end] {
+            let (prefix, line) = match op {
+                diff::Result::Left(line) => ('-', line),
+                diff::Result::Right(line) => ('+', line),
+                diff::Result::Both(line, _) => (' ', line),
+            };
+            out.push(prefix);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+fn golden_case_run(path: &Path, name: String, golden: &GoldenOpts) -> GoldenCase {
+    let start = Instant::now();
+    let reference_path = golden_reference_path(path);
+    let mut stderr = String::new();
+    let outcome = match golden_exec(path, golden) {
+        Ok(output) => {
+            let reference = fs::read(&reference_path)
+                .map(|i| String::from_utf8_lossy(&i).into_owned())
+                .unwrap_or_default();
+            if reference == output {
+                GoldenOutcome::Pass
+            } else if golden.bless {
+                match fs::write(&reference_path, output) {
+                    Ok(()) => GoldenOutcome::Blessed,
+                    Err(e) => GoldenOutcome::Error(format!(
+                        "failed to write {}: {e}",
+                        reference_path.display()
+                    )),
+                }
+            } else {
+                GoldenOutcome::Fail(golden_diff(&name, &reference, &output))
+            }
+        }
+        Err((e, err)) => {
+            stderr = err;
+            GoldenOutcome::Error(e)
+        }
+    };
+
+    GoldenCase {
+        name,
+        time: start.elapsed(),
+        outcome,
+        stderr,
+    }
+}
+
+fn golden_xml_escape(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // not allowed in XML 1.0, even escaped
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => out.push('\u{FFFD}'),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn golden_junit(suite: &str, cases: &[GoldenCase], time: Duration) -> String {
+    let count = |f: fn(&GoldenOutcome) -> bool| cases.iter().filter(|i| f(&i.outcome)).count();
+    let failures = count(|i| matches!(i, GoldenOutcome::Fail(_)));
+    let errors = count(|i| matches!(i, GoldenOutcome::Error(_)));
+    let skipped = count(|i| matches!(i, GoldenOutcome::Blacklisted));
+    let suite = golden_xml_escape(suite);
+
+    let counts = format!(
+        "tests=\"{}\" failures=\"{failures}\" errors=\"{errors}\" skipped=\"{skipped}\"",
+        cases.len()
+    );
+    let time = time.as_secs_f64();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(out, "<testsuites {counts} time=\"{time:.3}\">");
+    let _ = writeln!(
+        out,
+        "  <testsuite name=\"{suite}\" {counts} time=\"{time:.3}\">"
+    );
+    for case in cases {
+        let _ = write!(
+            out,
+            "    <testcase classname=\"{suite}\" name=\"{}\" time=\"{:.3}\"",
+            golden_xml_escape(&case.name),
+            case.time.as_secs_f64()
+        );
+        let _ = match &case.outcome {
+            GoldenOutcome::Pass | GoldenOutcome::Blessed => {
+                out.push_str("/>\n");
+                continue;
+            }
+            GoldenOutcome::Blacklisted => {
+                writeln!(out, ">\n      <skipped message=\"blacklisted\"/>")
+            }
+            GoldenOutcome::Fail(diff) => writeln!(
+                out,
+                ">\n      <failure message=\"output differs from the reference\">{}</failure>",
+                golden_xml_escape(diff)
+            ),
+            GoldenOutcome::Error(e) => writeln!(
+                out,
+                ">\n      <error message=\"{}\"/>\n      <system-err>{}</system-err>",
+                golden_xml_escape(e),
+                golden_xml_escape(&case.stderr)
+            ),
+        };
+        out.push_str("    </testcase>\n");
+    }
+    out.push_str("  </testsuite>\n</testsuites>\n");
+    out
+}
+
+/// Runs the golden-output cases below `golden.dir` and reports them to
+/// stdout. Returns whether no case failed.
+pub fn golden_run(golden: &GoldenOpts) -> bool {
+    let start = Instant::now();
+    let mut paths = Vec::new();
+    if let Err(e) = golden_cases_find(&golden.dir, &mut paths) {
+        eprintln!("{}: {e}", golden.dir.display());
+        return false;
+    }
+
+    let mut cases = Vec::new();
+    for path in paths {
+        let name = path
+            .strip_prefix(&golden.dir)
+            .unwrap_or(&path)
+            .components()
+            .map(|i| i.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let case = if golden.blacklist.contains(&name) {
+            GoldenCase {
+                name,
+                time: Duration::ZERO,
+                outcome: GoldenOutcome::Blacklisted,
+                stderr: String::new(),
+            }
+        } else {
+            golden_case_run(&path, name, golden)
+        };
+
+        let status = match &case.outcome {
+            GoldenOutcome::Pass => "ok",
+            GoldenOutcome::Blessed => "blessed",
+            GoldenOutcome::Fail(_) => "FAILED",
+            GoldenOutcome::Error(_) => "ERROR",
+            GoldenOutcome::Blacklisted => "blacklisted",
+        };
+        println!("test {} ... {status}", case.name);
+        cases.push(case);
+    }
+
+    let mut failed = false;
+    for case in &cases {
+        match &case.outcome {
+            GoldenOutcome::Fail(diff) => print!("\n{diff}"),
+            GoldenOutcome::Error(e) => print!("\n{}: {e}\n{}", case.name, case.stderr),
+            _ => continue,
+        }
+        failed = true;
+    }
+
+    let count = |f: fn(&GoldenOutcome) -> bool| cases.iter().filter(|i| f(&i.outcome)).count();
+    let time = start.elapsed();
+    println!(
+        "\ntest result: {}. {} passed; {} failed; {} errors; {} blessed; {} blacklisted; \
+         finished in {:.2}s",
+        if failed { "FAILED" } else { "ok" },
+        count(|i| matches!(i, GoldenOutcome::Pass)),
+        count(|i| matches!(i, GoldenOutcome::Fail(_))),
+        count(|i| matches!(i, GoldenOutcome::Error(_))),
+        count(|i| matches!(i, GoldenOutcome::Blessed)),
+        count(|i| matches!(i, GoldenOutcome::Blacklisted)),
+        time.as_secs_f64()
+    );
+
+    if let Some(path) = &golden.junit {
+        let suite = golden.dir.to_string_lossy();
+        if let Err(e) = fs::write(path, golden_junit(&suite, &cases, time)) {
+            eprintln!("failed to write {}: {e}", path.display());
+            return false;
+        }
+    }
+
+    !failed
+}
@@ -1,9 +1,8 @@
-use num_traits::cast::ToPrimitive;
 use rustyline::DefaultEditor;
 use std::cmp::Ordering;
 use std::panic;
 
-use crate::builtin::call::builtin_call;
+use crate::builtin::native::native_call;
 use crate::cli::InputOpts;
 use crate::interp::debug::DebugData;
 use crate::interp::except::*;
@@ -11,7 +10,10 @@ use crate::interp::exec::exec_proc;
 use crate::interp::get::InterpGet;
 use crate::interp::heap::*;
 use crate::interp::memoize::InterpMemoize;
-use crate::interp::ops::{val_cmp, val_int_quot, val_minus, val_mod, val_mult, val_plus, val_quot};
+use crate::interp::ops::{
+    obj_to_f64, obj_to_rational, val_cmp, val_int_quot, val_minus, val_mod, val_mult, val_plus,
+    val_quot,
+};
 use crate::interp::serialize::{SerializeOpts, serialize};
 
 use crate::interp::stack::InterpStack;
@@ -158,10 +160,8 @@ pub fn exec_assign(
             }
         }
         IROp::NativeCall(v) => {
-            let proc = a.source.to_builtin_proc();
-
-            res = builtin_call(
-                proc,
+            res = native_call(
+                a.source.to_native(),
                 v,
                 params,
                 vars,
@@ -220,6 +220,8 @@ pub fn exec_assign(
             // NUMBER < DOUBLE
             // DOUBLE < NUMBER
             // DOUBLE < DOUBLE
+            //
+            // RATIONAL is handled like NUMBER
             res = match (&lhs.val, &rhs.val) {
                 (InterpVal::Ref(rl), InterpVal::Ref(rr)) => {
                     match (unsafe { &*rl.0 }, unsafe { &*rr.0 }) {
@@ -227,19 +229,23 @@ pub fn exec_assign(
                             InterpVal::Bool(sl.0.is_subset(&sr.0))
                         }
                         (InterpObj::Number(nl), InterpObj::Number(nr)) => InterpVal::Bool(nl < nr),
+                        (
+                            l @ (InterpObj::Number(_) | InterpObj::Rational(_)),
+                            r @ (InterpObj::Number(_) | InterpObj::Rational(_)),
+                        ) => InterpVal::Bool(obj_to_rational(l) < obj_to_rational(r)),
                         _ => exception_throw("ir-op", "less undefined for type", breakpoints),
                     }
                 }
                 (InterpVal::Ref(r), InterpVal::Double(dr)) => {
-                    if let InterpObj::Number(nl) = unsafe { &*r.0 } {
-                        InterpVal::Bool(nl.to_f64().unwrap() < *dr)
+                    if let Some(nl) = obj_to_f64(unsafe { &*r.0 }) {
+                        InterpVal::Bool(nl < *dr)
                     } else {
                         exception_throw("ir-op", "less undefined for type", breakpoints);
                     }
                 }
                 (InterpVal::Double(dl), InterpVal::Ref(r)) => {
-                    if let InterpObj::Number(nr) = unsafe { &*r.0 } {
-                        InterpVal::Bool(*dl < nr.to_f64().unwrap())
+                    if let Some(nr) = obj_to_f64(unsafe { &*r.0 }) {
+                        InterpVal::Bool(*dl < nr)
                     } else {
                         exception_throw("ir-op", "less undefined for type", breakpoints);
                     }
@@ -0,0 +1,298 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::interp::debug::DebugData;
+use crate::ir::def::*;
+
+struct CoverageBranch {
+    /// code offset of the range the branch was last evaluated in, or of the
+    /// range preceding it in the procedure if it never ran
+    code: Option<usize>,
+    /// times the success and the failure block were taken
+    taken: [u64; 2],
+}
+
+#[derive(Default)]
+struct Coverage {
+    /// procedures whose statements were registered, kept alive so their
+    /// addresses can't be reused
+    procs: HashSet<*const RefCell<IRProcedure>>,
+    registered: Vec<Rc<RefCell<IRProcedure>>>,
+    /// executions of each statement range
+    stmts: HashMap<(usize, usize), u64>,
+    /// in the order they were registered, which numbers the branches of a line
+    branches: Vec<CoverageBranch>,
+    branch_ids: HashMap<*const IRBranch, usize>,
+}
+
+static mut COVERAGE: Option<Coverage> = None;
+
+#[allow(static_mut_refs)]
+fn coverage_get() -> &'static mut Coverage {
+    //SAFETY: setlx-rs is single-threaded
+    unsafe { COVERAGE.get_or_insert_with(Coverage::default) }
+}
+
+/// Discards the counts of a previous run.
+pub fn coverage_reset() {
+    *coverage_get() = Coverage::default();
+}
+
+fn coverage_procs_push(val: &IRValue, out: &mut Vec<Rc<RefCell<IRProcedure>>>) {
+    match val {
+        IRValue::Procedure(proc) => out.push(proc.clone()),
+        IRValue::Vector(vals) => vals.iter().for_each(|i| coverage_procs_push(i, out)),
+        IRValue::Matrix(rows) => rows
+            .iter()
+            .flatten()
+            .for_each(|i| coverage_procs_push(i, out)),
+        _ => (),
+    }
+}
+
+/* Statements and branches are registered before `proc` first runs, so the
+ * ones never executed are reported as well. This includes the procedures
+ * defined inside of `proc`, whether they are called or not.
+ *
+ * Only statement ranges are counted. Expression ranges are annotated again
+ * each time evaluation returns to the enclosing statement, so their counts
+ * don't say how often a line ran.
+ */
+pub fn coverage_register(proc: &Rc<RefCell<IRProcedure>>) {
+    let coverage = coverage_get();
+    if coverage.procs.contains(&Rc::as_ptr(proc)) {
+        return;
+    }
+
+    let mut pending = vec![proc.clone()];
+    while let Some(proc) = pending.pop() {
+        if !coverage.procs.insert(Rc::as_ptr(&proc)) {
+            continue;
+        }
+
+        {
+            let proc = proc.borrow();
+            // blocks reachable from the start, with the range in effect
+            let mut blocks = vec![(proc.start_block, None)];
+            let mut seen = HashSet::new();
+            while let Some((idx, mut code)) = blocks.pop() {
+                if !seen.insert(idx) {
+                    continue;
+                }
+                let Some(block) = proc.blocks.node_weight(idx) else {
+                    continue;
+                };
+
+                for stmt in block {
+                    match stmt {
+                        IRStmt::Annotate(lhs, rhs, is_stmt) => {
+                            code = Some(*lhs);
+                            if *is_stmt {
+                                coverage.stmts.entry((*lhs, *rhs)).or_default();
+                            }
+                        }
+                        IRStmt::Assign(a) => {
+                            coverage_procs_push(&a.source, &mut pending);
+                            match &a.op {
+                                IROp::NativeCall(vals) => vals
+                                    .iter()
+                                    .for_each(|i| coverage_procs_push(i, &mut pending)),
+                                IROp::AccessArray(val)
+                                | IROp::Or(val)
+                                | IROp::And(val)
+                                | IROp::Less(val)
+                                | IROp::Equal(val)
+                                | IROp::Plus(val)
+                                | IROp::Minus(val)
+                                | IROp::Mult(val)
+                                | IROp::Divide(val)
+                                | IROp::IntDivide(val)
+                                | IROp::Mod(val) => coverage_procs_push(val, &mut pending),
+                                _ => (),
+                            }
+                        }
+                        IRStmt::Branch(b) => {
+                            /* checks added by lowering and the branches of
+                             * builtin stubs aren't conditions of the program,
+                             * constant conditions only have one outcome
+                             */
+                            if b.source && !matches!(b.cond, IRValue::Bool(_)) {
+                                coverage
+                                    .branch_ids
+                                    .insert(b as *const IRBranch, coverage.branches.len());
+                                coverage.branches.push(CoverageBranch {
+                                    code,
+                                    taken: [0, 0],
+                                });
+                            }
+                            blocks.push((b.failure, code));
+                            blocks.push((b.success, code));
+                        }
+                        IRStmt::Try(t) => {
+                            blocks.push((t.catch, code));
+                            blocks.push((t.attempt, code));
+                        }
+                        IRStmt::TryEnd(next) | IRStmt::Goto(next) => blocks.push((*next, code)),
+                        IRStmt::Return(val) => coverage_procs_push(val, &mut pending),
+                        IRStmt::Unreachable => (),
+                    }
+                }
+            }
+        }
+        coverage.registered.push(proc);
+    }
+}
+
+/// Counts the execution of an annotated range.
+pub fn coverage_code(lhs: usize, rhs: usize, is_stmt: bool) {
+    if is_stmt {
+        *coverage_get().stmts.entry((lhs, rhs)).or_default() += 1;
+    }
+}
+
+/// Counts the outcome of a branch evaluated in the range at code offset
+/// `code`.
+pub fn coverage_branch(branch: &IRBranch, cond: bool, code: usize) {
+    let coverage = coverage_get();
+    if let Some(id) = coverage.branch_ids.get(&(branch as *const IRBranch)) {
+        let branch = &mut coverage.branches[*id];
+        branch.code = Some(code);
+        branch.taken[!cond as usize] += 1;
+    }
+}
+
+#[derive(Default)]
+struct CoverageFile {
+    /// executions of the statements starting on each line
+    lines: BTreeMap<usize, u64>,
+    /// outcomes of the branches of each line, `None` if the branch was
+    /// never reached
+    branches: BTreeMap<usize, Vec<Option<[u64; 2]>>>,
+}
+
+/* Counts are mapped to the lines of the source files they were lowered
+ * from. Files loaded more than once are merged, code that isn't read from a
+ * file, like strings passed to `execute`, is left out.
+ */
+fn coverage_files(data: &DebugData) -> BTreeMap<&str, CoverageFile> {
+    let coverage = coverage_get();
+    let mut files: BTreeMap<&str, CoverageFile> = BTreeMap::new();
+    let line_get = |offset: usize| {
+        let src = data.src_get(offset);
+        let (line, _) = data.line_col(offset);
+        Path::new(&src.name)
+            .is_file()
+            .then_some((src.name.as_str(), line))
+    };
+
+    for ((lhs, _), count) in &coverage.stmts {
+        if let Some((name, line)) = line_get(*lhs) {
+            let hits = files
+                .entry(name)
+                .or_default()
+                .lines
+                .entry(line)
+                .or_default();
+            *hits = (*hits).max(*count);
+        }
+    }
+
+    for branch in &coverage.branches {
+        if let Some((name, line)) = branch.code.and_then(line_get) {
+            let reached = branch.taken != [0, 0];
+            files
+                .entry(name)
+                .or_default()
+                .branches
+                .entry(line)
+                .or_default()
+                .push(reached.then_some(branch.taken));
+        }
+    }
+
+    files
+}
+
+/// Lines and branches executed per file, followed by the count of each line.
+pub fn coverage_report(data: &DebugData) -> String {
+    let mut out = String::new();
+    for (name, file) in coverage_files(data) {
+        let taken = |i: &Option<[u64; 2]>| i.map_or(0, |t| t.iter().filter(|i| **i > 0).count());
+        let branches = file.branches.values().flatten().collect::<Vec<_>>();
+        let _ = writeln!(
+            out,
+            "coverage: {name}: {} of {} lines, {} of {} branches",
+            file.lines.values().filter(|i| **i > 0).count(),
+            file.lines.len(),
+            branches.iter().map(|i| taken(i)).sum::<usize>(),
+            branches.len() * 2,
+        );
+
+        let Some(src) = data.sources.iter().rev().find(|i| i.name == name) else {
+            continue;
+        };
+        for (line, hits) in &file.lines {
+            let hits = match hits {
+                0 => String::from("#####"),
+                hits => hits.to_string(),
+            };
+            let _ = write!(out, "{hits:>9} {line:>5}  {}", src.line(*line).trim_end());
+            if let Some(branches) = file.branches.get(line) {
+                let _ = write!(
+                    out,
+                    "  [{} of {} branches]",
+                    branches.iter().map(taken).sum::<usize>(),
+                    branches.len() * 2
+                );
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Writes the coverage in the LCOV tracefile format read by `genhtml` and
+/// most CI services.
+pub fn coverage_lcov_write(path: &Path, data: &DebugData) -> io::Result<()> {
+    let mut out = String::from("TN:\n");
+    for (name, file) in coverage_files(data) {
+        let name = fs::canonicalize(name)
+            .map_or_else(|_| name.to_string(), |i| i.to_string_lossy().into_owned());
+        let _ = writeln!(out, "SF:{name}");
+
+        let mut found = 0;
+        let mut hit = 0;
+        for (line, branches) in &file.branches {
+            for (block, taken) in branches.iter().enumerate() {
+                for branch in 0..2 {
+                    let count = match taken {
+                        Some(taken) => {
+                            hit += (taken[branch] > 0) as usize;
+                            taken[branch].to_string()
+                        }
+                        None => String::from("-"),
+                    };
+                    let _ = writeln!(out, "BRDA:{line},{block},{branch},{count}");
+                }
+                found += 2;
+            }
+        }
+        let _ = writeln!(out, "BRF:{found}\nBRH:{hit}");
+
+        for (line, hits) in &file.lines {
+            let _ = writeln!(out, "DA:{line},{hits}");
+        }
+        let _ = writeln!(
+            out,
+            "LF:{}\nLH:{}\nend_of_record",
+            file.lines.len(),
+            file.lines.values().filter(|i| **i > 0).count()
+        );
+    }
+    fs::write(path, out)
+}
@@ -1,4 +1,5 @@
 use ariadne::{Color, Label, Report, ReportKind, Source};
+use rand::rngs::StdRng;
 use rustyline::{
     Config, Context, DefaultEditor, Editor, Helper,
     completion::{Completer, Pair},
@@ -9,11 +10,15 @@ use rustyline::{
     validate::Validator,
 };
 use std::collections::BTreeSet;
+use std::ops::Range;
 use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
 use std::process::exit;
 
+use crate::builtin::module::ModuleRegistry;
+use crate::builtin::rng::rng_new;
 use crate::cli::InputOpts;
-use crate::cst::cst_parse;
+use crate::cst::{cst_expr_parse_at, cst_parse_at};
 use crate::interp::except::exception_unwind_str;
 use crate::interp::exec::exec_proc;
 use crate::interp::heap::{
@@ -24,6 +29,10 @@ use crate::interp::serialize::{SerializeOpts, serialize};
 use crate::interp::stack::{InterpStack, InterpStackEntry};
 use crate::ir::def::IRCfg;
 use crate::ir::lower::CSTIRLower;
+use crate::ir::symbol::IRSymbol;
+
+/// Strings registered by `src_add_eval` that are kept at the same time
+const SRC_EVAL_MAX: usize = 256;
 
 #[derive(Default)]
 pub struct DebugHelper {
@@ -56,6 +65,28 @@ impl DebugHelper {
             hinter: HistoryHinter {},
         }
     }
+
+    pub fn source() -> Self {
+        Self {
+            commands: vec![
+                "backtrace",
+                "break",
+                "breakpoints",
+                "continue",
+                "delete",
+                "eval",
+                "exit",
+                "finish",
+                "help",
+                "list",
+                "locals",
+                "next",
+                "print",
+                "step",
+            ],
+            hinter: HistoryHinter {},
+        }
+    }
 }
 
 impl Helper for DebugHelper {}
@@ -133,6 +164,17 @@ impl Completer for DebugHelper {
 
 pub type DebugEditor = Editor<DebugHelper, DefaultHistory>;
 
+/// Procedure invocation as seen by the source-level debugger
+#[derive(Debug, Clone)]
+pub struct DebugFrame {
+    pub tag: String,
+    pub srcname: String,
+    pub line: usize,
+    pub col: usize,
+    pub code_lhs: usize,
+    pub code_rhs: usize,
+}
+
 pub struct DebugData {
     pub step: bool,
     pub try_start: bool,
@@ -152,8 +194,68 @@ pub struct DebugData {
     pub repeat_cmd: String,
     pub code_lhs: usize,
     pub code_rhs: usize,
+    /// sources registered with `src_add`, ordered by `base`
+    pub sources: Vec<DebugSource>,
+    /// base of the next source, released sources leave a gap
+    src_end: usize,
+    /// incremented with every registration, to find the least recently used
+    src_clock: u64,
+    /// stands in for sources released by `src_add_eval`
+    src_released: DebugSource,
+    /// stop at the next statement whose frame depth is at most n
+    pub src_step: Option<usize>,
+    pub src_breaks: BTreeSet<(String, usize)>,
+    pub frames: Vec<DebugFrame>,
+    /// generator of the builtins drawing random numbers
+    pub rng: StdRng,
+    /// files executed by `load` and `loadLibrary`
+    pub modules: ModuleRegistry,
+}
+
+/// Source text whose code ranges start at `base`.
+///
+/// Every parsed source is shifted into its own slice of a single offset space,
+/// so the code range of an `IRStmt::Annotate` identifies its file even after
+/// the procedure it belongs to has been called from elsewhere.
+pub struct DebugSource {
+    pub name: String,
     pub src: String,
-    pub srcname: String,
+    pub base: usize,
+    line_starts: Vec<usize>,
+    /// registered by `src_add_eval` only, so it may be released
+    eval: bool,
+    /// `src_clock` when last registered
+    used: u64,
+}
+
+impl DebugSource {
+    fn new(src: String, name: String, base: usize) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(src.match_indices('\n').map(|(idx, _)| idx + 1));
+
+        Self {
+            name,
+            src,
+            base,
+            line_starts,
+            eval: false,
+            used: 0,
+        }
+    }
+
+    /// 1-based line and column of a byte offset into this source
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|i| *i <= offset);
+        (line, offset - self.line_starts[line - 1] + 1)
+    }
+
+    /// Text of a 1-based line without its line break
+    pub fn line(&self, line: usize) -> &str {
+        self.src[self.line_starts[line - 1]..]
+            .lines()
+            .next()
+            .unwrap_or("")
+    }
 }
 
 impl DebugData {
@@ -181,18 +283,88 @@ impl DebugData {
             repeat_cmd: String::new(),
             code_lhs: 0,
             code_rhs: 0,
-            src,
-            srcname,
+            src_end: src.len() + 1,
+            src_clock: 0,
+            sources: vec![DebugSource::new(src, srcname, 0)],
+            src_released: DebugSource::new(String::new(), String::from("<released>"), 0),
+            src_step: Some(usize::MAX),
+            src_breaks: BTreeSet::new(),
+            frames: Vec::new(),
+            rng: rng_new(None),
+            modules: ModuleRegistry::default(),
         }
     }
 
-    pub fn get_src(&self) -> (String, String) {
-        (self.src.clone(), self.srcname.clone())
+    /// Registers a source and returns the base its code ranges have to be
+    /// shifted by. Sources that were registered before are reused, so that
+    /// repeatedly executing the same string doesn't grow the source map.
+    pub fn src_add(&mut self, src: &str, srcname: &str) -> usize {
+        self.src_insert(src, srcname, false)
     }
 
-    pub fn set_src(&mut self, src: String, srcname: String) {
-        self.src = src;
-        self.srcname = srcname;
+    /// Like `src_add`, for strings run by `eval`, `execute` and the like.
+    /// Only the `SRC_EVAL_MAX` most recently registered of them are kept,
+    /// code of released ones is reported without its source.
+    pub fn src_add_eval(&mut self, src: &str, srcname: &str) -> usize {
+        let base = self.src_insert(src, srcname, true);
+        if self.sources.iter().filter(|i| i.eval).count() > SRC_EVAL_MAX {
+            let oldest = self
+                .sources
+                .iter()
+                .enumerate()
+                .filter(|(_, i)| i.eval)
+                .min_by_key(|(_, i)| i.used)
+                .map(|(idx, _)| idx);
+            if let Some(idx) = oldest {
+                self.sources.remove(idx);
+            }
+        }
+        base
+    }
+
+    fn src_insert(&mut self, src: &str, srcname: &str, eval: bool) -> usize {
+        self.src_clock += 1;
+        if let Some(s) = self
+            .sources
+            .iter_mut()
+            .find(|i| i.name == srcname && i.src == src)
+        {
+            s.eval &= eval;
+            s.used = self.src_clock;
+            return s.base;
+        }
+
+        let base = self.src_end;
+        self.src_end += src.len() + 1;
+        let mut source = DebugSource::new(src.to_string(), srcname.to_string(), base);
+        source.eval = eval;
+        source.used = self.src_clock;
+        self.sources.push(source);
+        base
+    }
+
+    /// Source containing a code offset
+    pub fn src_get(&self, offset: usize) -> &DebugSource {
+        let idx = self.sources.partition_point(|i| i.base <= offset);
+        let src = &self.sources[idx.saturating_sub(1)];
+        if offset > src.base + src.src.len() {
+            &self.src_released
+        } else {
+            src
+        }
+    }
+
+    /// Source of the current code range and the range relative to it
+    pub fn code_src(&self) -> (&DebugSource, Range<usize>) {
+        let src = self.src_get(self.code_lhs);
+        let local = |offset: usize| offset.saturating_sub(src.base).min(src.src.len());
+        (src, local(self.code_lhs)..local(self.code_rhs))
+    }
+
+    /// 1-based line and column of a code offset in its source
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let src = self.src_get(offset);
+        src.line_col(offset.saturating_sub(src.base).min(src.src.len()))
     }
 }
 
@@ -273,12 +445,11 @@ pub fn debug_ctrl(
                 "exit" => exit(0),
                 "exec" => {
                     let input = iter.collect::<String>();
-                    let (src, srcname) = data.get_src();
-                    data.set_src(input.clone(), String::from("execute"));
+                    let base = data.src_add_eval(&input, "execute");
 
                     if let Err(e) = panic::catch_unwind(AssertUnwindSafe(|| {
                         let new_opts = iopts.exec_opts();
-                        let stmt = cst_parse(&input, &new_opts);
+                        let stmt = cst_parse_at(&input, &new_opts, base);
                         let stmt_proc = IRCfg::from_stmt(&stmt, &new_opts);
                         let out = exec_proc(
                             stmt_proc,
@@ -315,7 +486,6 @@ pub fn debug_ctrl(
                         eprint!("uncaught unwind: ");
                         exception_unwind_str(e, vars, stack, memo, cstore, data, iopts, rl);
                     }
-                    data.set_src(src, srcname);
                 }
                 "heap" => {
                     if let Some(idx) = iter.next() {
@@ -481,10 +651,10 @@ pub fn debug_ctrl(
                 "stack" => {
                     if let Some(idx) = iter.next() {
                         if let Ok(n) = idx.parse::<usize>()
-                            && n < stack.frames.len()
+                            && n < stack.entries().len()
                         {
                             let stack_ptr = stack as *mut InterpStack;
-                            match &stack.frames[n] {
+                            match &stack.entries()[n] {
                                 InterpStackEntry::StackFrameBoundary => {
                                     eprintln!("stack frame boundary")
                                 }
@@ -529,18 +699,18 @@ pub fn debug_ctrl(
                                 "dump" => {
                                     if let Some(idx) = iter.next()
                                         && let Ok(n) = idx.parse::<usize>()
-                                        && n < stack.frames.len()
+                                        && n < stack.entries().len()
                                     {
-                                        eprintln!("{:?}", stack.frames[n]);
+                                        eprintln!("{:?}", stack.entries()[n]);
                                     } else {
                                         eprintln!("invalid stack parameter");
                                     }
                                 }
-                                "ptr" => eprintln!("{:p}", stack.frames.as_ptr()),
-                                "len" => eprintln!("{}", stack.frames.len()),
+                                "ptr" => eprintln!("{:p}", stack.entries().as_ptr()),
+                                "len" => eprintln!("{}", stack.entries().len()),
                                 "get-idx" => {
                                     if let Some(name) = iter.next() {
-                                        eprintln!("{:?}", stack.get_pos(name));
+                                        eprintln!("{:?}", stack.get_pos(IRSymbol::new(name)));
                                     } else {
                                         eprintln!("missing name");
                                     }
@@ -609,23 +779,12 @@ pub fn debug_ctrl(
                     }
                 }
                 "location" => {
-                    Report::build(
-                        ReportKind::Advice,
-                        (
-                            &data.srcname,
-                            data.code_lhs..data.code_rhs,
-                        ),
-                    )
-                    .with_label(
-                        Label::new((
-                            &data.srcname,
-                            data.code_lhs..data.code_rhs,
-                        ))
-                        .with_color(Color::Yellow),
-                    )
-                    .finish()
-                    .eprint((&data.srcname, Source::from(&data.src)))
-                    .unwrap();
+                    let (src, span) = data.code_src();
+                    Report::build(ReportKind::Advice, (&src.name, span.clone()))
+                        .with_label(Label::new((&src.name, span)).with_color(Color::Yellow))
+                        .finish()
+                        .eprint((&src.name, Source::from(&src.src)))
+                        .unwrap();
                 }
                 _ => eprintln!("unrecognized command"),
             }
@@ -634,3 +793,338 @@ pub fn debug_ctrl(
         }
     }
 }
+
+fn src_matches(srcname: &str, file: &str) -> bool {
+    srcname == file || Path::new(srcname).ends_with(file)
+}
+
+fn src_break_parse(param: Option<&str>, data: &DebugData) -> Option<(String, usize)> {
+    let param = param?;
+    let (file, line) = match param.rsplit_once(':') {
+        Some((file, line)) => (file.to_string(), line),
+        None => (data.src_get(data.code_lhs).name.clone(), param),
+    };
+
+    line.parse::<usize>().ok().map(|n| (file, n))
+}
+
+fn src_frame_name(idx: usize, frame: &DebugFrame) -> &str {
+    if idx == 0 {
+        "<main>"
+    } else if frame.tag.is_empty() {
+        "<procedure>"
+    } else {
+        &frame.tag
+    }
+}
+
+pub fn debug_src_enter(tag: &str, data: &mut DebugData) {
+    data.frames.push(DebugFrame {
+        tag: tag.to_string(),
+        srcname: data.src_get(data.code_lhs).name.clone(),
+        line: 0,
+        col: 0,
+        code_lhs: 0,
+        code_rhs: 0,
+    });
+}
+
+pub fn debug_src_leave(data: &mut DebugData) {
+    data.frames.pop();
+}
+
+/// Called on every statement `IRStmt::Annotate` if `--debug` is set, after
+/// the code range has been updated.
+pub fn debug_src_stmt(
+    vars: &mut [InterpVal],
+    params: &InterpVal,
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    data: &mut DebugData,
+    iopts: &InputOpts,
+    rl: &mut DefaultEditor,
+) {
+    let (src, span) = data.code_src();
+    let (line, col) = src.line_col(span.start);
+    let srcname = src.name.clone();
+    if let Some(frame) = data.frames.last_mut() {
+        frame.srcname = srcname.clone();
+        frame.line = line;
+        frame.col = col;
+        frame.code_lhs = span.start;
+        frame.code_rhs = span.end;
+    }
+
+    let depth = data.frames.len();
+    let hit = data
+        .src_breaks
+        .iter()
+        .any(|(file, l)| *l == line && src_matches(&srcname, file));
+
+    if hit || data.src_step.is_some_and(|n| depth <= n) {
+        let text = data.src_get(data.code_lhs).line(line);
+        eprintln!("{srcname}:{line}:{col}\n{line:>4} | {text}");
+
+        debug_src_ctrl(vars, params, stack, heap, memo, cstore, data, iopts, rl);
+    }
+}
+
+fn debug_src_eval(
+    input: &str,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    data: &mut DebugData,
+    iopts: &InputOpts,
+    rl: &mut DefaultEditor,
+) {
+    let (code_lhs, code_rhs) = (data.code_lhs, data.code_rhs);
+    let src_step = data.src_step.take();
+    let depth = data.frames.len();
+    let base = data.src_add_eval(input, "eval");
+
+    if let Err(e) = panic::catch_unwind(AssertUnwindSafe(|| {
+        let new_opts = iopts.exec_opts();
+        let expr = cst_expr_parse_at(input, &new_opts, base);
+        let eval_proc = IRCfg::from_expr(&expr, &new_opts);
+        let out = exec_proc(
+            eval_proc,
+            &InterpVal::Undefined,
+            stack,
+            memo,
+            cstore,
+            data,
+            &new_opts,
+            rl,
+        );
+
+        eprintln!(
+            "{}",
+            serialize(
+                &out,
+                vars,
+                stack,
+                memo,
+                cstore,
+                data,
+                iopts,
+                rl,
+                SerializeOpts::ESCAPE_STR
+            )
+        );
+
+        if let InterpVal::Ref(r) = out {
+            unsafe {
+                r.invalidate();
+            }
+        }
+    })) {
+        let err = exception_unwind_str(e, vars, stack, memo, cstore, data, iopts, rl);
+        eprintln!("{err}");
+    }
+
+    data.frames.truncate(depth);
+    data.src_step = src_step;
+    data.code_lhs = code_lhs;
+    data.code_rhs = code_rhs;
+}
+
+pub fn debug_src_ctrl(
+    vars: &mut [InterpVal],
+    _params: &InterpVal,
+    stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    data: &mut DebugData,
+    iopts: &InputOpts,
+    rl: &mut DefaultEditor,
+) {
+    let depth = data.frames.len();
+
+    loop {
+        let line = match data.rl.readline("(debug) ") {
+            Ok(line) => {
+                let _ = data.rl.add_history_entry(line.as_str());
+                line
+            }
+            Err(ReadlineError::Interrupted) => {
+                exit(1);
+            }
+            Err(ReadlineError::Eof) => {
+                exit(0);
+            }
+            Err(err) => {
+                eprintln!("readline error: {err}");
+                exit(1);
+            }
+        };
+        let mut iter = line.split_whitespace();
+
+        match iter.next() {
+            Some("b" | "break") => {
+                let param = iter.next();
+                let brk = if param.is_none() {
+                    let src = data.src_get(data.code_lhs);
+                    Some((src.name.clone(), data.line_col(data.code_lhs).0))
+                } else {
+                    src_break_parse(param, data)
+                };
+
+                if let Some((file, n)) = brk {
+                    eprintln!("breakpoint at {file}:{n}");
+                    data.src_breaks.insert((file, n));
+                } else {
+                    eprintln!("break requires a location of the form [file:]line");
+                }
+            }
+            Some("breakpoints") => {
+                data.src_breaks
+                    .iter()
+                    .for_each(|(file, n)| eprintln!("{file}:{n}"));
+            }
+            Some("bt" | "backtrace") => {
+                for (idx, frame) in data.frames.iter().enumerate().rev() {
+                    eprintln!(
+                        "#{} {} at {}:{}:{} ({}..{})",
+                        depth - idx - 1,
+                        src_frame_name(idx, frame),
+                        frame.srcname,
+                        frame.line,
+                        frame.col,
+                        frame.code_lhs,
+                        frame.code_rhs,
+                    );
+                }
+            }
+            Some("c" | "continue") => {
+                data.src_step = None;
+                return;
+            }
+            Some("d" | "delete") => {
+                if let Some(param) = iter.next() {
+                    if let Some(brk) = src_break_parse(Some(param), data) {
+                        data.src_breaks.remove(&brk);
+                    } else {
+                        eprintln!("delete requires a location of the form [file:]line");
+                    }
+                } else {
+                    data.src_breaks.clear();
+                }
+            }
+            Some("e" | "eval") => {
+                let input = line.trim_start().split_once(char::is_whitespace);
+                if let Some((_, input)) = input
+                    && !input.trim().is_empty()
+                {
+                    debug_src_eval(input.trim(), vars, stack, memo, cstore, data, iopts, rl);
+                } else {
+                    eprintln!("eval requires an expression");
+                }
+            }
+            Some("q" | "exit") => exit(0),
+            Some("f" | "finish") => {
+                data.src_step = Some(depth.saturating_sub(1));
+                return;
+            }
+            Some("h" | "help") => eprintln!(concat!(
+                "b | break [file:]line\n",
+                "breakpoints\n",
+                "bt | backtrace\n",
+                "c | continue\n",
+                "d | delete [file:]line\n",
+                "e | eval [expr]\n",
+                "f | finish\n",
+                "h | help\n",
+                "l | list\n",
+                "locals\n",
+                "n | next\n",
+                "p | print [name...]\n",
+                "q | exit\n",
+                "s | step\n",
+            )),
+            Some("l" | "list") => {
+                let (src, span) = data.code_src();
+                Report::build(ReportKind::Advice, (&src.name, span.clone()))
+                    .with_label(Label::new((&src.name, span)).with_color(Color::Yellow))
+                    .finish()
+                    .eprint((&src.name, Source::from(&src.src)))
+                    .unwrap();
+            }
+            Some("locals") => {
+                let pos = stack
+                    .entries()
+                    .iter()
+                    .rposition(|e| matches!(*e, InterpStackEntry::StackFrameBoundary))
+                    .map(|i| i + 1)
+                    .unwrap_or(0);
+                let names = stack.entries()[pos..]
+                    .iter()
+                    .filter_map(|e| match e {
+                        InterpStackEntry::StackFrameBoundary => None,
+                        InterpStackEntry::Variable(v) => Some(v.var.to_string()),
+                        InterpStackEntry::Alias(v) => Some(v.var.to_string()),
+                    })
+                    .collect::<BTreeSet<_>>();
+
+                for name in names {
+                    src_var_print(&name, vars, stack, memo, cstore, data, iopts, rl);
+                }
+            }
+            Some("n" | "next") | None => {
+                data.src_step = Some(depth);
+                return;
+            }
+            Some("p" | "print") => {
+                let mut empty = true;
+                for name in iter {
+                    src_var_print(name, vars, stack, memo, cstore, data, iopts, rl);
+                    empty = false;
+                }
+
+                if empty {
+                    eprintln!("print requires a variable name");
+                }
+            }
+            Some("s" | "step") => {
+                data.src_step = Some(usize::MAX);
+                return;
+            }
+            Some(_) => eprintln!("unrecognized command"),
+        }
+    }
+}
+
+fn src_var_print(
+    name: &str,
+    vars: &mut [InterpVal],
+    stack: &mut InterpStack,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    data: &mut DebugData,
+    iopts: &InputOpts,
+    rl: &mut DefaultEditor,
+) {
+    if let Some(InterpVal::Ptr(p)) = stack.get(IRSymbol::new(name)) {
+        eprintln!(
+            "{name} = {}",
+            serialize(
+                // SAFETY: IR-PTR
+                unsafe { &*p.ptr },
+                vars,
+                stack,
+                memo,
+                cstore,
+                data,
+                iopts,
+                rl,
+                SerializeOpts::ESCAPE_STR
+            )
+        );
+    } else {
+        eprintln!("{name} is not in scope");
+    }
+}
@@ -61,22 +61,32 @@ pub fn exception_kind_num_get() -> u8 {
 }
 
 pub fn exception_throw(cat_msg: &str, msg: &str, data: &DebugData) -> ! {
+    exception_throw_kind(cat_msg, msg, data, ExceptionKind::Usr)
+}
+
+/// Like `exception_throw`, but only caught by `catchLng` and `catch`.
+pub fn exception_throw_lng(cat_msg: &str, msg: &str, data: &DebugData) -> ! {
+    exception_throw_kind(cat_msg, msg, data, ExceptionKind::Lng)
+}
+
+fn exception_throw_kind(cat_msg: &str, msg: &str, data: &DebugData, kind: ExceptionKind) -> ! {
     let mut input = String::new();
+    let (src, span) = data.code_src();
     report(
         ReportKind::Error,
         cat_msg,
         msg,
-        data.code_lhs,
-        data.code_rhs,
-        &data.src,
-        &data.srcname,
+        span.start,
+        span.end,
+        &src.src,
+        &src.name,
         &mut input,
     );
 
     exception_val_set(InterpVal::Ref(InterpObjRef::from_obj(InterpObj::String(
         input.clone(),
     ))));
-    exception_kind_set(ExceptionKind::Usr);
+    exception_kind_set(kind);
     panic!("{input}");
 }
 
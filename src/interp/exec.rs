@@ -8,19 +8,34 @@ use std::panic::AssertUnwindSafe;
 use std::process::exit;
 use std::rc::Rc;
 
+use crate::builtin::rng::rng_new;
 use crate::cli::InputOpts;
 use crate::interp::assign::exec_assign;
-use crate::interp::debug::{DebugData, debug_ctrl};
+use crate::interp::coverage::{
+    coverage_branch, coverage_code, coverage_lcov_write, coverage_register, coverage_report,
+    coverage_reset,
+};
+use crate::interp::debug::{
+    DebugData, DebugHelper, debug_ctrl, debug_src_enter, debug_src_leave, debug_src_stmt,
+};
 use crate::interp::except::exception_unwind_str;
 use crate::interp::get::InterpGet;
+use crate::interp::global::GlobalGuard;
 use crate::interp::heap::*;
+#[cfg(feature = "jit")]
+use crate::interp::jit::{jit_exec, jit_lookup};
+use crate::interp::limits::{LimitsDepth, limits_reset, limits_step};
 use crate::interp::memoize::InterpMemoize;
+use crate::interp::profile::{
+    ProfileCall, profile_code, profile_folded_write, profile_report, profile_reset, profile_step,
+    profile_unwind,
+};
 
 use crate::interp::stack::InterpStack;
 use crate::ir::def::*;
 use crate::ir::dump::ir_dump_stmt;
 
-fn exec_try(
+pub fn exec_try(
     proc: Rc<RefCell<IRProcedure>>,
     mut block_idx: NodeIndex,
     fail_idx: NodeIndex,
@@ -37,6 +52,9 @@ fn exec_try(
         breakpoints.step = true;
     }
 
+    let depth = breakpoints.frames.len();
+    let stack_len = stack.entries().len();
+
     let result = panic::catch_unwind(AssertUnwindSafe(|| {
         let mut immed_heap = InterpImmediateHeap::new();
 
@@ -52,6 +70,11 @@ fn exec_try(
             }
 
             for stmt in block {
+                limits_step(&opts.limits, breakpoints);
+                if opts.profile {
+                    profile_step();
+                }
+
                 if opts.debug_ir && (breakpoints.step || breakpoints.print) {
                     let mut stmt_str = String::new();
                     ir_dump_stmt(stmt, &mut stmt_str);
@@ -73,27 +96,22 @@ fn exec_try(
                 }
 
                 match stmt {
-                    IRStmt::Annotate(lhs, rhs) => {
+                    IRStmt::Annotate(lhs, rhs, is_stmt) => {
                         breakpoints.code_lhs = *lhs;
                         breakpoints.code_rhs = *rhs;
+                        if opts.profile {
+                            profile_code(*lhs);
+                        }
+                        if opts.coverage.is_some() {
+                            coverage_code(*lhs, *rhs, *is_stmt);
+                        }
                         if breakpoints.print_src {
-                            Report::build(
-                                ReportKind::Advice,
-                                (
-                                    &breakpoints.srcname,
-                                    breakpoints.code_lhs..breakpoints.code_rhs,
-                                ),
-                            )
-                            .with_label(
-                                Label::new((
-                                    &breakpoints.srcname,
-                                    breakpoints.code_lhs..breakpoints.code_rhs,
-                                ))
-                                .with_color(Color::Yellow),
-                            )
-                            .finish()
-                            .eprint((&breakpoints.srcname, Source::from(&breakpoints.src)))
-                            .unwrap();
+                            let (src, span) = breakpoints.code_src();
+                            Report::build(ReportKind::Advice, (&src.name, span.clone()))
+                                .with_label(Label::new((&src.name, span)).with_color(Color::Yellow))
+                                .finish()
+                                .eprint((&src.name, Source::from(&src.src)))
+                                .unwrap();
                         }
                         if breakpoints.break_src {
                             debug_ctrl(
@@ -108,6 +126,19 @@ fn exec_try(
                                 rl,
                             );
                         }
+                        if opts.debug && *is_stmt {
+                            debug_src_stmt(
+                                vars,
+                                params,
+                                stack,
+                                &mut immed_heap,
+                                memo,
+                                cstore,
+                                breakpoints,
+                                opts,
+                                rl,
+                            );
+                        }
                     }
                     IRStmt::Assign(a) => {
                         exec_assign(
@@ -125,6 +156,9 @@ fn exec_try(
                     }
                     IRStmt::Branch(b) => {
                         let cond = b.cond.to_bool(vars, params, breakpoints, "branch");
+                        if opts.coverage.is_some() {
+                            coverage_branch(b, cond, breakpoints.code_lhs);
+                        }
 
                         if cond {
                             block_idx = b.success;
@@ -170,7 +204,11 @@ fn exec_try(
 
     match result {
         Ok(next_idx) => next_idx,
-        Err(_) => fail_idx,
+        Err(_) => {
+            breakpoints.frames.truncate(depth);
+            stack.unwind(stack_len);
+            fail_idx
+        }
     }
 }
 
@@ -188,11 +226,40 @@ pub fn exec_proc(
 
     let mut vars = vec![InterpVal::Undefined; proc.borrow().vars.len()];
     let mut immed_heap = InterpImmediateHeap::new();
+    let _depth = LimitsDepth::enter(&opts.limits, breakpoints);
+    let _profile = opts.profile.then(|| ProfileCall::enter(&proc.borrow().tag));
+    if opts.coverage.is_some() {
+        coverage_register(&proc);
+    }
+
+    #[cfg(feature = "jit")]
+    if let Some(threshold) = opts.jit_threshold
+        && !(opts.debug || opts.debug_ir || opts.profile || opts.coverage.is_some())
+        && let Some(code) = jit_lookup(&proc, threshold)
+    {
+        return jit_exec(
+            &code,
+            &proc,
+            &mut vars,
+            params,
+            stack,
+            &mut immed_heap,
+            memo,
+            cstore,
+            breakpoints,
+            opts,
+            rl,
+        );
+    }
 
     if breakpoints.call || breakpoints.procs.contains(&proc.borrow().tag) {
         breakpoints.step = true;
     }
 
+    if opts.debug {
+        debug_src_enter(&proc.borrow().tag, breakpoints);
+    }
+
     if opts.debug_ir && (breakpoints.step || breakpoints.print) {
         eprintln!("_{}(){{", proc.borrow().tag);
     }
@@ -209,6 +276,11 @@ pub fn exec_proc(
         }
 
         for stmt in block {
+            limits_step(&opts.limits, breakpoints);
+            if opts.profile {
+                profile_step();
+            }
+
             if opts.debug_ir && (breakpoints.step || breakpoints.print) {
                 let mut stmt_str = String::new();
                 ir_dump_stmt(stmt, &mut stmt_str);
@@ -230,27 +302,22 @@ pub fn exec_proc(
             }
 
             match stmt {
-                IRStmt::Annotate(lhs, rhs) => {
+                IRStmt::Annotate(lhs, rhs, is_stmt) => {
                     breakpoints.code_lhs = *lhs;
                     breakpoints.code_rhs = *rhs;
+                    if opts.profile {
+                        profile_code(*lhs);
+                    }
+                    if opts.coverage.is_some() {
+                        coverage_code(*lhs, *rhs, *is_stmt);
+                    }
                     if breakpoints.print_src {
-                        Report::build(
-                            ReportKind::Advice,
-                            (
-                                &breakpoints.srcname,
-                                breakpoints.code_lhs..breakpoints.code_rhs,
-                            ),
-                        )
-                        .with_label(
-                            Label::new((
-                                &breakpoints.srcname,
-                                breakpoints.code_lhs..breakpoints.code_rhs,
-                            ))
-                            .with_color(Color::Yellow),
-                        )
-                        .finish()
-                        .eprint((&breakpoints.srcname, Source::from(&breakpoints.src)))
-                        .unwrap();
+                        let (src, span) = breakpoints.code_src();
+                        Report::build(ReportKind::Advice, (&src.name, span.clone()))
+                            .with_label(Label::new((&src.name, span)).with_color(Color::Yellow))
+                            .finish()
+                            .eprint((&src.name, Source::from(&src.src)))
+                            .unwrap();
                     }
                     if breakpoints.break_src {
                         debug_ctrl(
@@ -265,6 +332,19 @@ pub fn exec_proc(
                             rl,
                         );
                     }
+                    if opts.debug && *is_stmt {
+                        debug_src_stmt(
+                            &mut vars,
+                            params,
+                            stack,
+                            &mut immed_heap,
+                            memo,
+                            cstore,
+                            breakpoints,
+                            opts,
+                            rl,
+                        );
+                    }
                 }
                 IRStmt::Assign(a) => {
                     exec_assign(
@@ -282,6 +362,9 @@ pub fn exec_proc(
                 }
                 IRStmt::Branch(b) => {
                     let cond = b.cond.to_bool(&vars, params, breakpoints, "branch");
+                    if opts.coverage.is_some() {
+                        coverage_branch(b, cond, breakpoints.code_lhs);
+                    }
                     if cond {
                         block_idx = b.success;
                     } else {
@@ -333,6 +416,10 @@ pub fn exec_proc(
                         breakpoints.step = true;
                     }
 
+                    if opts.debug {
+                        debug_src_leave(breakpoints);
+                    }
+
                     return res;
                 }
                 IRStmt::Unreachable => {
@@ -343,17 +430,49 @@ pub fn exec_proc(
     }
 }
 
+/// Prints and writes the profile and coverage reports requested in `opts`,
+/// once the program has ended or called `exit`.
+pub fn exec_report(breakpoints: &DebugData, opts: &InputOpts) {
+    if opts.profile {
+        profile_unwind();
+        eprint!("{}", profile_report(breakpoints));
+        if let Some(path) = &opts.profile_folded {
+            profile_folded_write(path, breakpoints).unwrap_or_else(|e| {
+                eprintln!("failed to write {}: {e}", path.display());
+            });
+        }
+    }
+
+    if let Some(path) = &opts.coverage {
+        eprint!("{}", coverage_report(breakpoints));
+        coverage_lcov_write(path, breakpoints).unwrap_or_else(|e| {
+            eprintln!("failed to write {}: {e}", path.display());
+        });
+    }
+}
+
+/// Runs a parsed program. Callers parsing it on a process running other
+/// interpreters hold a [`GlobalGuard`] from the parse on.
 pub fn exec(cfg: IRCfg, opts: &InputOpts, src: String) {
+    let _global = GlobalGuard::acquire();
     let main = cfg.main.clone();
     drop(cfg);
 
     let old_hook = panic::take_hook();
     panic::set_hook(Box::new(|_| {}));
 
-    let mut stack = InterpStack::new();
+    limits_reset(&opts.limits);
+    profile_reset();
+    coverage_reset();
+
+    let mut stack = InterpStack::new(&opts.natives);
     let mut memo: InterpMemoize = BTreeMap::new();
     let mut cstore = InterpClassStore::default();
     let mut breakpoints = DebugData::from_src(src, opts.srcname.clone());
+    breakpoints.rng = rng_new(opts.seed);
+    if opts.debug {
+        breakpoints.rl.set_helper(Some(DebugHelper::source()));
+    }
 
     let config = Config::builder().enable_signals(true).build();
     let mut rl = DefaultEditor::with_config(config).unwrap();
@@ -372,6 +491,8 @@ pub fn exec(cfg: IRCfg, opts: &InputOpts, src: String) {
     }));
     panic::set_hook(old_hook);
 
+    exec_report(&breakpoints, opts);
+
     if let Err(e) = result {
         eprintln!(
             "{}",
@@ -1,19 +1,23 @@
 use nalgebra::{DMatrix, DVector};
 use num_bigint::BigInt;
+use num_rational::BigRational;
 use num_traits::cast::ToPrimitive;
 use std::cell::RefCell;
 use std::cmp::min;
 use std::collections::{BTreeSet, btree_map};
-use std::fs::File;
 use std::panic;
 use std::rc::Rc;
 
-use crate::builtin::BuiltinProc;
+use crate::builtin::file::FileHandle;
+use crate::builtin::native::{NativeProc, native_builtin_get};
+use crate::builtin::plot::PlotCanvas;
 use crate::cli::InputOpts;
 use crate::interp::debug::DebugData;
 use crate::interp::except::*;
 use crate::interp::heap::*;
+use crate::interp::ops::obj_to_f64;
 use crate::ir::def::*;
+use crate::ir::symbol::IRSymbol;
 
 pub struct InterpImmedVal {
     heap: *mut InterpImmediateHeap,
@@ -51,6 +55,30 @@ impl Drop for InterpImmedVal {
     }
 }
 
+fn val_to_vector(input: &InterpVal, data: &DebugData, op: &str) -> DVector<f64> {
+    if let InterpVal::Ref(r) = input {
+        match unsafe { &*r.0 } {
+            InterpObj::Vector(v) => return v.clone(),
+            InterpObj::Matrix(m) if m.ncols() == 1 => return m.column(0).into_owned(),
+            InterpObj::List(l) => {
+                return DVector::from_iterator(
+                    l.0.len(),
+                    l.0.iter().map(|i| match i {
+                        InterpVal::Double(d) => *d,
+                        InterpVal::Ref(r) => obj_to_f64(unsafe { &*r.0 }).unwrap_or_else(|| {
+                            exception_throw("cast", &format!("{op}: expected numbers"), data)
+                        }),
+                        _ => exception_throw("cast", &format!("{op}: expected numbers"), data),
+                    }),
+                );
+            }
+            _ => (),
+        }
+    }
+
+    exception_throw("cast", &format!("{op} undefined for type"), data);
+}
+
 pub trait InterpGet {
     fn to_bool(
         &self,
@@ -87,6 +115,13 @@ pub trait InterpGet {
         data: &DebugData,
         op: &str,
     ) -> BigInt;
+    fn to_rational(
+        &self,
+        vars: &[InterpVal],
+        proc_params: &InterpVal,
+        data: &DebugData,
+        op: &str,
+    ) -> BigRational;
     fn to_ptr(&self, vars: &[InterpVal], op: &str) -> InterpPtr;
     fn to_ptr_val(&self, vars: &[InterpVal], op: &str) -> InterpVal;
     // NOTE: Doesn't handle builtin vars. This function should only be used in contexts where
@@ -107,7 +142,13 @@ pub trait InterpGet {
         vars: &[InterpVal],
         proc_params: &InterpVal,
         data: &DebugData,
-    ) -> Rc<RefCell<File>>;
+    ) -> Rc<RefCell<FileHandle>>;
+    fn to_canvas(
+        &self,
+        vars: &[InterpVal],
+        proc_params: &InterpVal,
+        data: &DebugData,
+    ) -> Rc<RefCell<PlotCanvas>>;
     fn to_str(
         &self,
         vars: &[InterpVal],
@@ -142,6 +183,22 @@ pub trait InterpGet {
         proc_params: &InterpVal,
         data: &DebugData,
     ) -> &'static mut InterpList;
+    /// Accepts matrices, vectors as a single column, and lists of rows.
+    fn to_matrix(
+        &self,
+        vars: &[InterpVal],
+        proc_params: &InterpVal,
+        data: &DebugData,
+        op: &str,
+    ) -> DMatrix<f64>;
+    /// Accepts vectors, single column matrices, and lists of numbers.
+    fn to_vector(
+        &self,
+        vars: &[InterpVal],
+        proc_params: &InterpVal,
+        data: &DebugData,
+        op: &str,
+    ) -> DVector<f64>;
     fn to_iter(&self, vars: &[InterpVal], proc_params: &InterpVal, data: &DebugData) -> InterpIter;
     fn to_obj_iter(
         &self,
@@ -166,8 +223,10 @@ pub trait InterpGet {
         heap: &mut InterpImmediateHeap,
     ) -> InterpSlice;
     fn to_immed_str<'a>(&'a self, op: &str) -> &'a str;
+    fn to_immed_sym(&self, op: &str) -> IRSymbol;
     fn to_immed_bool(&self, op: &str) -> bool;
-    fn to_builtin_proc(&self) -> BuiltinProc;
+    /// Index of the called procedure in the native procedure table.
+    fn to_native(&self) -> &NativeProc;
 }
 
 impl InterpGet for IRValue {
@@ -216,6 +275,9 @@ impl InterpGet for IRValue {
                     InterpObj::Number(n) => n.to_f64().unwrap_or_else(|| {
                         exception_throw("cast", "{op} out of bounds number", data)
                     }),
+                    InterpObj::Rational(r) => r.to_f64().unwrap_or_else(|| {
+                        exception_throw("cast", &format!("{op} out of bounds number"), data)
+                    }),
                     _ => exception_throw("cast", &format!("{op} undefined for type"), data),
                 },
                 _ => exception_throw("cast", &format!("{op} undefined for type"), data),
@@ -320,6 +382,40 @@ impl InterpGet for IRValue {
         }
     }
 
+    fn to_rational(
+        &self,
+        vars: &[InterpVal],
+        proc_params: &InterpVal,
+        data: &DebugData,
+        op: &str,
+    ) -> BigRational {
+        fn f64_to_rational(d: f64, data: &DebugData, op: &str) -> BigRational {
+            BigRational::from_float(d).unwrap_or_else(|| {
+                exception_throw("cast", &format!("{op} undefined for {d}"), data)
+            })
+        }
+
+        fn val_to_rational(v: &InterpVal, data: &DebugData, op: &str) -> BigRational {
+            match v {
+                InterpVal::Double(d) => f64_to_rational(*d, data, op),
+                InterpVal::Ref(r) => match unsafe { &*r.0 } {
+                    InterpObj::Number(n) => BigRational::from_integer(n.clone()),
+                    InterpObj::Rational(r) => r.clone(),
+                    _ => exception_throw("cast", &format!("{op} undefined for type"), data),
+                },
+                _ => exception_throw("cast", &format!("{op} undefined for type"), data),
+            }
+        }
+
+        match self {
+            IRValue::Variable(v) => val_to_rational(&vars[*v], data, op),
+            IRValue::BuiltinVar(v) => val_to_rational(&v.to_val(proc_params), data, op),
+            IRValue::Number(n) => BigRational::from_integer(n.clone()),
+            IRValue::Double(d) => f64_to_rational(*d, data, op),
+            _ => exception_throw("cast", &format!("{op} undefined for type"), data),
+        }
+    }
+
     fn to_ptr(&self, vars: &[InterpVal], op: &str) -> InterpPtr {
         if let IRValue::Variable(v) = self
             && let InterpVal::Ptr(p) = &vars[*v]
@@ -402,6 +498,7 @@ impl InterpGet for IRValue {
                     InterpObj::Ast(_) => IRType::AST,
                     InterpObj::List(_) => IRType::LIST,
                     InterpObj::Number(_) => IRType::NUMBER,
+                    InterpObj::Rational(_) => IRType::RATIONAL,
                     InterpObj::Set(_) => IRType::SET,
                     InterpObj::String(_) => IRType::STRING,
                     InterpObj::Term(_) => IRType::TERM,
@@ -417,6 +514,7 @@ impl InterpGet for IRValue {
                         panic!("internal: stack images should only be used internally")
                     }
                     InterpObj::File(_) => IRType::FILE,
+                    InterpObj::Canvas(_) => IRType::CANVAS,
                 },
                 InterpVal::Procedure(_) => IRType::PROCEDURE,
                 InterpVal::Undefined => IRType::UNDEFINED,
@@ -424,11 +522,14 @@ impl InterpGet for IRValue {
         }
         match self {
             IRValue::Undefined => IRType::UNDEFINED,
-            IRValue::BuiltinProc(_) => panic!("internal: builtin procedures should only be called"),
+            IRValue::BuiltinProc(_) | IRValue::NativeProc(_) => {
+                panic!("internal: builtin procedures should only be called")
+            }
             IRValue::BuiltinVar(v) => val_to_type(&v.to_immed_val(proc_params, opts, heap).val),
             IRValue::Type(_) => IRType::TYPE,
             IRValue::Variable(v) => val_to_type(&vars[*v]),
             IRValue::String(_) => IRType::STRING,
+            IRValue::Symbol(_) => panic!("internal: symbols are only resolved by the stack"),
             IRValue::Number(_) => IRType::NUMBER,
             IRValue::Double(_) => IRType::DOUBLE,
             IRValue::Bool(_) => IRType::BOOL,
@@ -444,8 +545,8 @@ impl InterpGet for IRValue {
         vars: &[InterpVal],
         proc_params: &InterpVal,
         data: &DebugData,
-    ) -> Rc<RefCell<File>> {
-        fn var_file_get(v: &InterpVal, data: &DebugData) -> Rc<RefCell<File>> {
+    ) -> Rc<RefCell<FileHandle>> {
+        fn var_file_get(v: &InterpVal, data: &DebugData) -> Rc<RefCell<FileHandle>> {
             if let InterpVal::Ref(r) = v
                 && let InterpObj::File(f) = unsafe { &*r.0 }
             {
@@ -462,6 +563,29 @@ impl InterpGet for IRValue {
         }
     }
 
+    fn to_canvas(
+        &self,
+        vars: &[InterpVal],
+        proc_params: &InterpVal,
+        data: &DebugData,
+    ) -> Rc<RefCell<PlotCanvas>> {
+        fn var_canvas_get(v: &InterpVal, data: &DebugData) -> Rc<RefCell<PlotCanvas>> {
+            if let InterpVal::Ref(r) = v
+                && let InterpObj::Canvas(c) = unsafe { &*r.0 }
+            {
+                c.clone()
+            } else {
+                exception_throw("cast", "value isn't a canvas", data)
+            }
+        }
+
+        match self {
+            IRValue::BuiltinVar(v) => var_canvas_get(&v.to_val(proc_params), data),
+            IRValue::Variable(v) => var_canvas_get(&vars[*v], data),
+            _ => exception_throw("cast", "value isn't a canvas", data),
+        }
+    }
+
     fn to_proc(&self, vars: &[InterpVal], data: &DebugData, fnc: &str) -> Rc<RefCell<IRProcedure>> {
         match self {
             IRValue::Procedure(p) => p.clone(),
@@ -535,7 +659,9 @@ impl InterpGet for IRValue {
     ) -> InterpImmedVal {
         match self {
             IRValue::Undefined => InterpImmedVal::from_val(InterpVal::Undefined, heap),
-            IRValue::BuiltinProc(_) => panic!("internal: builtin procedures are not assignable"),
+            IRValue::BuiltinProc(_) | IRValue::NativeProc(_) => {
+                panic!("internal: builtin procedures are not assignable")
+            }
             IRValue::BuiltinVar(v) => v.to_immed_val(proc_params, opts, heap),
             IRValue::Type(t) => InterpImmedVal::from_val(InterpVal::Type(*t), heap),
             IRValue::Variable(v) => InterpImmedVal::from_val(vars[*v].clone(), heap).confirm(),
@@ -543,6 +669,7 @@ impl InterpGet for IRValue {
                 InterpVal::Ref(heap.push_obj(InterpObj::String(s.to_string()))),
                 heap,
             ),
+            IRValue::Symbol(_) => panic!("internal: symbols are only resolved by the stack"),
             IRValue::Number(n) => InterpImmedVal::from_val(
                 InterpVal::Ref(heap.push_obj(InterpObj::Number(n.clone()))),
                 heap,
@@ -643,6 +770,60 @@ impl InterpGet for IRValue {
         }
     }
 
+    fn to_matrix(
+        &self,
+        vars: &[InterpVal],
+        proc_params: &InterpVal,
+        data: &DebugData,
+        op: &str,
+    ) -> DMatrix<f64> {
+        fn val_to_matrix(input: &InterpVal, data: &DebugData, op: &str) -> DMatrix<f64> {
+            if let InterpVal::Ref(r) = input {
+                match unsafe { &*r.0 } {
+                    InterpObj::Matrix(m) => return m.clone(),
+                    InterpObj::Vector(v) => {
+                        return DMatrix::from_column_slice(v.len(), 1, v.as_slice());
+                    }
+                    InterpObj::List(l) => {
+                        let rows =
+                            l.0.iter()
+                                .map(|i| val_to_vector(i, data, op))
+                                .collect::<Vec<_>>();
+                        let ncols = rows.first().map(|i| i.len()).unwrap_or(0);
+                        if rows.iter().any(|i| i.len() != ncols) {
+                            exception_throw("cast", &format!("{op}: rows differ in length"), data);
+                        }
+
+                        return DMatrix::from_fn(rows.len(), ncols, |row, col| rows[row][col]);
+                    }
+                    _ => (),
+                }
+            }
+
+            exception_throw("cast", &format!("{op} undefined for type"), data);
+        }
+
+        match self {
+            IRValue::Variable(v) => val_to_matrix(&vars[*v], data, op),
+            IRValue::BuiltinVar(v) => val_to_matrix(&v.to_val(proc_params), data, op),
+            _ => exception_throw("cast", &format!("{op} undefined for type"), data),
+        }
+    }
+
+    fn to_vector(
+        &self,
+        vars: &[InterpVal],
+        proc_params: &InterpVal,
+        data: &DebugData,
+        op: &str,
+    ) -> DVector<f64> {
+        match self {
+            IRValue::Variable(v) => val_to_vector(&vars[*v], data, op),
+            IRValue::BuiltinVar(v) => val_to_vector(&v.to_val(proc_params), data, op),
+            _ => exception_throw("cast", &format!("{op} undefined for type"), data),
+        }
+    }
+
     fn to_iter(&self, vars: &[InterpVal], proc_params: &InterpVal, data: &DebugData) -> InterpIter {
         match self {
             IRValue::BuiltinVar(v) => InterpIter::from_val(&v.to_val(proc_params)),
@@ -864,6 +1045,14 @@ impl InterpGet for IRValue {
         }
     }
 
+    fn to_immed_sym(&self, op: &str) -> IRSymbol {
+        if let IRValue::Symbol(s) = self {
+            *s
+        } else {
+            panic!("internal: {op} is only defined for immediate symbols")
+        }
+    }
+
     fn to_immed_bool(&self, op: &str) -> bool {
         if let IRValue::Bool(b) = self {
             *b
@@ -872,11 +1061,11 @@ impl InterpGet for IRValue {
         }
     }
 
-    fn to_builtin_proc(&self) -> BuiltinProc {
-        if let IRValue::BuiltinProc(p) = self {
-            *p
-        } else {
-            panic!("internal: native call is only implemented for builtin procedures");
+    fn to_native(&self) -> &NativeProc {
+        match self {
+            IRValue::BuiltinProc(p) => native_builtin_get(*p),
+            IRValue::NativeProc(native) => native,
+            _ => panic!("internal: native call is only implemented for builtin procedures"),
         }
     }
 }
@@ -0,0 +1,43 @@
+use std::cell::Cell;
+use std::sync::{Mutex, MutexGuard};
+
+/* The interpreter keeps the pending exception, the heap count, the limits,
+ * the symbol table and others in unsynchronized statics, which are only used
+ * by the thread holding this lock.
+ */
+static GLOBAL: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    static GLOBAL_HELD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Grants the current thread the process-global state of the interpreter
+/// until it is dropped.
+pub struct GlobalGuard(Option<MutexGuard<'static, ()>>);
+
+impl GlobalGuard {
+    /// Blocks while another thread holds the state. Guards nested in one of
+    /// the current thread don't block.
+    pub fn acquire() -> Self {
+        if GlobalGuard::held() {
+            return GlobalGuard(None);
+        }
+
+        let guard = GLOBAL.lock().unwrap_or_else(|e| e.into_inner());
+        GLOBAL_HELD.set(true);
+        GlobalGuard(Some(guard))
+    }
+
+    /// Whether the current thread holds the state.
+    pub fn held() -> bool {
+        GLOBAL_HELD.get()
+    }
+}
+
+impl Drop for GlobalGuard {
+    fn drop(&mut self) {
+        if self.0.is_some() {
+            GLOBAL_HELD.set(false);
+        }
+    }
+}
@@ -1,20 +1,24 @@
 use nalgebra::{DMatrix, DVector};
 use num_bigint::BigInt;
+use num_rational::BigRational;
 use pcre2::bytes::Regex;
 use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet, btree_map, btree_set};
-use std::fs::File;
+use std::io;
 use std::iter::{Skip, Take};
 use std::rc::Rc;
 use std::slice;
 use std::str::Chars;
 
 use crate::builtin::BuiltinProc;
+use crate::builtin::file::FileHandle;
+use crate::builtin::plot::PlotCanvas;
 use crate::interp::memoize::InterpStackImage;
 use crate::interp::ops::val_cmp;
 use crate::ir::def::*;
 use crate::ir::lower::util::{block_get, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 pub struct InterpClassStoreEntry {
     pub get_proc: Rc<RefCell<IRProcedure>>,
@@ -53,7 +57,7 @@ impl InterpClassStore {
                     target: IRTarget::Variable(t_c_addr),
                     types: IRType::PTR,
                     source: IRValue::BuiltinProc(BuiltinProc::StackGetOrNew),
-                    op: IROp::NativeCall(vec![IRValue::String(name.to_string())]),
+                    op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(name))]),
                 }),
                 IRStmt::Assign(IRAssign {
                     target: IRTarget::Variable(t_c_ref),
@@ -85,8 +89,22 @@ impl InterpClassStore {
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct InterpObjRef(pub *mut InterpObj);
 
+/* Objects allocated by from_obj and not yet invalidated, checked against
+ * the heap limit
+ */
+static mut HEAP_LIVE: usize = 0;
+
+pub fn heap_live() -> usize {
+    //SAFETY: setlx-rs is single-threaded
+    unsafe { HEAP_LIVE }
+}
+
 impl InterpObjRef {
     pub fn from_obj(o: InterpObj) -> Self {
+        //SAFETY: setlx-rs is single-threaded
+        unsafe {
+            HEAP_LIVE += 1;
+        }
         InterpObjRef(Box::into_raw(Box::new(o)))
     }
 
@@ -101,7 +119,10 @@ impl InterpObjRef {
     ///
     /// IR-Op
     pub unsafe fn invalidate(self) {
-        unsafe { drop(Box::from_raw(self.0)) }
+        unsafe {
+            HEAP_LIVE -= 1;
+            drop(Box::from_raw(self.0))
+        }
     }
 }
 
@@ -149,6 +170,18 @@ pub enum InterpIter {
     StringIter(Take<Skip<Chars<'static>>>),
     SetIter(btree_set::Iter<'static, InterpVal>),
     ListIter(Take<slice::Iter<'static, InterpVal>>),
+    FileIter(Rc<RefCell<FileHandle>>, Rc<InterpLineBuf>),
+}
+
+/// String object holding the current line of a [`InterpIter::FileIter`].
+/// It is overwritten by every line, as iterating always copies the value.
+#[derive(Debug)]
+pub struct InterpLineBuf(InterpObjRef);
+
+impl Drop for InterpLineBuf {
+    fn drop(&mut self) {
+        unsafe { self.0.invalidate() }
+    }
 }
 
 impl InterpIter {
@@ -181,6 +214,12 @@ impl InterpIter {
                         std::collections::btree_set::Iter<'static, InterpVal>,
                     >(s.0.iter())
                 })),
+                InterpObj::File(f) => Some(InterpIter::FileIter(
+                    f.clone(),
+                    Rc::new(InterpLineBuf(InterpObjRef::from_obj(InterpObj::String(
+                        String::new(),
+                    )))),
+                )),
                 _ => None,
             },
             _ => None,
@@ -188,15 +227,23 @@ impl InterpIter {
     }
 }
 
-impl Iterator for InterpIter {
-    type Item = InterpVal;
-
-    fn next(&mut self) -> Option<InterpVal> {
-        match self {
+impl InterpIter {
+    /// Next element, `None` once exhausted. Fails if the next line of a file
+    /// can't be read.
+    pub fn try_next(&mut self) -> io::Result<Option<InterpVal>> {
+        Ok(match self {
             InterpIter::StringIter(c) => c.next().map(InterpVal::Char),
             InterpIter::SetIter(s) => s.next().cloned(),
             InterpIter::ListIter(l) => l.next().cloned(),
-        }
+            InterpIter::FileIter(f, buf) => {
+                let Some(line) = f.borrow_mut().read_line()? else {
+                    return Ok(None);
+                };
+                // SAFETY: the buffer is only reachable through this iterator
+                unsafe { *buf.0.0 = InterpObj::String(line) };
+                Some(InterpVal::Ref(buf.0))
+            }
+        })
     }
 }
 
@@ -251,6 +298,8 @@ pub enum InterpVal {
 }
 
 impl InterpVal {
+    pub const TAG_REF: u8 = 8;
+
     pub fn tag(&self) -> u8 {
         match self {
             InterpVal::Bool(_) => 0,
@@ -261,7 +310,7 @@ impl InterpVal {
             InterpVal::Slice(_) => 5,
             InterpVal::Type(_) => 6,
             InterpVal::Ptr(_) => 7,
-            InterpVal::Ref(_) => 8,
+            InterpVal::Ref(_) => Self::TAG_REF,
             InterpVal::Procedure(_) => 9,
             InterpVal::Undefined => 10,
             InterpVal::OffsetStrPtr(_) => 11,
@@ -582,17 +631,32 @@ pub enum InterpObj {
     Vector(DVector<f64>),
     Matrix(DMatrix<f64>),
     Procedure(InterpProc),
-    File(Rc<RefCell<File>>),
+    File(Rc<RefCell<FileHandle>>),
+    Rational(BigRational),
+    Canvas(Rc<RefCell<PlotCanvas>>),
     #[default]
     Uninitialized,
 }
 
 impl InterpObj {
+    /// Integral rationals are normalized to [`InterpObj::Number`].
+    pub fn from_rational(r: BigRational) -> Self {
+        if r.is_integer() {
+            InterpObj::Number(r.to_integer())
+        } else {
+            InterpObj::Rational(r)
+        }
+    }
+
+    pub const TAG_NUMBER: usize = 2;
+
+    /// Orders objects of different types. Numbers share a tag, so that they
+    /// are only ordered by value.
     pub fn tag(&self) -> usize {
         match self {
             InterpObj::Ast(_) => 0,
             InterpObj::List(_) => 1,
-            InterpObj::Number(_) => 2,
+            InterpObj::Number(_) | InterpObj::Rational(_) => Self::TAG_NUMBER,
             InterpObj::Set(_) => 3,
             InterpObj::String(_) => 4,
             InterpObj::Term(_) => 5,
@@ -606,6 +670,7 @@ impl InterpObj {
             InterpObj::Procedure(_) => 13,
             InterpObj::File(_) => 14,
             InterpObj::Uninitialized => 15,
+            InterpObj::Canvas(_) => 17,
         }
     }
 }
@@ -0,0 +1,26 @@
+/// Destination of `print`/`printErr` and source of `get`/`read`.
+///
+/// Without an installed handler the interpreter uses the process' stdout,
+/// stderr and a readline editor on stdin.
+pub trait InterpIO {
+    fn print(&mut self, s: &str);
+    fn print_err(&mut self, s: &str);
+    /// Returns `None` once the input is exhausted.
+    fn read_line(&mut self, prompt: &str) -> Option<String>;
+}
+
+static mut IO: Option<Box<dyn InterpIO>> = None;
+
+/// Installs `io` as the I/O handler and returns the previous one.
+#[allow(dead_code)]
+#[allow(static_mut_refs)]
+pub fn io_set(io: Option<Box<dyn InterpIO>>) -> Option<Box<dyn InterpIO>> {
+    //SAFETY: setlx-rs is single-threaded
+    unsafe { std::mem::replace(&mut IO, io) }
+}
+
+#[allow(static_mut_refs)]
+pub fn io_get() -> Option<&'static mut Box<dyn InterpIO>> {
+    //SAFETY: setlx-rs is single-threaded
+    unsafe { IO.as_mut() }
+}
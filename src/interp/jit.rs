@@ -0,0 +1,575 @@
+use cranelift_codegen::ir::condcodes::IntCC;
+use cranelift_codegen::ir::{
+    AbiParam, Block, InstBuilder, MemFlags, SigRef, Signature, Value, types,
+};
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Switch};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{Module, default_libcall_names};
+use petgraph::stable_graph::NodeIndex;
+use rustyline::DefaultEditor;
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::mem::offset_of;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::{Rc, Weak};
+
+use crate::cli::InputOpts;
+use crate::interp::assign::exec_assign;
+use crate::interp::debug::DebugData;
+use crate::interp::exec::exec_try;
+use crate::interp::get::InterpGet;
+use crate::interp::heap::*;
+use crate::interp::limits::limits_step;
+use crate::interp::memoize::InterpMemoize;
+use crate::interp::stack::InterpStack;
+use crate::ir::def::*;
+
+/// Returned by callbacks and compiled procedures once an exception was caught
+const JIT_PANIC: u8 = 1;
+/// Returned by the branch callback in place of a condition
+const JIT_BRANCH_PANIC: u8 = 2;
+/// Returned by the try callback in place of the next block
+const JIT_TRY_PANIC: u32 = u32::MAX;
+
+/* State of a running compiled procedure. The generated code only reads
+ * `breakpoints` and `limited`, everything else is accessed by the callbacks
+ * executing statements through the interpreter.
+ */
+#[repr(C)]
+struct JitFrame {
+    breakpoints: *mut DebugData,
+    /// steps have to be counted for the limits
+    limited: u8,
+    proc: *const Rc<RefCell<IRProcedure>>,
+    vars: *mut [InterpVal],
+    params: *const InterpVal,
+    stack: *mut InterpStack,
+    heap: *mut InterpImmediateHeap,
+    memo: *mut InterpMemoize,
+    cstore: *mut InterpClassStore,
+    opts: *const InputOpts,
+    rl: *mut DefaultEditor,
+    ret: InterpVal,
+    /// exception raised by a callback, rethrown once the native code returned
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+type JitFn = unsafe extern "C" fn(*mut JitFrame) -> u8;
+
+pub struct JitProc {
+    func: JitFn,
+    /// copy of the statements the native code points into
+    _blocks: Vec<IRBlock>,
+}
+
+enum JitState {
+    Interpreted(u32),
+    Compiled(Rc<JitProc>),
+    Unsupported,
+}
+
+struct JitEntry {
+    /* keeps the allocation alive, so the address used as key can't be
+     * reused by another procedure while the entry exists
+     */
+    proc: Weak<RefCell<IRProcedure>>,
+    state: JitState,
+}
+
+/// Entries kept before the first sweep of dropped procedures
+const JIT_SWEEP_MIN: usize = 256;
+
+struct Jit {
+    /// `None` if the host has no code generator
+    module: Option<JITModule>,
+    procs: HashMap<*const RefCell<IRProcedure>, JitEntry>,
+    /// entries left by the last sweep
+    swept: usize,
+}
+
+static mut JIT: Option<Jit> = None;
+
+#[allow(static_mut_refs)]
+fn jit_get_state() -> &'static mut Jit {
+    //SAFETY: setlx-rs is single-threaded
+    unsafe {
+        JIT.get_or_insert_with(|| Jit {
+            module: jit_module_new(),
+            procs: HashMap::new(),
+            swept: 0,
+        })
+    }
+}
+
+fn jit_module_new() -> Option<JITModule> {
+    let mut flags = settings::builder();
+    flags.set("opt_level", "speed").ok()?;
+    let isa = cranelift_native::builder()
+        .ok()?
+        .finish(settings::Flags::new(flags))
+        .ok()?;
+    Some(JITModule::new(JITBuilder::with_isa(
+        isa,
+        default_libcall_names(),
+    )))
+}
+
+/// Counts a call of `proc` and returns its native code once it was called
+/// `threshold` times. Procedures that can't be compiled stay interpreted.
+pub fn jit_lookup(proc: &Rc<RefCell<IRProcedure>>, threshold: u32) -> Option<Rc<JitProc>> {
+    let jit = jit_get_state();
+
+    /* procedures created by eval and load come and go, so the entries of
+     * dropped ones are released once the map doubled. Their native code
+     * stays in the module, which can only be freed as a whole
+     */
+    if jit.procs.len() >= 2 * jit.swept.max(JIT_SWEEP_MIN / 2) {
+        jit.procs.retain(|_, i| i.proc.strong_count() > 0);
+        jit.swept = jit.procs.len();
+    }
+
+    let entry = jit
+        .procs
+        .entry(Rc::as_ptr(proc))
+        .or_insert_with(|| JitEntry {
+            proc: Rc::downgrade(proc),
+            state: JitState::Interpreted(0),
+        });
+
+    match &mut entry.state {
+        JitState::Compiled(code) => Some(code.clone()),
+        JitState::Unsupported => None,
+        JitState::Interpreted(calls) => {
+            *calls += 1;
+            if *calls < threshold {
+                return None;
+            }
+
+            let code = jit
+                .module
+                .as_mut()
+                .and_then(|m| jit_compile(m, &proc.borrow()));
+            match code {
+                Some(code) => {
+                    let code = Rc::new(code);
+                    entry.state = JitState::Compiled(code.clone());
+                    Some(code)
+                }
+                None => {
+                    entry.state = JitState::Unsupported;
+                    None
+                }
+            }
+        }
+    }
+}
+
+fn jit_supported(blocks: &[IRBlock]) -> bool {
+    blocks.iter().all(|block| {
+        block.last().is_some_and(|stmt| {
+            matches!(
+                stmt,
+                IRStmt::Branch(_)
+                    | IRStmt::Goto(_)
+                    | IRStmt::Return(_)
+                    | IRStmt::Unreachable
+                    | IRStmt::Try(_)
+                    | IRStmt::TryEnd(_)
+            )
+        })
+    })
+}
+
+struct JitCallbacks {
+    step: SigRef,
+    stmt: SigRef,
+    attempt: SigRef,
+}
+
+fn jit_callback(
+    builder: &mut FunctionBuilder,
+    sig: SigRef,
+    func: *const u8,
+    args: &[Value],
+) -> Value {
+    let ptr = builder.func.dfg.value_type(args[0]);
+    let callee = builder.ins().iconst(ptr, func as i64);
+    let call = builder.ins().call_indirect(sig, callee, args);
+    builder.inst_results(call)[0]
+}
+
+/// Counts a step if the frame is limited and leaves through `exit` if that
+/// raised an exception.
+fn jit_step_emit(builder: &mut FunctionBuilder, sigs: &JitCallbacks, frame: Value, exit: Block) {
+    let step = builder.create_block();
+    let cont = builder.create_block();
+
+    let limited = builder.ins().load(
+        types::I8,
+        MemFlags::trusted(),
+        frame,
+        offset_of!(JitFrame, limited) as i32,
+    );
+    builder.ins().brif(limited, step, &[], cont, &[]);
+
+    builder.switch_to_block(step);
+    let status = jit_callback(builder, sigs.step, jit_step as *const u8, &[frame]);
+    builder.ins().brif(status, exit, &[], cont, &[]);
+
+    builder.switch_to_block(cont);
+}
+
+/* Each IR block becomes a native block. Control flow and annotations are
+ * native, assignments, conditions and return values are evaluated by
+ * callbacks into the interpreter. `try` regions are interpreted by
+ * `exec_try`, so blocks ending in `try_end` are only reached through it and
+ * the block it continues with is looked up in a native switch.
+ */
+fn jit_compile(module: &mut JITModule, proc: &IRProcedure) -> Option<JitProc> {
+    let indices = proc.blocks.node_indices().collect::<Vec<_>>();
+    let blocks = indices
+        .iter()
+        .map(|i| proc.blocks[*i].clone())
+        .collect::<Vec<_>>();
+    if !jit_supported(&blocks) {
+        return None;
+    }
+
+    let ptr = module.target_config().pointer_type();
+    let call_conv = module.isa().default_call_conv();
+    let mut ctx = module.make_context();
+    ctx.func.signature.params.push(AbiParam::new(ptr));
+    ctx.func.signature.returns.push(AbiParam::new(types::I8));
+
+    let mut fn_ctx = FunctionBuilderContext::new();
+    let mut builder = FunctionBuilder::new(&mut ctx.func, &mut fn_ctx);
+
+    let mut step_sig = Signature::new(call_conv);
+    step_sig.params.push(AbiParam::new(ptr));
+    step_sig.returns.push(AbiParam::new(types::I8));
+    let mut stmt_sig = step_sig.clone();
+    stmt_sig.params.push(AbiParam::new(ptr));
+    let mut attempt_sig = Signature::new(call_conv);
+    attempt_sig.params.push(AbiParam::new(ptr));
+    attempt_sig.params.push(AbiParam::new(ptr));
+    attempt_sig.returns.push(AbiParam::new(types::I32));
+    let sigs = JitCallbacks {
+        step: builder.import_signature(step_sig),
+        stmt: builder.import_signature(stmt_sig),
+        attempt: builder.import_signature(attempt_sig),
+    };
+
+    let entry = builder.create_block();
+    builder.append_block_params_for_function_params(entry);
+    let exit = builder.create_block();
+    let natives: HashMap<NodeIndex, Block> = indices
+        .iter()
+        .map(|i| (*i, builder.create_block()))
+        .collect();
+
+    builder.switch_to_block(entry);
+    let frame = builder.block_params(entry)[0];
+    builder.ins().jump(natives[&proc.start_block], &[]);
+
+    builder.switch_to_block(exit);
+    let status = builder.ins().iconst(types::I8, JIT_PANIC as i64);
+    builder.ins().return_(&[status]);
+
+    let mut resumes = Switch::new();
+    for stmt in blocks.iter().filter_map(|block| block.last()) {
+        match stmt {
+            IRStmt::Try(t) => resumes.set_entry(t.catch.index() as u128, natives[&t.catch]),
+            IRStmt::TryEnd(next) => resumes.set_entry(next.index() as u128, natives[next]),
+            _ => (),
+        }
+    }
+    let resume = builder.create_block();
+    builder.append_block_param(resume, types::I32);
+
+    for (idx, block) in indices.iter().zip(&blocks) {
+        builder.switch_to_block(natives[idx]);
+
+        for stmt in block {
+            match stmt {
+                IRStmt::Annotate(lhs, rhs, _) => {
+                    jit_step_emit(&mut builder, &sigs, frame, exit);
+                    let data = builder.ins().load(
+                        ptr,
+                        MemFlags::trusted(),
+                        frame,
+                        offset_of!(JitFrame, breakpoints) as i32,
+                    );
+                    for (val, offset) in [
+                        (*lhs, offset_of!(DebugData, code_lhs)),
+                        (*rhs, offset_of!(DebugData, code_rhs)),
+                    ] {
+                        let val = builder.ins().iconst(ptr, val as i64);
+                        builder
+                            .ins()
+                            .store(MemFlags::trusted(), val, data, offset as i32);
+                    }
+                }
+                IRStmt::Assign(a) => {
+                    let a = builder.ins().iconst(ptr, a as *const IRAssign as i64);
+                    let status = jit_callback(
+                        &mut builder,
+                        sigs.stmt,
+                        jit_assign as *const u8,
+                        &[frame, a],
+                    );
+                    let cont = builder.create_block();
+                    builder.ins().brif(status, exit, &[], cont, &[]);
+                    builder.switch_to_block(cont);
+                }
+                IRStmt::Branch(b) => {
+                    jit_step_emit(&mut builder, &sigs, frame, exit);
+                    let (success, failure) = (natives[&b.success], natives[&b.failure]);
+                    if let IRValue::Bool(cond) = b.cond {
+                        builder
+                            .ins()
+                            .jump(if cond { success } else { failure }, &[]);
+                        break;
+                    }
+
+                    let cond = builder.ins().iconst(ptr, &b.cond as *const IRValue as i64);
+                    let cond = jit_callback(
+                        &mut builder,
+                        sigs.stmt,
+                        jit_branch as *const u8,
+                        &[frame, cond],
+                    );
+                    let failed =
+                        builder
+                            .ins()
+                            .icmp_imm(IntCC::Equal, cond, JIT_BRANCH_PANIC as i64);
+                    let cont = builder.create_block();
+                    builder.ins().brif(failed, exit, &[], cont, &[]);
+                    builder.switch_to_block(cont);
+                    builder.ins().brif(cond, success, &[], failure, &[]);
+                    break;
+                }
+                IRStmt::Goto(next) => {
+                    jit_step_emit(&mut builder, &sigs, frame, exit);
+                    builder.ins().jump(natives[next], &[]);
+                    break;
+                }
+                IRStmt::Return(val) => {
+                    jit_step_emit(&mut builder, &sigs, frame, exit);
+                    let val = builder.ins().iconst(ptr, val as *const IRValue as i64);
+                    let status = jit_callback(
+                        &mut builder,
+                        sigs.stmt,
+                        jit_return as *const u8,
+                        &[frame, val],
+                    );
+                    builder.ins().return_(&[status]);
+                    break;
+                }
+                IRStmt::Unreachable => {
+                    jit_step_emit(&mut builder, &sigs, frame, exit);
+                    let status = jit_callback(
+                        &mut builder,
+                        sigs.step,
+                        jit_unreachable as *const u8,
+                        &[frame],
+                    );
+                    builder.ins().return_(&[status]);
+                    break;
+                }
+                IRStmt::Try(t) => {
+                    let t = builder.ins().iconst(ptr, t as *const IRTry as i64);
+                    let next = jit_callback(
+                        &mut builder,
+                        sigs.attempt,
+                        jit_try as *const u8,
+                        &[frame, t],
+                    );
+                    builder.ins().jump(resume, &[next]);
+                    break;
+                }
+                IRStmt::TryEnd(_) => {
+                    let status =
+                        jit_callback(&mut builder, sigs.step, jit_try_end as *const u8, &[frame]);
+                    builder.ins().return_(&[status]);
+                    break;
+                }
+            }
+        }
+    }
+
+    builder.switch_to_block(resume);
+    let next = builder.block_params(resume)[0];
+    /* every block `exec_try` can continue with is an entry, anything else
+     * is `JIT_TRY_PANIC`
+     */
+    resumes.emit(&mut builder, next, exit);
+
+    builder.seal_all_blocks();
+    builder.finalize();
+
+    let id = module
+        .declare_anonymous_function(&ctx.func.signature)
+        .ok()?;
+    module.define_function(id, &mut ctx).ok()?;
+    module.clear_context(&mut ctx);
+    module.finalize_definitions().ok()?;
+
+    let func = module.get_finalized_function(id);
+    Some(JitProc {
+        // SAFETY: the function was generated with the signature of JitFn
+        func: unsafe { std::mem::transmute::<*const u8, JitFn>(func) },
+        _blocks: blocks,
+    })
+}
+
+/// Runs `f`, catching exceptions so they never unwind through native frames.
+fn jit_catch(frame: *mut JitFrame, f: impl FnOnce(&mut JitFrame) -> u8) -> u8 {
+    // SAFETY: the frame outlives the native code calling back
+    let frame = unsafe { &mut *frame };
+    match panic::catch_unwind(AssertUnwindSafe(|| f(frame))) {
+        Ok(status) => status,
+        Err(e) => {
+            frame.panic = Some(e);
+            JIT_PANIC
+        }
+    }
+}
+
+fn jit_frame_step(f: &JitFrame) {
+    unsafe { limits_step(&(*f.opts).limits, &*f.breakpoints) };
+}
+
+extern "C" fn jit_step(frame: *mut JitFrame) -> u8 {
+    jit_catch(frame, |f| {
+        jit_frame_step(f);
+        0
+    })
+}
+
+extern "C" fn jit_assign(frame: *mut JitFrame, a: *const IRAssign) -> u8 {
+    jit_catch(frame, |f| unsafe {
+        if f.limited != 0 {
+            jit_frame_step(f);
+        }
+        exec_assign(
+            &*a,
+            &mut *f.vars,
+            &*f.params,
+            &mut *f.stack,
+            &mut *f.heap,
+            &mut *f.memo,
+            &mut *f.cstore,
+            &mut *f.breakpoints,
+            &*f.opts,
+            &mut *f.rl,
+        );
+        0
+    })
+}
+
+extern "C" fn jit_branch(frame: *mut JitFrame, cond: *const IRValue) -> u8 {
+    let mut out = JIT_BRANCH_PANIC;
+    jit_catch(frame, |f| unsafe {
+        out = (*cond).to_bool(&*f.vars, &*f.params, &*f.breakpoints, "branch") as u8;
+        0
+    });
+    out
+}
+
+extern "C" fn jit_return(frame: *mut JitFrame, val: *const IRValue) -> u8 {
+    jit_catch(frame, |f| unsafe {
+        f.ret = (*val)
+            .to_val(
+                &*f.vars,
+                &*f.params,
+                &*f.breakpoints,
+                &*f.opts,
+                &mut *f.heap,
+            )
+            .confirm()
+            .val
+            .clone()
+            .persist(&mut *f.heap);
+        0
+    })
+}
+
+extern "C" fn jit_unreachable(frame: *mut JitFrame) -> u8 {
+    jit_catch(frame, |_| panic!("internal: encountered unreachable guard"))
+}
+
+extern "C" fn jit_try(frame: *mut JitFrame, t: *const IRTry) -> u32 {
+    let mut next = JIT_TRY_PANIC;
+    jit_catch(frame, |f| unsafe {
+        if f.limited != 0 {
+            jit_frame_step(f);
+        }
+        next = exec_try(
+            (*f.proc).clone(),
+            (*t).attempt,
+            (*t).catch,
+            &*f.params,
+            &mut *f.vars,
+            &mut *f.stack,
+            &mut *f.memo,
+            &mut *f.cstore,
+            &mut *f.breakpoints,
+            &*f.opts,
+            &mut *f.rl,
+        )
+        .index() as u32;
+        0
+    });
+    next
+}
+
+extern "C" fn jit_try_end(frame: *mut JitFrame) -> u8 {
+    jit_catch(frame, |_| {
+        panic!("internal: encountered unmet try end statement")
+    })
+}
+
+/// Executes compiled code in place of the interpreter loop of `exec_proc`.
+/// Exceptions caught by the callbacks are rethrown here, so they unwind into
+/// the enclosing `try` like interpreted ones.
+pub fn jit_exec(
+    code: &JitProc,
+    proc: &Rc<RefCell<IRProcedure>>,
+    vars: &mut [InterpVal],
+    params: &InterpVal,
+    stack: &mut InterpStack,
+    heap: &mut InterpImmediateHeap,
+    memo: &mut InterpMemoize,
+    cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    opts: &InputOpts,
+    rl: &mut DefaultEditor,
+) -> InterpVal {
+    let mut frame = JitFrame {
+        breakpoints,
+        limited: (opts.limits.steps.is_some()
+            || opts.limits.heap.is_some()
+            || opts.limits.timeout.is_some()) as u8,
+        proc,
+        vars,
+        params,
+        stack,
+        heap,
+        memo,
+        cstore,
+        opts,
+        rl,
+        ret: InterpVal::Undefined,
+        panic: None,
+    };
+
+    // SAFETY: the frame lives until the native code returned
+    unsafe { (code.func)(&mut frame) };
+
+    if let Some(e) = frame.panic.take() {
+        panic::resume_unwind(e);
+    }
+    frame.ret
+}
@@ -0,0 +1,128 @@
+use std::time::{Duration, Instant};
+
+use crate::interp::debug::DebugData;
+use crate::interp::except::exception_throw_lng;
+use crate::interp::heap::heap_live;
+
+/// Statements between two looks at the clock
+const TIMEOUT_INTERVAL: u64 = 256;
+
+/// Native stack nested calls may use below the start of a run, whatever
+/// `ExecLimits::depth` allows. Runs need a thread with a larger stack.
+pub const LIMITS_STACK: usize = 64 << 20;
+
+/// Resource limits of a run, unlimited where `None`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExecLimits {
+    /// executed IR statements
+    pub steps: Option<u64>,
+    /// heap objects alive at the same time
+    pub heap: Option<usize>,
+    pub timeout: Option<Duration>,
+    /// nested procedure calls
+    pub depth: Option<usize>,
+}
+
+#[derive(Default)]
+struct LimitsState {
+    steps: u64,
+    depth: usize,
+    deadline: Option<Instant>,
+    exceeded: bool,
+    /// address of the stack when the run started
+    stack_start: usize,
+}
+
+static mut LIMITS: Option<LimitsState> = None;
+
+#[allow(static_mut_refs)]
+fn limits_get() -> &'static mut LimitsState {
+    //SAFETY: setlx-rs is single-threaded
+    unsafe { LIMITS.get_or_insert_with(LimitsState::default) }
+}
+
+fn stack_addr() -> usize {
+    let marker = 0u8;
+    &marker as *const u8 as usize
+}
+
+/// Restarts the step count and the clock of `limits`, and measures the stack
+/// used by nested calls from the caller on. Returns whether a limit was hit
+/// since the last reset.
+pub fn limits_reset(limits: &ExecLimits) -> bool {
+    let state = limits_get();
+    let exceeded = state.exceeded;
+    *state = LimitsState {
+        deadline: limits.timeout.map(|i| Instant::now() + i),
+        stack_start: stack_addr(),
+        ..Default::default()
+    };
+    exceeded
+}
+
+/* Limits can be caught like any language error, the step and time budgets
+ * stay exhausted, so handlers of those only get to run a few statements
+ */
+fn limits_throw(msg: &str, data: &DebugData) -> ! {
+    limits_get().exceeded = true;
+    exception_throw_lng("execution limit", msg, data);
+}
+
+/// Counts one executed statement and checks the step, heap and time budgets.
+pub fn limits_step(limits: &ExecLimits, data: &DebugData) {
+    let state = limits_get();
+    state.steps += 1;
+
+    if let Some(max) = limits.steps
+        && state.steps > max
+    {
+        limits_throw(&format!("exceeded the limit of {max} steps"), data);
+    }
+    if let Some(max) = limits.heap
+        && heap_live() > max
+    {
+        limits_throw(&format!("exceeded the limit of {max} heap objects"), data);
+    }
+    if let Some(deadline) = state.deadline
+        && state.steps.is_multiple_of(TIMEOUT_INTERVAL)
+        && Instant::now() > deadline
+    {
+        let timeout = limits.timeout.unwrap_or_default();
+        limits_throw(&format!("exceeded the timeout of {timeout:?}"), data);
+    }
+}
+
+/// Call depth of a running procedure, released again when it returns or
+/// unwinds.
+pub struct LimitsDepth;
+
+impl LimitsDepth {
+    pub fn enter(limits: &ExecLimits, data: &DebugData) -> Self {
+        let state = limits_get();
+        state.depth += 1;
+
+        if let Some(max) = limits.depth
+            && state.depth > max
+        {
+            state.depth -= 1;
+            limits_throw(&format!("exceeded the call depth of {max}"), data);
+        }
+        // the stack grows downwards on every supported target
+        if state.stack_start.saturating_sub(stack_addr()) > LIMITS_STACK {
+            state.depth -= 1;
+            limits_throw(
+                &format!("exceeded the stack after {} nested calls", state.depth),
+                data,
+            );
+        }
+
+        LimitsDepth
+    }
+}
+
+impl Drop for LimitsDepth {
+    fn drop(&mut self) {
+        let state = limits_get();
+        state.depth = state.depth.saturating_sub(1);
+    }
+}
@@ -1,11 +1,18 @@
 pub mod assign;
 pub mod ast;
+pub mod coverage;
 pub mod debug;
 pub mod except;
 pub mod exec;
 pub mod get;
+pub mod global;
 pub mod heap;
+pub mod io;
+#[cfg(feature = "jit")]
+pub mod jit;
+pub mod limits;
 pub mod memoize;
 pub mod ops;
+pub mod profile;
 pub mod serialize;
 pub mod stack;
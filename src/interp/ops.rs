@@ -1,4 +1,6 @@
 use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::Zero;
 use num_traits::cast::ToPrimitive;
 use rustyline::DefaultEditor;
 use std::cmp::Ordering;
@@ -12,6 +14,23 @@ use crate::interp::memoize::InterpMemoize;
 use crate::interp::serialize::{SerializeOpts, serialize};
 use crate::interp::stack::InterpStack;
 
+/// Returns the exact value of integers and rationals.
+pub fn obj_to_rational(obj: &InterpObj) -> Option<BigRational> {
+    match obj {
+        InterpObj::Number(n) => Some(BigRational::from_integer(n.clone())),
+        InterpObj::Rational(r) => Some(r.clone()),
+        _ => None,
+    }
+}
+
+pub fn obj_to_f64(obj: &InterpObj) -> Option<f64> {
+    match obj {
+        InterpObj::Number(n) => n.to_f64(),
+        InterpObj::Rational(r) => r.to_f64(),
+        _ => None,
+    }
+}
+
 fn tagged_cmp(t1: &InterpTaggedList, t2: &InterpTaggedList) -> Ordering {
     let tag_ord = t1.tag.cmp(&t2.tag);
     if tag_ord != Ordering::Equal {
@@ -26,6 +45,18 @@ fn tagged_cmp(t1: &InterpTaggedList, t2: &InterpTaggedList) -> Ordering {
         .unwrap_or(t1.list.len().cmp(&t2.list.len()))
 }
 
+/* Values of different types are ordered by their rank. Doubles rank like the
+ * numbers on the heap, otherwise the order of a set mixing them wouldn't be
+ * transitive.
+ */
+fn val_rank(val: &InterpVal) -> (u8, usize) {
+    match val {
+        InterpVal::Ref(r) => (val.tag(), unsafe { &*r.0 }.tag()),
+        InterpVal::Double(_) => (InterpVal::TAG_REF, InterpObj::TAG_NUMBER),
+        _ => (val.tag(), 0),
+    }
+}
+
 pub fn val_cmp(lhs: &InterpVal, rhs: &InterpVal) -> Ordering {
     match (lhs, rhs) {
         (InterpVal::Bool(bl), InterpVal::Bool(br)) => bl.cmp(br),
@@ -33,13 +64,14 @@ pub fn val_cmp(lhs: &InterpVal, rhs: &InterpVal) -> Ordering {
             dl.partial_cmp(dr).unwrap_or(Ordering::Less)
         }
         (InterpVal::Type(tl), InterpVal::Type(tr)) => tl.cmp(tr),
-        (InterpVal::Double(d), InterpVal::Ref(r)) | (InterpVal::Ref(r), InterpVal::Double(d)) => {
-            if let InterpObj::Number(i) = unsafe { &*r.0 } {
-                i.to_f64().unwrap().partial_cmp(d).unwrap_or(Ordering::Less)
-            } else {
-                lhs.tag().cmp(&rhs.tag())
-            }
-        }
+        (InterpVal::Double(d), InterpVal::Ref(r)) => match obj_to_f64(unsafe { &*r.0 }) {
+            Some(i) => d.partial_cmp(&i).unwrap_or(Ordering::Less),
+            None => val_rank(lhs).cmp(&val_rank(rhs)),
+        },
+        (InterpVal::Ref(r), InterpVal::Double(d)) => match obj_to_f64(unsafe { &*r.0 }) {
+            Some(i) => i.partial_cmp(d).unwrap_or(Ordering::Less),
+            None => val_rank(lhs).cmp(&val_rank(rhs)),
+        },
         (InterpVal::Slice(sl), InterpVal::Slice(sr)) => match (sl, sr) {
             (InterpSlice::StringSlice(s1), InterpSlice::StringSlice(s2)) => {
                 s1.slice.clone().cmp(s2.slice.clone())
@@ -50,7 +82,7 @@ pub fn val_cmp(lhs: &InterpVal, rhs: &InterpVal) -> Ordering {
                 .map(|(a, b)| val_cmp(a, b))
                 .find(|&ord| ord != Ordering::Equal)
                 .unwrap_or(ll.len().cmp(&lr.len())),
-            _ => lhs.tag().cmp(&rhs.tag()),
+            _ => val_rank(lhs).cmp(&val_rank(rhs)),
         },
         (InterpVal::Ref(r), InterpVal::Char(c)) | (InterpVal::Char(c), InterpVal::Ref(r)) => {
             if let InterpObj::String(s) = unsafe { &*r.0 }
@@ -58,7 +90,7 @@ pub fn val_cmp(lhs: &InterpVal, rhs: &InterpVal) -> Ordering {
             {
                 Ordering::Equal
             } else {
-                lhs.tag().cmp(&rhs.tag())
+                val_rank(lhs).cmp(&val_rank(rhs))
             }
         }
         (InterpVal::Slice(sl), InterpVal::Char(c)) | (InterpVal::Char(c), InterpVal::Slice(sl)) => {
@@ -67,7 +99,7 @@ pub fn val_cmp(lhs: &InterpVal, rhs: &InterpVal) -> Ordering {
             {
                 Ordering::Equal
             } else {
-                lhs.tag().cmp(&rhs.tag())
+                val_rank(lhs).cmp(&val_rank(rhs))
             }
         }
         (InterpVal::Ref(rl), InterpVal::Ref(rr)) => match (unsafe { &*rl.0 }, unsafe { &*rr.0 }) {
@@ -87,6 +119,10 @@ pub fn val_cmp(lhs: &InterpVal, rhs: &InterpVal) -> Ordering {
             }
             (InterpObj::String(s1), InterpObj::String(s2)) => s1.cmp(s2),
             (InterpObj::Number(n1), InterpObj::Number(n2)) => n1.cmp(n2),
+            (l @ InterpObj::Rational(_), r @ (InterpObj::Number(_) | InterpObj::Rational(_)))
+            | (l @ InterpObj::Number(_), r @ InterpObj::Rational(_)) => {
+                obj_to_rational(l).cmp(&obj_to_rational(r))
+            }
             (InterpObj::Term(t1), InterpObj::Term(t2))
             | (InterpObj::TTerm(t1), InterpObj::TTerm(t2))
             | (InterpObj::Ast(t1), InterpObj::Ast(t2)) => tagged_cmp(t1, t2),
@@ -99,7 +135,7 @@ pub fn val_cmp(lhs: &InterpVal, rhs: &InterpVal) -> Ordering {
             (InterpObj::Procedure(p1), InterpObj::Procedure(p2)) => {
                 tagged_cmp(p1.info.as_ref().unwrap(), p2.info.as_ref().unwrap())
             }
-            _ => unsafe { (&*rl.0).tag().cmp(&(&*rr.0).tag()) },
+            _ => val_rank(lhs).cmp(&val_rank(rhs)),
         },
         (InterpVal::Ref(r), InterpVal::Slice(s)) | (InterpVal::Slice(s), InterpVal::Ref(r)) => {
             match (unsafe { &*r.0 }, s) {
@@ -113,10 +149,10 @@ pub fn val_cmp(lhs: &InterpVal, rhs: &InterpVal) -> Ordering {
                         .find(|&ord| ord != Ordering::Equal)
                         .unwrap_or(ll.0.len().cmp(&lr.len()))
                 }
-                _ => lhs.tag().cmp(&rhs.tag()),
+                _ => val_rank(lhs).cmp(&val_rank(rhs)),
             }
         }
-        _ => lhs.tag().cmp(&rhs.tag()),
+        _ => val_rank(lhs).cmp(&val_rank(rhs)),
     }
 }
 
@@ -138,6 +174,8 @@ pub fn val_plus(
     // Double + Double
     // Number + Double
     // Number + Number
+    // Rational + Number
+    // Rational + Rational
     // Ast + anything
     // TTerm + anything
     // Term + anything
@@ -205,6 +243,12 @@ pub fn val_plus(
                 (InterpObj::Number(nl), InterpObj::Number(nr)) => {
                     InterpVal::Ref(heap.push_obj(InterpObj::Number(nl + nr)))
                 }
+                (InterpObj::Rational(_), InterpObj::Number(_) | InterpObj::Rational(_))
+                | (InterpObj::Number(_), InterpObj::Rational(_)) => {
+                    InterpVal::Ref(heap.push_obj(InterpObj::from_rational(
+                        obj_to_rational(rlr).unwrap() + obj_to_rational(rrr).unwrap(),
+                    )))
+                }
                 (InterpObj::Vector(vl), InterpObj::Vector(vr)) => {
                     InterpVal::Ref(heap.push_obj(InterpObj::Vector(vl + vr)))
                 }
@@ -293,9 +337,9 @@ pub fn val_plus(
                         SerializeOpts::default(),
                     ),
             ))),
-            InterpObj::Number(n) => {
+            n @ (InterpObj::Number(_) | InterpObj::Rational(_)) => {
                 if let InterpVal::Double(d) = rhs {
-                    InterpVal::Double(n.to_f64().unwrap() + d)
+                    InterpVal::Double(obj_to_f64(n).unwrap() + d)
                 } else {
                     exception_throw("ir-op", "plus is not defined for type", breakpoints);
                 }
@@ -357,9 +401,9 @@ pub fn val_plus(
                     SerializeOpts::default(),
                 ) + s,
             ))),
-            InterpObj::Number(n) => {
+            n @ (InterpObj::Number(_) | InterpObj::Rational(_)) => {
                 if let InterpVal::Double(d) = lhs {
-                    InterpVal::Double(n.to_f64().unwrap() + d)
+                    InterpVal::Double(obj_to_f64(n).unwrap() + d)
                 } else {
                     exception_throw("ir-op", "plus is not defined for type", breakpoints);
                 }
@@ -419,6 +463,8 @@ pub fn val_minus(
     // vector - vector
     // matrix - matrix
     // number - number
+    // rational - number
+    // rational - rational
     // number - double
     // double - number
     // double - double
@@ -438,22 +484,28 @@ pub fn val_minus(
             (InterpObj::Number(nl), InterpObj::Number(nr)) => {
                 InterpVal::Ref(heap.push_obj(InterpObj::Number(nl - nr)))
             }
+            (l @ InterpObj::Rational(_), r @ (InterpObj::Number(_) | InterpObj::Rational(_)))
+            | (l @ InterpObj::Number(_), r @ InterpObj::Rational(_)) => {
+                InterpVal::Ref(heap.push_obj(InterpObj::from_rational(
+                    obj_to_rational(l).unwrap() - obj_to_rational(r).unwrap(),
+                )))
+            }
             _ => exception_throw("ir-op", "minus is not defined for type", breakpoints),
         },
         (InterpVal::Ref(r), _) => {
-            if let InterpObj::Number(n) = unsafe { &*r.0 }
+            if let Some(n) = obj_to_f64(unsafe { &*r.0 })
                 && let InterpVal::Double(d) = rhs
             {
-                InterpVal::Double(n.to_f64().unwrap() - d)
+                InterpVal::Double(n - d)
             } else {
                 exception_throw("ir-op", "minus not defined for type", breakpoints)
             }
         }
         (_, InterpVal::Ref(r)) => {
-            if let InterpObj::Number(n) = unsafe { &*r.0 }
+            if let Some(n) = obj_to_f64(unsafe { &*r.0 })
                 && let InterpVal::Double(d) = lhs
             {
-                InterpVal::Double(d - n.to_f64().unwrap())
+                InterpVal::Double(d - n)
             } else {
                 exception_throw("ir-op", "minus not defined for type", breakpoints)
             }
@@ -478,6 +530,8 @@ pub fn val_mult(
     // vector * number
     // vector * double
     // number * number
+    // rational * number
+    // rational * rational
     // number * string
     // number * char
     // number * double
@@ -519,6 +573,12 @@ pub fn val_mult(
             (InterpObj::Number(l), InterpObj::Number(r)) => {
                 InterpVal::Ref(heap.push_obj(InterpObj::Number(l * r)))
             }
+            (l @ InterpObj::Rational(_), r @ (InterpObj::Number(_) | InterpObj::Rational(_)))
+            | (l @ InterpObj::Number(_), r @ InterpObj::Rational(_)) => {
+                InterpVal::Ref(heap.push_obj(InterpObj::from_rational(
+                    obj_to_rational(l).unwrap() * obj_to_rational(r).unwrap(),
+                )))
+            }
             (InterpObj::String(l), InterpObj::Number(r)) => {
                 InterpVal::Ref(heap.push_obj(InterpObj::String(l.repeat(r.to_usize().unwrap()))))
             }
@@ -539,7 +599,9 @@ pub fn val_mult(
             match unsafe { &*r.0 } {
                 InterpObj::Matrix(m) => InterpVal::Ref(heap.push_obj(InterpObj::Matrix(m * *d))),
                 InterpObj::Vector(v) => InterpVal::Ref(heap.push_obj(InterpObj::Vector(v * *d))),
-                InterpObj::Number(n) => InterpVal::Double(n.to_f64().unwrap() * d),
+                n @ (InterpObj::Number(_) | InterpObj::Rational(_)) => {
+                    InterpVal::Double(obj_to_f64(n).unwrap() * d)
+                }
                 _ => exception_throw("ir-op", "multiply is not defined for type", breakpoints),
             }
         }
@@ -558,6 +620,8 @@ pub fn val_quot(
     // vector / number
     // vector / double
     // number / number
+    // rational / number
+    // rational / rational
     // number / double
     // double / double
     // double / number
@@ -569,19 +633,33 @@ pub fn val_quot(
             (InterpObj::Vector(v), InterpObj::Number(n)) => {
                 InterpVal::Ref(heap.push_obj(InterpObj::Vector(v / n.to_f64().unwrap())))
             }
-            (InterpObj::Number(l), InterpObj::Number(r)) => {
-                InterpVal::Double(l.to_f64().unwrap() / r.to_f64().unwrap())
+            (
+                l @ (InterpObj::Number(_) | InterpObj::Rational(_)),
+                r @ (InterpObj::Number(_) | InterpObj::Rational(_)),
+            ) => {
+                let r = obj_to_rational(r).unwrap();
+                if r.is_zero() {
+                    exception_throw("ir-op", "division by zero", breakpoints);
+                }
+
+                InterpVal::Ref(
+                    heap.push_obj(InterpObj::from_rational(obj_to_rational(l).unwrap() / r)),
+                )
             }
             _ => exception_throw("ir-op", "divide is not defined for type", breakpoints),
         },
         (InterpVal::Ref(r), InterpVal::Double(d)) => match unsafe { &*r.0 } {
             InterpObj::Matrix(m) => InterpVal::Ref(heap.push_obj(InterpObj::Matrix(m / *d))),
             InterpObj::Vector(v) => InterpVal::Ref(heap.push_obj(InterpObj::Vector(v / *d))),
-            InterpObj::Number(n) => InterpVal::Double(n.to_f64().unwrap() / d),
+            n @ (InterpObj::Number(_) | InterpObj::Rational(_)) => {
+                InterpVal::Double(obj_to_f64(n).unwrap() / d)
+            }
             _ => exception_throw("ir-op", "divide is not defined for type", breakpoints),
         },
         (InterpVal::Double(d), InterpVal::Ref(r)) => match unsafe { &*r.0 } {
-            InterpObj::Number(n) => InterpVal::Double(d / n.to_f64().unwrap()),
+            n @ (InterpObj::Number(_) | InterpObj::Rational(_)) => {
+                InterpVal::Double(d / obj_to_f64(n).unwrap())
+            }
             _ => exception_throw("ir-op", "divide is not defined for type", breakpoints),
         },
         (InterpVal::Double(dl), InterpVal::Double(dr)) => InterpVal::Double(dl / dr),
@@ -596,6 +674,8 @@ pub fn val_int_quot(
     heap: &mut InterpImmediateHeap,
 ) -> InterpVal {
     // number / number
+    // rational / number
+    // rational / rational
     // number / double
     // double / double
     // double / number
@@ -604,18 +684,31 @@ pub fn val_int_quot(
             (InterpObj::Number(l), InterpObj::Number(r)) => {
                 InterpVal::Ref(heap.push_obj(InterpObj::Number(l / r)))
             }
+            (
+                l @ (InterpObj::Number(_) | InterpObj::Rational(_)),
+                r @ (InterpObj::Number(_) | InterpObj::Rational(_)),
+            ) => {
+                let r = obj_to_rational(r).unwrap();
+                if r.is_zero() {
+                    exception_throw("ir-op", "division by zero", breakpoints);
+                }
+
+                // truncated like the quotient of numbers
+                let quot = (obj_to_rational(l).unwrap() / r).trunc();
+                InterpVal::Ref(heap.push_obj(InterpObj::Number(quot.to_integer())))
+            }
             _ => exception_throw("ir-op", "int divide is not defined for type", breakpoints),
         },
         (InterpVal::Ref(r), InterpVal::Double(d)) => match unsafe { &*r.0 } {
-            InterpObj::Number(n) => InterpVal::Ref(heap.push_obj(InterpObj::Number(BigInt::from(
-                (n.to_f64().unwrap() / d) as i64,
-            )))),
+            n @ (InterpObj::Number(_) | InterpObj::Rational(_)) => InterpVal::Ref(heap.push_obj(
+                InterpObj::Number(BigInt::from((obj_to_f64(n).unwrap() / d) as i64)),
+            )),
             _ => exception_throw("ir-op", "int divide is not defined for type", breakpoints),
         },
         (InterpVal::Double(d), InterpVal::Ref(r)) => match unsafe { &*r.0 } {
-            InterpObj::Number(n) => InterpVal::Ref(heap.push_obj(InterpObj::Number(BigInt::from(
-                (d / n.to_f64().unwrap()) as i64,
-            )))),
+            n @ (InterpObj::Number(_) | InterpObj::Rational(_)) => InterpVal::Ref(heap.push_obj(
+                InterpObj::Number(BigInt::from((d / obj_to_f64(n).unwrap()) as i64)),
+            )),
             _ => exception_throw("ir-op", "int divide is not defined for type", breakpoints),
         },
         (InterpVal::Double(dl), InterpVal::Double(dr)) => {
@@ -633,6 +726,8 @@ pub fn val_mod(
 ) -> InterpVal {
     // set % set
     // number % number
+    // rational % number
+    // rational % rational
     // double % number
     // number % double
     // double % double
@@ -648,18 +743,31 @@ pub fn val_mod(
             (InterpObj::Number(nl), InterpObj::Number(nr)) => {
                 InterpVal::Ref(heap.push_obj(InterpObj::Number(nl % nr)))
             }
+            (
+                l @ (InterpObj::Number(_) | InterpObj::Rational(_)),
+                r @ (InterpObj::Number(_) | InterpObj::Rational(_)),
+            ) => {
+                let r = obj_to_rational(r).unwrap();
+                if r.is_zero() {
+                    exception_throw("ir-op", "division by zero", breakpoints);
+                }
+
+                InterpVal::Ref(
+                    heap.push_obj(InterpObj::from_rational(obj_to_rational(l).unwrap() % r)),
+                )
+            }
             _ => exception_throw("ir-op", "mod is not defined for type", breakpoints),
         },
         (InterpVal::Double(d), InterpVal::Ref(r)) => match unsafe { &*r.0 } {
-            InterpObj::Number(n) => InterpVal::Ref(heap.push_obj(InterpObj::Number(BigInt::from(
-                (d % n.to_f64().unwrap()) as i64,
-            )))),
+            n @ (InterpObj::Number(_) | InterpObj::Rational(_)) => InterpVal::Ref(heap.push_obj(
+                InterpObj::Number(BigInt::from((d % obj_to_f64(n).unwrap()) as i64)),
+            )),
             _ => exception_throw("ir-op", "mod is not defined for type", breakpoints),
         },
         (InterpVal::Ref(r), InterpVal::Double(d)) => match unsafe { &*r.0 } {
-            InterpObj::Number(n) => InterpVal::Ref(heap.push_obj(InterpObj::Number(BigInt::from(
-                (n.to_f64().unwrap() % d) as i64,
-            )))),
+            n @ (InterpObj::Number(_) | InterpObj::Rational(_)) => InterpVal::Ref(heap.push_obj(
+                InterpObj::Number(BigInt::from((obj_to_f64(n).unwrap() % d) as i64)),
+            )),
             _ => exception_throw("ir-op", "mod is not defined for type", breakpoints),
         },
         (InterpVal::Double(dl), InterpVal::Double(dr)) => {
@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::interp::debug::DebugData;
+
+/// Source lines listed in the report
+const REPORT_LINES: usize = 20;
+
+#[derive(Default)]
+struct ProfileStats {
+    calls: u64,
+    steps: u64,
+    /// time spent in the procedure or line itself
+    time: Duration,
+    /// time including nested calls, counting recursive calls once
+    total: Duration,
+    /// smallest code offset annotated inside the procedure
+    first_code: Option<usize>,
+}
+
+struct ProfileFrame {
+    tag: String,
+    start: Instant,
+    children: Duration,
+    steps: u64,
+    /// code offset of the statement being executed, and since when
+    code: Option<usize>,
+    code_start: Instant,
+    code_steps: u64,
+}
+
+#[derive(Default)]
+struct Profiler {
+    frames: Vec<ProfileFrame>,
+    procs: BTreeMap<String, ProfileStats>,
+    /// keyed by the code offset of the statement
+    code: BTreeMap<usize, ProfileStats>,
+    /// time spent in each chain of calls, outermost first
+    folded: BTreeMap<Vec<String>, Duration>,
+    steps: u64,
+    start: Option<Instant>,
+}
+
+static mut PROFILE: Option<Profiler> = None;
+
+#[allow(static_mut_refs)]
+fn profile_get() -> &'static mut Profiler {
+    //SAFETY: setlx-rs is single-threaded
+    unsafe { PROFILE.get_or_insert_with(Profiler::default) }
+}
+
+/// Discards the measurements of a previous run.
+pub fn profile_reset() {
+    *profile_get() = Profiler {
+        start: Some(Instant::now()),
+        ..Default::default()
+    };
+}
+
+impl ProfileFrame {
+    /// Attributes the time and steps since the last change of statement to
+    /// that statement.
+    fn code_flush(&mut self, code: &mut BTreeMap<usize, ProfileStats>, now: Instant) {
+        if let Some(offset) = self.code {
+            let stats = code.entry(offset).or_default();
+            stats.steps += self.code_steps;
+            stats.time += now - self.code_start;
+        }
+        self.code_start = now;
+        self.code_steps = 0;
+    }
+}
+
+/// Counts one executed IR statement.
+pub fn profile_step() {
+    let profile = profile_get();
+    profile.steps += 1;
+    if let Some(frame) = profile.frames.last_mut() {
+        frame.steps += 1;
+        frame.code_steps += 1;
+    }
+}
+
+/// Marks the start of the source range at code offset `offset`.
+pub fn profile_code(offset: usize) {
+    let profile = profile_get();
+    let Some(frame) = profile.frames.last_mut() else {
+        return;
+    };
+    if frame.code == Some(offset) {
+        return;
+    }
+
+    frame.code_flush(&mut profile.code, Instant::now());
+    frame.code = Some(offset);
+
+    let first = &mut profile
+        .procs
+        .entry(frame.tag.clone())
+        .or_default()
+        .first_code;
+    if first.is_none_or(|i| offset < i) {
+        *first = Some(offset);
+    }
+}
+
+/// Measures a running procedure until it returns or unwinds.
+pub struct ProfileCall;
+
+impl ProfileCall {
+    pub fn enter(tag: &str) -> Self {
+        let profile = profile_get();
+        let now = Instant::now();
+        if let Some(caller) = profile.frames.last_mut() {
+            caller.code_flush(&mut profile.code, now);
+        }
+
+        profile.frames.push(ProfileFrame {
+            tag: tag.to_string(),
+            start: now,
+            children: Duration::ZERO,
+            steps: 0,
+            code: None,
+            code_start: now,
+            code_steps: 0,
+        });
+        ProfileCall
+    }
+}
+
+impl Drop for ProfileCall {
+    fn drop(&mut self) {
+        let profile = profile_get();
+        let now = Instant::now();
+        let Some(mut frame) = profile.frames.pop() else {
+            return;
+        };
+        frame.code_flush(&mut profile.code, now);
+
+        let total = now - frame.start;
+        let time = total.saturating_sub(frame.children);
+        let recursive = profile.frames.iter().any(|i| i.tag == frame.tag);
+
+        let path = profile
+            .frames
+            .iter()
+            .map(|i| i.tag.clone())
+            .chain(std::iter::once(frame.tag.clone()))
+            .collect();
+        *profile.folded.entry(path).or_default() += time;
+
+        let stats = profile.procs.entry(frame.tag).or_default();
+        stats.calls += 1;
+        stats.steps += frame.steps;
+        stats.time += time;
+        if !recursive {
+            stats.total += total;
+        }
+
+        if let Some(caller) = profile.frames.last_mut() {
+            caller.children += total;
+            caller.code_start = now;
+        }
+    }
+}
+
+/// Ends the procedures still running, for programs stopped by `exit`.
+pub fn profile_unwind() {
+    while !profile_get().frames.is_empty() {
+        drop(ProfileCall);
+    }
+}
+
+/// Procedures are only known by their tag, so user defined ones are named
+/// after the line their body starts at.
+fn profile_proc_name(tag: &str, stats: &ProfileStats, data: &DebugData) -> String {
+    match stats.first_code {
+        Some(offset) => {
+            let (line, _) = data.line_col(offset);
+            format!("{tag} ({}:{line})", data.src_get(offset).name)
+        }
+        None => tag.to_string(),
+    }
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Procedures sorted by their own time, followed by the most expensive source
+/// lines.
+pub fn profile_report(data: &DebugData) -> String {
+    let profile = profile_get();
+    let elapsed = profile.start.map(|i| i.elapsed()).unwrap_or_default();
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        "profile: {} steps in {:.3} ms\n",
+        profile.steps,
+        ms(elapsed)
+    );
+
+    let mut procs = profile.procs.iter().collect::<Vec<_>>();
+    procs.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(a.0.cmp(b.0)));
+    let _ = writeln!(
+        out,
+        "{:<40} {:>10} {:>12} {:>12} {:>12}",
+        "procedure", "calls", "steps", "self ms", "total ms"
+    );
+    for (tag, stats) in procs {
+        let _ = writeln!(
+            out,
+            "{:<40} {:>10} {:>12} {:>12.3} {:>12.3}",
+            profile_proc_name(tag, stats, data),
+            stats.calls,
+            stats.steps,
+            ms(stats.time),
+            ms(stats.total)
+        );
+    }
+
+    let mut lines: BTreeMap<(&str, usize), ProfileStats> = BTreeMap::new();
+    for (offset, stats) in &profile.code {
+        let (line, _) = data.line_col(*offset);
+        let entry = lines
+            .entry((&data.src_get(*offset).name, line))
+            .or_default();
+        entry.steps += stats.steps;
+        entry.time += stats.time;
+    }
+
+    let mut lines = lines.into_iter().collect::<Vec<_>>();
+    lines.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(a.0.cmp(&b.0)));
+    let _ = writeln!(
+        out,
+        "\n{:<40} {:>12} {:>12}  source",
+        "line", "steps", "self ms"
+    );
+    for ((srcname, line), stats) in lines.into_iter().take(REPORT_LINES) {
+        let src = data
+            .sources
+            .iter()
+            .find(|i| i.name == srcname)
+            .map(|i| i.line(line).trim())
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "{:<40} {:>12} {:>12.3}  {}",
+            format!("{srcname}:{line}"),
+            stats.steps,
+            ms(stats.time),
+            src.chars().take(60).collect::<String>()
+        );
+    }
+
+    out
+}
+
+/// Writes the time spent in each chain of calls in microseconds, in the
+/// folded stack format read by flamegraph tools.
+pub fn profile_folded_write(path: &Path, data: &DebugData) -> io::Result<()> {
+    let profile = profile_get();
+    let mut out = String::new();
+    for (path, time) in &profile.folded {
+        let names = path
+            .iter()
+            .map(|tag| match profile.procs.get(tag) {
+                Some(stats) => profile_proc_name(tag, stats, data),
+                None => tag.clone(),
+            })
+            .collect::<Vec<_>>();
+        let _ = writeln!(out, "{} {}", names.join(";"), time.as_micros());
+    }
+    std::fs::write(path, out)
+}
@@ -2437,6 +2437,7 @@ pub fn serialize(
                     .collect::<Vec<String>>()
                     .join(", "),
             ),
+            InterpIter::FileIter(..) => String::from("/* file */"),
         },
         InterpVal::Ptr(p) => {
             let sgmt = match &p.sgmt {
@@ -2514,6 +2515,7 @@ pub fn serialize(
                 }
             }
             InterpObj::Number(n) => n.to_string(),
+            InterpObj::Rational(r) => r.to_string(),
             InterpObj::Set(s) => format!(
                 "{{{}}}",
                 s.0.iter()
@@ -2687,6 +2689,7 @@ pub fn serialize(
             }
             InterpObj::Regex(_) => String::from("/* compiled regex */"),
             InterpObj::File(_) => String::from("/* file */"),
+            InterpObj::Canvas(_) => String::from("/* canvas */"),
             InterpObj::Uninitialized => String::from("om"),
         },
         InterpVal::Undefined => String::from("om"),
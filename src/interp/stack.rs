@@ -1,18 +1,21 @@
 use std::collections::BTreeMap;
+use std::rc::Rc;
 
+use crate::builtin::native::NativeProc;
 use crate::builtin::stubs::stubs_init;
 use crate::interp::heap::*;
 use crate::interp::memoize::InterpStackImage;
+use crate::ir::symbol::IRSymbol;
 
 #[derive(Debug, Clone)]
 pub struct InterpStackVar {
-    pub var: String,
+    pub var: IRSymbol,
     pub val: Box<InterpVal>,
 }
 
 #[derive(Debug, Clone)]
 pub struct InterpStackAlias {
-    pub var: String,
+    pub var: IRSymbol,
     pub ptr: *mut InterpVal,
     pub cross_frame: bool,
 }
@@ -24,33 +27,113 @@ pub enum InterpStackEntry {
     Variable(InterpStackVar),
 }
 
+impl InterpStackEntry {
+    fn var(&self) -> Option<IRSymbol> {
+        match self {
+            InterpStackEntry::StackFrameBoundary => None,
+            InterpStackEntry::Alias(v) => Some(v.var),
+            InterpStackEntry::Variable(v) => Some(v.var),
+        }
+    }
+
+    /// Whether the entry is visible from the frames of called procedures
+    fn crosses_frames(&self) -> bool {
+        match self {
+            InterpStackEntry::StackFrameBoundary => false,
+            InterpStackEntry::Variable(v) => v.val.crosses_frames(),
+            // SAFETY: IR-PTR
+            InterpStackEntry::Alias(v) => v.cross_frame || unsafe { (*v.ptr).crosses_frames() },
+        }
+    }
+}
+
+/* Entries are searched from the top of the stack, skipping entries of
+ * enclosing frames unless they cross frames. Instead of scanning `frames`,
+ * lookups walk the positions binding the searched symbol, which are rarely
+ * more than a few even when the stack holds all builtins and a deep
+ * recursion.
+ */
 #[derive(Debug)]
 pub struct InterpStack {
-    pub frames: Vec<InterpStackEntry>,
+    frames: Vec<InterpStackEntry>,
+    /// positions in `frames` binding each symbol, indexed by symbol
+    bindings: Vec<Vec<usize>>,
+    /// positions of the frame boundaries in `frames`
+    boundaries: Vec<usize>,
 }
 
 impl Default for InterpStack {
     fn default() -> Self {
-        Self::new()
+        Self::new(&[])
     }
 }
 
 impl InterpStack {
-    pub fn new() -> Self {
-        InterpStack {
-            frames: stubs_init(),
+    pub fn new(natives: &[Rc<NativeProc>]) -> Self {
+        let mut stack = InterpStack {
+            frames: Vec::new(),
+            bindings: Vec::new(),
+            boundaries: Vec::new(),
+        };
+        stubs_init(natives).into_iter().for_each(|i| stack.push(i));
+        stack
+    }
+
+    pub fn entries(&self) -> &[InterpStackEntry] {
+        &self.frames
+    }
+
+    fn bindings_get(&mut self, var: IRSymbol) -> &mut Vec<usize> {
+        if self.bindings.len() <= var.index() {
+            self.bindings.resize_with(var.index() + 1, Vec::new);
+        }
+        &mut self.bindings[var.index()]
+    }
+
+    pub fn push(&mut self, entry: InterpStackEntry) {
+        let pos = self.frames.len();
+        match entry.var() {
+            Some(var) => self.bindings_get(var).push(pos),
+            None => self.boundaries.push(pos),
+        }
+        self.frames.push(entry);
+    }
+
+    fn truncate(&mut self, len: usize) {
+        while self.frames.len() > len {
+            let entry = self.frames.pop().unwrap();
+            match entry.var() {
+                Some(var) => {
+                    self.bindings[var.index()].pop();
+                }
+                None => {
+                    self.boundaries.pop();
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, pos: usize) {
+        if let Some(var) = self.frames.remove(pos).var() {
+            self.bindings[var.index()].retain(|i| *i != pos);
+        }
+
+        for idx in pos..self.frames.len() {
+            let moved = match self.frames[idx].var() {
+                Some(var) => &mut self.bindings[var.index()],
+                None => &mut self.boundaries,
+            };
+            if let Some(i) = moved.iter_mut().rev().find(|i| **i == idx + 1) {
+                *i = idx;
+            }
         }
     }
 
     pub fn frame_push(&mut self) {
-        self.frames.push(InterpStackEntry::StackFrameBoundary);
+        self.push(InterpStackEntry::StackFrameBoundary);
     }
     pub fn frame_pop(&mut self) {
-        let pos = self
-            .frames
-            .iter()
-            .rposition(|e| matches!(*e, InterpStackEntry::StackFrameBoundary))
-            .unwrap_or(0);
+        let pos = self.boundaries.last().copied().unwrap_or(0);
 
         self.frames.iter().skip(pos).for_each(|i| {
             if let InterpStackEntry::Variable(v) = i
@@ -62,7 +145,15 @@ impl InterpStack {
             }
         });
 
-        self.frames.truncate(pos);
+        self.truncate(pos);
+    }
+
+    /// Pops the frames of procedures that were left by an exception after
+    /// the stack had `len` entries.
+    pub fn unwind(&mut self, len: usize) {
+        while self.boundaries.last().is_some_and(|i| *i >= len) {
+            self.frame_pop();
+        }
     }
 
     pub fn frame_save(&mut self) -> InterpStackImage {
@@ -71,7 +162,7 @@ impl InterpStack {
         for (idx, entry) in self.frames.iter().enumerate().rev() {
             match entry {
                 InterpStackEntry::StackFrameBoundary => {
-                    self.frames.truncate(idx);
+                    self.truncate(idx);
                     break;
                 }
                 InterpStackEntry::Variable(v) => {
@@ -159,125 +250,50 @@ impl InterpStack {
 
     pub fn frame_restore(&mut self, image: &InterpStackImage) {
         image.0.iter().for_each(|(name, val)| {
-            self.frames.push(InterpStackEntry::Variable(InterpStackVar {
-                var: name.to_string(),
+            self.push(InterpStackEntry::Variable(InterpStackVar {
+                var: IRSymbol::new(name),
                 val: Box::new(val.unshare()),
             }))
         });
     }
 
-    pub fn get_pos(&mut self, input: &str) -> Option<(usize, bool)> {
-        let mut cross_frame = false;
-
-        for (idx, i) in self.frames.iter_mut().enumerate().rev() {
-            match i {
-                InterpStackEntry::StackFrameBoundary => {
-                    cross_frame = true;
-                }
-                InterpStackEntry::Variable(v) => {
-                    if v.var != input {
-                        continue;
-                    }
-
-                    if cross_frame {
-                        if v.val.crosses_frames() {
-                            return Some((idx, cross_frame));
-                        }
-                    } else {
-                        return Some((idx, cross_frame));
-                    }
-                }
-                InterpStackEntry::Alias(v) => {
-                    if v.var != input {
-                        continue;
-                    }
-
-                    if cross_frame {
-                        unsafe {
-                            if v.cross_frame || (*v.ptr).crosses_frames() {
-                                return Some((idx, cross_frame));
-                            }
-                        }
-                    } else {
-                        return Some((idx, cross_frame));
-                    }
-                }
-            }
-        }
+    pub fn get_pos(&self, input: IRSymbol) -> Option<(usize, bool)> {
+        let boundary = self.boundaries.last().copied().unwrap_or(0);
 
-        None
+        self.bindings
+            .get(input.index())?
+            .iter()
+            .rev()
+            .map(|idx| (*idx, *idx < boundary))
+            .find(|(idx, cross_frame)| !cross_frame || self.frames[*idx].crosses_frames())
     }
 
-    pub fn get(&mut self, input: &str) -> Option<InterpVal> {
-        let mut cross_frame = false;
-
-        for i in self.frames.iter_mut().rev() {
-            match i {
-                InterpStackEntry::StackFrameBoundary => {
-                    cross_frame = true;
-                }
-                InterpStackEntry::Variable(v) => {
-                    if v.var != input {
-                        continue;
-                    }
-
-                    if cross_frame {
-                        if v.val.crosses_frames() {
-                            return Some(InterpVal::Ptr(InterpPtr {
-                                sgmt: InterpPtrSgmt::Stack,
-                                ptr: &mut *v.val,
-                            }));
-                        }
-                    } else {
-                        return Some(InterpVal::Ptr(InterpPtr {
-                            sgmt: InterpPtrSgmt::Stack,
-                            ptr: &mut *v.val,
-                        }));
-                    }
-                }
-                InterpStackEntry::Alias(v) => {
-                    if v.var != input {
-                        continue;
-                    }
-
-                    unsafe {
-                        if cross_frame {
-                            if v.cross_frame || (*v.ptr).crosses_frames() {
-                                return Some(InterpVal::Ptr(InterpPtr {
-                                    sgmt: InterpPtrSgmt::Stack,
-                                    ptr: v.ptr,
-                                }));
-                            }
-                        } else {
-                            return Some(InterpVal::Ptr(InterpPtr {
-                                sgmt: InterpPtrSgmt::Stack,
-                                ptr: v.ptr,
-                            }));
-                        }
-                    }
-                }
-            }
-        }
-
-        None
+    pub fn get(&mut self, input: IRSymbol) -> Option<InterpVal> {
+        let (idx, _) = self.get_pos(input)?;
+
+        let ptr = match &mut self.frames[idx] {
+            InterpStackEntry::Variable(v) => &mut *v.val as *mut InterpVal,
+            InterpStackEntry::Alias(v) => v.ptr,
+            InterpStackEntry::StackFrameBoundary => unreachable!(),
+        };
+        Some(InterpVal::Ptr(InterpPtr {
+            sgmt: InterpPtrSgmt::Stack,
+            ptr,
+        }))
     }
-    pub fn add(&mut self, input: &str) -> InterpVal {
-        let entry = InterpStackEntry::Variable(InterpStackVar {
-            var: input.to_string(),
-            val: if let Some(val) = self.get(input) {
-                if let InterpVal::Ptr(p) = val {
-                    Box::new(unsafe {&*p.ptr}.unshare())
-                } else {
-                    unreachable!();
-                }
-            } else {
-                Box::new(InterpVal::Undefined)
-            },
-        });
-        self.frames.push(entry);
 
-        let f_len = self.frames.len() - 1;
-        if let InterpStackEntry::Variable(v) = &mut self.frames[f_len] {
+    pub fn add(&mut self, input: IRSymbol) -> InterpVal {
+        let val = match self.get(input) {
+            Some(InterpVal::Ptr(p)) => Box::new(unsafe { &*p.ptr }.unshare()),
+            Some(_) => unreachable!(),
+            None => Box::new(InterpVal::Undefined),
+        };
+        self.push(InterpStackEntry::Variable(InterpStackVar {
+            var: input,
+            val,
+        }));
+
+        if let Some(InterpStackEntry::Variable(v)) = self.frames.last_mut() {
             InterpVal::Ptr(InterpPtr {
                 sgmt: InterpPtrSgmt::Stack,
                 ptr: &mut *v.val,
@@ -287,16 +303,16 @@ impl InterpStack {
         }
     }
 
-    pub fn alias(&mut self, name: &str, ptr: &InterpPtr, cross_frame: bool) {
-        self.frames.push(InterpStackEntry::Alias(InterpStackAlias {
-            var: name.to_string(),
+    pub fn alias(&mut self, name: IRSymbol, ptr: &InterpPtr, cross_frame: bool) {
+        self.push(InterpStackEntry::Alias(InterpStackAlias {
+            var: name,
             ptr: ptr.ptr,
             cross_frame,
         }));
     }
 
-    pub fn pop(&mut self, input: &str) {
+    pub fn pop(&mut self, input: IRSymbol) {
         let (pos, _) = self.get_pos(input).unwrap();
-        self.frames.remove(pos);
+        self.remove(pos);
     }
 }
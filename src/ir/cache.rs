@@ -0,0 +1,633 @@
+use num_bigint::BigInt;
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+use petgraph::visit::{EdgeRef, IntoEdgeReferences, NodeIndexable};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::builtin::{BuiltinProc, BuiltinVar};
+use crate::cli::InputOpts;
+use crate::ir::def::*;
+use crate::ir::symbol::IRSymbol;
+
+const IR_CACHE_MAGIC: &[u8] = b"STLXC\n";
+/// Has to be bumped whenever the encoding below changes. Caches written by
+/// another version of setlx-rs are rejected as well, since the lowering may
+/// differ.
+const IR_CACHE_VERSION: u32 = 2;
+
+/// Lowered IR of a source file, as written by `--compile` to a `.stlxc` file.
+/// The program and module forms are decoded on demand.
+pub struct IRCache {
+    /// source the IR was lowered from, needed for reports
+    pub src: String,
+    /// `IRCfg::from_cst`, executed when the cache is run directly
+    program: Vec<u8>,
+    /// `IRCfg::from_stmt`, executed by `load` and `loadLibrary`
+    module: Vec<u8>,
+}
+
+pub fn ir_cache_path(src: &Path) -> PathBuf {
+    src.with_extension("stlxc")
+}
+
+/// Whether the cache of `src` exists and was written after `src` was last
+/// modified.
+pub fn ir_cache_fresh(src: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|i| i.modified()).ok();
+    match (modified(src), modified(&ir_cache_path(src))) {
+        (Some(src), Some(cache)) => cache >= src,
+        _ => false,
+    }
+}
+
+impl IRCache {
+    pub fn new(
+        src: &str,
+        program: &IRCfg,
+        module: &Rc<RefCell<IRProcedure>>,
+        opts: &InputOpts,
+    ) -> Result<Self, String> {
+        let mut w = IRCacheWriter::default();
+        w.u8(opts.opt_level);
+        w.u8(opts.disable_annotations as u8);
+        let procs = program.procedures.node_weights().collect::<Vec<_>>();
+        w.u32(procs.len() as u32);
+        for proc in procs {
+            w.proc_ref(proc);
+        }
+        w.proc_ref(&program.main);
+        let program = w.finish()?;
+
+        let mut w = IRCacheWriter::default();
+        w.proc_ref(module);
+        let module = w.finish()?;
+
+        Ok(IRCache {
+            src: src.to_string(),
+            program,
+            module,
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let mut w = IRCacheWriter::default();
+        w.out.extend_from_slice(IR_CACHE_MAGIC);
+        w.u32(IR_CACHE_VERSION);
+        w.str(env!("CARGO_PKG_VERSION"));
+        w.str(&self.src);
+        w.bytes(&self.program);
+        w.bytes(&self.module);
+        fs::write(path, w.out).map_err(|e| e.to_string())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let buf = fs::read(path).map_err(|e| e.to_string())?;
+        let mut r = IRCacheReader::new(&buf, 0);
+        if r.take(IR_CACHE_MAGIC.len())? != IR_CACHE_MAGIC {
+            return Err(String::from("not a compiled SetlX file"));
+        }
+
+        let version = r.u32()?;
+        let pkg_version = r.str()?;
+        if version != IR_CACHE_VERSION || pkg_version != env!("CARGO_PKG_VERSION") {
+            return Err(format!(
+                "compiled by setlx-rs {pkg_version}, compile the source again"
+            ));
+        }
+
+        Ok(IRCache {
+            src: r.str()?,
+            program: r.take_bytes()?.to_vec(),
+            module: r.take_bytes()?.to_vec(),
+        })
+    }
+
+    /// Decodes the program form, which has to be lowered with the same
+    /// options as `opts`.
+    pub fn program(&self, opts: &InputOpts) -> Result<IRCfg, String> {
+        let mut r = IRCacheReader::new(&self.program, 0);
+        if r.u8()? != opts.opt_level || r.u8()? != opts.disable_annotations as u8 {
+            return Err(String::from(
+                "compiled with other optimization or annotation options",
+            ));
+        }
+
+        let mut procedures = StableGraph::new();
+        for _ in 0..r.u32()? {
+            procedures.add_node(r.proc_ref()?);
+        }
+        let main = r.proc_ref()?;
+        r.finish()?;
+
+        Ok(IRCfg { procedures, main })
+    }
+
+    /// Decodes the module form, with its annotations moved to `base`, the
+    /// offset of the source in the debug data.
+    pub fn module(&self, base: usize) -> Result<Rc<RefCell<IRProcedure>>, String> {
+        let mut r = IRCacheReader::new(&self.module, base);
+        let out = r.proc_ref()?;
+        r.finish()?;
+        Ok(out)
+    }
+}
+
+/* Procedures are shared between values, so each one is written once to a
+ * table following the section and referenced by its index.
+ */
+#[derive(Default)]
+struct IRCacheWriter {
+    out: Vec<u8>,
+    ids: HashMap<*const RefCell<IRProcedure>, u32>,
+    pending: VecDeque<Rc<RefCell<IRProcedure>>>,
+    error: Option<String>,
+}
+
+impl IRCacheWriter {
+    fn u8(&mut self, i: u8) {
+        self.out.push(i);
+    }
+
+    fn u32(&mut self, i: u32) {
+        self.out.extend_from_slice(&i.to_le_bytes());
+    }
+
+    fn u64(&mut self, i: u64) {
+        self.out.extend_from_slice(&i.to_le_bytes());
+    }
+
+    fn usize(&mut self, i: usize) {
+        self.u64(i as u64);
+    }
+
+    fn bytes(&mut self, i: &[u8]) {
+        self.usize(i.len());
+        self.out.extend_from_slice(i);
+    }
+
+    fn str(&mut self, i: &str) {
+        self.bytes(i.as_bytes());
+    }
+
+    fn node(&mut self, i: NodeIndex) {
+        self.u32(i.index() as u32);
+    }
+
+    fn proc_ref(&mut self, proc: &Rc<RefCell<IRProcedure>>) {
+        let next = self.ids.len() as u32;
+        let id = *self.ids.entry(Rc::as_ptr(proc)).or_insert_with(|| {
+            self.pending.push_back(proc.clone());
+            next
+        });
+        self.u32(id);
+    }
+
+    fn finish(mut self) -> Result<Vec<u8>, String> {
+        let mut table = IRCacheWriter {
+            ids: std::mem::take(&mut self.ids),
+            pending: std::mem::take(&mut self.pending),
+            ..Default::default()
+        };
+        let mut count = 0;
+        while let Some(proc) = table.pending.pop_front() {
+            table.proc(&proc.borrow());
+            count += 1;
+        }
+        if let Some(e) = table.error {
+            return Err(e);
+        }
+
+        self.u32(count);
+        self.out.extend_from_slice(&table.out);
+        Ok(self.out)
+    }
+
+    fn proc(&mut self, proc: &IRProcedure) {
+        self.str(&proc.tag);
+        self.node(proc.start_block);
+        self.node(proc.end_block);
+        self.usize(proc.vars.len());
+        for var in &proc.vars {
+            self.usize(*var);
+        }
+
+        self.u32(proc.blocks.node_bound() as u32);
+        for i in 0..proc.blocks.node_bound() {
+            match proc.blocks.node_weight(NodeIndex::new(i)) {
+                Some(block) => {
+                    self.u8(1);
+                    self.usize(block.len());
+                    for stmt in block {
+                        self.stmt(stmt);
+                    }
+                }
+                None => self.u8(0),
+            }
+        }
+
+        self.usize(proc.blocks.edge_count());
+        for edge in proc.blocks.edge_references() {
+            self.node(edge.source());
+            self.node(edge.target());
+        }
+    }
+
+    fn stmt(&mut self, stmt: &IRStmt) {
+        match stmt {
+            IRStmt::Annotate(lhs, rhs, start) => {
+                self.u8(0);
+                self.usize(*lhs);
+                self.usize(*rhs);
+                self.u8(*start as u8);
+            }
+            IRStmt::Assign(a) => {
+                self.u8(1);
+                self.target(a.target);
+                self.u32(a.types.bits());
+                self.val(&a.source);
+                self.op(&a.op);
+            }
+            IRStmt::Branch(b) => {
+                self.u8(2);
+                self.val(&b.cond);
+                self.node(b.success);
+                self.node(b.failure);
+                self.u8(b.source as u8);
+            }
+            IRStmt::Try(t) => {
+                self.u8(3);
+                self.node(t.attempt);
+                self.node(t.catch);
+            }
+            IRStmt::TryEnd(next) => {
+                self.u8(4);
+                self.node(*next);
+            }
+            IRStmt::Goto(next) => {
+                self.u8(5);
+                self.node(*next);
+            }
+            IRStmt::Return(val) => {
+                self.u8(6);
+                self.val(val);
+            }
+            IRStmt::Unreachable => self.u8(7),
+        }
+    }
+
+    fn target(&mut self, target: IRTarget) {
+        match target {
+            IRTarget::Ignore => self.u8(0),
+            IRTarget::Variable(var) => {
+                self.u8(1);
+                self.usize(var);
+            }
+            IRTarget::Deref(var) => {
+                self.u8(2);
+                self.usize(var);
+            }
+        }
+    }
+
+    fn op(&mut self, op: &IROp) {
+        let (tag, rhs) = match op {
+            IROp::AccessArray(rhs) => (0, rhs),
+            IROp::Call(var) => {
+                self.u8(1);
+                self.usize(*var);
+                return;
+            }
+            IROp::NativeCall(params) => {
+                self.u8(2);
+                self.vals(params);
+                return;
+            }
+            IROp::PtrAddress => return self.u8(3),
+            IROp::PtrDeref => return self.u8(4),
+            IROp::Assign => return self.u8(5),
+            IROp::Or(rhs) => (6, rhs),
+            IROp::And(rhs) => (7, rhs),
+            IROp::Not => return self.u8(8),
+            IROp::Less(rhs) => (9, rhs),
+            IROp::Equal(rhs) => (10, rhs),
+            IROp::Plus(rhs) => (11, rhs),
+            IROp::Minus(rhs) => (12, rhs),
+            IROp::Mult(rhs) => (13, rhs),
+            IROp::Divide(rhs) => (14, rhs),
+            IROp::IntDivide(rhs) => (15, rhs),
+            IROp::Mod(rhs) => (16, rhs),
+        };
+        self.u8(tag);
+        self.val(rhs);
+    }
+
+    fn vals(&mut self, vals: &[IRValue]) {
+        self.usize(vals.len());
+        for val in vals {
+            self.val(val);
+        }
+    }
+
+    fn val(&mut self, val: &IRValue) {
+        match val {
+            IRValue::Undefined => self.u8(0),
+            IRValue::BuiltinProc(p) => {
+                self.u8(1);
+                self.str(&p.to_string());
+            }
+            IRValue::BuiltinVar(v) => {
+                self.u8(2);
+                self.str(&v.to_string());
+            }
+            IRValue::Type(t) => {
+                self.u8(3);
+                self.u32(t.bits());
+            }
+            IRValue::Variable(var) => {
+                self.u8(4);
+                self.usize(*var);
+            }
+            IRValue::String(s) => {
+                self.u8(5);
+                self.str(s);
+            }
+            IRValue::Symbol(sym) => {
+                self.u8(6);
+                self.str(&sym.name());
+            }
+            IRValue::Number(n) => {
+                self.u8(7);
+                self.bytes(&n.to_signed_bytes_le());
+            }
+            IRValue::Double(d) => {
+                self.u8(8);
+                self.u64(d.to_bits());
+            }
+            IRValue::Bool(b) => {
+                self.u8(9);
+                self.u8(*b as u8);
+            }
+            IRValue::Vector(vals) => {
+                self.u8(10);
+                self.vals(vals);
+            }
+            IRValue::Matrix(rows) => {
+                self.u8(11);
+                self.usize(rows.len());
+                for row in rows {
+                    self.vals(row);
+                }
+            }
+            IRValue::Procedure(proc) => {
+                self.u8(12);
+                self.proc_ref(proc);
+            }
+            /* only created at runtime, they refer to the state of the
+             * running interpreter
+             */
+            IRValue::NativeProc(_) | IRValue::HeapRef(_) => {
+                self.error
+                    .get_or_insert_with(|| String::from("runtime values can't be compiled"));
+            }
+        }
+    }
+}
+
+struct IRCacheReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    /// offset added to annotations
+    base: usize,
+    procs: Vec<Rc<RefCell<IRProcedure>>>,
+}
+
+fn ir_cache_corrupt<T>() -> Result<T, String> {
+    Err(String::from("compiled file is corrupt"))
+}
+
+impl<'a> IRCacheReader<'a> {
+    fn new(buf: &'a [u8], base: usize) -> Self {
+        IRCacheReader {
+            buf,
+            pos: 0,
+            base,
+            procs: Vec::new(),
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        match self.buf.get(self.pos..self.pos.saturating_add(len)) {
+            Some(out) => {
+                self.pos += len;
+                Ok(out)
+            }
+            None => ir_cache_corrupt(),
+        }
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn usize(&mut self) -> Result<usize, String> {
+        usize::try_from(self.u64()?).or_else(|_| ir_cache_corrupt())
+    }
+
+    fn take_bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.usize()?;
+        self.take(len)
+    }
+
+    fn str(&mut self) -> Result<String, String> {
+        String::from_utf8(self.take_bytes()?.to_vec()).or_else(|_| ir_cache_corrupt())
+    }
+
+    fn node(&mut self) -> Result<NodeIndex, String> {
+        Ok(NodeIndex::new(self.u32()? as usize))
+    }
+
+    fn ty(&mut self) -> Result<IRType, String> {
+        Ok(IRType::from_bits_retain(self.u32()?))
+    }
+
+    fn proc_ref(&mut self) -> Result<Rc<RefCell<IRProcedure>>, String> {
+        let id = self.u32()? as usize;
+        self.procs
+            .resize_with(self.procs.len().max(id + 1), Default::default);
+        Ok(self.procs[id].clone())
+    }
+
+    /// Reads the procedure table, which fills the procedures referenced so
+    /// far.
+    fn finish(&mut self) -> Result<(), String> {
+        let count = self.u32()? as usize;
+        if count < self.procs.len() {
+            return ir_cache_corrupt();
+        }
+        self.procs.resize_with(count, Default::default);
+
+        for id in 0..count {
+            let proc = self.proc()?;
+            *self.procs[id].borrow_mut() = proc;
+        }
+        if self.procs.len() != count || self.pos != self.buf.len() {
+            return ir_cache_corrupt();
+        }
+        Ok(())
+    }
+
+    fn proc(&mut self) -> Result<IRProcedure, String> {
+        let tag = self.str()?;
+        let start_block = self.node()?;
+        let end_block = self.node()?;
+        let vars = (0..self.usize()?)
+            .map(|_| self.usize())
+            .collect::<Result<_, _>>()?;
+
+        /* removed blocks are restored as well, so the indices of the
+         * remaining ones don't change
+         */
+        let mut blocks = StableGraph::new();
+        let mut removed = Vec::new();
+        for _ in 0..self.u32()? {
+            match self.u8()? {
+                0 => removed.push(blocks.add_node(Vec::new())),
+                1 => {
+                    let block = (0..self.usize()?)
+                        .map(|_| self.stmt())
+                        .collect::<Result<_, _>>()?;
+                    blocks.add_node(block);
+                }
+                _ => return ir_cache_corrupt(),
+            }
+        }
+        for idx in removed {
+            blocks.remove_node(idx);
+        }
+
+        for _ in 0..self.usize()? {
+            let (a, b) = (self.node()?, self.node()?);
+            if !blocks.contains_node(a) || !blocks.contains_node(b) {
+                return ir_cache_corrupt();
+            }
+            blocks.add_edge(a, b, ());
+        }
+
+        Ok(IRProcedure {
+            start_block,
+            end_block,
+            blocks,
+            vars,
+            tag,
+        })
+    }
+
+    fn stmt(&mut self) -> Result<IRStmt, String> {
+        Ok(match self.u8()? {
+            0 => IRStmt::Annotate(
+                self.usize()? + self.base,
+                self.usize()? + self.base,
+                self.u8()? != 0,
+            ),
+            1 => IRStmt::Assign(IRAssign {
+                target: self.target()?,
+                types: self.ty()?,
+                source: self.val()?,
+                op: self.op()?,
+            }),
+            2 => IRStmt::Branch(IRBranch {
+                cond: self.val()?,
+                success: self.node()?,
+                failure: self.node()?,
+                source: self.u8()? != 0,
+            }),
+            3 => IRStmt::Try(IRTry {
+                attempt: self.node()?,
+                catch: self.node()?,
+            }),
+            4 => IRStmt::TryEnd(self.node()?),
+            5 => IRStmt::Goto(self.node()?),
+            6 => IRStmt::Return(self.val()?),
+            7 => IRStmt::Unreachable,
+            _ => return ir_cache_corrupt(),
+        })
+    }
+
+    fn target(&mut self) -> Result<IRTarget, String> {
+        Ok(match self.u8()? {
+            0 => IRTarget::Ignore,
+            1 => IRTarget::Variable(self.usize()?),
+            2 => IRTarget::Deref(self.usize()?),
+            _ => return ir_cache_corrupt(),
+        })
+    }
+
+    fn op(&mut self) -> Result<IROp, String> {
+        Ok(match self.u8()? {
+            0 => IROp::AccessArray(self.val()?),
+            1 => IROp::Call(self.usize()?),
+            2 => IROp::NativeCall(self.vals()?),
+            3 => IROp::PtrAddress,
+            4 => IROp::PtrDeref,
+            5 => IROp::Assign,
+            6 => IROp::Or(self.val()?),
+            7 => IROp::And(self.val()?),
+            8 => IROp::Not,
+            9 => IROp::Less(self.val()?),
+            10 => IROp::Equal(self.val()?),
+            11 => IROp::Plus(self.val()?),
+            12 => IROp::Minus(self.val()?),
+            13 => IROp::Mult(self.val()?),
+            14 => IROp::Divide(self.val()?),
+            15 => IROp::IntDivide(self.val()?),
+            16 => IROp::Mod(self.val()?),
+            _ => return ir_cache_corrupt(),
+        })
+    }
+
+    fn vals(&mut self) -> Result<Vec<IRValue>, String> {
+        (0..self.usize()?).map(|_| self.val()).collect()
+    }
+
+    fn val(&mut self) -> Result<IRValue, String> {
+        Ok(match self.u8()? {
+            0 => IRValue::Undefined,
+            1 => IRValue::BuiltinProc(
+                self.str()?
+                    .parse::<BuiltinProc>()
+                    .or_else(|_| ir_cache_corrupt())?,
+            ),
+            2 => IRValue::BuiltinVar(
+                self.str()?
+                    .parse::<BuiltinVar>()
+                    .or_else(|_| ir_cache_corrupt())?,
+            ),
+            3 => IRValue::Type(self.ty()?),
+            4 => IRValue::Variable(self.usize()?),
+            5 => IRValue::String(self.str()?),
+            6 => IRValue::Symbol(IRSymbol::new(&self.str()?)),
+            7 => IRValue::Number(BigInt::from_signed_bytes_le(self.take_bytes()?)),
+            8 => IRValue::Double(f64::from_bits(self.u64()?)),
+            9 => IRValue::Bool(self.u8()? != 0),
+            10 => IRValue::Vector(self.vals()?),
+            11 => IRValue::Matrix(
+                (0..self.usize()?)
+                    .map(|_| self.vals())
+                    .collect::<Result<_, _>>()?,
+            ),
+            12 => IRValue::Procedure(self.proc_ref()?),
+            _ => return ir_cache_corrupt(),
+        })
+    }
+}
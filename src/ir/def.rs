@@ -6,8 +6,10 @@ use std::cell::RefCell;
 use std::fmt;
 use std::rc::Rc;
 
+use crate::builtin::native::NativeProc;
 use crate::builtin::{BuiltinProc, BuiltinVar};
 use crate::interp::heap::InterpObjRef;
+use crate::ir::symbol::IRSymbol;
 
 #[derive(Debug)]
 pub struct IRCfg {
@@ -34,23 +36,30 @@ macro_rules! IRTypes {
             | IRType::LIST
             | IRType::STRING
             | IRType::NUMBER
+            | IRType::RATIONAL
             | IRType::DOUBLE
             | IRType::MATRIX
             | IRType::VECTOR
     };
     ("minus") => {
-        IRType::SET | IRType::NUMBER | IRType::DOUBLE | IRType::MATRIX | IRType::VECTOR
+        IRType::SET
+            | IRType::NUMBER
+            | IRType::RATIONAL
+            | IRType::DOUBLE
+            | IRType::MATRIX
+            | IRType::VECTOR
     };
     ("mul") => {
         IRType::SET
             | IRType::STRING
             | IRType::NUMBER
+            | IRType::RATIONAL
             | IRType::DOUBLE
             | IRType::MATRIX
             | IRType::VECTOR
     };
     ("quot") => {
-        IRType::NUMBER | IRType::DOUBLE | IRType::MATRIX | IRType::VECTOR
+        IRType::NUMBER | IRType::RATIONAL | IRType::DOUBLE | IRType::MATRIX | IRType::VECTOR
     };
 }
 
@@ -74,6 +83,7 @@ bitflags! {
     /// - [`AST`]
     /// - [`STRING`]
     /// - [`NUMBER`]
+    /// - [`RATIONAL`]
     /// - [`MATRIX`]
     /// - [`VECTOR`]
     #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
@@ -92,14 +102,14 @@ bitflags! {
         const STRING        = 1 << 11;
         const BOOL          = 1 << 12;
         const NUMBER        = 1 << 13;
-        //const FLOAT         = 1 << 14; // "float"
+        const RATIONAL      = 1 << 14;
         const MATRIX        = 1 << 15;
         const VECTOR        = 1 << 16;
         const TYPE          = 1 << 17;
         const UNDEFINED     = 1 << 18; // "om"
         const STACK_IMAGE   = 1 << 19;
-        /*const STRING_SLICE  = 1 << 20;
-        const LIST_SLICE    = 1 << 21;
+        const CANVAS        = 1 << 20;
+        /*const LIST_SLICE    = 1 << 21;
         const SET_ITER      = 1 << 22;
         const LIST_ITER     = 1 << 23;
         const STATIC_STR    = 1 << 24;
@@ -158,6 +168,9 @@ impl fmt::Display for IRType {
         if self.contains(IRType::NUMBER) {
             parts.push("number");
         }
+        if self.contains(IRType::RATIONAL) {
+            parts.push("rational");
+        }
         if self.contains(IRType::DOUBLE) {
             parts.push("float");
         }
@@ -174,6 +187,10 @@ impl fmt::Display for IRType {
             parts.push("om");
         }
 
+        if self.contains(IRType::CANVAS) {
+            parts.push("canvas");
+        }
+
         if self.contains(IRType::STACK_IMAGE) {
             parts.push("stack_image");
         }
@@ -218,7 +235,7 @@ pub type IRBlock = Vec<IRStmt>;
 
 #[derive(Clone, Debug)]
 pub enum IRStmt {
-    Annotate(usize, usize),
+    Annotate(usize, usize, bool /* statement start */),
     Assign(IRAssign),
     Branch(IRBranch),
     Try(IRTry),
@@ -268,10 +285,14 @@ pub enum IROp {
 pub enum IRValue {
     Undefined,
     BuiltinProc(BuiltinProc),
+    // native procedure registered at runtime
+    NativeProc(Rc<NativeProc>),
     BuiltinVar(BuiltinVar),
     Type(IRType),
     Variable(IRVar),
     String(String),
+    // variable name resolved by the interpreter stack
+    Symbol(IRSymbol),
     Number(BigInt),
     Double(f64),
     Bool(bool),
@@ -286,6 +307,8 @@ pub struct IRBranch {
     pub cond: IRValue,
     pub success: NodeIndex,
     pub failure: NodeIndex,
+    // condition written in the source, as opposed to checks added by lowering
+    pub source: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -15,6 +15,7 @@ fn ir_dump_val(a: &IRValue, out: &mut String) {
         IRValue::Number(i) => out.push_str(&format!("{i}")),
         IRValue::Double(i) => out.push_str(&format!("{i}")),
         IRValue::String(i) => out.push_str(&format!("\"{i}\"")),
+        IRValue::Symbol(i) => out.push_str(&format!("${i}")),
         IRValue::Bool(b) => out.push_str(&format!("{:?}", b)),
         IRValue::Procedure(p) => out.push_str(&format!("_{} /* procedure */", p.borrow().tag)),
         IRValue::Matrix(m) => {
@@ -46,6 +47,7 @@ fn ir_dump_val(a: &IRValue, out: &mut String) {
             out.push_str(">>");
         }
         IRValue::BuiltinProc(p) => out.push_str(&format!("{}", p)),
+        IRValue::NativeProc(native) => out.push_str(&native.name),
         IRValue::BuiltinVar(p) => out.push_str(&format!("{}", p)),
         IRValue::Type(p) => out.push_str(&format!("TYPE_{}", p)),
         IRValue::HeapRef(r) => out.push_str(&format!("(heap_ref) {:#?}", r)),
@@ -163,7 +165,10 @@ pub fn ir_dump_stmt(stmt: &IRStmt, out: &mut String) {
     out.push('\t');
 
     match stmt {
-        IRStmt::Annotate(lhs, rhs) => out.push_str(&format!("/* source section {lhs} {rhs} */")),
+        IRStmt::Annotate(lhs, rhs, stmt) => {
+            let kind = if *stmt { "statement" } else { "source section" };
+            out.push_str(&format!("/* {kind} {lhs} {rhs} */"))
+        }
         IRStmt::Assign(a) => ir_dump_stmt_assign(a, out),
         IRStmt::Branch(a) => ir_dump_stmt_br(a, out),
         IRStmt::Return(a) => {
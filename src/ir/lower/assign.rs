@@ -8,6 +8,7 @@ use crate::ir::lower::expr::access_expr::block_arr_access_push;
 use crate::ir::lower::expr::block_expr_push;
 use crate::ir::lower::expr::term_expr::tterm_ast_tag_get;
 use crate::ir::lower::util::{block_get, stack_get, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 fn assign_arr_access(
     block_idx: &mut NodeIndex,
@@ -27,7 +28,7 @@ fn assign_arr_access(
      *  goto <arr_idx>
      *
      * <set_assign_idx>:
-     *  t_o := set_get_tag(t_n_val, t_expr);
+     *  t_o := set_get_tag_mut(t_n_val, t_expr);
      *  t_o_om := t_o == om;
      *  t_expr_om := t_expr == om;
      *  t_expr_nom := !t_expr_type_om;
@@ -191,6 +192,7 @@ fn assign_arr_access(
             cond: IRValue::Variable(t_n_val_set),
             success: set_assign_idx,
             failure: arr_idx,
+            source: false,
         }),
     ]);
 
@@ -201,7 +203,7 @@ fn assign_arr_access(
         IRStmt::Assign(IRAssign {
             target: IRTarget::Variable(t_o),
             types: IRType::PTR | IRType::UNDEFINED,
-            source: IRValue::BuiltinProc(BuiltinProc::SetGetTag),
+            source: IRValue::BuiltinProc(BuiltinProc::SetGetTagMut),
             op: IROp::NativeCall(vec![IRValue::Variable(t_n_val), IRValue::Variable(t_expr)]),
         }),
         IRStmt::Assign(IRAssign {
@@ -232,6 +234,7 @@ fn assign_arr_access(
             cond: IRValue::Variable(t_o_om),
             success: set_insert_idx,
             failure: inval_idx,
+            source: false,
         }),
     ]);
 
@@ -340,6 +343,7 @@ fn assign_arr_access(
             cond: IRValue::Variable(t_expr_fits),
             success: arr_assign_idx,
             failure: fill_idx,
+            source: false,
         }),
     ]);
 
@@ -369,6 +373,7 @@ fn assign_arr_access(
             cond: IRValue::Variable(t_n_val_list),
             success: list_push_loop_idx,
             failure: fill_str_check_idx,
+            source: false,
         }),
     ]);
 
@@ -399,6 +404,7 @@ fn assign_arr_access(
             cond: IRValue::Variable(t_lp_cond),
             success: list_push_idx,
             failure: inv_i_idx,
+            source: false,
         }),
     ]);
 
@@ -458,6 +464,7 @@ fn assign_arr_access(
             cond: IRValue::Variable(t_n_val_str),
             success: fill_str_idx,
             failure: throw_arr_idx,
+            source: false,
         }),
     ]);
 
@@ -546,7 +553,7 @@ fn assign_accessible(
                 target: IRTarget::Variable(t_n),
                 types: IRType::PTR,
                 source: IRValue::BuiltinProc(BuiltinProc::StackGetOrNew),
-                op: IROp::NativeCall(vec![IRValue::String(v.clone())]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(v))]),
             }));
         }
     } else {
@@ -600,7 +607,7 @@ fn assign_accessible(
                          * _ := list_push(t_list, t_expr);
                          * //...
                          * t_n_val := *t_n;
-                         * t_o := set_get_tag(t_n_val, t_list);
+                         * t_o := set_get_tag_mut(t_n_val, t_list);
                          * t_o_om := t_o == om;
                          * if t_o_om
                          *  goto <set_insert_idx>
@@ -682,7 +689,7 @@ fn assign_accessible(
                             IRStmt::Assign(IRAssign {
                                 target: IRTarget::Variable(t_o),
                                 types: IRType::PTR | IRType::UNDEFINED,
-                                source: IRValue::BuiltinProc(BuiltinProc::SetGetTag),
+                                source: IRValue::BuiltinProc(BuiltinProc::SetGetTagMut),
                                 op: IROp::NativeCall(vec![
                                     IRValue::Variable(t_n_val),
                                     IRValue::Variable(t_list),
@@ -698,6 +705,7 @@ fn assign_accessible(
                                 cond: IRValue::Variable(t_o_is_om),
                                 success: insert_idx,
                                 failure: inval_idx,
+                                source: false,
                             }),
                         ]);
 
@@ -905,6 +913,7 @@ fn assign_list(
             cond: IRValue::Variable(t_type_fits),
             success: assign_idx,
             failure: except_idx,
+            source: false,
         }),
     ]);
 
@@ -947,6 +956,7 @@ fn assign_list(
                 cond: IRValue::Variable(t_s),
                 success: next_idx,
                 failure: follow_idx,
+                source: false,
             }));
 
             proc.blocks.add_edge(assign_idx, next_idx, ());
@@ -1110,6 +1120,7 @@ fn assign_set(
             cond: IRValue::Variable(t_type_set),
             success: assign_idx,
             failure: except_idx,
+            source: false,
         }),
     ]);
 
@@ -1161,6 +1172,7 @@ fn assign_set(
                 cond: IRValue::Variable(t_s),
                 success: next_idx,
                 failure: follow_idx,
+                source: false,
             }));
 
             proc.blocks.add_edge(assign_idx, next_idx, ());
@@ -1316,6 +1328,7 @@ fn assign_collection(
             cond: IRValue::Variable(t_len_cond),
             success: coll_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -1396,7 +1409,7 @@ fn assign_var(
             target: IRTarget::Variable(t),
             types: IRType::PTR,
             source: IRValue::BuiltinProc(BuiltinProc::StackGetOrNew),
-            op: IROp::NativeCall(vec![IRValue::String(v.to_string())]),
+            op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(v))]),
         }));
         t
     };
@@ -1601,6 +1614,7 @@ fn assign_term(
             cond: IRValue::Variable(t_type_p),
             success: tag_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -1692,6 +1706,7 @@ fn assign_term(
             cond: IRValue::Variable(t_cond),
             success: check_mem_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -1734,6 +1749,7 @@ fn assign_term(
                 cond: IRValue::Variable(t_s),
                 success: next_idx,
                 failure: follow_idx,
+                source: false,
             }));
 
             proc.blocks.add_edge(check_mem_idx, next_idx, ());
@@ -1834,6 +1850,7 @@ fn ast_kind_str_eq_push(
             cond: IRValue::Variable(t_expr_ast),
             success: ast_check_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -1926,6 +1943,7 @@ fn assign_op(
         cond: IRValue::Variable(t_kind_check),
         success: assign_lhs_idx,
         failure: fail_idx,
+        source: false,
     }));
 
     proc.blocks.add_edge(*block_idx, assign_lhs_idx, ());
@@ -1952,6 +1970,7 @@ fn assign_op(
         cond: IRValue::Variable(t_succeeded.unwrap()),
         success: assign_rhs_idx,
         failure: follow_idx,
+        source: false,
     }));
 
     proc.blocks.add_edge(assign_lhs_idx, assign_rhs_idx, ());
@@ -2042,6 +2061,7 @@ fn assign_unary_op(
         cond: IRValue::Variable(t_kind_check),
         success: assign_idx,
         failure: fail_idx,
+        source: false,
     }));
 
     proc.blocks.add_edge(*block_idx, assign_idx, ());
@@ -2230,6 +2250,7 @@ fn assign_call(
         cond: IRValue::Variable(t_kind),
         success: ast_check_idx,
         failure: fail_idx,
+        source: false,
     }));
 
     proc.blocks.add_edge(*block_idx, ast_check_idx, ());
@@ -2262,6 +2283,7 @@ fn assign_call(
             cond: IRValue::Variable(t_params_eq),
             success: ast_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -2276,7 +2298,7 @@ fn assign_call(
             target: IRTarget::Variable(t),
             types: IRType::PTR,
             source: IRValue::BuiltinProc(BuiltinProc::StackGetOrNew),
-            op: IROp::NativeCall(vec![IRValue::String(c.name.to_string())]),
+            op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(&c.name))]),
         }));
         t
     };
@@ -2311,6 +2333,7 @@ fn assign_call(
             cond: IRValue::Variable(t_succeeded.unwrap()),
             success: next_idx,
             failure: follow_idx,
+            source: false,
         }));
 
         proc.blocks.add_edge(ast_idx, next_idx, ());
@@ -88,6 +88,7 @@ pub fn block_arr_access_push(
                 cond: IRValue::Variable(t_check_zero),
                 success: throw_idx,
                 failure: assign_idx,
+                source: false,
             })
         },
     ]);
@@ -123,6 +124,7 @@ pub fn block_arr_access_push(
             cond: IRValue::Variable(t_check_neq),
             success: amount_idx,
             failure: decr_idx,
+            source: false,
         }),
     ]);
 
@@ -172,6 +174,7 @@ pub fn block_arr_access_push(
             cond: IRValue::Variable(t_check_neq),
             success: set_zero_idx,
             failure: follow_idx,
+            source: false,
         }),
     ]);
 
@@ -384,6 +387,7 @@ pub fn block_obj_call_impl_push(
             cond: IRValue::Variable(t_cond),
             success: stack_add_idx,
             failure: call_idx,
+            source: false,
         }),
     ]);
 
@@ -526,6 +530,7 @@ fn block_obj_call_push(
             cond: IRValue::Variable(t_ptr_om),
             success: except_idx,
             failure: frame_idx,
+            source: false,
         }),
     ]);
 
@@ -946,6 +951,7 @@ pub fn block_access_ref_push(
                                 cond: IRValue::Variable(t_entry_om),
                                 success: dfl_idx,
                                 failure: assign_idx,
+                                source: false,
                             }),
                         ]);
 
@@ -1137,6 +1143,7 @@ pub fn block_access_ref_push(
                                 cond: IRValue::Variable(t_check),
                                 success: access_check_idx,
                                 failure: set_idx,
+                                source: false,
                             }),
                         ]);
 
@@ -1175,6 +1182,7 @@ pub fn block_access_ref_push(
                                 cond: IRValue::Variable(t_acc_check),
                                 success: access_idx,
                                 failure: det_dfl_acc_idx,
+                                source: false,
                             }),
                         ]);
 
@@ -1252,6 +1260,7 @@ pub fn block_access_ref_push(
                                 cond: IRValue::Variable(t_entry_is_om),
                                 success: det_dfl_idx,
                                 failure: assign_entry_idx,
+                                source: false,
                             }),
                         ]);
 
@@ -6,6 +6,7 @@ use crate::ir::def::*;
 use crate::ir::lower::IRSharedProc;
 use crate::ir::lower::proc::{call_params_invalidate_push, call_params_push};
 use crate::ir::lower::util::{block_get, stack_get, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 pub fn block_call_push(
     c: &CSTProcedureCall,
@@ -40,7 +41,7 @@ pub fn block_call_push(
                 target: IRTarget::Variable(t_3),
                 types: IRType::PTR,
                 source: IRValue::BuiltinProc(BuiltinProc::StackGetOrNew),
-                op: IROp::NativeCall(vec![IRValue::String(c.name.to_string())]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(&c.name))]),
             }),
             IRStmt::Assign(IRAssign {
                 target: IRTarget::Variable(t_proc),
@@ -85,6 +85,7 @@ pub fn block_list_truncate_push(
             cond: IRValue::Variable(t_i_zero),
             success: inv_list_idx,
             failure: om_idx,
+            source: false,
         }),
     ]);
 
@@ -144,6 +145,7 @@ pub fn block_list_truncate_push(
             cond: IRValue::Variable(t_entry_om),
             success: cond_idx,
             failure: resize_idx,
+            source: false,
         }),
     ]);
 
@@ -452,6 +454,7 @@ pub fn block_collection_push(
                     cond: IRValue::Variable(t_cond),
                     success: set_insert_idx,
                     failure: follow_idx,
+                    source: false,
                 }),
             ]);
 
@@ -9,6 +9,7 @@ use crate::ir::lower::IRSharedProc;
 use crate::ir::lower::ast::expr::block_cst_expr_push;
 use crate::ir::lower::expr::block_expr_push;
 use crate::ir::lower::util::{block_get, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 pub fn block_lambda_push(
     expr: &CSTExpression,
@@ -118,7 +119,7 @@ pub fn block_lambda_push(
                 target: IRTarget::Variable(t_var),
                 types: IRType::PTR,
                 source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-                op: IROp::NativeCall(vec![IRValue::String(var_name)]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(&var_name))]),
             }),
             IRStmt::Assign(IRAssign {
                 target: IRTarget::Variable(t_p_val),
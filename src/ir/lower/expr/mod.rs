@@ -47,7 +47,7 @@ pub fn block_expr_push(
     let lhs_old = shared_proc.code_lhs;
     let rhs_old = shared_proc.code_rhs;
     if !shared_proc.disable_annotations {
-        block_get(proc, *block_idx).push(IRStmt::Annotate(expr.lhs, expr.rhs));
+        block_get(proc, *block_idx).push(IRStmt::Annotate(expr.lhs, expr.rhs, false));
     }
     shared_proc.code_lhs = expr.lhs;
     shared_proc.code_rhs = expr.rhs;
@@ -282,7 +282,7 @@ pub fn block_expr_push(
     };
 
     if !shared_proc.disable_annotations {
-        block_get(proc, *block_idx).push(IRStmt::Annotate(lhs_old, rhs_old));
+        block_get(proc, *block_idx).push(IRStmt::Annotate(lhs_old, rhs_old, false));
     }
     shared_proc.code_lhs = lhs_old;
     shared_proc.code_rhs = rhs_old;
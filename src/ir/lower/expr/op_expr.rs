@@ -102,6 +102,7 @@ fn set_eq_push(
             cond: IRValue::Variable(t_cond),
             success: ast_idx,
             failure: eq_idx,
+            source: false,
         }),
     ]);
 
@@ -356,6 +357,7 @@ pub fn block_obj_overload_push(
             cond: IRValue::Variable(t_lhs_obj),
             success: obj_check_idx,
             failure: dfl_idx,
+            source: false,
         }),
     ]);
 
@@ -379,6 +381,7 @@ pub fn block_obj_overload_push(
             cond: IRValue::Variable(t_p_om),
             success: fail_idx,
             failure: obj_check_type_idx,
+            source: false,
         }),
     ]);
 
@@ -426,6 +429,7 @@ pub fn block_obj_overload_push(
             cond: IRValue::Variable(t_proc_proc),
             success: obj_call_idx,
             failure: fail_idx,
+            source: false,
         }),
     ]);
 
@@ -620,6 +624,7 @@ pub fn block_op_rhs_deferred_push<F, FRhs>(
         cond: IRValue::Variable(t_lhs_t),
         success: rhs_idx,
         failure: not_idx,
+        source: false,
     }));
 
     proc.blocks.add_edge(dfl_impl_idx, rhs_idx, ());
@@ -632,12 +637,14 @@ pub fn block_op_rhs_deferred_push<F, FRhs>(
             cond: IRValue::Variable(t_1),
             success: fallthrough_idx,
             failure: rhs_idx,
+            source: false,
         }));
     } else {
         block_get(proc, not_idx).push(IRStmt::Branch(IRBranch {
             cond: IRValue::Variable(t_1),
             success: rhs_idx,
             failure: fallthrough_idx,
+            source: false,
         }));
     }
 
@@ -688,6 +695,7 @@ pub fn block_op_rhs_deferred_push<F, FRhs>(
             cond: IRValue::Variable(t_rhs_t),
             success: build_term_idx,
             failure: assign_idx,
+            source: false,
         }),
     ]);
 
@@ -883,6 +891,7 @@ pub fn block_op_plus_push(
             cond: IRValue::Variable(t_left_obj),
             success: obj_idx,
             failure: assign_idx,
+            source: false,
         }),
     ]);
 
@@ -1031,6 +1040,7 @@ pub fn block_op_push(
                             cond: IRValue::Variable(t_lhs_bool),
                             success: not_idx,
                             failure: fail_idx,
+                            source: false,
                         }),
                     ]);
                     proc.blocks.add_edge(*idx, not_idx, ());
@@ -1129,6 +1139,7 @@ pub fn block_op_push(
                             cond: IRValue::Variable(t_lhs_bool),
                             success: assign_idx,
                             failure: fail_idx,
+                            source: false,
                         }),
                     ]);
                     proc.blocks.add_edge(*idx, assign_idx, ());
@@ -1215,6 +1226,7 @@ pub fn block_op_push(
                             cond: IRValue::Variable(t_lhs_bool),
                             success: assign_idx,
                             failure: fail_idx,
+                            source: false,
                         }),
                     ]);
                     proc.blocks.add_edge(*idx, assign_idx, ());
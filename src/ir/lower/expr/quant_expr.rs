@@ -92,6 +92,7 @@ pub fn block_quant_push(
                     cond: IRValue::Variable(tmp),
                     success: success_idx,
                     failure: backtrack_idx,
+                    source: false,
                 }));
 
                 proc.blocks.add_edge(main_idx, success_idx, ());
@@ -122,6 +123,7 @@ pub fn block_quant_push(
                     cond: IRValue::Variable(tmp),
                     success: backtrack_idx,
                     failure: failure_idx,
+                    source: false,
                 }));
 
                 proc.blocks.add_edge(main_idx, backtrack_idx, ());
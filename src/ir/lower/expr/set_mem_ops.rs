@@ -59,6 +59,7 @@ pub fn block_set_mem_op_dfl_push(
             cond: IRValue::Variable(t_len_zero),
             success: assign_idx,
             failure: prod_mem_idx,
+            source: false,
         }),
     ]);
 
@@ -249,6 +250,7 @@ pub fn block_sum_mem_push(
             cond: IRValue::Variable(t_amount_zero),
             success: out_om_idx,
             failure: set_check_idx,
+            source: false,
         }),
     ]);
 
@@ -284,6 +286,7 @@ pub fn block_sum_mem_push(
             cond: IRValue::Variable(t_set_type_set),
             success: set_get_zero_idx,
             failure: arr_get_zero_idx,
+            source: false,
         }),
     ]);
 
@@ -364,6 +367,7 @@ pub fn block_sum_mem_push(
             cond: IRValue::Variable(t_set_zero_type_set),
             success: set_union_idx,
             failure: mult_idx,
+            source: false,
         }),
     ]);
 
@@ -396,6 +400,7 @@ pub fn block_sum_mem_push(
             cond: IRValue::Variable(t_i_cond),
             success: set_iter_loop_idx,
             failure: follow_idx,
+            source: false,
         }),
     ]);
 
@@ -441,6 +446,7 @@ pub fn block_sum_mem_push(
             cond: IRValue::Variable(t_j_cond),
             success: set_iter_loop_loop_idx,
             failure: set_iter_idx,
+            source: false,
         }),
     ]);
 
@@ -493,6 +499,7 @@ pub fn block_sum_mem_push(
             cond: IRValue::Variable(t_cond),
             success: mult_loop_mult_idx,
             failure: follow_idx,
+            source: false,
         }),
     ]);
 
@@ -709,6 +716,7 @@ pub fn block_prod_mem_push(
             cond: IRValue::Variable(t_amount_zero),
             success: out_om_idx,
             failure: set_check_idx,
+            source: false,
         }),
     ]);
 
@@ -744,6 +752,7 @@ pub fn block_prod_mem_push(
             cond: IRValue::Variable(t_set_type_set),
             success: set_get_zero_idx,
             failure: arr_get_zero_idx,
+            source: false,
         }),
     ]);
 
@@ -824,6 +833,7 @@ pub fn block_prod_mem_push(
             cond: IRValue::Variable(t_set_zero_type_set),
             success: set_union_idx,
             failure: mult_idx,
+            source: false,
         }),
     ]);
 
@@ -856,6 +866,7 @@ pub fn block_prod_mem_push(
             cond: IRValue::Variable(t_i_cond),
             success: set_iter_loop_idx,
             failure: follow_idx,
+            source: false,
         }),
     ]);
 
@@ -907,6 +918,7 @@ pub fn block_prod_mem_push(
             cond: IRValue::Variable(t_j_cond),
             success: set_iter_loop_loop_idx,
             failure: set_iter_assign_idx,
+            source: false,
         }),
     ]);
 
@@ -926,6 +938,7 @@ pub fn block_prod_mem_push(
             cond: IRValue::Variable(t_cont),
             success: set_iter_loop_insert_idx,
             failure: set_iter_loop_iter_idx,
+            source: false,
         }),
     ]);
 
@@ -1000,6 +1013,7 @@ pub fn block_prod_mem_push(
             cond: IRValue::Variable(t_cond),
             success: mult_loop_mult_idx,
             failure: follow_idx,
+            source: false,
         }),
     ]);
 
@@ -199,6 +199,7 @@ pub fn block_term_op_push(
         cond: IRValue::Variable(t_lhs_t),
         success: build_term_idx,
         failure: dfl_idx,
+        source: false,
     }));
 
     proc.blocks.add_edge(block_idx, build_term_idx, ());
@@ -272,6 +273,7 @@ pub fn block_term_unary_op_push(
         cond: IRValue::Variable(t_lhs_t),
         success: build_term_idx,
         failure: dfl_idx,
+        source: false,
     }));
 
     proc.blocks.add_edge(block_idx, build_term_idx, ());
@@ -223,6 +223,7 @@ pub fn block_unary_op_push(
                             cond: IRValue::Variable(t_source_type_num),
                             success: factor_idx,
                             failure: assert_idx,
+                            source: false,
                         }),
                     ]);
 
@@ -253,6 +254,7 @@ pub fn block_unary_op_push(
                             cond: IRValue::Variable(t_assert_lz),
                             success: assert_idx,
                             failure: factor_iter_idx,
+                            source: false,
                         }),
                     ]);
 
@@ -289,6 +291,7 @@ pub fn block_unary_op_push(
                             cond: IRValue::Variable(t_check),
                             success: loop_idx,
                             failure: follow_dfl_idx,
+                            source: false,
                         }),
                     ]);
 
@@ -4,6 +4,7 @@ use crate::builtin::BuiltinProc;
 use crate::ir::def::*;
 use crate::ir::lower::IRSharedProc;
 use crate::ir::lower::util::{block_get, stack_get, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 pub fn block_var_push(
     c: &str,
@@ -30,7 +31,7 @@ pub fn block_var_push(
                 target: IRTarget::Variable(tmp),
                 types: IRType::PTR,
                 source: IRValue::BuiltinProc(BuiltinProc::StackGetOrNew),
-                op: IROp::NativeCall(vec![IRValue::String(c.to_string())]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(c))]),
             }),
             IRStmt::Assign(IRAssign {
                 target,
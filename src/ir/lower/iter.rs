@@ -9,6 +9,7 @@ use crate::ir::lower::assign::assign_parse;
 use crate::ir::lower::expr::block_expr_push;
 use crate::ir::lower::proc::expr_vars_push;
 use crate::ir::lower::util::{block_get, stack_pop, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 /// iterator template function
 ///
@@ -158,6 +159,7 @@ pub fn block_iterator_push<T>(
                 cond: IRValue::Variable(t_cond),
                 success: next_idx,
                 failure: backtrack_expr_idx,
+                source: false,
             }),
         ]);
         proc.blocks.add_edge(iter_idx, next_idx, ());
@@ -173,7 +175,7 @@ pub fn block_iterator_push<T>(
                 target: IRTarget::Variable(t_var),
                 types: IRType::PTR,
                 source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-                op: IROp::NativeCall(vec![IRValue::String(i.to_string())]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(i))]),
             }));
 
             if let Some(ret_pop_idx_val) = ret_pop_idx {
@@ -181,7 +183,7 @@ pub fn block_iterator_push<T>(
                     target: IRTarget::Ignore,
                     types: IRType::UNDEFINED,
                     source: IRValue::BuiltinProc(BuiltinProc::StackPop),
-                    op: IROp::NativeCall(vec![IRValue::String(i.to_string())]),
+                    op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(i))]),
                 }));
             }
 
@@ -286,6 +288,7 @@ pub fn block_iterator_push<T>(
         cond: IRValue::Variable(t_equal_all),
         success: expr_idx,
         failure: backtrack_idx,
+        source: false,
     }));
     proc.blocks.add_edge(current_idx, expr_idx, ());
     proc.blocks.add_edge(current_idx, backtrack_idx, ());
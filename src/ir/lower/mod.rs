@@ -2,7 +2,7 @@ mod assign;
 mod ast;
 pub mod expr;
 mod iter;
-mod proc;
+pub mod proc;
 mod stmt;
 pub mod util;
 
@@ -20,6 +20,8 @@ use crate::ir::lower::expr::block_expr_push;
 use crate::ir::lower::proc::procedure_vars_aggregate;
 use crate::ir::lower::stmt::block_populate;
 use crate::ir::lower::util::{block_get, tmp_var_new};
+use crate::ir::opt::ir_opt;
+use crate::ir::symbol::IRSymbol;
 
 use proc::procedure_new;
 
@@ -52,6 +54,36 @@ pub trait CSTIRLower {
     fn from_ast_block(_: &CSTBlock, opts: &InputOpts) -> Rc<RefCell<IRProcedure>>;
 }
 
+/// Binds the command line arguments to `params` in the global scope.
+fn main_args_push(proc: &mut IRProcedure) {
+    /*  t_params := stack_add("params");
+     *  *t_params := copy(args);
+     */
+    let t_params = tmp_var_new(proc);
+    let start_idx = proc.start_block;
+    let start = block_get(proc, start_idx);
+
+    // after the stack frame is added and before the goto into the body
+    let idx = start.len() - 1;
+    start.splice(
+        idx..idx,
+        [
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Variable(t_params),
+                types: IRType::PTR,
+                source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new("params"))]),
+            }),
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Deref(t_params),
+                types: IRType::LIST,
+                source: IRValue::BuiltinProc(BuiltinProc::Copy),
+                op: IROp::NativeCall(vec![IRValue::BuiltinVar(BuiltinVar::Args)]),
+            }),
+        ],
+    );
+}
+
 impl CSTIRLower for IRCfg {
     fn from_cst(cst: &CSTBlock, opts: &InputOpts) -> IRCfg {
         let mut out = IRCfg {
@@ -67,11 +99,14 @@ impl CSTIRLower for IRCfg {
             opts.disable_annotations,
             &mut out,
         );
+        main_args_push(&mut out.main.borrow_mut());
 
         if opts.dump_ir_lower {
             ir_dump(&out, opts, "00-lower");
         }
 
+        ir_opt(&out, opts);
+
         out
     }
 
@@ -147,7 +182,7 @@ impl CSTIRLower for IRCfg {
                     target: IRTarget::Variable(t_ptr),
                     types: IRType::PTR,
                     source: IRValue::BuiltinProc(BuiltinProc::StackGetOrNew),
-                    op: IROp::NativeCall(vec![IRValue::String(i)]),
+                    op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(&i))]),
                 })
             })
             .collect::<Vec<_>>();
@@ -10,6 +10,7 @@ use crate::ir::lower::expr::access_expr::block_access_ref_push;
 use crate::ir::lower::expr::block_expr_push;
 use crate::ir::lower::stmt::block_populate;
 use crate::ir::lower::util::{block_get, stack_get, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 /// Emits IR to assemble the parameter list for a call to `t_proc` into `target`.
 ///
@@ -87,6 +88,7 @@ pub fn call_params_push(
             cond: IRValue::Variable(t_stack_check),
             success: follow_idx,
             failure: assign_idx,
+            source: false,
         }),
     ]);
 
@@ -137,7 +139,7 @@ pub fn call_params_push(
                             target: IRTarget::Variable(tmp_addr),
                             types: IRType::PTR,
                             source: IRValue::BuiltinProc(BuiltinProc::StackGetOrNew),
-                            op: IROp::NativeCall(vec![IRValue::String(v.to_string())]),
+                            op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(v))]),
                         }));
                     }
                     None
@@ -298,6 +300,7 @@ pub fn call_params_push(
                 cond: IRValue::Variable(t_rest_type_list),
                 success: assign_idx,
                 failure: throw_idx,
+                source: false,
             }),
         ]);
 
@@ -369,6 +372,7 @@ pub fn call_params_push(
                 cond: IRValue::Variable(t_check),
                 success: loop_idx,
                 failure: follow_idx,
+                source: false,
             }),
         ]);
 
@@ -723,7 +727,7 @@ pub fn proc_params_push(
                     target: IRTarget::Variable(tmp),
                     types: IRType::PTR,
                     source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-                    op: IROp::NativeCall(vec![IRValue::String(i.name.clone())]),
+                    op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(&i.name))]),
                 }),
                 IRStmt::Assign(IRAssign {
                     target: IRTarget::Variable(t_3),
@@ -776,6 +780,7 @@ pub fn proc_params_push(
                     cond: IRValue::Variable(t_2),
                     success: set_idx,
                     failure: follow_idx,
+                    source: false,
                 }),
             ]);
 
@@ -810,7 +815,7 @@ pub fn proc_params_push(
                 target: IRTarget::Variable(t_list_param),
                 types: IRType::PTR,
                 source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-                op: IROp::NativeCall(vec![IRValue::String(list_param_val.clone())]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(list_param_val))]),
             }),
             IRStmt::Assign(IRAssign {
                 target: IRTarget::Variable(t_slice),
@@ -870,12 +875,13 @@ pub fn proc_vars_push(
                 target: IRTarget::Variable(t_exists),
                 types: IRType::BOOL,
                 source: IRValue::BuiltinProc(BuiltinProc::StackInScope),
-                op: IROp::NativeCall(vec![IRValue::String(String::from("this"))]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new("this"))]),
             }),
             IRStmt::Branch(IRBranch {
                 cond: IRValue::Variable(t_exists),
                 success: add_idx,
                 failure: follow_idx,
+                source: false,
             }),
         ]);
 
@@ -887,7 +893,7 @@ pub fn proc_vars_push(
                 target: IRTarget::Variable(t_obj_addr),
                 types: IRType::PTR,
                 source: IRValue::BuiltinProc(BuiltinProc::StackGetOrNew),
-                op: IROp::NativeCall(vec![IRValue::String(String::from("this"))]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new("this"))]),
             }),
             IRStmt::Assign(IRAssign {
                 target: IRTarget::Variable(t_obj),
@@ -929,7 +935,7 @@ pub fn proc_vars_push(
             target: IRTarget::Variable(t_var),
             types: IRType::PTR,
             source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-            op: IROp::NativeCall(vec![IRValue::String(i.clone())]),
+            op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(i))]),
         }));
         shared_proc.definitions.push((i.clone(), t_var));
     });
@@ -1010,6 +1016,7 @@ fn params_to_cache_key_push(block_idx: &mut NodeIndex, proc: &mut IRProcedure) -
             cond: IRValue::Variable(t_cond),
             success: push_idx,
             failure: follow_idx,
+            source: false,
         }),
     ]);
 
@@ -1153,6 +1160,7 @@ pub fn procedure_new(
                 cond: IRValue::Variable(t_lookup_res),
                 success: ret_idx,
                 failure: main_idx,
+                source: false,
             }),
         ]);
 
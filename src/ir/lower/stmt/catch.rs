@@ -6,6 +6,7 @@ use crate::ir::def::*;
 use crate::ir::lower::IRSharedProc;
 use crate::ir::lower::stmt::block_populate;
 use crate::ir::lower::util::{block_get, stack_pop, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 fn catch_block_new(
     exception_kind: u8,
@@ -29,7 +30,7 @@ fn catch_block_new(
                 target: IRTarget::Ignore,
                 types: IRType::UNDEFINED,
                 source: IRValue::BuiltinProc(BuiltinProc::StackPop),
-                op: IROp::NativeCall(vec![IRValue::String(exception.to_string())]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(exception))]),
             }),
             IRStmt::Goto(ret_idx),
         ]);
@@ -50,7 +51,7 @@ fn catch_block_new(
                 target: IRTarget::Ignore,
                 types: IRType::UNDEFINED,
                 source: IRValue::BuiltinProc(BuiltinProc::StackPop),
-                op: IROp::NativeCall(vec![IRValue::String(exception.to_string())]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(exception))]),
             }),
             IRStmt::Goto(continue_idx_val),
         ]);
@@ -71,7 +72,7 @@ fn catch_block_new(
                 target: IRTarget::Ignore,
                 types: IRType::UNDEFINED,
                 source: IRValue::BuiltinProc(BuiltinProc::StackPop),
-                op: IROp::NativeCall(vec![IRValue::String(exception.to_string())]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(exception))]),
             }),
             IRStmt::Goto(break_idx_val),
         ]);
@@ -112,7 +113,7 @@ fn catch_block_new(
                     target: IRTarget::Ignore,
                     types: IRType::UNDEFINED,
                     source: IRValue::BuiltinProc(BuiltinProc::StackPop),
-                    op: IROp::NativeCall(vec![IRValue::String(exception.to_string())]),
+                    op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(exception))]),
                 }),
                 IRStmt::Goto(next_idx),
             ]);
@@ -141,7 +142,7 @@ fn catch_block_new(
                 target: IRTarget::Variable(t_exception),
                 types: IRType::PTR,
                 source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-                op: IROp::NativeCall(vec![IRValue::String(exception.to_string())]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(exception))]),
             }),
             IRStmt::Assign(IRAssign {
                 target: IRTarget::Deref(t_exception),
@@ -191,6 +192,7 @@ fn catch_block_new(
             cond: IRValue::Variable(t_except),
             success: catch_block,
             failure: rethrow_idx,
+            source: false,
         }),
     ]);
     proc.blocks.add_edge(o, catch_block, ());
@@ -201,9 +203,9 @@ fn catch_block_new(
 /// Emits IR for a try-catch statement, advancing `block_idx` past the statement.
 ///
 /// The try body is wrapped in a try region. Catch branches are matched against
-/// the exception kind: `catch` handles user exceptions, `catchLng` handles
-/// internal exceptions, and `finally` handles both. Unmatched exceptions are
-/// rethrown. The exception value is pushed onto the stack under the catch
+/// the exception kind: `catchUsr` handles user exceptions, `catchLng` handles
+/// language exceptions, and a trailing `catch` handles both. Unmatched
+/// exceptions are rethrown. The exception value is pushed onto the stack under the catch
 /// variable name for the duration of the catch body.
 pub fn block_try_push(
     t: &CSTTryCatch,
@@ -300,9 +302,7 @@ pub fn block_try_push(
         rethrow_idx
     };
 
-    let catch_lng_idx = if let Some(c) = cst_catch_usr
-        && cst_catch_usr != cst_catch_lng
-    {
+    let catch_lng_idx = if let Some(c) = cst_catch_lng {
         catch_block_new(
             0,
             Some(&c.exception),
@@ -311,7 +311,7 @@ pub fn block_try_push(
             continue_idx,
             break_idx,
             ret_idx,
-            rethrow_idx,
+            catch_usr_idx,
             proc,
             shared_proc,
             cfg,
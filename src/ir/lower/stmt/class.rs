@@ -9,6 +9,7 @@ use crate::ir::lower::IRSharedProc;
 use crate::ir::lower::proc::{proc_params_push, proc_vars_push};
 use crate::ir::lower::stmt::block_populate;
 use crate::ir::lower::util::{block_get, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 fn constructor_new(
     c: &CSTClass,
@@ -49,6 +50,7 @@ fn constructor_new(
         block_get(&mut constructor_proc.borrow_mut(), init_idx).push(IRStmt::Annotate(
             constructor_shared.code_lhs,
             constructor_shared.code_rhs,
+            false,
         ));
     }
 
@@ -117,7 +119,7 @@ fn constructor_new(
             target: IRTarget::Ignore,
             types: IRType::UNDEFINED,
             source: IRValue::BuiltinProc(BuiltinProc::StackPop),
-            op: IROp::NativeCall(vec![IRValue::String(String::from("this"))]),
+            op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new("this"))]),
         }),
         IRStmt::Assign(IRAssign {
             target: IRTarget::Variable(t_obj),
@@ -187,6 +189,7 @@ fn static_new(
         block_get(&mut static_proc.borrow_mut(), static_init_idx).push(IRStmt::Annotate(
             static_shared.code_lhs,
             static_shared.code_rhs,
+            false,
         ));
     }
     block_get(&mut static_proc.borrow_mut(), static_init_idx).push(IRStmt::Assign(IRAssign {
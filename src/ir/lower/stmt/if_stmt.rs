@@ -65,6 +65,7 @@ pub fn block_if_push(
             cond: IRValue::Variable(t_cond),
             success: branch_idx,
             failure: else_init_idx,
+            source: true,
         }));
 
         proc.blocks.add_edge(current_idx, branch_idx, ());
@@ -11,6 +11,7 @@ use crate::ir::lower::expr::var_expr::block_var_push;
 use crate::ir::lower::proc::expr_vars_push;
 use crate::ir::lower::stmt::block_populate;
 use crate::ir::lower::util::{block_get, stack_pop, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 fn block_case_equiv_push(
     t_expr: IRVar,
@@ -28,7 +29,7 @@ fn block_case_equiv_push(
     cfg: &mut IRCfg,
 ) {
     if !shared_proc.disable_annotations {
-        block_get(proc, *current_idx).push(IRStmt::Annotate(e.lhs, e.rhs));
+        block_get(proc, *current_idx).push(IRStmt::Annotate(e.lhs, e.rhs, false));
     }
     shared_proc.code_lhs = e.lhs;
     shared_proc.code_rhs = e.rhs;
@@ -86,7 +87,7 @@ fn block_case_equiv_push(
                     target: IRTarget::Variable(t_var),
                     types: IRType::PTR,
                     source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-                    op: IROp::NativeCall(vec![IRValue::String(i.to_string())]),
+                    op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(i))]),
                 }));
 
                 shared_proc.definitions.push((i.to_string(), t_var));
@@ -223,6 +224,7 @@ fn block_case_equiv_push(
                     cond: IRValue::Variable(t_expr_ast),
                     success: match_idx,
                     failure: fail_idx,
+                    source: false,
                 }),
             ]);
 
@@ -310,12 +312,13 @@ fn block_stack_copied_add_push(
             target: IRTarget::Variable(t_exists),
             types: IRType::BOOL,
             source: IRValue::BuiltinProc(BuiltinProc::StackInScope),
-            op: IROp::NativeCall(vec![IRValue::String(name.to_string())]),
+            op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(name))]),
         }),
         IRStmt::Branch(IRBranch {
             cond: IRValue::Variable(t_exists),
             success: assign_idx,
             failure: tmp_om_idx,
+            source: false,
         }),
     ]);
 
@@ -348,7 +351,7 @@ fn block_stack_copied_add_push(
             target: IRTarget::Variable(t_ptr),
             types: IRType::PTR,
             source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-            op: IROp::NativeCall(vec![IRValue::String(name.to_string())]),
+            op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(name))]),
         }),
         IRStmt::Assign(IRAssign {
             target: IRTarget::Deref(t_ptr),
@@ -415,6 +418,7 @@ fn block_match_case_matches_push(
                 cond: IRValue::Variable(t_matched),
                 success: follow_idx,
                 failure: next_idx,
+                source: false,
             }));
             proc.blocks.add_edge(current_idx, follow_idx, ());
             proc.blocks.add_edge(current_idx, next_idx, ());
@@ -489,6 +493,7 @@ fn block_match_case_push(
         cond: IRValue::Variable(t_matched),
         success: cond_idx,
         failure: pop_follow_idx,
+        source: false,
     }));
 
     proc.blocks.add_edge(current_idx, cond_idx, ());
@@ -525,6 +530,7 @@ fn block_match_case_push(
             cond: IRValue::Variable(t_cond),
             success: block_idx,
             failure: pop_follow_idx,
+            source: true,
         }));
         proc.blocks.add_edge(cond_idx, block_idx, ());
         proc.blocks.add_edge(cond_idx, pop_follow_idx, ());
@@ -552,7 +558,7 @@ pub fn pop_block_new(out: &[String], masked_idx: NodeIndex, proc: &mut IRProcedu
                     target: IRTarget::Ignore,
                     types: IRType::UNDEFINED,
                     source: IRValue::BuiltinProc(BuiltinProc::StackPop),
-                    op: IROp::NativeCall(vec![IRValue::String(i.to_string())]),
+                    op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(i))]),
                 })
             })
             .collect::<Vec<IRStmt>>(),
@@ -736,6 +742,7 @@ pub fn block_match_regex_push(
                 cond: IRValue::Variable(t_matched),
                 success: assign_idx,
                 failure: pop_follow_idx,
+                source: false,
             }),
         ]);
 
@@ -751,7 +758,7 @@ pub fn block_match_regex_push(
                     target: IRTarget::Variable(t_var),
                     types: IRType::PTR,
                     source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-                    op: IROp::NativeCall(vec![IRValue::String(i.to_string())]),
+                    op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(i))]),
                 }));
             }
         } else if let CSTExpressionKind::Variable(v) = &pattern_out.kind {
@@ -762,7 +769,7 @@ pub fn block_match_regex_push(
                 target: IRTarget::Variable(t_var),
                 types: IRType::PTR,
                 source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-                op: IROp::NativeCall(vec![IRValue::String(v.to_string())]),
+                op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(v))]),
             }));
 
             pop_vars.push(v.to_string());
@@ -800,6 +807,7 @@ pub fn block_match_regex_push(
             cond: IRValue::Variable(t_assign_success),
             success: cond_idx,
             failure: pop_follow_idx,
+            source: false,
         }));
         proc.blocks.add_edge(assign_idx, cond_idx, ());
         proc.blocks.add_edge(assign_idx, pop_follow_idx, ());
@@ -817,6 +825,7 @@ pub fn block_match_regex_push(
                 cond: IRValue::Variable(t_matched),
                 success: cond_idx,
                 failure: pop_follow_idx,
+                source: false,
             }),
         ]);
 
@@ -858,6 +867,7 @@ pub fn block_match_regex_push(
             cond: IRValue::Variable(t_cond),
             success: block_init_idx,
             failure: pop_follow_idx,
+            source: true,
         }));
         proc.blocks.add_edge(cond_idx, block_init_idx, ());
         proc.blocks.add_edge(cond_idx, pop_follow_idx, ());
@@ -50,10 +50,44 @@ pub fn block_populate(
     let lhs_old = shared_proc.code_lhs;
     let rhs_old = shared_proc.code_rhs;
 
+    let terminated = block_stmts_push(
+        block_idx,
+        cst,
+        continue_idx,
+        break_idx,
+        ret_idx,
+        proc,
+        shared_proc,
+        cfg,
+    );
+
+    if !terminated && !shared_proc.disable_annotations {
+        block_get(proc, *block_idx).push(IRStmt::Annotate(lhs_old, rhs_old, false));
+    }
+
+    /* restored after terminating statements as well, code lowered after the
+     * block belongs to the enclosing statement
+     */
+    shared_proc.code_lhs = lhs_old;
+    shared_proc.code_rhs = rhs_old;
+
+    terminated
+}
+
+fn block_stmts_push(
+    block_idx: &mut NodeIndex,
+    cst: &[CSTStatement],
+    continue_idx: Option<NodeIndex>,
+    break_idx: Option<NodeIndex>,
+    ret_idx: NodeIndex,
+    proc: &mut IRProcedure,
+    shared_proc: &mut IRSharedProc,
+    cfg: &mut IRCfg,
+) -> bool /* follow block terminated */ {
     for stmt in cst {
 
         if !shared_proc.disable_annotations {
-            block_get(proc, *block_idx).push(IRStmt::Annotate(stmt.lhs, stmt.rhs));
+            block_get(proc, *block_idx).push(IRStmt::Annotate(stmt.lhs, stmt.rhs, true));
         }
 
         shared_proc.code_lhs = stmt.lhs;
@@ -257,12 +291,5 @@ pub fn block_populate(
         }
     }
 
-    if !shared_proc.disable_annotations {
-        block_get(proc, *block_idx).push(IRStmt::Annotate(lhs_old, rhs_old));
-    }
-
-    shared_proc.code_lhs = lhs_old;
-    shared_proc.code_rhs = rhs_old;
-
     false
 }
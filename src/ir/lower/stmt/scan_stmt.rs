@@ -10,6 +10,7 @@ use crate::ir::lower::proc::expr_vars_push;
 use crate::ir::lower::stmt::block_populate;
 use crate::ir::lower::stmt::match_stmt::pop_block_new;
 use crate::ir::lower::util::{block_get, stack_pop, tmp_var_new};
+use crate::ir::symbol::IRSymbol;
 
 fn block_strline_push(
     overwrite_idx: &mut NodeIndex,
@@ -118,6 +119,7 @@ fn block_strline_push(
             cond: IRValue::Variable(t_i_check),
             success: loop_idx,
             failure: end_idx,
+            source: false,
         }),
     ]);
 
@@ -141,6 +143,7 @@ fn block_strline_push(
             cond: IRValue::Variable(t_c_check),
             success: add_line_idx,
             failure: add_column_idx,
+            source: false,
         }),
     ]);
 
@@ -458,7 +461,7 @@ pub fn block_scan_push(
             target: IRTarget::Variable(t_var),
             types: IRType::PTR,
             source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-            op: IROp::NativeCall(vec![IRValue::String(v.to_string())]),
+            op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(v))]),
         }));
         shared_proc.definitions.push((v.to_string(), t_var));
         t_var
@@ -471,7 +474,7 @@ pub fn block_scan_push(
             target: IRTarget::Ignore,
             types: IRType::UNDEFINED,
             source: IRValue::BuiltinProc(BuiltinProc::StackPop),
-            op: IROp::NativeCall(vec![IRValue::String(v.to_string())]),
+            op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(v))]),
         })]
     } else {
         Vec::new()
@@ -517,6 +520,7 @@ pub fn block_scan_push(
             cond: IRValue::Variable(t_len_check),
             success: end_idx,
             failure: loop_idx,
+            source: false,
         }),
     ]);
     proc.blocks.add_edge(check_idx, end_idx, ());
@@ -915,7 +919,7 @@ pub fn block_scan_push(
                     target: IRTarget::Variable(t_i),
                     types: IRType::PTR,
                     source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-                    op: IROp::NativeCall(vec![IRValue::String(i.to_string())]),
+                    op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(i))]),
                 }));
                 pop_vars.push((i.to_string(), t_i));
                 shared_proc.definitions.push((i.to_string(), t_i));
@@ -955,7 +959,7 @@ pub fn block_scan_push(
                         target: IRTarget::Ignore,
                         types: IRType::UNDEFINED,
                         source: IRValue::BuiltinProc(BuiltinProc::StackPop),
-                        op: IROp::NativeCall(vec![IRValue::String(i.to_string())]),
+                        op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(i))]),
                     })
                 })
                 .collect::<Vec<IRStmt>>(),
@@ -983,6 +987,7 @@ pub fn block_scan_push(
                 cond: IRValue::Variable(t_cond),
                 success: overwrite_init_idx,
                 failure: follow_idx,
+                source: true,
             }));
             proc.blocks.add_edge(idx, overwrite_init_idx, ());
             proc.blocks.add_edge(idx, follow_idx, ());
@@ -1011,6 +1016,7 @@ pub fn block_scan_push(
             cond: IRValue::Variable(t_check),
             success: cond_idx,
             failure: follow_idx,
+            source: false,
         }));
         proc.blocks.add_edge(current_idx, cond_idx, ());
         proc.blocks.add_edge(current_idx, follow_idx, ());
@@ -1048,6 +1054,7 @@ pub fn block_scan_push(
             cond: IRValue::Variable(t_check_matched_branch_neg),
             success: panic_idx,
             failure: new_current_idx,
+            source: false,
         }),
     ]);
 
@@ -1101,6 +1108,7 @@ pub fn block_scan_push(
                 cond: IRValue::Variable(t_check_matched_branch_eq),
                 success: branch_idx,
                 failure: follow_idx,
+                source: false,
             }),
         ]);
         proc.blocks.add_edge(current_idx, branch_idx, ());
@@ -1116,7 +1124,7 @@ pub fn block_scan_push(
                     target: IRTarget::Variable(t_i),
                     types: IRType::PTR,
                     source: IRValue::BuiltinProc(BuiltinProc::StackAdd),
-                    op: IROp::NativeCall(vec![IRValue::String(i.to_string())]),
+                    op: IROp::NativeCall(vec![IRValue::Symbol(IRSymbol::new(i))]),
                 }));
                 shared_proc.definitions.push((i.to_string(), t_i));
             });
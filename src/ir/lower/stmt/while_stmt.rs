@@ -51,6 +51,7 @@ pub fn block_while_push(
         cond: IRValue::Variable(t_cond),
         success: loop_idx,
         failure: follow_idx,
+        source: true,
     }));
     proc.blocks.add_edge(cond_idx, loop_idx, ());
     proc.blocks.add_edge(cond_idx, follow_idx, ());
@@ -117,6 +118,7 @@ pub fn block_do_while_push(
         cond: IRValue::Variable(t_cond),
         success: loop_idx,
         failure: follow_idx,
+        source: true,
     }));
     proc.blocks.add_edge(cond_idx, loop_idx, ());
     proc.blocks.add_edge(cond_idx, follow_idx, ());
@@ -1,4 +1,7 @@
 #[macro_use]
 pub mod def;
+pub mod cache;
 pub mod dump;
 pub mod lower;
+pub mod opt;
+pub mod symbol;
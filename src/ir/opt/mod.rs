@@ -0,0 +1,94 @@
+use petgraph::stable_graph::NodeIndex;
+use std::collections::BTreeSet;
+
+use crate::cli::InputOpts;
+use crate::ir::def::*;
+use crate::ir::dump::ir_dump;
+
+mod pass_branch;
+mod pass_dead;
+mod pass_fold;
+mod pass_propagate;
+mod pass_prune;
+
+/// Returns the blocks control can continue to after `bb`.
+/// Statements following the first terminator are never executed.
+fn block_successors(bb: &IRBlock) -> Vec<NodeIndex> {
+    for stmt in bb {
+        match stmt {
+            IRStmt::Branch(b) => return vec![b.success, b.failure],
+            IRStmt::Try(t) => return vec![t.attempt, t.catch],
+            IRStmt::TryEnd(next) | IRStmt::Goto(next) => return vec![*next],
+            IRStmt::Return(_) | IRStmt::Unreachable => return Vec::new(),
+            _ => (),
+        }
+    }
+
+    Vec::new()
+}
+
+/// Returns all variables whose address is taken somewhere in `proc`.
+fn procedure_escaped_vars(proc: &IRProcedure) -> BTreeSet<IRVar> {
+    proc.blocks
+        .node_weights()
+        .flatten()
+        .filter_map(|stmt| match stmt {
+            IRStmt::Assign(IRAssign {
+                op: IROp::PtrAddress,
+                source: IRValue::Variable(v),
+                ..
+            }) => Some(*v),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Values that don't own memory and can be freely duplicated.
+fn val_is_literal(val: &IRValue) -> bool {
+    matches!(
+        val,
+        IRValue::Undefined
+            | IRValue::Number(_)
+            | IRValue::Double(_)
+            | IRValue::Bool(_)
+            | IRValue::Type(_)
+    )
+}
+
+fn val_same(lhs: &IRValue, rhs: &IRValue) -> bool {
+    match (lhs, rhs) {
+        (IRValue::Undefined, IRValue::Undefined) => true,
+        (IRValue::Variable(l), IRValue::Variable(r)) => l == r,
+        (IRValue::Number(l), IRValue::Number(r)) => l == r,
+        (IRValue::Double(l), IRValue::Double(r)) => l.to_bits() == r.to_bits(),
+        (IRValue::Bool(l), IRValue::Bool(r)) => l == r,
+        (IRValue::Type(l), IRValue::Type(r)) => l == r,
+        _ => false,
+    }
+}
+
+pub fn ir_opt(cfg: &IRCfg, opts: &InputOpts) {
+    if opts.opt_level == 0 {
+        return;
+    }
+
+    for proc in cfg.procedures.node_weights() {
+        let mut proc = proc.borrow_mut();
+
+        loop {
+            let mut changed = pass_propagate::pass(&mut proc);
+            changed |= pass_fold::pass(&mut proc);
+            changed |= pass_branch::pass(&mut proc);
+            changed |= pass_prune::pass(&mut proc);
+            changed |= pass_dead::pass(&mut proc);
+
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    if opts.dump_ir_opt {
+        ir_dump(cfg, opts, "01-opt");
+    }
+}
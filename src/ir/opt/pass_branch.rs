@@ -0,0 +1,23 @@
+use crate::ir::def::*;
+
+pub fn pass(proc: &mut IRProcedure) -> bool {
+    let mut changed = false;
+
+    for bb in proc.blocks.node_weights_mut() {
+        for stmt in bb.iter_mut() {
+            if let IRStmt::Branch(b) = stmt {
+                let target = match b.cond {
+                    IRValue::Bool(true) => b.success,
+                    IRValue::Bool(false) => b.failure,
+                    _ if b.success == b.failure => b.success,
+                    _ => continue,
+                };
+
+                *stmt = IRStmt::Goto(target);
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
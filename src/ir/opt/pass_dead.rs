@@ -0,0 +1,83 @@
+use std::collections::BTreeSet;
+
+use crate::ir::def::*;
+use crate::ir::opt::val_is_literal;
+
+fn val_uses(val: &IRValue, uses: &mut BTreeSet<IRVar>) {
+    match val {
+        IRValue::Variable(v) => {
+            uses.insert(*v);
+        }
+        IRValue::Vector(v) => v.iter().for_each(|i| val_uses(i, uses)),
+        IRValue::Matrix(m) => m.iter().flatten().for_each(|i| val_uses(i, uses)),
+        _ => (),
+    }
+}
+
+fn stmt_uses(stmt: &IRStmt, uses: &mut BTreeSet<IRVar>) {
+    match stmt {
+        IRStmt::Assign(a) => {
+            if let IRTarget::Deref(v) = a.target {
+                uses.insert(v);
+            }
+
+            val_uses(&a.source, uses);
+
+            match &a.op {
+                IROp::Call(v) => {
+                    uses.insert(*v);
+                }
+                IROp::NativeCall(params) => params.iter().for_each(|i| val_uses(i, uses)),
+                IROp::AccessArray(r)
+                | IROp::Or(r)
+                | IROp::And(r)
+                | IROp::Less(r)
+                | IROp::Equal(r)
+                | IROp::Plus(r)
+                | IROp::Minus(r)
+                | IROp::Mult(r)
+                | IROp::Divide(r)
+                | IROp::IntDivide(r)
+                | IROp::Mod(r) => val_uses(r, uses),
+                IROp::PtrAddress | IROp::PtrDeref | IROp::Assign | IROp::Not => (),
+            }
+        }
+        IRStmt::Branch(b) => val_uses(&b.cond, uses),
+        IRStmt::Return(v) => val_uses(v, uses),
+        _ => (),
+    }
+}
+
+/// Removes plain assignments to variables that are never read.
+pub fn pass(proc: &mut IRProcedure) -> bool {
+    let mut uses = BTreeSet::new();
+    proc.blocks
+        .node_weights()
+        .flatten()
+        .for_each(|stmt| stmt_uses(stmt, &mut uses));
+
+    let mut changed = false;
+
+    for bb in proc.blocks.node_weights_mut() {
+        let len = bb.len();
+
+        bb.retain(|stmt| match stmt {
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Variable(v),
+                op: IROp::Assign,
+                ..
+            }) => uses.contains(v),
+            IRStmt::Assign(IRAssign {
+                target: IRTarget::Ignore,
+                op: IROp::Assign,
+                source,
+                ..
+            }) => !matches!(source, IRValue::Variable(_)) && !val_is_literal(source),
+            _ => true,
+        });
+
+        changed |= bb.len() != len;
+    }
+
+    changed
+}
@@ -0,0 +1,103 @@
+use num_traits::Zero;
+use std::cmp::Ordering;
+
+use crate::builtin::BuiltinProc;
+use crate::ir::def::*;
+use crate::ir::opt::val_is_literal;
+
+fn literal_type(val: &IRValue) -> Option<IRType> {
+    match val {
+        IRValue::Undefined => Some(IRType::UNDEFINED),
+        IRValue::Number(_) => Some(IRType::NUMBER),
+        IRValue::Double(_) => Some(IRType::DOUBLE),
+        IRValue::Bool(_) => Some(IRType::BOOL),
+        IRValue::Type(_) => Some(IRType::TYPE),
+        _ => None,
+    }
+}
+
+/* Only combinations for which the interpreter can't throw are folded.
+ * Number / Number is only folded if the quotient is a number, as
+ * rationals can't be represented as literals.
+ */
+fn binary_fold(op: &IROp, lhs: &IRValue, rhs: &IRValue) -> Option<IRValue> {
+    Some(match (op, lhs, rhs) {
+        (IROp::Or(_), IRValue::Bool(l), IRValue::Bool(r)) => IRValue::Bool(*l || *r),
+        (IROp::And(_), IRValue::Bool(l), IRValue::Bool(r)) => IRValue::Bool(*l && *r),
+        (IROp::Less(_), IRValue::Number(l), IRValue::Number(r)) => IRValue::Bool(l < r),
+        (IROp::Less(_), IRValue::Double(l), IRValue::Double(r)) => IRValue::Bool(l < r),
+        (IROp::Equal(_), IRValue::Number(l), IRValue::Number(r)) => IRValue::Bool(l == r),
+        (IROp::Equal(_), IRValue::Double(l), IRValue::Double(r)) => {
+            IRValue::Bool(l.partial_cmp(r) == Some(Ordering::Equal))
+        }
+        (IROp::Equal(_), IRValue::Bool(l), IRValue::Bool(r)) => IRValue::Bool(l == r),
+        (IROp::Equal(_), IRValue::Type(l), IRValue::Type(r)) => IRValue::Bool(l == r),
+        (IROp::Plus(_), IRValue::Number(l), IRValue::Number(r)) => IRValue::Number(l + r),
+        (IROp::Plus(_), IRValue::Double(l), IRValue::Double(r)) => IRValue::Double(l + r),
+        (IROp::Minus(_), IRValue::Number(l), IRValue::Number(r)) => IRValue::Number(l - r),
+        (IROp::Minus(_), IRValue::Double(l), IRValue::Double(r)) => IRValue::Double(l - r),
+        (IROp::Mult(_), IRValue::Number(l), IRValue::Number(r)) => IRValue::Number(l * r),
+        (IROp::Mult(_), IRValue::Double(l), IRValue::Double(r)) => IRValue::Double(l * r),
+        (IROp::Divide(_), IRValue::Double(l), IRValue::Double(r)) => IRValue::Double(l / r),
+        (IROp::Divide(_), IRValue::Number(l), IRValue::Number(r))
+            if !r.is_zero() && (l % r).is_zero() =>
+        {
+            IRValue::Number(l / r)
+        }
+        (IROp::IntDivide(_), IRValue::Number(l), IRValue::Number(r)) if !r.is_zero() => {
+            IRValue::Number(l / r)
+        }
+        (IROp::Mod(_), IRValue::Number(l), IRValue::Number(r)) if !r.is_zero() => {
+            IRValue::Number(l % r)
+        }
+        _ => return None,
+    })
+}
+
+/// Evaluates `a` at compile time.
+///
+/// Returns `None` if the result depends on runtime values or if evaluating
+/// the assignment could have side effects.
+pub fn assign_fold(a: &IRAssign) -> Option<IRValue> {
+    match &a.op {
+        IROp::Assign if val_is_literal(&a.source) => Some(a.source.clone()),
+        IROp::Not => match &a.source {
+            IRValue::Bool(b) => Some(IRValue::Bool(!b)),
+            _ => None,
+        },
+        IROp::NativeCall(params) => match (&a.source, params.as_slice()) {
+            (IRValue::BuiltinProc(BuiltinProc::TypeOf), [p]) => literal_type(p).map(IRValue::Type),
+            _ => None,
+        },
+        op @ (IROp::Or(r)
+        | IROp::And(r)
+        | IROp::Less(r)
+        | IROp::Equal(r)
+        | IROp::Plus(r)
+        | IROp::Minus(r)
+        | IROp::Mult(r)
+        | IROp::Divide(r)
+        | IROp::IntDivide(r)
+        | IROp::Mod(r)) => binary_fold(op, &a.source, r),
+        _ => None,
+    }
+}
+
+pub fn pass(proc: &mut IRProcedure) -> bool {
+    let mut changed = false;
+
+    for bb in proc.blocks.node_weights_mut() {
+        for stmt in bb.iter_mut() {
+            if let IRStmt::Assign(a) = stmt
+                && !matches!(a.op, IROp::Assign)
+                && let Some(val) = assign_fold(a)
+            {
+                a.op = IROp::Assign;
+                a.source = val;
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
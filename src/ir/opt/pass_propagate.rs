@@ -0,0 +1,204 @@
+use petgraph::stable_graph::NodeIndex;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::builtin::BuiltinProc;
+use crate::ir::def::*;
+use crate::ir::opt::pass_fold::assign_fold;
+use crate::ir::opt::{procedure_escaped_vars, val_same};
+
+/// Operands that are only read by the assignment and can therefore be
+/// replaced by an equivalent value.
+fn assign_operands(a: &mut IRAssign) -> Vec<&mut IRValue> {
+    let IRAssign { source, op, .. } = a;
+
+    match op {
+        IROp::Or(r)
+        | IROp::And(r)
+        | IROp::Less(r)
+        | IROp::Equal(r)
+        | IROp::Plus(r)
+        | IROp::Minus(r)
+        | IROp::Mult(r)
+        | IROp::Divide(r)
+        | IROp::IntDivide(r)
+        | IROp::Mod(r) => vec![source, r],
+        IROp::Not | IROp::Assign => vec![source],
+        IROp::NativeCall(params) if matches!(source, IRValue::BuiltinProc(BuiltinProc::TypeOf)) => {
+            params.iter_mut().collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Known values of variables at a program point.
+/// A variable is either mapped to a literal or to another variable it is a copy of.
+/// Variables whose address has been taken can be modified through pointers
+/// and are no longer tracked.
+#[derive(Clone, Default)]
+struct PropState {
+    vals: BTreeMap<IRVar, IRValue>,
+    escaped: BTreeSet<IRVar>,
+}
+
+impl PropState {
+    fn resolve(&self, val: &IRValue) -> Option<IRValue> {
+        match val {
+            IRValue::Variable(v) => self.vals.get(v).cloned(),
+            _ => None,
+        }
+    }
+
+    fn kill(&mut self, v: IRVar) {
+        self.vals.remove(&v);
+        self.vals
+            .retain(|_, val| !matches!(val, IRValue::Variable(u) if *u == v));
+    }
+
+    /// Keeps the values both states agree on. Returns whether `self` changed.
+    fn merge(&mut self, other: &PropState) -> bool {
+        let len = (self.vals.len(), self.escaped.len());
+        self.escaped.extend(other.escaped.iter().copied());
+
+        let escaped = &self.escaped;
+        self.vals.retain(|v, val| {
+            !escaped.contains(v) && other.vals.get(v).is_some_and(|o| val_same(val, o))
+        });
+
+        len != (self.vals.len(), self.escaped.len())
+    }
+
+    /* Literal numbers are not substituted into plain assignments as the
+     * target would then own a new number instead of sharing the original.
+     */
+    fn subst(&self, a: &mut IRAssign, all: bool) -> bool {
+        let is_assign = matches!(a.op, IROp::Assign);
+        let mut changed = false;
+
+        for val in assign_operands(a) {
+            if let Some(r) = self.resolve(val)
+                && (all || !is_assign || !matches!(r, IRValue::Number(_)))
+            {
+                *val = r;
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    fn transfer(&mut self, a: &IRAssign) {
+        if let (IROp::PtrAddress, IRValue::Variable(u)) = (&a.op, &a.source) {
+            self.kill(*u);
+            self.escaped.insert(*u);
+        }
+
+        let val = match a.target {
+            IRTarget::Variable(v) if !self.escaped.contains(&v) => {
+                let mut a = a.clone();
+                self.subst(&mut a, true);
+
+                match (&a.op, &a.source) {
+                    (IROp::Assign, IRValue::Variable(u)) => {
+                        (*u != v && !self.escaped.contains(u)).then_some(IRValue::Variable(*u))
+                    }
+                    _ => assign_fold(&a),
+                }
+            }
+            _ => None,
+        };
+
+        // natives may modify the variables they are passed, e.g. iterators
+        if let IROp::NativeCall(params) = &a.op
+            && !matches!(a.source, IRValue::BuiltinProc(BuiltinProc::TypeOf))
+        {
+            for p in params {
+                if let IRValue::Variable(u) = p {
+                    self.kill(*u);
+                }
+            }
+        }
+
+        if let IRTarget::Variable(v) = a.target {
+            self.kill(v);
+            if let Some(val) = val {
+                self.vals.insert(v, val);
+            }
+        }
+    }
+}
+
+fn entry_states(proc: &IRProcedure) -> BTreeMap<NodeIndex, PropState> {
+    let catch_state = PropState {
+        vals: BTreeMap::new(),
+        escaped: procedure_escaped_vars(proc),
+    };
+    let mut entry = BTreeMap::from([(proc.start_block, PropState::default())]);
+    let mut work = vec![proc.start_block];
+
+    while let Some(idx) = work.pop() {
+        let mut state = entry[&idx].clone();
+        let mut succs = Vec::new();
+
+        for stmt in &proc.blocks[idx] {
+            match stmt {
+                IRStmt::Assign(a) => state.transfer(a),
+                IRStmt::Branch(b) => {
+                    succs.push((b.success, state.clone()));
+                    succs.push((b.failure, state));
+                    break;
+                }
+                // the catch block can be entered from anywhere within the attempt
+                IRStmt::Try(t) => {
+                    succs.push((t.attempt, state));
+                    succs.push((t.catch, catch_state.clone()));
+                    break;
+                }
+                IRStmt::TryEnd(next) | IRStmt::Goto(next) => {
+                    succs.push((*next, state));
+                    break;
+                }
+                IRStmt::Return(_) | IRStmt::Unreachable => break,
+                IRStmt::Annotate(..) => (),
+            }
+        }
+
+        for (succ, s) in succs {
+            if let Some(e) = entry.get_mut(&succ) {
+                if e.merge(&s) {
+                    work.push(succ);
+                }
+            } else {
+                entry.insert(succ, s);
+                work.push(succ);
+            }
+        }
+    }
+
+    entry
+}
+
+pub fn pass(proc: &mut IRProcedure) -> bool {
+    let mut changed = false;
+
+    for (idx, mut state) in entry_states(proc) {
+        for stmt in proc.blocks[idx].iter_mut() {
+            match stmt {
+                IRStmt::Assign(a) => {
+                    changed |= state.subst(a, false);
+                    state.transfer(a);
+                }
+                IRStmt::Branch(b) => {
+                    if let Some(cond) = state.resolve(&b.cond) {
+                        b.cond = cond;
+                        changed = true;
+                    }
+                    break;
+                }
+                IRStmt::Annotate(..) => (),
+                _ => break,
+            }
+        }
+    }
+
+    changed
+}
@@ -0,0 +1,33 @@
+use petgraph::stable_graph::NodeIndex;
+use std::collections::BTreeSet;
+
+use crate::ir::def::*;
+use crate::ir::opt::block_successors;
+
+pub fn pass(proc: &mut IRProcedure) -> bool {
+    let mut reachable = BTreeSet::from([proc.start_block]);
+    let mut work = vec![proc.start_block];
+
+    while let Some(idx) = work.pop() {
+        for succ in block_successors(&proc.blocks[idx]) {
+            if reachable.insert(succ) {
+                work.push(succ);
+            }
+        }
+    }
+
+    // the end block is referenced by the procedure even if it can't be reached
+    reachable.insert(proc.end_block);
+
+    let unreachable: Vec<NodeIndex> = proc
+        .blocks
+        .node_indices()
+        .filter(|idx| !reachable.contains(idx))
+        .collect();
+
+    for idx in &unreachable {
+        proc.blocks.remove_node(*idx);
+    }
+
+    !unreachable.is_empty()
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Variable name interned once for the lifetime of the process, so the
+/// interpreter stack can compare and index names by number.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IRSymbol(u32);
+
+/* The table owns the names, both maps share one allocation per name. Names
+ * are never released, the number of distinct variable names is bounded by
+ * the loaded sources and the symbols of cached IR stay valid.
+ */
+#[derive(Default)]
+struct IRSymbolTable {
+    names: Vec<Arc<str>>,
+    ids: HashMap<Arc<str>, u32>,
+}
+
+static mut SYMBOLS: Option<IRSymbolTable> = None;
+
+#[allow(static_mut_refs)]
+fn symbols_get() -> &'static mut IRSymbolTable {
+    //SAFETY: setlx-rs is single-threaded
+    unsafe { SYMBOLS.get_or_insert_with(IRSymbolTable::default) }
+}
+
+impl IRSymbol {
+    pub fn new(name: &str) -> Self {
+        let table = symbols_get();
+        if let Some(id) = table.ids.get(name) {
+            return IRSymbol(*id);
+        }
+
+        let name: Arc<str> = Arc::from(name);
+        let id = table.names.len() as u32;
+        table.names.push(name.clone());
+        table.ids.insert(name, id);
+        IRSymbol(id)
+    }
+
+    pub fn name(self) -> Arc<str> {
+        symbols_get().names[self.0 as usize].clone()
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for IRSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+impl fmt::Debug for IRSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.name())
+    }
+}
@@ -10,6 +10,9 @@ pub mod builtin;
 mod diagnostics;
 pub mod cli;
 pub mod cst;
+pub mod embed;
+pub mod golden;
 pub mod interp;
 pub mod ir;
+pub mod lsp;
 mod util;
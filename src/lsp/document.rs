@@ -0,0 +1,113 @@
+use std::collections::BTreeSet;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::cli::InputOpts;
+use crate::cst::cst_parse;
+use crate::diagnostics::{Diagnostic, collector_install, collector_take};
+use crate::lsp::scope::ScopeTree;
+
+/// Open text document together with the results of its last analysis.
+pub struct Document {
+    pub text: String,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Scopes of the last version that parsed successfully, kept around so
+    /// completion keeps working while the user is in the middle of an edit.
+    pub scopes: Option<ScopeTree>,
+    line_starts: Vec<usize>,
+}
+
+impl Document {
+    pub fn new(text: String, srcname: &str, globals: &BTreeSet<String>) -> Self {
+        let mut out = Document {
+            text: String::new(),
+            diagnostics: Vec::new(),
+            scopes: None,
+            line_starts: Vec::new(),
+        };
+        out.update(text, srcname, globals);
+        out
+    }
+
+    /// Replaces the content of the document and reruns the CST passes on it.
+    pub fn update(&mut self, text: String, srcname: &str, globals: &BTreeSet<String>) {
+        self.line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        let mut opts = InputOpts::none();
+        opts.srcname = srcname.to_string();
+
+        collector_install();
+        let old_hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let cst = panic::catch_unwind(AssertUnwindSafe(|| cst_parse(&text, &opts)));
+        panic::set_hook(old_hook);
+        self.diagnostics = collector_take();
+
+        if let Ok(cst) = cst {
+            let scopes = ScopeTree::from_cst(&cst, &text);
+            if opts.warn_implicit_decl {
+                self.diagnostics
+                    .extend(scopes.implicit_decls(globals).iter().map(|i| Diagnostic {
+                        is_error: false,
+                        cat_msg: String::from("implicit declaration"),
+                        msg: format!("implicit declaration of {}", i.name),
+                        lhs: i.lhs,
+                        rhs: i.rhs,
+                    }));
+            }
+            self.scopes = Some(scopes);
+        }
+
+        self.text = text;
+    }
+
+    /// Converts a byte offset into a zero based line and UTF-16 column.
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|i| *i <= offset) - 1;
+        let col = self.text[self.line_starts[line]..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum();
+        (line, col)
+    }
+
+    /// Converts a zero based line and UTF-16 column into a byte offset.
+    pub fn offset(&self, line: usize, col: usize) -> usize {
+        let Some(start) = self.line_starts.get(line) else {
+            return self.text.len();
+        };
+
+        let mut units = 0;
+        for (idx, c) in self.text[*start..].char_indices() {
+            if units >= col || c == '\n' {
+                return start + idx;
+            }
+            units += c.len_utf16();
+        }
+        self.text.len()
+    }
+
+    /// Returns the identifier around `offset` and the byte offset it starts at.
+    pub fn word_at(&self, offset: usize) -> Option<(&str, usize)> {
+        let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let lhs = self.text[..offset]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_ident(*c))
+            .last()
+            .map(|(i, _)| i)
+            .unwrap_or(offset);
+        let rhs = self.text[offset..]
+            .find(|c: char| !is_ident(c))
+            .map(|i| i + offset)
+            .unwrap_or(self.text.len());
+
+        if lhs < rhs && self.text[lhs..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            Some((&self.text[lhs..rhs], lhs))
+        } else {
+            None
+        }
+    }
+}
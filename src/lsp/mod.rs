@@ -0,0 +1,326 @@
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, BufRead, Write};
+
+use crate::builtin::stubs::stubs_init;
+use crate::interp::stack::InterpStackEntry;
+
+mod document;
+pub mod scope;
+
+use document::Document;
+use scope::SymbolKind;
+
+const COMPLETION_KIND_FUNCTION: u32 = 3;
+const COMPLETION_KIND_VARIABLE: u32 = 6;
+const COMPLETION_KIND_CLASS: u32 = 7;
+
+const ERROR_PARSE: i32 = -32700;
+const ERROR_METHOD_NOT_FOUND: i32 = -32601;
+
+/// Language server speaking JSON-RPC over stdio.
+///
+/// Diagnostics come from the CST passes run by `cst_parse`, with implicit
+/// declarations, which the interpreter only reports at runtime,
+/// approximated by `ScopeTree::implicit_decls`.
+pub struct Server {
+    docs: BTreeMap<String, Document>,
+    builtins: BTreeSet<String>,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Server {
+            docs: BTreeMap::new(),
+            builtins: stubs_init(&[])
+                .into_iter()
+                .filter_map(|i| match i {
+                    InterpStackEntry::Variable(v) => Some(v.var.to_string()),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Serves requests from `input` until the client sends `exit` or closes
+    /// the stream.
+    pub fn run(&mut self, input: &mut impl BufRead, output: &mut impl Write) -> io::Result<()> {
+        while let Some(msg) = message_read(input)? {
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(e) => {
+                    let response = json!({
+                        "jsonrpc": "2.0",
+                        "id": null,
+                        "error": {"code": ERROR_PARSE, "message": e.to_string()},
+                    });
+                    message_write(output, &response)?;
+                    continue;
+                }
+            };
+
+            let method = msg["method"].as_str().unwrap_or("");
+            if method == "exit" {
+                break;
+            }
+
+            let (result, notifications) = self.handle(method, &msg["params"]);
+            for n in notifications {
+                message_write(output, &n)?;
+            }
+
+            if let Some(id) = msg.get("id") {
+                let response = match result {
+                    Some(r) => json!({"jsonrpc": "2.0", "id": id, "result": r}),
+                    None => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {
+                            "code": ERROR_METHOD_NOT_FOUND,
+                            "message": format!("unsupported method {method}"),
+                        },
+                    }),
+                };
+                message_write(output, &response)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the result of a request, `None` for unsupported methods, and
+    /// the notifications to send to the client.
+    fn handle(&mut self, method: &str, params: &Value) -> (Option<Value>, Vec<Value>) {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+
+        match method {
+            "initialize" => (
+                Some(json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "completionProvider": {},
+                        "hoverProvider": true,
+                        "definitionProvider": true,
+                    },
+                    "serverInfo": {
+                        "name": "setlx-lsp",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                })),
+                Vec::new(),
+            ),
+            "shutdown" => (Some(Value::Null), Vec::new()),
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or("");
+                let doc = Document::new(text.to_string(), srcname(&uri), &self.builtins);
+                let n = diagnostics_publish(&uri, &doc);
+                self.docs.insert(uri, doc);
+                (None, vec![n])
+            }
+            "textDocument/didChange" => {
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|i| i.last())
+                    .and_then(|i| i["text"].as_str());
+                match (self.docs.get_mut(&uri), text) {
+                    (Some(doc), Some(text)) => {
+                        doc.update(text.to_string(), srcname(&uri), &self.builtins);
+                        (None, vec![diagnostics_publish(&uri, doc)])
+                    }
+                    _ => (None, Vec::new()),
+                }
+            }
+            "textDocument/didClose" => {
+                self.docs.remove(&uri);
+                (
+                    None,
+                    vec![json!({
+                        "jsonrpc": "2.0",
+                        "method": "textDocument/publishDiagnostics",
+                        "params": {"uri": uri, "diagnostics": []},
+                    })],
+                )
+            }
+            "textDocument/completion" => (Some(self.completion(&uri, params)), Vec::new()),
+            "textDocument/hover" => (Some(self.hover(&uri, params)), Vec::new()),
+            "textDocument/definition" => (Some(self.definition(&uri, params)), Vec::new()),
+            _ => (None, Vec::new()),
+        }
+    }
+
+    fn doc_offset(&self, uri: &str, params: &Value) -> Option<(&Document, usize)> {
+        let doc = self.docs.get(uri)?;
+        let line = params["position"]["line"].as_u64()? as usize;
+        let col = params["position"]["character"].as_u64()? as usize;
+        Some((doc, doc.offset(line, col)))
+    }
+
+    fn completion(&self, uri: &str, params: &Value) -> Value {
+        let Some((doc, offset)) = self.doc_offset(uri, params) else {
+            return json!([]);
+        };
+        let prefix = doc
+            .word_at(offset)
+            .map(|(w, lhs)| &w[..offset - lhs])
+            .unwrap_or("");
+
+        let mut items: BTreeMap<&str, u32> = self
+            .builtins
+            .iter()
+            .map(|i| (i.as_str(), COMPLETION_KIND_FUNCTION))
+            .collect();
+        if let Some(scopes) = &doc.scopes {
+            items.extend(
+                scopes
+                    .visible_vars(scopes.scope_at(offset))
+                    .into_iter()
+                    .map(|i| (i, COMPLETION_KIND_VARIABLE)),
+            );
+            items.extend(scopes.symbols.iter().map(|i| match i.kind {
+                SymbolKind::Procedure => (i.name.as_str(), COMPLETION_KIND_FUNCTION),
+                SymbolKind::Class => (i.name.as_str(), COMPLETION_KIND_CLASS),
+            }));
+        }
+
+        Value::Array(
+            items
+                .into_iter()
+                .filter(|(name, _)| name.starts_with(prefix))
+                .map(|(name, kind)| json!({"label": name, "kind": kind}))
+                .collect(),
+        )
+    }
+
+    fn hover(&self, uri: &str, params: &Value) -> Value {
+        let Some((doc, offset)) = self.doc_offset(uri, params) else {
+            return Value::Null;
+        };
+        let Some((word, _)) = doc.word_at(offset) else {
+            return Value::Null;
+        };
+
+        let signatures: Vec<&str> = doc
+            .scopes
+            .iter()
+            .flat_map(|i| &i.symbols)
+            .filter(|i| i.name == word)
+            .map(|i| i.signature.as_str())
+            .collect();
+
+        let value = if !signatures.is_empty() {
+            format!("```setlx\n{}\n```", signatures.join("\n"))
+        } else if self.builtins.contains(word) {
+            format!("builtin procedure `{word}`")
+        } else {
+            return Value::Null;
+        };
+
+        json!({"contents": {"kind": "markdown", "value": value}})
+    }
+
+    fn definition(&self, uri: &str, params: &Value) -> Value {
+        let Some((doc, offset)) = self.doc_offset(uri, params) else {
+            return json!([]);
+        };
+        let Some((word, _)) = doc.word_at(offset) else {
+            return json!([]);
+        };
+
+        Value::Array(
+            doc.scopes
+                .iter()
+                .flat_map(|i| &i.symbols)
+                .filter(|i| i.name == word)
+                .map(|i| json!({"uri": uri, "range": range(doc, i.lhs, i.rhs)}))
+                .collect(),
+        )
+    }
+}
+
+fn srcname(uri: &str) -> &str {
+    uri.strip_prefix("file://").unwrap_or(uri)
+}
+
+fn range(doc: &Document, lhs: usize, rhs: usize) -> Value {
+    let (l_line, l_col) = doc.position(lhs);
+    let (r_line, r_col) = doc.position(rhs);
+    json!({
+        "start": {"line": l_line, "character": l_col},
+        "end": {"line": r_line, "character": r_col},
+    })
+}
+
+fn diagnostics_publish(uri: &str, doc: &Document) -> Value {
+    let diagnostics: Vec<Value> = doc
+        .diagnostics
+        .iter()
+        .map(|i| {
+            json!({
+                "range": range(doc, i.lhs, i.rhs),
+                "severity": if i.is_error { 1 } else { 2 },
+                "source": "setlx-rs",
+                "message": if i.msg.is_empty() {
+                    i.cat_msg.clone()
+                } else {
+                    format!("{}: {}", i.cat_msg, i.msg)
+                },
+            })
+        })
+        .collect();
+
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {"uri": uri, "diagnostics": diagnostics},
+    })
+}
+
+/// Reads one `Content-Length` framed message, `None` at the end of input.
+///
+/// A body that isn't valid JSON is returned as the inner error, since the
+/// framing is intact and the following messages can still be read.
+fn message_read(input: &mut impl BufRead) -> io::Result<Option<serde_json::Result<Value>>> {
+    let mut len = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, val)) = line.split_once(':')
+            && key.eq_ignore_ascii_case("content-length")
+        {
+            len = val.trim().parse::<usize>().ok();
+        }
+    }
+
+    let Some(len) = len else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "missing Content-Length header",
+        ));
+    };
+    let mut buf = vec![0; len];
+    input.read_exact(&mut buf)?;
+
+    Ok(Some(serde_json::from_slice(&buf)))
+}
+
+fn message_write(output: &mut impl Write, msg: &Value) -> io::Result<()> {
+    let body = msg.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()
+}
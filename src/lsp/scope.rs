@@ -0,0 +1,443 @@
+use std::collections::BTreeSet;
+
+use crate::ast::*;
+use crate::ir::lower::proc::{expr_vars_push, procedure_vars_aggregate};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SymbolKind {
+    Procedure,
+    Class,
+}
+
+/// Procedure or class definition, `lhs..rhs` spans the defined name.
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub signature: String,
+    pub lhs: usize,
+    pub rhs: usize,
+}
+
+/// Region of the source with its own set of variables.
+///
+/// Scopes with a `parent` additionally see the variables of their parent,
+/// e.g. closures, lambdas, comprehensions and class methods.
+#[derive(Debug)]
+pub struct Scope {
+    pub lhs: usize,
+    pub rhs: usize,
+    pub parent: Option<usize>,
+    pub is_class: bool,
+    pub vars: BTreeSet<String>,
+}
+
+#[derive(Debug)]
+pub struct VarRead {
+    pub scope: usize,
+    pub name: String,
+    pub lhs: usize,
+    pub rhs: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct ScopeTree {
+    pub scopes: Vec<Scope>,
+    pub symbols: Vec<Symbol>,
+    pub reads: Vec<VarRead>,
+}
+
+impl ScopeTree {
+    pub fn from_cst(cst: &CSTBlock, src: &str) -> Self {
+        let mut out = ScopeTree::default();
+        let scope = out.scope_new(0, src.len(), None, false);
+        // command line arguments, bound before the program starts
+        out.scopes[scope].vars.insert(String::from("params"));
+        let mut w = ScopeWalker {
+            src,
+            tree: &mut out,
+        };
+        w.body(cst, scope);
+
+        out
+    }
+
+    fn scope_new(
+        &mut self,
+        lhs: usize,
+        rhs: usize,
+        parent: Option<usize>,
+        is_class: bool,
+    ) -> usize {
+        self.scopes.push(Scope {
+            lhs,
+            rhs,
+            parent,
+            is_class,
+            vars: BTreeSet::new(),
+        });
+        self.scopes.len() - 1
+    }
+
+    /// Returns the innermost scope containing `offset`.
+    pub fn scope_at(&self, offset: usize) -> usize {
+        let mut out = 0;
+        for (idx, s) in self.scopes.iter().enumerate() {
+            if s.lhs <= offset
+                && offset <= s.rhs
+                && s.rhs - s.lhs <= self.scopes[out].rhs - self.scopes[out].lhs
+            {
+                out = idx;
+            }
+        }
+        out
+    }
+
+    /// Returns all variables visible from `scope`.
+    pub fn visible_vars(&self, scope: usize) -> BTreeSet<&str> {
+        let mut out = BTreeSet::new();
+        let mut s = Some(scope);
+        while let Some(idx) = s {
+            out.extend(self.scopes[idx].vars.iter().map(|i| i.as_str()));
+            s = self.scopes[idx].parent;
+        }
+        out
+    }
+
+    /// Returns variable reads without a definition in any visible scope, which
+    /// the interpreter would implicitly declare at runtime.
+    ///
+    /// Procedures defined in the main block, classes and the names in
+    /// `globals` are resolved without a declaration and are skipped.
+    pub fn implicit_decls(&self, globals: &BTreeSet<String>) -> Vec<&VarRead> {
+        let procs: BTreeSet<&str> = self
+            .symbols
+            .iter()
+            .filter(|i| i.kind == SymbolKind::Class || self.scope_at(i.lhs) == 0)
+            .map(|i| i.name.as_str())
+            .collect();
+
+        self.reads
+            .iter()
+            .filter(|i| {
+                i.name != "this"
+                    && !globals.contains(&i.name)
+                    && !procs.contains(i.name.as_str())
+                    && !self.visible_vars(i.scope).contains(i.name.as_str())
+            })
+            .collect()
+    }
+}
+
+struct ScopeWalker<'a> {
+    src: &'a str,
+    tree: &'a mut ScopeTree,
+}
+
+impl ScopeWalker<'_> {
+    fn bind(&mut self, name: &str, scope: usize) {
+        self.tree.scopes[scope].vars.insert(name.to_string());
+    }
+
+    fn read(&mut self, name: &str, lhs: usize, rhs: usize, scope: usize) {
+        self.tree.reads.push(VarRead {
+            scope,
+            name: name.to_string(),
+            lhs,
+            rhs,
+        });
+    }
+
+    fn params_str(&self, params: &[CSTParam], list_param: Option<&String>) -> String {
+        params
+            .iter()
+            .map(|i| {
+                let mut out = String::new();
+                if i.is_rw {
+                    out.push_str("rw ");
+                }
+                out.push_str(&i.name);
+                if let Some(d) = &i.default {
+                    out.push_str(" := ");
+                    out.push_str(&self.src[d.lhs..d.rhs]);
+                }
+                out
+            })
+            .chain(list_param.map(|i| format!("*{i}")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Walks the body of a program, procedure or class, which declares the
+    /// assignment targets of all its nested blocks the same way the lowering
+    /// does.
+    fn body(&mut self, block: &CSTBlock, scope: usize) {
+        procedure_vars_aggregate(block)
+            .iter()
+            .for_each(|i| self.bind(i, scope));
+        self.block(block, scope);
+    }
+
+    fn block(&mut self, block: &CSTBlock, scope: usize) {
+        block.iter().for_each(|i| self.stmt(i, scope));
+    }
+
+    fn stmt(&mut self, s: &CSTStatement, scope: usize) {
+        match &s.kind {
+            CSTStatementKind::Class(c) => self.class(c, s, scope),
+            CSTStatementKind::If(i) | CSTStatementKind::Switch(i) => {
+                for b in &i.branches {
+                    self.expr(&b.condition, scope);
+                    self.block(&b.block, scope);
+                }
+                if let Some(alt) = &i.alternative {
+                    self.block(alt, scope);
+                }
+            }
+            CSTStatementKind::Match(m) => {
+                self.expr(&m.expression, scope);
+                m.branches.iter().for_each(|i| self.match_branch(i, scope));
+                self.block(&m.default, scope);
+            }
+            CSTStatementKind::Scan(sc) => {
+                self.expr(&sc.expression, scope);
+                if let Some(v) = &sc.variable {
+                    self.bind(v, scope);
+                }
+                sc.branches.iter().for_each(|i| self.match_branch(i, scope));
+                if let Some(d) = &sc.default {
+                    self.block(d, scope);
+                }
+            }
+            CSTStatementKind::For(f) => {
+                self.iter_params(&f.params, scope);
+                if let Some(c) = &f.condition {
+                    self.expr(c, scope);
+                }
+                self.block(&f.block, scope);
+            }
+            CSTStatementKind::While(w) | CSTStatementKind::DoWhile(w) => {
+                self.expr(&w.condition, scope);
+                self.block(&w.block, scope);
+            }
+            CSTStatementKind::TryCatch(t) => {
+                self.block(&t.try_branch, scope);
+                for c in &t.catch_branches {
+                    self.bind(&c.exception, scope);
+                    self.block(&c.block, scope);
+                }
+            }
+            CSTStatementKind::Check(c) => {
+                self.block(&c.block, scope);
+                self.block(&c.after_backtrack, scope);
+            }
+            CSTStatementKind::Return(r) => {
+                if let Some(v) = &r.val {
+                    self.expr(v, scope);
+                }
+            }
+            CSTStatementKind::Assign(a) => self.assign(a, scope),
+            CSTStatementKind::AssignMod(a) => {
+                self.expr(&a.assign, scope);
+                self.expr(&a.expr, scope);
+            }
+            CSTStatementKind::Expression(e) => self.expr(e, scope),
+            CSTStatementKind::Backtrack
+            | CSTStatementKind::Break
+            | CSTStatementKind::Continue
+            | CSTStatementKind::Exit => (),
+        }
+    }
+
+    fn class(&mut self, c: &CSTClass, s: &CSTStatement, scope: usize) {
+        let lhs = self.src[s.lhs..s.rhs]
+            .find(c.name.as_str())
+            .map(|i| i + s.lhs)
+            .unwrap_or(s.lhs);
+        self.tree.symbols.push(Symbol {
+            name: c.name.clone(),
+            kind: SymbolKind::Class,
+            signature: format!("class {}({})", c.name, self.params_str(&c.params, None)),
+            lhs,
+            rhs: lhs + c.name.len(),
+        });
+
+        let class_scope = self.tree.scope_new(s.lhs, s.rhs, None, true);
+        for p in &c.params {
+            if let Some(d) = &p.default {
+                self.expr(d, scope);
+            }
+            self.bind(&p.name, class_scope);
+        }
+        self.body(&c.block, class_scope);
+        if let Some(b) = &c.static_block {
+            self.body(b, class_scope);
+        }
+    }
+
+    fn assign(&mut self, a: &CSTAssign, scope: usize) {
+        // the target is already bound by `body`
+        self.expr(&a.assign, scope);
+
+        match &a.expr.kind {
+            CSTStatementKind::Assign(next) => self.assign(next, scope),
+            CSTStatementKind::Expression(e) => {
+                if let (CSTExpressionKind::Variable(name), CSTExpressionKind::Procedure(p)) =
+                    (&a.assign.kind, &e.kind)
+                {
+                    self.tree.symbols.push(Symbol {
+                        name: name.clone(),
+                        kind: SymbolKind::Procedure,
+                        signature: format!(
+                            "{name} := {}({})",
+                            p.kind,
+                            self.params_str(&p.params, p.list_param.as_ref())
+                        ),
+                        lhs: a.assign.lhs,
+                        rhs: a.assign.rhs,
+                    });
+                }
+                self.expr(e, scope);
+            }
+            _ => self.stmt(&a.expr, scope),
+        }
+    }
+
+    /// Binds the variables of an iteration, lambda or pattern target and
+    /// records the remaining reads.
+    fn target(&mut self, e: &CSTExpression, scope: usize) {
+        let mut vars = Vec::new();
+        expr_vars_push(e, &mut vars);
+        vars.iter().for_each(|i| self.bind(i, scope));
+        self.expr(e, scope);
+    }
+
+    fn iter_params(&mut self, params: &[CSTIterParam], scope: usize) {
+        for p in params {
+            self.expr(&p.collection, scope);
+            self.target(&p.variable, scope);
+        }
+    }
+
+    fn match_branch(&mut self, b: &CSTMatchBranch, scope: usize) {
+        match b {
+            CSTMatchBranch::Case(c) => {
+                c.expressions.iter().for_each(|i| self.target(i, scope));
+                if let Some(cond) = &c.condition {
+                    self.expr(cond, scope);
+                }
+                self.block(&c.statements, scope);
+            }
+            CSTMatchBranch::Regex(r) => {
+                self.expr(&r.pattern, scope);
+                if let Some(out) = &r.pattern_out {
+                    self.target(out, scope);
+                }
+                if let Some(cond) = &r.condition {
+                    self.expr(cond, scope);
+                }
+                self.block(&r.statements, scope);
+            }
+        }
+    }
+
+    fn set(&mut self, s: &CSTSet, scope: usize) {
+        if let Some(r) = &s.range {
+            if let Some(l) = &r.left {
+                self.expr(l, scope);
+            }
+            if let Some(r) = &r.right {
+                self.expr(r, scope);
+            }
+        }
+        s.expressions.iter().for_each(|i| self.expr(i, scope));
+        if let Some(rest) = &s.rest {
+            self.expr(rest, scope);
+        }
+    }
+
+    fn expr(&mut self, e: &CSTExpression, scope: usize) {
+        match &e.kind {
+            CSTExpressionKind::Lambda(l) => {
+                let inner = self.tree.scope_new(e.lhs, e.rhs, Some(scope), false);
+                if let CSTCollection::List(p) = &l.params {
+                    p.expressions.iter().for_each(|i| self.target(i, inner));
+                }
+                self.expr(&l.expr, inner);
+            }
+            CSTExpressionKind::Op(o) => {
+                self.expr(&o.left, scope);
+                self.expr(&o.right, scope);
+            }
+            CSTExpressionKind::UnaryOp(o) => self.expr(&o.expr, scope),
+            CSTExpressionKind::Procedure(p) => {
+                let parent =
+                    if p.kind == CSTProcedureKind::Closure || self.tree.scopes[scope].is_class {
+                        Some(scope)
+                    } else {
+                        None
+                    };
+                let inner = self.tree.scope_new(e.lhs, e.rhs, parent, false);
+                for i in &p.params {
+                    if let Some(d) = &i.default {
+                        self.expr(d, scope);
+                    }
+                    self.bind(&i.name, inner);
+                }
+                if let Some(l) = &p.list_param {
+                    self.bind(l, inner);
+                }
+                self.body(&p.block, inner);
+            }
+            CSTExpressionKind::Call(c) => {
+                c.params.iter().for_each(|i| self.expr(i, scope));
+                if let Some(rest) = &c.rest_param {
+                    self.expr(rest, scope);
+                }
+            }
+            CSTExpressionKind::Term(t) => t.params.iter().for_each(|i| self.expr(i, scope)),
+            CSTExpressionKind::Variable(v) => self.read(v, e.lhs, e.rhs, scope),
+            CSTExpressionKind::Accessible(a) => {
+                self.expr(&a.head, scope);
+                for i in &a.body {
+                    match &i.kind {
+                        CSTExpressionKind::Variable(_) => (),
+                        CSTExpressionKind::Call(c) => {
+                            c.params.iter().for_each(|i| self.expr(i, scope));
+                            if let Some(rest) = &c.rest_param {
+                                self.expr(rest, scope);
+                            }
+                        }
+                        _ => self.expr(i, scope),
+                    }
+                }
+            }
+            CSTExpressionKind::Collection(c) => match c {
+                CSTCollection::Set(s) | CSTCollection::List(s) => self.set(s, scope),
+                CSTCollection::SetComprehension(c) | CSTCollection::ListComprehension(c) => {
+                    let inner = self.tree.scope_new(e.lhs, e.rhs, Some(scope), false);
+                    self.iter_params(&c.iterators, inner);
+                    if let Some(cond) = &c.condition {
+                        self.expr(cond, inner);
+                    }
+                    self.expr(&c.expression, inner);
+                }
+            },
+            CSTExpressionKind::Matrix(m) => m.iter().flatten().for_each(|i| self.expr(i, scope)),
+            CSTExpressionKind::Vector(v) => v.iter().for_each(|i| self.expr(i, scope)),
+            CSTExpressionKind::Quantifier(q) => {
+                let inner = self.tree.scope_new(e.lhs, e.rhs, Some(scope), false);
+                self.iter_params(&q.iterators, inner);
+                self.expr(&q.condition, inner);
+            }
+            CSTExpressionKind::Serialize(s) => self.expr(s, scope),
+            CSTExpressionKind::String(_)
+            | CSTExpressionKind::Literal(_)
+            | CSTExpressionKind::Bool(_)
+            | CSTExpressionKind::Double(_)
+            | CSTExpressionKind::Number(_)
+            | CSTExpressionKind::Om
+            | CSTExpressionKind::Ignore => (),
+        }
+    }
+}
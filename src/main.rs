@@ -5,6 +5,8 @@ use gag::BufferRedirect;
 use pretty_assertions::assert_eq;
 use std::fs;
 use std::io::Read;
+use std::process::exit;
+use std::thread;
 
 mod setlx_parse {
     include!(concat!(env!("OUT_DIR"), "/grammar.rs"));
@@ -17,25 +19,77 @@ use cli::cli;
 mod cst;
 use cst::cst_parse;
 mod diagnostics;
+mod golden;
+use golden::golden_run;
 mod interp;
 use interp::exec::exec;
+use interp::limits::LIMITS_STACK;
 mod ir;
+use ir::cache::{IRCache, ir_cache_path};
 use ir::def::*;
 use ir::lower::CSTIRLower;
 mod util;
 use util::file::file_read;
 
+/// Stack of the thread running the program, leaving room for the natives
+/// called once nested calls reached `LIMITS_STACK`
+const MAIN_STACK: usize = LIMITS_STACK + (16 << 20);
+
 fn main() {
-    let opts = cli();
+    let main = thread::Builder::new()
+        .name(String::from("main"))
+        .stack_size(MAIN_STACK)
+        .spawn(run)
+        .unwrap();
+    if main.join().is_err() {
+        exit(101);
+    }
+}
+
+fn run() {
+    let mut opts = cli();
+
+    if let Some(golden) = &opts.test {
+        exit(!golden_run(golden) as i32);
+    }
 
-    let input = if !opts.shell {
-        file_read(&opts.path)
+    let (ir, input) = if opts.path.extension().is_some_and(|i| i == "stlxc") {
+        // reports show the source the program was compiled from
+        opts.srcname = opts
+            .path
+            .with_extension("stlx")
+            .to_string_lossy()
+            .into_owned();
+        let cache =
+            IRCache::read(&opts.path).and_then(|cache| Ok((cache.program(&opts)?, cache.src)));
+        cache.unwrap_or_else(|e| {
+            eprintln!("{}: {e}", opts.path.display());
+            exit(1);
+        })
     } else {
-        include_str!("shell.stlx").to_string()
-    };
+        let input = if !opts.shell {
+            file_read(&opts.path)
+        } else {
+            include_str!("shell.stlx").to_string()
+        };
+
+        let cst = cst_parse(&input, &opts);
+        let ir = IRCfg::from_cst(&cst, &opts);
 
-    let cst = cst_parse(&input, &opts);
-    let ir = IRCfg::from_cst(&cst, &opts);
+        if opts.compile {
+            let module = IRCfg::from_stmt(&cst, &opts.exec_opts());
+            let path = ir_cache_path(&opts.path);
+            if let Err(e) =
+                IRCache::new(&input, &ir, &module, &opts).and_then(|cache| cache.write(&path))
+            {
+                eprintln!("{}: {e}", path.display());
+                exit(1);
+            }
+            return;
+        }
+
+        (ir, input)
+    };
 
     if opts.dry_run {
         return;
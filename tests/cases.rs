@@ -3,13 +3,16 @@ use pretty_assertions::assert_eq;
 use setlx_rs::{
     cli::InputOpts, cst::cst_parse, interp::exec::exec, ir::def::IRCfg, ir::lower::CSTIRLower,
 };
+use std::env;
 use std::fs;
 use std::io::Read;
 use walkdir::WalkDir;
 
 #[test]
 fn cases_main() {
-    for i in WalkDir::new("tests/cases/")
+    // cases run from their directory, like `setlx-rs test` runs them
+    let root = env::current_dir().unwrap().join("tests");
+    for i in WalkDir::new(root.join("cases"))
         .into_iter()
         .filter_map(Result::ok)
         .filter(|e| {
@@ -17,7 +20,7 @@ fn cases_main() {
                 && e.path().extension().and_then(|ext| ext.to_str()) == Some("stlx")
         })
     {
-        let pathname = i.path().strip_prefix("tests/").unwrap().to_str().unwrap();
+        let pathname = i.path().strip_prefix(&root).unwrap().to_str().unwrap();
         let content = fs::read_to_string(i.path()).unwrap();
 
         eprintln!("parsing {pathname}");
@@ -25,12 +28,15 @@ fn cases_main() {
         let cst = cst_parse(&content, &opts);
         let ir = IRCfg::from_cst(&cst, &opts);
 
-        if let Ok(reference) = fs::read_to_string(format!("tests/{pathname}.reference")) {
+        if let Ok(reference) = fs::read_to_string(root.join(format!("{pathname}.reference"))) {
+            let cwd = env::current_dir().unwrap();
+            env::set_current_dir(i.path().parent().unwrap()).unwrap();
             let mut buf = BufferRedirect::stdout().unwrap();
             exec(ir, &opts, content);
 
             let mut output = String::new();
             buf.read_to_string(&mut output).unwrap();
+            env::set_current_dir(cwd).unwrap();
 
             assert_eq!(reference, output);
         }
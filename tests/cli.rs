@@ -0,0 +1,388 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime};
+
+fn cli_run(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_setlx-rs"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+
+    assert!(out.status.success());
+    String::from_utf8(out.stdout).unwrap()
+}
+
+fn cli_fail(args: &[&str]) -> String {
+    let out = Command::new(env!("CARGO_BIN_EXE_setlx-rs"))
+        .args(args)
+        .output()
+        .unwrap();
+
+    assert_eq!(out.status.code(), Some(1));
+    String::from_utf8(out.stderr).unwrap()
+}
+
+#[test]
+fn cli_params_filter() {
+    assert_eq!(
+        cli_run(
+            &["tests/cli/filter.stlx", "a", "--seed", "-x"],
+            "abc\n12\r\n"
+        ),
+        "[\"a\", \"--seed\", \"-x\"]\ncba\n21\nlines: 2\n"
+    );
+    assert_eq!(
+        cli_run(&["--seed", "1", "tests/cli/filter.stlx", "--", "--"], ""),
+        "[\"--\"]\nlines: 0\n"
+    );
+}
+
+#[test]
+fn cli_limits() {
+    for (flag, val, msg) in [
+        ("--max-steps", "100", "exceeded the limit of 100 steps"),
+        ("--max-heap", "50", "exceeded the limit of 50 heap objects"),
+        ("--timeout", "0.2", "exceeded the timeout of 200ms"),
+    ] {
+        let err = cli_fail(&[flag, val, "tests/cli/runaway.stlx"]);
+        assert!(err.contains(msg), "{err}");
+        assert!(err.contains("tests/cli/runaway.stlx:2:"), "{err}");
+    }
+
+    // without --max-depth the stack bounds the recursion, either way the
+    // error reaches the catch
+    for (args, msg) in [
+        (
+            &["--max-depth", "100"][..],
+            "exceeded the call depth of 100",
+        ),
+        (&[], "exceeded the stack after"),
+    ] {
+        let out = cli_run(&[args, &["tests/cli/recursion.stlx"]].concat(), "");
+        assert!(out.contains(msg), "{out}");
+    }
+    assert_eq!(
+        cli_run(&["--max-heap", "50", "tests/cli/heap_catch.stlx"], ""),
+        "caught\n0\n"
+    );
+}
+
+#[test]
+fn cli_sandbox() {
+    let scratch = tempfile::tempdir().unwrap();
+    assert_eq!(
+        cli_run(
+            &[
+                "--sandbox",
+                "--sandbox-write",
+                scratch.path().to_str().unwrap(),
+                "tests/cli/sandbox/untrusted.stlx",
+            ],
+            "",
+        ),
+        "[\"sandboxed\"]\nrun denied\nrunProcess denied\nread denied\n[\"scratch\"]\nwrite denied\n"
+    );
+    assert_eq!(scratch.path().read_dir().unwrap().count(), 0);
+
+    let err = cli_fail(&["--sandbox", "tests/cli/sandbox/untrusted.stlx"]);
+    assert!(err.contains("writing ./out.txt is not allowed"), "{err}");
+}
+
+#[test]
+fn cli_plot() {
+    let scratch = tempfile::tempdir().unwrap();
+    let dir = scratch.path().to_str().unwrap();
+    assert_eq!(
+        cli_run(&["tests/cli/plot.stlx", dir], ""),
+        "/* canvas */\n\
+         true\n\
+         <svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"600\" \
+         viewBox=\"0 0 800 600\" font-family=\"sans-serif\">\n\
+         5\n\
+         2\n\
+         <text x=\"400.00\" y=\"30\" text-anchor=\"middle\" font-size=\"18\">\
+         Parabola &lt;&amp; friends&gt;</text>\n\
+         true\n\
+         3\n\
+         6\n"
+    );
+    for name in ["parabola.svg", "charts.svg"] {
+        assert!(scratch.path().join(name).is_file(), "{name}");
+    }
+}
+
+#[test]
+fn cli_profile() {
+    let scratch = tempfile::tempdir().unwrap();
+    let folded = scratch.path().join("fib.folded");
+    let out = Command::new(env!("CARGO_BIN_EXE_setlx-rs"))
+        .args(["--profile-folded", folded.to_str().unwrap()])
+        .arg("tests/cli/fib.stlx")
+        .output()
+        .unwrap();
+
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "55\n");
+    let report = String::from_utf8(out.stderr).unwrap();
+    assert!(report.contains("fib.stlx:2)"), "{report}");
+    assert!(report.contains(" 177 "), "{report}");
+    assert!(
+        report.contains("return fib(n - 1) + fib(n - 2);"),
+        "{report}"
+    );
+
+    let folded = std::fs::read_to_string(folded).unwrap();
+    assert!(
+        folded.lines().all(|i| i
+            .rsplit_once(' ')
+            .is_some_and(|(_, t)| t.parse::<u64>().is_ok())),
+        "{folded}"
+    );
+    assert!(folded.contains("fib.stlx:1);print"), "{folded}");
+}
+
+#[test]
+fn cli_compile() {
+    let scratch = tempfile::tempdir().unwrap();
+    let lib = scratch.path().join("lib.stlx");
+    let main = scratch.path().join("main.stlx");
+    let run = |args: &[&std::path::Path]| {
+        Command::new(env!("CARGO_BIN_EXE_setlx-rs"))
+            .arg("--library-path")
+            .arg(scratch.path())
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    std::fs::write(
+        &lib,
+        "twice := procedure(x) { return 2 * x; };\nprint(\"v1\");\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &main,
+        "loadLibrary(\"lib\");\nprint(twice(21));\nprint(1 / 0);\n",
+    )
+    .unwrap();
+
+    for path in [&lib, &main] {
+        assert!(run(&[Path::new("--compile"), path]).status.success());
+    }
+    let out = run(&[&main.with_extension("stlxc")]);
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "v1\n42\n");
+    let err = String::from_utf8(out.stderr).unwrap();
+    assert!(err.contains("main.stlx:3:"), "{err}");
+
+    // the cache is used while it is newer than the source
+    let old = SystemTime::now() - Duration::from_secs(60);
+    std::fs::write(
+        &lib,
+        "twice := procedure(x) { return 3 * x; };\nprint(\"v2\");\n",
+    )
+    .unwrap();
+    File::options()
+        .write(true)
+        .open(&lib)
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+    let out = run(&[&main]);
+    assert!(out.stdout.starts_with(b"v1\n42\n"));
+
+    File::options()
+        .write(true)
+        .open(&lib)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+    let out = run(&[&main]);
+    assert!(out.stdout.starts_with(b"v2\n63\n"));
+
+    // caches of another format version are rejected
+    let stale = main.with_extension("stlxc");
+    let mut cache = std::fs::read(&stale).unwrap();
+    cache[6..10].copy_from_slice(&0u32.to_le_bytes());
+    std::fs::write(&stale, cache).unwrap();
+    let err = cli_fail(&[stale.to_str().unwrap()]);
+    assert!(err.contains("compile the source again"), "{err}");
+}
+
+#[test]
+fn cli_coverage() {
+    let scratch = tempfile::tempdir().unwrap();
+    let lcov = scratch.path().join("main.info");
+    let out = Command::new(env!("CARGO_BIN_EXE_setlx-rs"))
+        .args(["--library-path", "tests/cli/coverage", "--coverage"])
+        .arg(&lcov)
+        .arg("tests/cli/coverage/main.stlx")
+        .output()
+        .unwrap();
+
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "1\n1\n-1\n");
+    let report = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        report.contains("coverage: tests/cli/coverage/sign.stlx: 5 of 7 lines"),
+        "{report}"
+    );
+    assert!(
+        report.contains("#####     5          return 0;"),
+        "{report}"
+    );
+
+    let lcov = std::fs::read_to_string(lcov).unwrap();
+    let records = lcov.split("end_of_record\n").collect::<Vec<_>>();
+    assert_eq!(records.len(), 3, "{lcov}");
+    let sign = records.iter().find(|i| i.contains("sign.stlx\n")).unwrap();
+    for line in ["DA:2,3\n", "DA:3,1\n", "DA:5,0\n", "DA:7,2\n", "DA:10,0\n"] {
+        assert!(sign.contains(line), "{sign}");
+    }
+    // `x < 0` failed twice and succeeded once
+    for taken in [",0,1", ",1,2"] {
+        assert!(
+            sign.lines()
+                .any(|i| i.starts_with("BRDA:2,") && i.ends_with(taken)),
+            "{sign}"
+        );
+    }
+}
+
+#[test]
+fn cli_exit_reports() {
+    let scratch = tempfile::tempdir().unwrap();
+    let lcov = scratch.path().join("exit.info");
+    let folded = scratch.path().join("exit.folded");
+    let out = Command::new(env!("CARGO_BIN_EXE_setlx-rs"))
+        .arg("--coverage")
+        .arg(&lcov)
+        .arg("--profile-folded")
+        .arg(&folded)
+        .arg("tests/cli/coverage/exit.stlx")
+        .output()
+        .unwrap();
+
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "1\n");
+    let report = String::from_utf8(out.stderr).unwrap();
+    assert!(report.contains("profile: "), "{report}");
+    assert!(report.contains("#####     7  print(2);"), "{report}");
+
+    let lcov = std::fs::read_to_string(lcov).unwrap();
+    assert!(lcov.contains("DA:7,0\n"), "{lcov}");
+    // the procedures still running at `exit` are part of the profile
+    let folded = std::fs::read_to_string(folded).unwrap();
+    assert!(
+        folded.lines().any(|i| i.matches(';').count() == 3),
+        "{folded}"
+    );
+}
+
+#[test]
+fn cli_test_golden() {
+    let scratch = tempfile::tempdir().unwrap();
+    let dir = scratch.path().join("cases");
+    let junit = scratch.path().join("junit.xml");
+    for (name, src, reference) in [
+        ("pass.stlx", "print(1 + 1);\n", "2\n"),
+        ("nested/fail.stlx", "print(1);\nprint(3);\n", "1\n2\n"),
+        // cases run from their directory
+        (
+            "nested/read.stlx",
+            "print(readFile(\"data.txt\"));\n",
+            "[\"data\"]\n",
+        ),
+        (
+            "error.stlx",
+            "print(\"before\");\nprint(1 / 0);\n",
+            "before\n",
+        ),
+        ("hang.stlx", "while (true) {}\n", ""),
+        ("skip.stlx", "print(1 / 0);\n", ""),
+        ("helper.stlx", "print(\"not a case\");\n", ""),
+    ] {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, src).unwrap();
+        if name != "helper.stlx" {
+            std::fs::write(path.with_extension("stlx.reference"), reference).unwrap();
+        }
+    }
+    std::fs::write(dir.join("nested/data.txt"), "data\n").unwrap();
+    let blacklist = scratch.path().join("suite.json");
+    std::fs::write(
+        &blacklist,
+        "{\"name\": \"cases\", \"blacklist\": [\"skip.stlx\"]}",
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_setlx-rs"))
+            .arg("test")
+            .arg(&dir)
+            .args(["--timeout", "1", "--blacklist"])
+            .arg(&blacklist)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let out = run(&["--junit", junit.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(1));
+    let report = String::from_utf8(out.stdout).unwrap();
+    for line in [
+        "test error.stlx ... ERROR\n",
+        "test hang.stlx ... ERROR\n",
+        "test nested/fail.stlx ... FAILED\n",
+        "test nested/read.stlx ... ok\n",
+        "test pass.stlx ... ok\n",
+        "test skip.stlx ... blacklisted\n",
+        "--- nested/fail.stlx.reference\n+++ nested/fail.stlx\n@@ -1,2 +1,2 @@\n 1\n-2\n+3\n",
+        "error.stlx: exited with code 1\n",
+        "hang.stlx: timed out after 1s\n",
+        "2 passed; 1 failed; 2 errors; 0 blessed; 1 blacklisted;",
+    ] {
+        assert!(report.contains(line), "{report}");
+    }
+    assert!(!report.contains("helper.stlx"), "{report}");
+
+    let junit = std::fs::read_to_string(junit).unwrap();
+    assert!(
+        junit.contains("tests=\"6\" failures=\"1\" errors=\"2\" skipped=\"1\""),
+        "{junit}"
+    );
+    assert!(
+        junit.contains("<skipped message=\"blacklisted\"/>"),
+        "{junit}"
+    );
+    assert!(junit.contains("-2\n+3\n</failure>"), "{junit}");
+
+    // only the references of cases that ran to the end are overwritten
+    let out = run(&["--bless"]);
+    assert_eq!(out.status.code(), Some(1));
+    let report = String::from_utf8(out.stdout).unwrap();
+    assert!(
+        report.contains("test nested/fail.stlx ... blessed\n"),
+        "{report}"
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("nested/fail.stlx.reference")).unwrap(),
+        "1\n3\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("error.stlx.reference")).unwrap(),
+        "before\n"
+    );
+    assert!(!dir.join("helper.stlx.reference").exists());
+}
@@ -0,0 +1,77 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn debug_run(path: &str, cmds: &str) -> (String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_setlx-rs"))
+        .args(["--debug", path])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(cmds.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+
+    (
+        String::from_utf8(out.stdout).unwrap(),
+        String::from_utf8(out.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn debug_break_inspect() {
+    let (stdout, stderr) = debug_run(
+        "tests/debug/fib.stlx",
+        "break fib.stlx:3\ncontinue\nbacktrace\nprint n x\neval n * 10\ndelete\ncontinue\n",
+    );
+
+    assert_eq!(stdout, "7\n");
+    assert!(stderr.contains("breakpoint at fib.stlx:3\n"));
+    assert!(stderr.contains("tests/debug/fib.stlx:3:9\n   3 |         return n;\n"));
+    assert!(stderr.contains("#3 <main> at tests/debug/fib.stlx:8:1 (110..121)\n"));
+    assert!(stderr.contains("n = 1\nx is not in scope\n10\n"));
+}
+
+#[test]
+fn debug_step() {
+    let (stdout, stderr) = debug_run(
+        "tests/debug/fib.stlx",
+        "next\nnext\nstep\nstep\nfinish\ncontinue\n",
+    );
+    let stops = stderr
+        .lines()
+        .filter(|l| l.starts_with("tests/debug/fib.stlx:"))
+        .collect::<Vec<_>>();
+
+    assert_eq!(stdout, "7\n");
+    assert_eq!(
+        stops,
+        [
+            "tests/debug/fib.stlx:1:1",
+            "tests/debug/fib.stlx:7:1",
+            "tests/debug/fib.stlx:8:1",
+            "tests/debug/fib.stlx:2:5",
+            "tests/debug/fib.stlx:5:5",
+            "tests/debug/fib.stlx:9:1",
+        ]
+    );
+}
+
+#[test]
+fn debug_load() {
+    let (stdout, stderr) = debug_run(
+        "tests/debug/load.stlx",
+        "break lib.stlx:2\ncontinue\nbacktrace\ncontinue\n",
+    );
+
+    assert_eq!(stdout, "9\n");
+    assert!(stderr.contains("tests/debug/lib.stlx:2:5\n   2 |     return n * n;\n"));
+    assert!(stderr.contains("at tests/debug/lib.stlx:2:5 (25..38)\n"));
+    assert!(stderr.contains("#1 <main> at tests/debug/load.stlx:2:1 (30..43)\n"));
+}
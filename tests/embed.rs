@@ -0,0 +1,236 @@
+use num_rational::BigRational;
+use setlx_rs::builtin::sandbox::Sandbox;
+use setlx_rs::cli::InputOpts;
+use setlx_rs::embed::{InterpErrorKind, Interpreter, Value};
+use setlx_rs::interp::io::InterpIO;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::rc::Rc;
+use std::thread;
+
+#[derive(Default)]
+struct BufIO {
+    out: Rc<RefCell<String>>,
+    err: Rc<RefCell<String>>,
+    input: VecDeque<String>,
+}
+
+impl InterpIO for BufIO {
+    fn print(&mut self, s: &str) {
+        self.out.borrow_mut().push_str(s);
+    }
+
+    fn print_err(&mut self, s: &str) {
+        self.err.borrow_mut().push_str(s);
+    }
+
+    fn read_line(&mut self, _prompt: &str) -> Option<String> {
+        self.input.pop_front()
+    }
+}
+
+#[test]
+fn embed_globals() {
+    let mut interp = Interpreter::default();
+
+    interp.set("xs", &Value::from(vec![1, 2, 3]));
+    interp
+        .run("s := +/ xs; f := procedure(n) { return n * 2; };")
+        .unwrap();
+    assert_eq!(interp.get("s"), Some(Value::from(6)));
+
+    interp.run("t := f(s);").unwrap();
+    assert_eq!(interp.get("t"), Some(Value::from(12)));
+    assert_eq!(interp.eval("[t, \"a\", 1.5, {true}]").unwrap(), {
+        Value::List(vec![
+            Value::from(12),
+            Value::from("a"),
+            Value::from(1.5),
+            Value::Set(vec![Value::from(true)]),
+        ])
+    });
+    assert_eq!(interp.get("undeclared"), None);
+}
+
+#[test]
+fn embed_rational() {
+    let mut interp = Interpreter::default();
+    let half = Value::from(BigRational::new(1.into(), 2.into()));
+
+    interp.set("h", &half);
+    interp.run("r := h * 3; n := h * 2;").unwrap();
+    assert_eq!(
+        interp.get("r"),
+        Some(Value::Rational(BigRational::new(3.into(), 2.into())))
+    );
+    assert_eq!(interp.get("n"), Some(Value::from(1)));
+    assert_eq!(interp.eval("1/4 + h").unwrap(), {
+        Value::from(BigRational::new(3.into(), 4.into()))
+    });
+}
+
+#[test]
+fn embed_io() {
+    let out = Rc::new(RefCell::new(String::new()));
+    let err = Rc::new(RefCell::new(String::new()));
+    let mut interp = Interpreter::default();
+    interp.set_io(Box::new(BufIO {
+        out: out.clone(),
+        err: err.clone(),
+        input: VecDeque::from([String::from("42")]),
+    }));
+
+    interp
+        .run("x := get(\"> \"); print(\"got $x$\"); printErr(\"done\");")
+        .unwrap();
+    assert_eq!(*out.borrow(), "got 42\n");
+    assert_eq!(*err.borrow(), "done\n");
+}
+
+#[test]
+fn embed_errors() {
+    let mut interp = Interpreter::default();
+
+    let e = interp
+        .run_file(Path::new("tests/cases/missing.stlx"))
+        .unwrap_err();
+    assert_eq!(e.kind, InterpErrorKind::Io);
+
+    let e = interp.run("x := ;").unwrap_err();
+    assert_eq!(e.kind, InterpErrorKind::Parse);
+
+    let e = interp.run("x := 1;\nthrow(\"oops\");").unwrap_err();
+    assert_eq!(e.kind, InterpErrorKind::Runtime);
+    assert_eq!(e.value.as_deref(), Some(&Value::from("oops")));
+    assert_eq!(e.span, Some(8..21));
+
+    // state survives failed runs
+    assert_eq!(interp.get("x"), Some(Value::from(1)));
+    assert_eq!(interp.eval("x + 1").unwrap(), Value::from(2));
+
+    let e = interp
+        .run("load(\"tests/debug/lib.stlx\");\nsq(\"a\");")
+        .unwrap_err();
+    assert_eq!(e.srcname, "tests/debug/lib.stlx");
+    assert_eq!(e.span, Some(32..37));
+}
+
+#[test]
+fn embed_limits() {
+    let mut opts = InputOpts::none();
+    opts.limits.steps = Some(10000);
+    opts.limits.depth = Some(50);
+    let mut interp = Interpreter::new(opts);
+
+    let e = interp.run("n := 0;\nwhile (true) { n += 1; }").unwrap_err();
+    assert_eq!(e.kind, InterpErrorKind::Limit);
+    assert_eq!(e.span, Some(23..30));
+    assert!(e.message.contains("exceeded the limit of 10000 steps"));
+
+    // the budget restarts with every run and the error can be caught
+    interp
+        .run(
+            "f := procedure(n) { return f(n + 1); };\n\
+             try { f(0); } catch(e) { r := 1; }",
+        )
+        .unwrap();
+    assert_eq!(interp.get("r"), Some(Value::from(1)));
+    let e = interp.run("f(0);").unwrap_err();
+    assert_eq!(e.kind, InterpErrorKind::Limit);
+    assert!(e.message.contains("exceeded the call depth of 50"));
+    assert_eq!(interp.eval("1 + 1").unwrap(), Value::from(2));
+}
+
+#[test]
+fn embed_sandbox() {
+    let mut opts = InputOpts::none();
+    opts.sandbox = Some(Sandbox::new(&[], None));
+    let mut interp = Interpreter::new(opts);
+
+    let e = interp.run("run(\"true\");").unwrap_err();
+    assert_eq!(e.kind, InterpErrorKind::Runtime);
+    assert!(e.message.contains("running commands is not allowed"));
+
+    let e = interp.run("load(\"tests/debug/lib.stlx\");").unwrap_err();
+    assert!(
+        e.message
+            .contains("reading tests/debug/lib.stlx is not allowed")
+    );
+}
+
+#[test]
+fn embed_reload() {
+    let scratch = tempfile::tempdir().unwrap();
+    let path = scratch.path().join("counter.stlx");
+    let load = format!("load(\"{}\");", path.display());
+    let mut interp = Interpreter::default();
+
+    // unchanged files are only executed once, changed ones again
+    std::fs::write(&path, "n += 1;").unwrap();
+    interp.run("n := 0;").unwrap();
+    interp.run(&load).unwrap();
+    interp.run(&load).unwrap();
+    assert_eq!(interp.get("n"), Some(Value::from(1)));
+
+    std::fs::write(&path, "n += 10;").unwrap();
+    interp.run(&load).unwrap();
+    interp.run(&load).unwrap();
+    assert_eq!(interp.get("n"), Some(Value::from(11)));
+
+    // the registry belongs to the interpreter
+    let mut other = Interpreter::default();
+    other.run("n := 0;").unwrap();
+    other.run(&load).unwrap();
+    assert_eq!(other.get("n"), Some(Value::from(10)));
+}
+
+/// Interpreter running another one from its handler.
+struct NestedIO {
+    inner: Interpreter,
+    errors: Rc<RefCell<Vec<InterpErrorKind>>>,
+}
+
+impl InterpIO for NestedIO {
+    fn print(&mut self, s: &str) {
+        if let Err(e) = self.inner.run(s) {
+            self.errors.borrow_mut().push(e.kind);
+        }
+    }
+
+    fn print_err(&mut self, _s: &str) {}
+
+    fn read_line(&mut self, _prompt: &str) -> Option<String> {
+        None
+    }
+}
+
+#[test]
+fn embed_threads() {
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            thread::spawn(move || {
+                let mut interp = Interpreter::default();
+                interp.set("i", &Value::from(i));
+                interp
+                    .run("s := 0; for (k in [1..2000]) { s += k * i; }")
+                    .unwrap();
+                interp.get("s")
+            })
+        })
+        .collect();
+
+    for (i, h) in handles.into_iter().enumerate() {
+        assert_eq!(h.join().unwrap(), Some(Value::from(2001000 * i as i64)));
+    }
+
+    // runs nested in the handler of another interpreter fail
+    let errors = Rc::new(RefCell::new(Vec::new()));
+    let mut interp = Interpreter::default();
+    interp.set_io(Box::new(NestedIO {
+        inner: Interpreter::default(),
+        errors: errors.clone(),
+    }));
+    interp.run("print(\"x := 1;\");").unwrap();
+    assert_eq!(*errors.borrow(), vec![InterpErrorKind::Runtime]);
+}
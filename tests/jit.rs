@@ -0,0 +1,74 @@
+#![cfg(feature = "jit")]
+
+use gag::BufferRedirect;
+use pretty_assertions::assert_eq;
+use setlx_rs::{
+    cli::InputOpts,
+    cst::cst_parse,
+    embed::{Interpreter, Value},
+    interp::{exec::exec, global::GlobalGuard},
+    ir::def::IRCfg,
+    ir::lower::CSTIRLower,
+};
+use std::env;
+use std::fs;
+use std::io::Read;
+use walkdir::WalkDir;
+
+/// Runs the golden cases with every procedure compiled on its first call.
+#[test]
+fn jit_cases() {
+    // the cases are parsed outside of `exec`
+    let _global = GlobalGuard::acquire();
+    // cases run from their directory, like `setlx-rs test` runs them
+    let root = env::current_dir().unwrap().join("tests");
+    for i in WalkDir::new(root.join("cases"))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| {
+            e.file_type().is_file()
+                && e.path().extension().and_then(|ext| ext.to_str()) == Some("stlx")
+        })
+    {
+        let pathname = i.path().strip_prefix(&root).unwrap().to_str().unwrap();
+        let content = fs::read_to_string(i.path()).unwrap();
+
+        eprintln!("parsing {pathname}");
+        let mut opts = InputOpts::none();
+        opts.jit_threshold = Some(1);
+        let cst = cst_parse(&content, &opts);
+        let ir = IRCfg::from_cst(&cst, &opts);
+
+        if let Ok(reference) = fs::read_to_string(root.join(format!("{pathname}.reference"))) {
+            let cwd = env::current_dir().unwrap();
+            env::set_current_dir(i.path().parent().unwrap()).unwrap();
+            let mut buf = BufferRedirect::stdout().unwrap();
+            exec(ir, &opts, content);
+
+            let mut output = String::new();
+            buf.read_to_string(&mut output).unwrap();
+            env::set_current_dir(cwd).unwrap();
+
+            assert_eq!(reference, output, "{pathname}");
+        }
+    }
+}
+
+/// Procedures dropped after being compiled release their entries, whose
+/// addresses are then reused by new procedures.
+#[test]
+fn jit_dropped_procs() {
+    let mut opts = InputOpts::none();
+    opts.jit_threshold = Some(1);
+    let mut interp = Interpreter::new(opts);
+    interp
+        .run(
+            "s := 0;\n\
+             for (i in [1..600]) {\n\
+                 f := eval(\"procedure(x) { return x + \" + i + \"; }\");\n\
+                 s += f(1);\n\
+             }",
+        )
+        .unwrap();
+    assert_eq!(interp.get("s"), Some(Value::from(180900)));
+}
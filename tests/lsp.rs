@@ -0,0 +1,177 @@
+use serde_json::{Value, json};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const URI: &str = "file:///tmp/lsp.stlx";
+const SRC: &str = r#"fib := procedure(n, rw memo, k := 2, *rest) {
+    if (n < 2) { return n; print(n); }
+    return fib(n - 1) + fib(n - 2) + undef;
+};
+class Point(x, y) {
+    norm := procedure() { return x * x + y * y; };
+}
+total := 0;
+print("a\q");
+print(fib(10), Point(1, 2), to);
+"#;
+
+fn lsp_run(requests: &[Value]) -> Vec<Value> {
+    lsp_run_raw(&requests.iter().map(|i| i.to_string()).collect::<Vec<_>>())
+}
+
+fn lsp_run_raw(bodies: &[String]) -> Vec<Value> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_setlx-lsp"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    for body in bodies {
+        write!(stdin, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+    }
+    drop(stdin);
+
+    let out = String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap();
+    let mut msgs = Vec::new();
+    let mut rest = out.as_str();
+    while let Some((header, body)) = rest.split_once("\r\n\r\n") {
+        let len: usize = header
+            .strip_prefix("Content-Length: ")
+            .unwrap()
+            .parse()
+            .unwrap();
+        msgs.push(serde_json::from_str(&body[..len]).unwrap());
+        rest = &body[len..];
+    }
+    msgs
+}
+
+fn request(id: u64, method: &str, line: u64, character: u64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": {
+            "textDocument": {"uri": URI},
+            "position": {"line": line, "character": character},
+        },
+    })
+}
+
+fn response(msgs: &[Value], id: u64) -> &Value {
+    &msgs.iter().find(|i| i["id"] == id).unwrap()["result"]
+}
+
+#[test]
+fn lsp_session() {
+    let msgs = lsp_run(&[
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+        json!({"jsonrpc": "2.0", "method": "initialized", "params": {}}),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {"textDocument": {"uri": URI, "languageId": "setlx", "version": 1, "text": SRC}},
+        }),
+        request(2, "textDocument/completion", 9, 30),
+        request(3, "textDocument/completion", 2, 37),
+        request(4, "textDocument/hover", 9, 7),
+        request(5, "textDocument/definition", 9, 17),
+        request(6, "textDocument/definition", 2, 12),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {"textDocument": {"uri": URI, "version": 2}, "contentChanges": [{"text": "x := ;"}]},
+        }),
+        request(7, "textDocument/completion", 8, 3),
+        json!({"jsonrpc": "2.0", "id": 8, "method": "shutdown"}),
+        json!({"jsonrpc": "2.0", "method": "exit"}),
+    ]);
+
+    assert_eq!(response(&msgs, 1)["capabilities"]["hoverProvider"], true);
+
+    let diagnostics: Vec<&Value> = msgs
+        .iter()
+        .filter(|i| i["method"] == "textDocument/publishDiagnostics")
+        .collect();
+    assert_eq!(diagnostics.len(), 2);
+    let messages: Vec<(&str, &Value)> = diagnostics[0]["params"]["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| (i["message"].as_str().unwrap(), &i["range"]["start"]))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            (
+                "parse error: invalid escape sequence",
+                &json!({"line": 8, "character": 7})
+            ),
+            (
+                "unreachable code: encountered statements in block after a terminating statement",
+                &json!({"line": 1, "character": 27})
+            ),
+            (
+                "implicit declaration: implicit declaration of undef",
+                &json!({"line": 2, "character": 37})
+            ),
+            (
+                "implicit declaration: implicit declaration of to",
+                &json!({"line": 9, "character": 28})
+            ),
+        ]
+    );
+    let parse_err = &diagnostics[1]["params"]["diagnostics"][0];
+    assert_eq!(parse_err["severity"], 1);
+    assert!(
+        parse_err["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Unexpected token")
+    );
+
+    let labels = |id| {
+        response(&msgs, id)
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i["label"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(labels(2), ["toJson", "total"]);
+    assert!(labels(3).contains(&String::from("memo")));
+    assert!(!labels(3).contains(&String::from("total")));
+    assert!(labels(7).contains(&String::from("printErr")));
+
+    assert_eq!(
+        response(&msgs, 4)["contents"]["value"],
+        "```setlx\nfib := procedure(n, rw memo, k := 2, *rest)\n```"
+    );
+    assert_eq!(
+        response(&msgs, 5),
+        &json!([{
+            "uri": URI,
+            "range": {"start": {"line": 4, "character": 6}, "end": {"line": 4, "character": 11}},
+        }])
+    );
+    assert_eq!(
+        response(&msgs, 6)[0]["range"]["start"],
+        json!({"line": 0, "character": 0})
+    );
+    assert_eq!(response(&msgs, 8), &Value::Null);
+}
+
+#[test]
+fn lsp_parse_error() {
+    let msgs = lsp_run_raw(&[
+        String::from("{\"jsonrpc\": \"2.0\", \"id\": 1,"),
+        json!({"jsonrpc": "2.0", "id": 2, "method": "shutdown"}).to_string(),
+        json!({"jsonrpc": "2.0", "method": "exit"}).to_string(),
+    ]);
+
+    assert_eq!(msgs.len(), 2);
+    assert_eq!(msgs[0]["id"], Value::Null);
+    assert_eq!(msgs[0]["error"]["code"], -32700);
+    assert_eq!(response(&msgs, 2), &Value::Null);
+}
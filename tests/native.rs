@@ -0,0 +1,121 @@
+#![allow(clippy::too_many_arguments)]
+
+use rustyline::DefaultEditor;
+use setlx_rs::{
+    builtin::native::NativeProc,
+    cli::InputOpts,
+    embed::{Interpreter, Value},
+    interp::{
+        debug::DebugData,
+        get::InterpGet,
+        heap::{InterpClassStore, InterpImmediateHeap, InterpVal},
+        memoize::InterpMemoize,
+        stack::InterpStack,
+    },
+    ir::def::{IRType, IRValue},
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+thread_local! {
+    static RECORDED: RefCell<Vec<f64>> = const { RefCell::new(Vec::new()) };
+}
+
+fn hypot(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let x = params[0].to_f64(vars, params_proc, breakpoints, "hypot");
+    let y = params[1].to_f64(vars, params_proc, breakpoints, "hypot");
+
+    InterpVal::Double(x.hypot(y))
+}
+
+fn record(
+    params: &[IRValue],
+    params_proc: &InterpVal,
+    vars: &mut [InterpVal],
+    _stack: &mut InterpStack,
+    _heap: &mut InterpImmediateHeap,
+    _memo: &mut InterpMemoize,
+    _cstore: &mut InterpClassStore,
+    breakpoints: &mut DebugData,
+    _opts: &InputOpts,
+    _rl: &mut DefaultEditor,
+) -> InterpVal {
+    let list = params[0].to_list(vars, params_proc, breakpoints);
+    for i in list.0.iter() {
+        let v = match i {
+            // SAFETY: IR-PTR
+            InterpVal::Ptr(p) => unsafe { (*p.ptr).clone() },
+            _ => i.clone(),
+        };
+        let d = IRValue::Variable(0).to_f64(&[v], params_proc, breakpoints, "record");
+        RECORDED.with(|r| r.borrow_mut().push(d));
+    }
+
+    InterpVal::Undefined
+}
+
+fn natives_opts() -> InputOpts {
+    let mut opts = InputOpts::none();
+    opts.natives.push(Rc::new(NativeProc::new(
+        "hypot",
+        vec![IRType::NUMBER | IRType::DOUBLE; 2],
+        IRType::DOUBLE,
+        hypot,
+    )));
+    opts.natives.push(Rc::new(NativeProc {
+        name: String::from("record"),
+        arity: None,
+        params: Vec::new(),
+        ret: IRType::UNDEFINED,
+        handler: record,
+    }));
+    opts
+}
+
+#[test]
+fn native_register_call() {
+    let mut interp = Interpreter::new(natives_opts());
+    interp
+        .run("record(hypot(3.0, 4), hypot(5, 12) + 1);")
+        .unwrap();
+
+    RECORDED.with(|r| assert_eq!(*r.borrow(), vec![5.0, 14.0]));
+}
+
+#[test]
+fn native_arity() {
+    let mut interp = Interpreter::new(natives_opts());
+    let err = interp.run("hypot(3.0);").unwrap_err();
+    assert!(
+        err.message.contains("hypot: expected 2 parameters"),
+        "{}",
+        err.message
+    );
+
+    interp
+        .run("try { hypot(1, 2, 3); } catch (e) { caught := true; }")
+        .unwrap();
+    assert_eq!(interp.get("caught"), Some(Value::Bool(true)));
+}
+
+#[test]
+fn native_owned() {
+    let mut interp = Interpreter::new(natives_opts());
+    assert_eq!(interp.eval("hypot(3, 4)").unwrap(), Value::Double(5.0));
+    drop(interp);
+
+    // natives are only bound in the interpreter they were registered with
+    let mut interp = Interpreter::default();
+    assert!(interp.eval("hypot(3, 4)").is_err());
+}